@@ -0,0 +1,224 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use meadow_dsp_agpl::parametric_eq::f32::{
+    coeff::{F32Coeffs, MeadowEqDspCoeff},
+    mono::MeadowEqDspMono,
+    stereo::{
+        dual::MeadowEqDspStereoDual, f64_coeff::MeadowEqDspStereoLinkedF64Coeff,
+        scalar::MeadowEqDspStereoLinked,
+    },
+    EqParams, FilterOrder,
+};
+
+const SAMPLE_RATE: f64 = 48_000.0;
+const FRAMES: usize = 512;
+
+/// A typical mixing EQ with all 8 bands (and the lowpass and highpass bands)
+/// enabled.
+fn params() -> EqParams<8> {
+    EqParams::builder()
+        .highpass(40.0, FilterOrder::X4)
+        .lowpass(18_000.0, FilterOrder::X2)
+        .low_shelf(80.0, 0.7, 3.0)
+        .bell(200.0, 1.0, -2.0)
+        .bell(400.0, 2.0, -4.0)
+        .bell(800.0, 1.0, 1.5)
+        .bell(1_600.0, 3.0, -3.0)
+        .bell(3_200.0, 1.0, 2.0)
+        .bell(6_400.0, 0.7, 1.0)
+        .high_shelf(10_000.0, 0.7, 2.0)
+        .build()
+}
+
+/// An 8th order highpass and eight narrow bells, all below 100 Hz, which is
+/// where [`MeadowEqDspStereoLinkedF64Coeff`] is meant to be used. (The same
+/// as the `f64_coeff_is_more_accurate` test, which measures the accuracy.)
+fn low_cascade_params() -> EqParams<8> {
+    EqParams::builder()
+        .highpass(30.0, FilterOrder::X8)
+        .bell(35.0, 8.0, 6.0)
+        .bell(42.0, 8.0, -6.0)
+        .bell(50.0, 8.0, 6.0)
+        .bell(58.0, 8.0, -6.0)
+        .bell(67.0, 8.0, 6.0)
+        .bell(76.0, 8.0, -6.0)
+        .bell(87.0, 8.0, 6.0)
+        .bell(98.0, 8.0, -6.0)
+        .build()
+}
+
+/// An 8-band EQ over a buffer of `FRAMES` frames.
+///
+/// With the `portable-simd` feature, `stereo_linked` processes both channels
+/// with SIMD, while `stereo_dual` always processes each channel separately,
+/// so comparing the two shows the gain from SIMD.
+fn eq(c: &mut Criterion) {
+    let mut group = c.benchmark_group("eq_8_band");
+    let params = params();
+
+    group.throughput(Throughput::Elements(FRAMES as u64));
+    group.bench_function("mono", |b| {
+        let mut eq = MeadowEqDspMono::<8, 24>::new(SAMPLE_RATE);
+        eq.set_params(&params);
+        let mut buf = input();
+
+        b.iter(|| {
+            eq.process(&mut buf);
+            black_box(&mut buf);
+        });
+    });
+
+    group.throughput(Throughput::Elements((FRAMES * 2) as u64));
+    group.bench_function("stereo_linked", |b| {
+        let mut eq = MeadowEqDspStereoLinked::<8, 24>::new(SAMPLE_RATE);
+        eq.set_params(&params);
+        let mut buf_l = input();
+        let mut buf_r = input();
+
+        b.iter(|| {
+            eq.process(&mut buf_l, &mut buf_r);
+            black_box((&mut buf_l, &mut buf_r));
+        });
+    });
+
+    group.bench_function("stereo_dual", |b| {
+        let mut eq = MeadowEqDspStereoDual::<8, 24>::new(SAMPLE_RATE);
+        eq.set_left_params(&params);
+        eq.set_right_params(&params);
+        let mut buf_l = input();
+        let mut buf_r = input();
+
+        b.iter(|| {
+            eq.process(&mut buf_l, &mut buf_r);
+            black_box((&mut buf_l, &mut buf_r));
+        });
+    });
+
+    group.bench_function("stereo_linked_f64_coeff", |b| {
+        let mut eq = MeadowEqDspStereoLinkedF64Coeff::<8, 24>::new(SAMPLE_RATE);
+        eq.set_params(&params);
+        let mut buf_l = input();
+        let mut buf_r = input();
+
+        b.iter(|| {
+            eq.process(&mut buf_l, &mut buf_r);
+            black_box((&mut buf_l, &mut buf_r));
+        });
+    });
+
+    group.finish();
+}
+
+/// f32 against f64 coefficients for a long cascade of low filters.
+fn eq_low_cascade(c: &mut Criterion) {
+    let mut group = c.benchmark_group("eq_low_cascade");
+    let params = low_cascade_params();
+
+    group.throughput(Throughput::Elements((FRAMES * 2) as u64));
+    group.bench_function("stereo_linked", |b| {
+        let mut eq = MeadowEqDspStereoLinked::<8, 24>::new(SAMPLE_RATE);
+        eq.set_params(&params);
+        let mut buf_l = input();
+        let mut buf_r = input();
+
+        b.iter(|| {
+            eq.process(&mut buf_l, &mut buf_r);
+            black_box((&mut buf_l, &mut buf_r));
+        });
+    });
+
+    group.bench_function("stereo_linked_f64_coeff", |b| {
+        let mut eq = MeadowEqDspStereoLinkedF64Coeff::<8, 24>::new(SAMPLE_RATE);
+        eq.set_params(&params);
+        let mut buf_l = input();
+        let mut buf_r = input();
+
+        b.iter(|| {
+            eq.process(&mut buf_l, &mut buf_r);
+            black_box((&mut buf_l, &mut buf_r));
+        });
+    });
+
+    group.finish();
+}
+
+/// Flushing parameter changes.
+///
+/// `automation_repeated` moves the cutoffs of all 8 bands back and forth
+/// between a few values, flushing after every step. Run this with and without the `coeff-cache` feature to see the win of the
+/// cache, which only needs to compute the coefficients the first time each
+/// value is seen.
+fn coeff_flush(c: &mut Criterion) {
+    let mut group = c.benchmark_group("coeff");
+    let params = params();
+
+    group.bench_function("automation_repeated", |b| {
+        let mut coeff = MeadowEqDspCoeff::<8, 24, F32Coeffs>::new(SAMPLE_RATE);
+        coeff.set_params(&params);
+        let _ = coeff.flush_param_changes();
+
+        let mut step = 0;
+        b.iter(|| {
+            let scale = [0.9, 1.0, 1.1][step % 3];
+            step += 1;
+
+            coeff.modify_params(|p| {
+                for (band, base) in p.bands.iter_mut().zip(params.bands.iter()) {
+                    band.cutoff_hz = base.cutoff_hz * scale;
+                }
+            });
+            black_box(coeff.flush_param_changes());
+        });
+    });
+
+    // Everything changed (i.e. a preset load): one forced full flush against
+    // flushing each band separately as it changes.
+    group.bench_function("recompute_all", |b| {
+        let mut coeff = MeadowEqDspCoeff::<8, 24, F32Coeffs>::new(SAMPLE_RATE);
+        coeff.set_params(&params);
+        let _ = coeff.flush_param_changes();
+
+        b.iter(|| black_box(coeff.recompute_all()));
+    });
+
+    group.bench_function("individual_flushes", |b| {
+        let mut coeff = MeadowEqDspCoeff::<8, 24, F32Coeffs>::new(SAMPLE_RATE);
+        coeff.set_params(&params);
+        let _ = coeff.flush_param_changes();
+
+        let mut step = 0;
+        b.iter(|| {
+            // Alternate between two cutoffs so that every band is dirty.
+            let scale = [1.0, 1.01][step % 2];
+            step += 1;
+
+            coeff.modify_params(|p| p.lp_band.cutoff_hz = params.lp_band.cutoff_hz * scale);
+            black_box(coeff.flush_param_changes());
+            coeff.modify_params(|p| p.hp_band.cutoff_hz = params.hp_band.cutoff_hz * scale);
+            black_box(coeff.flush_param_changes());
+            for i in 0..8 {
+                coeff.modify_params(|p| p.bands[i].cutoff_hz = params.bands[i].cutoff_hz * scale);
+                black_box(coeff.flush_param_changes());
+            }
+        });
+    });
+
+    group.finish();
+}
+
+/// White-ish noise.
+fn input() -> Vec<f32> {
+    let mut seed = 1u32;
+
+    (0..FRAMES)
+        .map(|_| {
+            // A simple LCG, so that the input is not trivially predictable.
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            (seed >> 8) as f32 / (1 << 23) as f32 - 1.0
+        })
+        .collect()
+}
+
+criterion_group!(benches, eq, eq_low_cascade, coeff_flush);
+criterion_main!(benches);
@@ -1 +1,6 @@
 pub mod parametric_eq;
+pub mod prelude;
+#[cfg(test)]
+mod test_util;
+#[cfg(feature = "wasm")]
+pub mod wasm;
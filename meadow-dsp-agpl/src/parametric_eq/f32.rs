@@ -1,6 +1,39 @@
+//! A fully-featured parametric EQ.
+//!
+//! # Bypass
+//!
+//! If every band (including the lowpass and highpass bands) is disabled and
+//! the input and output gains are at 0 dB, then `process` returns early and
+//! leaves the buffers bit-identical to the input.
+//!
+//! # Real-time safety
+//!
+//! None of the `process*` methods of the processors (or
+//! [`mono::process_channel`]) allocate, lock, or block, so they are safe to
+//! call on the audio thread. The same goes for setting parameters and
+//! flushing parameter changes. All filter states and coefficients are stored
+//! inline.
+//!
+//! Anything which needs extra buffers either takes scratch buffers from the
+//! caller (e.g. `process_mix`) or allocates them once up front in `new` (e.g.
+//! [`stereo::crossfade::CrossfadingEq`]). The only things which allocate
+//! after construction are [`matching::match_response`],
+//! [`coeff::MeadowEqDspCoeff::pack_coeffs_into`] (if the `Vec` does not
+//! have enough capacity), and adding bands to a
+//! [`dynamic::MeadowEqDspDynamic`] beyond its capacity, which are not meant
+//! for the audio thread.
+
+pub mod atomic;
 pub mod coeff;
+pub mod dynamic;
+mod listen;
+pub mod matching;
+pub mod mono;
+pub mod shared;
 pub mod state;
 pub mod stereo;
+#[cfg(test)]
+mod tests;
 
 pub const DEFAULT_Q: f32 = meadow_dsp_mit::filter::svf::f64::Q_BUTTERWORTH_ORD2 as f32;
 
@@ -12,6 +45,8 @@ pub enum FilterOrder {
     X4,
     X6,
     X8,
+    X12,
+    X16,
 }
 
 impl FilterOrder {
@@ -21,7 +56,22 @@ impl FilterOrder {
             1 => Self::X2,
             2 => Self::X4,
             3 => Self::X6,
-            _ => Self::X8,
+            4 => Self::X8,
+            5 => Self::X12,
+            _ => Self::X16,
+        }
+    }
+
+    /// The number of SVF filters used to implement this order.
+    pub fn num_svf_filters(&self) -> usize {
+        match self {
+            Self::X1 => 0,
+            Self::X2 => 1,
+            Self::X4 => 2,
+            Self::X6 => 3,
+            Self::X8 => 4,
+            Self::X12 => 6,
+            Self::X16 => 8,
         }
     }
 }
@@ -32,8 +82,21 @@ pub enum BandType {
     Bell = 0,
     LowShelf,
     HighShelf,
+    /// A notch with a true zero at `cutoff_hz`, whose width is set by `q`.
+    ///
+    /// This is deep enough for hum removal (use one band per harmonic). A
+    /// 60 Hz sine is attenuated by more than 70 dB even with a `q` of `30.0`,
+    /// and by more than 100 dB with
+    /// [`MeadowEqDspStereoLinkedF64Coeff`](stereo::f64_coeff::MeadowEqDspStereoLinkedF64Coeff).
     Notch,
     Allpass,
+    /// A notch with a broadband makeup gain so that the perceived loudness
+    /// stays roughly constant. (This is a heuristic.)
+    NotchCompensated,
+    /// A resonant filter which morphs from a lowpass through a bandpass to a
+    /// highpass as [`BandParams::morph`] goes from `0.0` to `1.0`. `q` sets
+    /// the resonance, and `gain_db` is ignored.
+    Multimode,
 }
 
 impl BandType {
@@ -43,7 +106,56 @@ impl BandType {
             1 => Self::LowShelf,
             2 => Self::HighShelf,
             3 => Self::Notch,
-            _ => Self::Allpass,
+            4 => Self::Allpass,
+            5 => Self::NotchCompensated,
+            _ => Self::Multimode,
+        }
+    }
+}
+
+/// How the bandwidth of a [`BandType::Bell`] band relates to its gain.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum QMode {
+    /// The bandwidth is measured at half of the gain (in decibels), so the
+    /// bell looks narrower the more it is boosted or cut.
+    #[default]
+    Proportional = 0,
+    /// The bandwidth is measured 3 dB below the peak (or above the dip), so
+    /// the width of the bell stays the same as the gain changes. (For gains
+    /// below +/-6 dB this behaves like [`QMode::Proportional`].)
+    Constant,
+}
+
+impl QMode {
+    pub fn from_u32(v: u32) -> Self {
+        match v {
+            0 => Self::Proportional,
+            _ => Self::Constant,
+        }
+    }
+}
+
+/// How the bands (not including the lowpass and highpass bands) are combined.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BandRouting {
+    /// Each band filters the output of the previous band.
+    #[default]
+    Series = 0,
+    /// Each band filters the same input, and the differences between the
+    /// filtered signals and the input are summed together with the input
+    /// (the "graphic EQ" style). The lowpass and highpass bands are still
+    /// applied in series before the bands.
+    ///
+    /// Note that overlapping bands interact differently than in series mode,
+    /// i.e. two overlapping +6 dB bells do not add up to +12 dB.
+    Parallel,
+}
+
+impl BandRouting {
+    pub fn from_u32(v: u32) -> Self {
+        match v {
+            0 => Self::Series,
+            _ => Self::Parallel,
         }
     }
 }
@@ -52,9 +164,30 @@ impl BandType {
 pub struct BandParams {
     pub enabled: bool,
     pub band_type: BandType,
+    /// Must be positive. Cutoffs at or above the Nyquist frequency are
+    /// clamped to just below it (see [`coeff::MAX_CUTOFF_RATIO`]).
+    ///
+    /// Since the cutoff is prewarped, the center of a bell or notch and the
+    /// midpoint of a shelf land exactly on `cutoff_hz` at any sample rate,
+    /// even close to the Nyquist frequency. What changes close to the Nyquist
+    /// frequency is the shape of the response above the cutoff, which is
+    /// squeezed into the remaining range ("cramping"). For example, a +6 dB
+    /// bell at 20 kHz with a Q of `1.0` at 44.1 kHz still peaks at 20 kHz,
+    /// but its lower half-gain point is at about 18.8 kHz instead of 12.4 kHz.
+    /// [`BandParams::actual_cutoff_hz`] measures where the cutoff lands.
     pub cutoff_hz: f32,
+    /// The bandwidth of bells and notches. For shelves, raising this above
+    /// [`DEFAULT_Q`] makes the transition steeper and adds a resonant
+    /// overshoot.
     pub q: f32,
     pub gain_db: f32,
+    /// How `q` relates to the gain. This only has an effect for
+    /// [`BandType::Bell`].
+    pub q_mode: QMode,
+    /// The position of a [`BandType::Multimode`] band, from `0.0` (lowpass)
+    /// through `0.5` (bandpass) to `1.0` (highpass). This has no effect for
+    /// other band types.
+    pub morph: f32,
 }
 
 impl Default for BandParams {
@@ -63,18 +196,120 @@ impl Default for BandParams {
             enabled: false,
             band_type: BandType::Notch,
             cutoff_hz: 1000.0,
-            q: DEFAULT_Q as f32,
+            q: DEFAULT_Q,
             gain_db: 0.0,
+            q_mode: QMode::default(),
+            morph: 0.0,
         }
     }
 }
 
+impl BandParams {
+    pub const CUTOFF_MIN_HZ: f32 = 10.0;
+    /// This lies below the Nyquist frequency for sample rates of 44.1 kHz
+    /// and above. For lower sample rates, the cutoff is also clamped to just
+    /// below the Nyquist frequency when the coefficients are computed.
+    pub const CUTOFF_MAX_HZ: f32 = 21_480.0;
+    pub const Q_MIN: f32 = 0.1;
+    pub const Q_MAX: f32 = 40.0;
+    pub const GAIN_MIN_DB: f32 = -24.0;
+    pub const GAIN_MAX_DB: f32 = 24.0;
+
+    /// Clamp the cutoff, Q, gain, and morph to their valid ranges.
+    pub fn clamp(&mut self) {
+        self.cutoff_hz = self
+            .cutoff_hz
+            .clamp(Self::CUTOFF_MIN_HZ, Self::CUTOFF_MAX_HZ);
+        self.q = self.q.clamp(Self::Q_MIN, Self::Q_MAX);
+        self.gain_db = self.gain_db.clamp(Self::GAIN_MIN_DB, Self::GAIN_MAX_DB);
+        self.morph = self.morph.clamp(0.0, 1.0);
+    }
+
+    /// The frequency which `cutoff_hz` actually lands on at the given sample
+    /// rate, measured from the magnitude response of the band: the peak or
+    /// dip of a bell or notch, and the half-gain point of a shelf.
+    ///
+    /// Far below the Nyquist frequency (below an eighth of the sample rate)
+    /// this returns `cutoff_hz` without measuring anything. The same goes
+    /// for bands without such a point (allpasses, multimode bands, and bells
+    /// and shelves with a gain of 0 dB).
+    pub fn actual_cutoff_hz(&self, sample_rate: f64) -> f32 {
+        /// The ratio between the two frequencies used to find the slope.
+        const SLOPE_RATIO: f64 = 1.0 + 1e-6;
+
+        let cutoff_hz = self.cutoff_hz as f64;
+        if cutoff_hz < sample_rate * (1.0 / 8.0) {
+            return self.cutoff_hz;
+        }
+
+        let sample_rate_recip = sample_rate.recip();
+        let coeff = coeff::band_coeff(self, sample_rate_recip);
+        let db = |freq_hz: f64| {
+            meadow_dsp_mit::decibel::f64::amp_to_db(coeff.magnitude(freq_hz, sample_rate_recip))
+        };
+
+        let actual_hz = match self.band_type {
+            BandType::Bell | BandType::Notch | BandType::NotchCompensated => {
+                let is_boost = match self.band_type {
+                    BandType::Bell if self.gain_db == 0.0 => return self.cutoff_hz,
+                    BandType::Bell => self.gain_db > 0.0,
+                    _ => false,
+                };
+
+                // Below the peak the response rises, and below the dip it
+                // falls.
+                coeff::bisect_log_hz(sample_rate_recip, |freq_hz| {
+                    (db(freq_hz * SLOPE_RATIO) > db(freq_hz / SLOPE_RATIO)) == is_boost
+                })
+            }
+            BandType::LowShelf | BandType::HighShelf => {
+                if self.gain_db == 0.0 {
+                    return self.cutoff_hz;
+                }
+
+                let half_gain_db = 0.5 * self.gain_db as f64;
+                let low_side_is_higher =
+                    (self.band_type == BandType::LowShelf) == (self.gain_db > 0.0);
+
+                coeff::bisect_log_hz(sample_rate_recip, |freq_hz| {
+                    (db(freq_hz) > half_gain_db) == low_side_is_higher
+                })
+            }
+            BandType::Allpass | BandType::Multimode => return self.cutoff_hz,
+        };
+
+        actual_hz as f32
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct LpOrHpBandParams {
     pub enabled: bool,
+    /// Must be positive. Cutoffs at or above the Nyquist frequency are
+    /// clamped to just below it (see [`coeff::MAX_CUTOFF_RATIO`]).
+    ///
+    /// As with [`BandParams::cutoff_hz`], the cutoff is exact at any sample
+    /// rate, while the slope above it gets steeper close to the Nyquist
+    /// frequency. The -3 dB point only differs from `cutoff_hz` because of
+    /// the Q (see `matched_cutoff` and
+    /// [`MeadowEqDspCoeff::lp_band_corner_hz`](coeff::MeadowEqDspCoeff::lp_band_corner_hz)).
     pub cutoff_hz: f32,
     pub q: f32,
     pub order: FilterOrder,
+    /// If `true`, then the cutoff is adjusted so that the -3 dB point lands
+    /// exactly at `cutoff_hz` for any `q`. (By default this only happens for
+    /// the Butterworth `q` of [`DEFAULT_Q`].)
+    ///
+    /// This has no effect for [`FilterOrder::X1`].
+    pub matched_cutoff: bool,
+    /// If `Some` and `order` is [`FilterOrder::X1`], then the band is a
+    /// gentle first-order (6 dB/octave) shelf with this gain (usually
+    /// negative) instead of a lowpass or highpass: the lowpass band becomes a
+    /// high shelf, and the highpass band a low shelf. `cutoff_hz` is then the
+    /// midpoint of the transition.
+    ///
+    /// This has no effect for higher orders.
+    pub shelf_gain_db: Option<f32>,
 }
 
 impl Default for LpOrHpBandParams {
@@ -82,8 +317,32 @@ impl Default for LpOrHpBandParams {
         Self {
             enabled: false,
             cutoff_hz: 21_480.0,
-            q: DEFAULT_Q as f32,
+            q: DEFAULT_Q,
             order: FilterOrder::X2,
+            matched_cutoff: false,
+            shelf_gain_db: None,
+        }
+    }
+}
+
+impl LpOrHpBandParams {
+    pub const CUTOFF_MIN_HZ: f32 = BandParams::CUTOFF_MIN_HZ;
+    /// The same as the default cutoff of the lowpass band. See
+    /// [`BandParams::CUTOFF_MAX_HZ`].
+    pub const CUTOFF_MAX_HZ: f32 = BandParams::CUTOFF_MAX_HZ;
+    pub const Q_MIN: f32 = 0.1;
+    pub const Q_MAX: f32 = 10.0;
+    pub const SHELF_GAIN_MIN_DB: f32 = -24.0;
+    pub const SHELF_GAIN_MAX_DB: f32 = 24.0;
+
+    /// Clamp the cutoff, Q, and shelf gain to their valid ranges.
+    pub fn clamp(&mut self) {
+        self.cutoff_hz = self
+            .cutoff_hz
+            .clamp(Self::CUTOFF_MIN_HZ, Self::CUTOFF_MAX_HZ);
+        self.q = self.q.clamp(Self::Q_MIN, Self::Q_MAX);
+        if let Some(gain_db) = &mut self.shelf_gain_db {
+            *gain_db = gain_db.clamp(Self::SHELF_GAIN_MIN_DB, Self::SHELF_GAIN_MAX_DB);
         }
     }
 }
@@ -96,15 +355,343 @@ pub struct EqParams<const NUM_BANDS: usize> {
     pub bands: [BandParams; NUM_BANDS],
 }
 
+impl<const NUM_BANDS: usize> EqParams<NUM_BANDS> {
+    /// Iterate over all bands as [`AnyBand`]s, in the order of the lowpass
+    /// band, the highpass band, and then the rest of the bands.
+    pub fn any_bands(&self) -> impl Iterator<Item = AnyBand> + '_ {
+        [
+            AnyBand::Lowpass(self.lp_band),
+            AnyBand::Highpass(self.hp_band),
+        ]
+        .into_iter()
+        .chain(
+            self.bands
+                .iter()
+                .enumerate()
+                .map(|(i, b)| AnyBand::Band(i, *b)),
+        )
+    }
+
+    /// Like [`EqParams::any_bands`], but only the enabled bands (including
+    /// the lowpass and highpass bands).
+    pub fn enabled_any_bands(&self) -> impl Iterator<Item = AnyBand> + '_ {
+        self.any_bands().filter(AnyBand::enabled)
+    }
+
+    /// The number of enabled bands (not including the lowpass and highpass
+    /// bands).
+    pub fn num_enabled_bands(&self) -> usize {
+        self.bands.iter().filter(|b| b.enabled).count()
+    }
+
+    /// Iterate over the enabled bands (not including the lowpass and highpass
+    /// bands) along with their indices into [`EqParams::bands`]. See
+    /// [`EqParams::enabled_any_bands`] to include the lowpass and highpass
+    /// bands.
+    pub fn enabled_bands(&self) -> impl Iterator<Item = (usize, &BandParams)> + '_ {
+        self.bands.iter().enumerate().filter(|(_, b)| b.enabled)
+    }
+
+    /// Write the given band back into the slot it refers to.
+    ///
+    /// # Panics
+    /// Panics if the band is an [`AnyBand::Band`] with an index
+    /// `>= NUM_BANDS`.
+    pub fn set_any_band(&mut self, band: AnyBand) {
+        match band {
+            AnyBand::Lowpass(params) => self.lp_band = params,
+            AnyBand::Highpass(params) => self.hp_band = params,
+            AnyBand::Band(i, params) => self.bands[i] = params,
+        }
+    }
+
+    /// Clamp the parameters of every band to their valid ranges. See
+    /// [`BandParams::clamp`] and [`LpOrHpBandParams::clamp`].
+    pub fn clamp(&mut self) {
+        self.lp_band.clamp();
+        self.hp_band.clamp();
+        for band in self.bands.iter_mut() {
+            band.clamp();
+        }
+    }
+
+    /// How the lowpass and highpass bands combine, or `None` if they are not
+    /// both enabled.
+    pub fn lp_hp_mode(&self) -> Option<LpHpMode> {
+        if !self.lp_band.enabled || !self.hp_band.enabled {
+            return None;
+        }
+
+        let lp_cutoff_hz = self.lp_band.cutoff_hz;
+        let hp_cutoff_hz = self.hp_band.cutoff_hz;
+
+        Some(if lp_cutoff_hz <= hp_cutoff_hz {
+            LpHpMode::BandReject
+        } else if lp_cutoff_hz < hp_cutoff_hz * 2.0 {
+            LpHpMode::Overlap
+        } else {
+            LpHpMode::Bandpass
+        })
+    }
+}
+
 impl<const NUM_BANDS: usize> Default for EqParams<NUM_BANDS> {
     fn default() -> Self {
         Self {
-            lp_band: LpOrHpBandParams {
+            lp_band: LpOrHpBandParams::default(),
+            hp_band: LpOrHpBandParams {
                 cutoff_hz: 20.0,
                 ..Default::default()
             },
-            hp_band: LpOrHpBandParams::default(),
             bands: [BandParams::default(); NUM_BANDS],
         }
     }
 }
+
+impl<const NUM_BANDS: usize> EqParams<NUM_BANDS> {
+    /// Start building a set of parameters. See [`EqParamsBuilder`].
+    pub fn builder() -> EqParamsBuilder<NUM_BANDS> {
+        EqParamsBuilder::new()
+    }
+}
+
+/// Builds an [`EqParams`] by chaining calls, e.g.
+/// `EqParams::<8>::builder().highpass(30.0, FilterOrder::X4).bell(250.0, 1.0, -3.0).build()`.
+///
+/// Each band method enables the next unused slot in [`EqParams::bands`], in
+/// order starting from index `0`. All other parameters keep their defaults.
+#[derive(Debug, Clone, Copy)]
+pub struct EqParamsBuilder<const NUM_BANDS: usize> {
+    params: EqParams<NUM_BANDS>,
+    next_band: usize,
+}
+
+impl<const NUM_BANDS: usize> EqParamsBuilder<NUM_BANDS> {
+    pub fn new() -> Self {
+        Self {
+            params: EqParams::default(),
+            next_band: 0,
+        }
+    }
+
+    /// Enable the lowpass band with the given cutoff and order.
+    pub fn lowpass(mut self, cutoff_hz: f32, order: FilterOrder) -> Self {
+        self.params.lp_band = LpOrHpBandParams {
+            enabled: true,
+            cutoff_hz,
+            order,
+            ..Default::default()
+        };
+        self
+    }
+
+    /// Enable the highpass band with the given cutoff and order.
+    pub fn highpass(mut self, cutoff_hz: f32, order: FilterOrder) -> Self {
+        self.params.hp_band = LpOrHpBandParams {
+            enabled: true,
+            cutoff_hz,
+            order,
+            ..Default::default()
+        };
+        self
+    }
+
+    /// Add a [`BandType::Bell`] band.
+    ///
+    /// # Panics
+    /// Panics if all `NUM_BANDS` slots are already used.
+    pub fn bell(self, cutoff_hz: f32, q: f32, gain_db: f32) -> Self {
+        self.band_with_gain(BandType::Bell, cutoff_hz, q, gain_db)
+    }
+
+    /// Add a [`BandType::LowShelf`] band.
+    ///
+    /// # Panics
+    /// Panics if all `NUM_BANDS` slots are already used.
+    pub fn low_shelf(self, cutoff_hz: f32, q: f32, gain_db: f32) -> Self {
+        self.band_with_gain(BandType::LowShelf, cutoff_hz, q, gain_db)
+    }
+
+    /// Add a [`BandType::HighShelf`] band.
+    ///
+    /// # Panics
+    /// Panics if all `NUM_BANDS` slots are already used.
+    pub fn high_shelf(self, cutoff_hz: f32, q: f32, gain_db: f32) -> Self {
+        self.band_with_gain(BandType::HighShelf, cutoff_hz, q, gain_db)
+    }
+
+    /// Add a [`BandType::Notch`] band.
+    ///
+    /// # Panics
+    /// Panics if all `NUM_BANDS` slots are already used.
+    pub fn notch(self, cutoff_hz: f32, q: f32) -> Self {
+        self.band_with_gain(BandType::Notch, cutoff_hz, q, 0.0)
+    }
+
+    /// Add a [`BandType::Allpass`] band.
+    ///
+    /// # Panics
+    /// Panics if all `NUM_BANDS` slots are already used.
+    pub fn allpass(self, cutoff_hz: f32, q: f32) -> Self {
+        self.band_with_gain(BandType::Allpass, cutoff_hz, q, 0.0)
+    }
+
+    /// Add a [`BandType::Multimode`] band.
+    ///
+    /// # Panics
+    /// Panics if all `NUM_BANDS` slots are already used.
+    pub fn multimode(self, cutoff_hz: f32, q: f32, morph: f32) -> Self {
+        self.band(BandParams {
+            band_type: BandType::Multimode,
+            cutoff_hz,
+            q,
+            morph,
+            ..Default::default()
+        })
+    }
+
+    /// Add a band with arbitrary parameters. The band is enabled regardless of
+    /// `params.enabled`.
+    ///
+    /// # Panics
+    /// Panics if all `NUM_BANDS` slots are already used.
+    pub fn band(mut self, params: BandParams) -> Self {
+        assert!(
+            self.next_band < NUM_BANDS,
+            "all {NUM_BANDS} band slots are already used"
+        );
+
+        self.params.bands[self.next_band] = BandParams {
+            enabled: true,
+            ..params
+        };
+        self.next_band += 1;
+        self
+    }
+
+    pub fn build(self) -> EqParams<NUM_BANDS> {
+        self.params
+    }
+
+    fn band_with_gain(self, band_type: BandType, cutoff_hz: f32, q: f32, gain_db: f32) -> Self {
+        self.band(BandParams {
+            band_type,
+            cutoff_hz,
+            q,
+            gain_db,
+            ..Default::default()
+        })
+    }
+}
+
+impl<const NUM_BANDS: usize> Default for EqParamsBuilder<NUM_BANDS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// How the lowpass and highpass bands combine when they are both enabled.
+///
+/// The two bands are always applied in series, so the combined response is
+/// simply the product of both responses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LpHpMode {
+    /// The lowpass cutoff is at least an octave above the highpass cutoff,
+    /// leaving a passband between the two cutoffs.
+    Bandpass,
+    /// The lowpass cutoff is above the highpass cutoff, but by less than an
+    /// octave. The slopes of the two bands overlap, so even the loudest point
+    /// between the two cutoffs is attenuated.
+    Overlap,
+    /// The lowpass cutoff is at or below the highpass cutoff. Note that since
+    /// the bands are in series, this attenuates the whole spectrum rather
+    /// than only rejecting the band between the two cutoffs.
+    BandReject,
+}
+
+/// Any band of the EQ, tagged with the slot it belongs to. This allows UIs to
+/// treat all bands uniformly.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AnyBand {
+    Lowpass(LpOrHpBandParams),
+    Highpass(LpOrHpBandParams),
+    /// A band with the given index into [`EqParams::bands`].
+    Band(usize, BandParams),
+}
+
+impl AnyBand {
+    pub fn enabled(&self) -> bool {
+        match self {
+            Self::Lowpass(p) | Self::Highpass(p) => p.enabled,
+            Self::Band(_, p) => p.enabled,
+        }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        match self {
+            Self::Lowpass(p) | Self::Highpass(p) => p.enabled = enabled,
+            Self::Band(_, p) => p.enabled = enabled,
+        }
+    }
+
+    pub fn cutoff_hz(&self) -> f32 {
+        match self {
+            Self::Lowpass(p) | Self::Highpass(p) => p.cutoff_hz,
+            Self::Band(_, p) => p.cutoff_hz,
+        }
+    }
+
+    pub fn set_cutoff_hz(&mut self, cutoff_hz: f32) {
+        match self {
+            Self::Lowpass(p) | Self::Highpass(p) => p.cutoff_hz = cutoff_hz,
+            Self::Band(_, p) => p.cutoff_hz = cutoff_hz,
+        }
+    }
+
+    pub fn q(&self) -> f32 {
+        match self {
+            Self::Lowpass(p) | Self::Highpass(p) => p.q,
+            Self::Band(_, p) => p.q,
+        }
+    }
+
+    pub fn set_q(&mut self, q: f32) {
+        match self {
+            Self::Lowpass(p) | Self::Highpass(p) => p.q = q,
+            Self::Band(_, p) => p.q = q,
+        }
+    }
+
+    /// The gain of the band, or `None` for the lowpass and highpass bands.
+    pub fn gain_db(&self) -> Option<f32> {
+        match self {
+            Self::Lowpass(_) | Self::Highpass(_) => None,
+            Self::Band(_, p) => Some(p.gain_db),
+        }
+    }
+
+    /// Set the gain of the band. This does nothing for the lowpass and
+    /// highpass bands.
+    pub fn set_gain_db(&mut self, gain_db: f32) {
+        if let Self::Band(_, p) = self {
+            p.gain_db = gain_db;
+        }
+    }
+
+    /// The type of the band, or `None` for the lowpass and highpass bands.
+    pub fn band_type(&self) -> Option<BandType> {
+        match self {
+            Self::Lowpass(_) | Self::Highpass(_) => None,
+            Self::Band(_, p) => Some(p.band_type),
+        }
+    }
+
+    /// The order of the band, or `None` if this is not the lowpass or the
+    /// highpass band.
+    pub fn order(&self) -> Option<FilterOrder> {
+        match self {
+            Self::Lowpass(p) | Self::Highpass(p) => Some(p.order),
+            Self::Band(_, _) => None,
+        }
+    }
+}
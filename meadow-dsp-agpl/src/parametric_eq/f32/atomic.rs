@@ -0,0 +1,252 @@
+use std::sync::atomic::{fence, AtomicBool, AtomicU32, Ordering};
+
+use super::{BandParams, BandType, QMode};
+
+/// The parameters of a band stored in atomics, so that they can be set from
+/// one thread (i.e. the GUI thread or a host automation thread) and read from
+/// the audio thread without a mutex.
+///
+/// The `f32` fields are stored as their bit patterns, and the enums as their
+/// discriminants.
+///
+/// Every write bumps a sequence counter (a "seqlock"), so
+/// [`AtomicBandParams::load`] always returns a consistent snapshot of all of
+/// the fields, never a mix of an old and a new write. The audio thread should
+/// use [`AtomicBandParams::load_if_changed`], which never waits: it cheaply
+/// checks for changes, and returns `None` while a write is in progress so
+/// that the change is picked up on the next call instead. Pass the result to
+/// e.g.
+/// [`MeadowEqDspCoeff::modify_params`](super::coeff::MeadowEqDspCoeff::modify_params).
+///
+/// Writers never wait on readers, only on other writers. `load` spins until
+/// no write is in progress, so if the writing thread is preempted halfway
+/// through a write, `load` waits until that thread is scheduled again.
+pub struct AtomicBandParams {
+    /// Odd while a write is in progress.
+    seq: AtomicU32,
+
+    enabled: AtomicBool,
+    band_type: AtomicU32,
+    cutoff_hz: AtomicU32,
+    q: AtomicU32,
+    gain_db: AtomicU32,
+    q_mode: AtomicU32,
+    morph: AtomicU32,
+}
+
+impl AtomicBandParams {
+    pub fn new(params: BandParams) -> Self {
+        Self {
+            seq: AtomicU32::new(0),
+            enabled: AtomicBool::new(params.enabled),
+            band_type: AtomicU32::new(params.band_type as u32),
+            cutoff_hz: AtomicU32::new(params.cutoff_hz.to_bits()),
+            q: AtomicU32::new(params.q.to_bits()),
+            gain_db: AtomicU32::new(params.gain_db.to_bits()),
+            q_mode: AtomicU32::new(params.q_mode as u32),
+            morph: AtomicU32::new(params.morph.to_bits()),
+        }
+    }
+
+    /// Load a consistent snapshot of the parameters, spinning while a write
+    /// is in progress. See [`AtomicBandParams::load_if_changed`] for a
+    /// version which never waits.
+    pub fn load(&self) -> BandParams {
+        loop {
+            if let Some((params, _)) = self.try_load() {
+                return params;
+            }
+
+            std::hint::spin_loop();
+        }
+    }
+
+    /// Load the parameters only if they have been written to since the last
+    /// call, where `last_seq` is the value stored by the previous call. (Start
+    /// with `last_seq` at `u32::MAX` to always load the first time.)
+    ///
+    /// This returns `None` without waiting if a write is in progress (or
+    /// happens during the read), and leaves `last_seq` alone so that the next
+    /// call loads the parameters instead.
+    pub fn load_if_changed(&self, last_seq: &mut u32) -> Option<BandParams> {
+        if self.seq.load(Ordering::Relaxed) == *last_seq {
+            return None;
+        }
+
+        let (params, seq) = self.try_load()?;
+        *last_seq = seq;
+        Some(params)
+    }
+
+    /// Store all of the parameters at once.
+    pub fn store(&self, params: &BandParams) {
+        self.write(|s| {
+            s.enabled.store(params.enabled, Ordering::Relaxed);
+            s.band_type
+                .store(params.band_type as u32, Ordering::Relaxed);
+            s.cutoff_hz
+                .store(params.cutoff_hz.to_bits(), Ordering::Relaxed);
+            s.q.store(params.q.to_bits(), Ordering::Relaxed);
+            s.gain_db.store(params.gain_db.to_bits(), Ordering::Relaxed);
+            s.q_mode.store(params.q_mode as u32, Ordering::Relaxed);
+            s.morph.store(params.morph.to_bits(), Ordering::Relaxed);
+        });
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.write(|s| s.enabled.store(enabled, Ordering::Relaxed));
+    }
+
+    pub fn set_band_type(&self, band_type: BandType) {
+        self.write(|s| s.band_type.store(band_type as u32, Ordering::Relaxed));
+    }
+
+    pub fn set_cutoff_hz(&self, cutoff_hz: f32) {
+        self.write(|s| s.cutoff_hz.store(cutoff_hz.to_bits(), Ordering::Relaxed));
+    }
+
+    pub fn set_q(&self, q: f32) {
+        self.write(|s| s.q.store(q.to_bits(), Ordering::Relaxed));
+    }
+
+    pub fn set_gain_db(&self, gain_db: f32) {
+        self.write(|s| s.gain_db.store(gain_db.to_bits(), Ordering::Relaxed));
+    }
+
+    pub fn set_q_mode(&self, q_mode: QMode) {
+        self.write(|s| s.q_mode.store(q_mode as u32, Ordering::Relaxed));
+    }
+
+    pub fn set_morph(&self, morph: f32) {
+        self.write(|s| s.morph.store(morph.to_bits(), Ordering::Relaxed));
+    }
+
+    fn write(&self, f: impl FnOnce(&Self)) {
+        // Mark the write as in progress by making the sequence odd. If another
+        // writer is already in progress, wait for it to finish first.
+        let mut seq = self.seq.load(Ordering::Relaxed);
+        loop {
+            if seq % 2 == 1 {
+                std::hint::spin_loop();
+                seq = self.seq.load(Ordering::Relaxed);
+                continue;
+            }
+
+            match self.seq.compare_exchange_weak(
+                seq,
+                seq.wrapping_add(1),
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => break,
+                Err(s) => seq = s,
+            }
+        }
+        fence(Ordering::Release);
+
+        f(self);
+
+        self.seq.store(seq.wrapping_add(2), Ordering::Release);
+    }
+
+    /// Returns `None` if a write happened during the read.
+    fn try_load(&self) -> Option<(BandParams, u32)> {
+        let seq = self.seq.load(Ordering::Acquire);
+        if seq % 2 == 1 {
+            return None;
+        }
+
+        let params = BandParams {
+            enabled: self.enabled.load(Ordering::Relaxed),
+            band_type: BandType::from_u32(self.band_type.load(Ordering::Relaxed)),
+            cutoff_hz: f32::from_bits(self.cutoff_hz.load(Ordering::Relaxed)),
+            q: f32::from_bits(self.q.load(Ordering::Relaxed)),
+            gain_db: f32::from_bits(self.gain_db.load(Ordering::Relaxed)),
+            q_mode: QMode::from_u32(self.q_mode.load(Ordering::Relaxed)),
+            morph: f32::from_bits(self.morph.load(Ordering::Relaxed)),
+        };
+
+        fence(Ordering::Acquire);
+        (self.seq.load(Ordering::Relaxed) == seq).then_some((params, seq))
+    }
+}
+
+impl Default for AtomicBandParams {
+    fn default() -> Self {
+        Self::new(BandParams::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parameters whose `f32` fields all hold `i`, so that a mix of two
+    /// writes is easy to spot.
+    fn params(i: u32) -> BandParams {
+        BandParams {
+            enabled: i.is_multiple_of(2),
+            band_type: BandType::from_u32(i % 7),
+            cutoff_hz: i as f32,
+            q: i as f32,
+            gain_db: i as f32,
+            q_mode: QMode::from_u32(i % 2),
+            morph: i as f32,
+        }
+    }
+
+    fn assert_consistent(p: &BandParams) {
+        let i = p.cutoff_hz as u32;
+        assert_eq!(*p, params(i), "torn read");
+    }
+
+    #[test]
+    fn reads_are_never_torn() {
+        const WRITES: u32 = 200_000;
+
+        let atomic = AtomicBandParams::new(params(0));
+        let done = AtomicBool::new(false);
+
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                for i in 1..=WRITES {
+                    atomic.store(&params(i));
+                }
+                done.store(true, Ordering::Release);
+            });
+
+            let mut last_seq = u32::MAX;
+            let mut last_i = 0;
+            while !done.load(Ordering::Acquire) {
+                assert_consistent(&atomic.load());
+
+                if let Some(p) = atomic.load_if_changed(&mut last_seq) {
+                    assert_consistent(&p);
+
+                    let i = p.cutoff_hz as u32;
+                    assert!(i >= last_i, "went back from write {last_i} to {i}");
+                    last_i = i;
+                }
+            }
+        });
+
+        let mut last_seq = u32::MAX;
+        assert_eq!(atomic.load_if_changed(&mut last_seq), Some(params(WRITES)));
+        assert_eq!(atomic.load_if_changed(&mut last_seq), None);
+    }
+
+    #[test]
+    fn load_if_changed_does_not_wait_for_a_write() {
+        let atomic = AtomicBandParams::new(params(0));
+        let mut last_seq = u32::MAX;
+
+        // Simulate a write which is in progress.
+        atomic.seq.store(1, Ordering::Relaxed);
+        assert_eq!(atomic.load_if_changed(&mut last_seq), None);
+        assert_eq!(last_seq, u32::MAX);
+
+        atomic.seq.store(2, Ordering::Relaxed);
+        assert_eq!(atomic.load_if_changed(&mut last_seq), Some(params(0)));
+        assert_eq!(last_seq, 2);
+    }
+}
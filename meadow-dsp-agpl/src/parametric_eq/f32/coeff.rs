@@ -1,19 +1,154 @@
 use arrayvec::ArrayVec;
-use meadow_dsp_mit::filter::{
-    one_pole_iir::{f32::OnePoleIirCoeff, f64::OnePoleIirCoeff as OnePoleIirCoeffF64},
-    svf::{f32::SvfCoeff, f64::SvfCoeff as SvfCoeffF64},
+use meadow_dsp_mit::{
+    decibel::f64::amp_to_db,
+    filter::{
+        one_pole_iir::{f32::OnePoleIirCoeff, f64::OnePoleIirCoeff as OnePoleIirCoeffF64},
+        svf::{
+            f32::SvfCoeff,
+            f64::{prewarp_fast, SvfCoeff as SvfCoeffF64},
+        },
+    },
 };
 
-use super::{BandParams, BandType, EqParams, FilterOrder, LpOrHpBandParams};
+use super::{BandParams, BandRouting, BandType, EqParams, FilterOrder, LpOrHpBandParams, QMode};
 
 pub const MAX_ONE_POLE_FILTERS: usize = 2;
 
+/// The maximum number of SVF filters in a lowpass or highpass band (for
+/// [`FilterOrder::X16`]).
+pub const MAX_LP_HP_SVF_FILTERS: usize = 8;
+
+/// The default duration of the fade applied when a band is disabled, in
+/// seconds. See [`MeadowEqDspCoeff::set_band_fade_secs`].
+pub const DEFAULT_BAND_FADE_SECS: f64 = 0.005;
+
+/// While a band is fading out, processors should process in chunks of at
+/// most this many frames (calling [`MeadowEqDspCoeff::advance_band_fades`]
+/// before each chunk) so that the fade is smooth.
+pub const BAND_FADE_STEP_FRAMES: usize = 4;
+
+/// The number of frames between the coefficient updates of
+/// [`MeadowEqDspMono::process_with_cutoff_mod`](super::mono::MeadowEqDspMono::process_with_cutoff_mod)
+/// and its stereo counterpart.
+pub const CUTOFF_MOD_STEP_FRAMES: usize = 16;
+
+/// Cutoffs are clamped to at most this fraction of the sample rate, so that
+/// the coefficients stay stable when a cutoff is at or above the Nyquist
+/// frequency (e.g. the default lowpass band at a sample rate of 32 kHz).
+pub const MAX_CUTOFF_RATIO: f64 = 0.49;
+
+/// The precision that the filter coefficients are stored in. (The
+/// coefficients are always computed in f64.)
+pub trait CoeffPrecision {
+    type OnePoleIirCoeff: Copy;
+    type SvfCoeff: Copy;
+
+    fn one_pole_iir_coeff(coeff: OnePoleIirCoeffF64) -> Self::OnePoleIirCoeff;
+    fn svf_coeff(coeff: SvfCoeffF64) -> Self::SvfCoeff;
+
+    fn one_pole_iir_coeff_to_f64(coeff: Self::OnePoleIirCoeff) -> OnePoleIirCoeffF64;
+    fn svf_coeff_to_f64(coeff: Self::SvfCoeff) -> SvfCoeffF64;
+}
+
+/// Store the filter coefficients in f32.
+#[derive(Clone, Copy)]
+pub struct F32Coeffs;
+
+impl CoeffPrecision for F32Coeffs {
+    type OnePoleIirCoeff = OnePoleIirCoeff;
+    type SvfCoeff = SvfCoeff;
+
+    #[inline]
+    fn one_pole_iir_coeff(coeff: OnePoleIirCoeffF64) -> Self::OnePoleIirCoeff {
+        coeff.to_f32()
+    }
+
+    #[inline]
+    fn svf_coeff(coeff: SvfCoeffF64) -> Self::SvfCoeff {
+        coeff.to_f32()
+    }
+
+    #[inline]
+    fn one_pole_iir_coeff_to_f64(coeff: Self::OnePoleIirCoeff) -> OnePoleIirCoeffF64 {
+        coeff.to_f64()
+    }
+
+    #[inline]
+    fn svf_coeff_to_f64(coeff: Self::SvfCoeff) -> SvfCoeffF64 {
+        coeff.to_f64()
+    }
+}
+
+/// Store the filter coefficients in f64.
+#[derive(Clone, Copy)]
+pub struct F64Coeffs;
+
+impl CoeffPrecision for F64Coeffs {
+    type OnePoleIirCoeff = OnePoleIirCoeffF64;
+    type SvfCoeff = SvfCoeffF64;
+
+    #[inline]
+    fn one_pole_iir_coeff(coeff: OnePoleIirCoeffF64) -> Self::OnePoleIirCoeff {
+        coeff
+    }
+
+    #[inline]
+    fn svf_coeff(coeff: SvfCoeffF64) -> Self::SvfCoeff {
+        coeff
+    }
+
+    #[inline]
+    fn one_pole_iir_coeff_to_f64(coeff: Self::OnePoleIirCoeff) -> OnePoleIirCoeffF64 {
+        coeff
+    }
+
+    #[inline]
+    fn svf_coeff_to_f64(coeff: Self::SvfCoeff) -> SvfCoeffF64 {
+        coeff
+    }
+}
+
+/// Convert packed SVF coefficients from the precision `Src` to the precision
+/// `Dst`, e.g. to run coefficients which were computed in f64 offline with f32
+/// in real time:
+/// `convert_svf_coeffs::<F64Coeffs, F32Coeffs, N>(&coeffs)`.
+pub fn convert_svf_coeffs<Src: CoeffPrecision, Dst: CoeffPrecision, const N: usize>(
+    coeffs: &ArrayVec<Src::SvfCoeff, N>,
+) -> ArrayVec<Dst::SvfCoeff, N> {
+    coeffs
+        .iter()
+        .map(|&c| Dst::svf_coeff(Src::svf_coeff_to_f64(c)))
+        .collect()
+}
+
+/// Convert packed one-pole coefficients from the precision `Src` to the
+/// precision `Dst`. See [`convert_svf_coeffs`].
+pub fn convert_one_pole_iir_coeffs<Src: CoeffPrecision, Dst: CoeffPrecision, const N: usize>(
+    coeffs: &ArrayVec<Src::OnePoleIirCoeff, N>,
+) -> ArrayVec<Dst::OnePoleIirCoeff, N> {
+    coeffs
+        .iter()
+        .map(|&c| Dst::one_pole_iir_coeff(Src::one_pole_iir_coeff_to_f64(c)))
+        .collect()
+}
+
 /// The struct that manages the filter coefficients for a fully-featured
 /// parametric equalizer. (For a single channel).
 ///
-/// TODO: Get rid of `NUM_BANDS_PLUS_8` const generic once const generic expressions
+/// The precision the coefficients are stored in is chosen with `P`.
+///
+/// `NUM_BANDS` may be `0` for an EQ with only the lowpass and highpass bands
+/// (e.g. one side of a crossover). `NUM_BANDS_PLUS_16` must be at least
+/// `NUM_BANDS + 16`, which is checked at compile time.
+///
+/// TODO: Get rid of `NUM_BANDS_PLUS_16` const generic once const generic expressions
 /// are stabilized. (please rust compiler team)
-pub struct MeadowEqDspCoeff<const NUM_BANDS: usize, const NUM_BANDS_PLUS_8: usize> {
+#[derive(Clone)]
+pub struct MeadowEqDspCoeff<
+    const NUM_BANDS: usize,
+    const NUM_BANDS_PLUS_16: usize,
+    P: CoeffPrecision = F32Coeffs,
+> {
     params: EqParams<NUM_BANDS>,
 
     lp_band: MultiOrderBand,
@@ -21,8 +156,8 @@ pub struct MeadowEqDspCoeff<const NUM_BANDS: usize, const NUM_BANDS_PLUS_8: usiz
 
     bands: [SecondOrderBand; NUM_BANDS],
 
-    one_pole_coeffs: ArrayVec<OnePoleIirCoeff, MAX_ONE_POLE_FILTERS>,
-    svf_coeffs: ArrayVec<SvfCoeff, NUM_BANDS_PLUS_8>,
+    one_pole_coeffs: ArrayVec<P::OnePoleIirCoeff, MAX_ONE_POLE_FILTERS>,
+    svf_coeffs: ArrayVec<P::SvfCoeff, NUM_BANDS_PLUS_16>,
 
     needs_param_flush: bool,
     num_filters_changed: bool,
@@ -30,13 +165,19 @@ pub struct MeadowEqDspCoeff<const NUM_BANDS: usize, const NUM_BANDS_PLUS_8: usiz
     hp_band_needs_param_sync: bool,
     bands_needing_param_sync: [bool; NUM_BANDS],
 
+    band_fade_frames: usize,
+    fixed_band_slots: bool,
+
+    sample_rate: f64,
     sample_rate_recip: f64,
 }
 
-impl<const NUM_BANDS: usize, const NUM_BANDS_PLUS_8: usize>
-    MeadowEqDspCoeff<NUM_BANDS, NUM_BANDS_PLUS_8>
+impl<const NUM_BANDS: usize, const NUM_BANDS_PLUS_16: usize, P: CoeffPrecision>
+    MeadowEqDspCoeff<NUM_BANDS, NUM_BANDS_PLUS_16, P>
 {
     pub fn new(sample_rate: f64) -> Self {
+        const { assert!(NUM_BANDS_PLUS_16 >= NUM_BANDS + 16) };
+
         let sample_rate_recip = sample_rate.recip();
 
         Self {
@@ -51,10 +192,147 @@ impl<const NUM_BANDS: usize, const NUM_BANDS_PLUS_8: usize>
             lp_band_needs_param_sync: false,
             hp_band_needs_param_sync: false,
             bands_needing_param_sync: [false; NUM_BANDS],
+            band_fade_frames: fade_frames(DEFAULT_BAND_FADE_SECS, sample_rate_recip),
+            fixed_band_slots: false,
+            sample_rate,
             sample_rate_recip,
         }
     }
 
+    /// The number of bands (not including the lowpass and highpass bands).
+    pub const fn num_bands() -> usize {
+        NUM_BANDS
+    }
+
+    /// The sample rate this EQ was created with.
+    pub fn sample_rate(&self) -> f64 {
+        self.sample_rate
+    }
+
+    /// Set the duration of the fade applied when a band is disabled.
+    ///
+    /// Removing a band from the cascade abruptly can produce a click if the
+    /// band was contributing significant gain. Instead, the output of a
+    /// disabled band is crossfaded to its input over this duration, and only
+    /// then is the band removed. Set this to `0.0` to remove disabled bands
+    /// immediately.
+    ///
+    /// This only applies to the bands, not the lowpass and highpass bands.
+    pub fn set_band_fade_secs(&mut self, fade_secs: f64) {
+        self.band_fade_frames = fade_frames(fade_secs, self.sample_rate_recip);
+    }
+
+    pub fn fixed_band_slots(&self) -> bool {
+        self.fixed_band_slots
+    }
+
+    /// Whether every band (not including the lowpass and highpass bands)
+    /// always keeps its filter slot, even while it is disabled. By default
+    /// this is `false`.
+    ///
+    /// Normally, enabling or disabling a band changes the number of filters,
+    /// so all of the coefficients and states are repacked on the next flush.
+    /// With fixed slots, a disabled band is instead "parked": its filter keeps
+    /// running and tracking the input, but its output is just the input.
+    /// Toggling a band then only updates the coefficients of that band, at
+    /// the cost of processing disabled bands as well.
+    pub fn set_fixed_band_slots(&mut self, fixed: bool) {
+        if self.fixed_band_slots != fixed {
+            self.fixed_band_slots = fixed;
+            self.num_filters_changed = true;
+            self.needs_param_flush = true;
+        }
+    }
+
+    /// Returns `true` if any disabled band is still fading out. See
+    /// [`MeadowEqDspCoeff::advance_band_fades`].
+    pub fn is_fading_bands(&self) -> bool {
+        self.bands.iter().any(|b| b.is_fading())
+    }
+
+    /// Advance the fades of the bands which are being disabled by the given
+    /// number of frames. The coefficients are updated in place, and once a
+    /// fade has finished the band is removed on the next flush.
+    pub fn advance_band_fades(&mut self, frames: usize) {
+        for (band_i, band) in self.bands.iter_mut().enumerate() {
+            if !band.is_fading() {
+                continue;
+            }
+
+            band.fade_frames_left = band.fade_frames_left.saturating_sub(frames);
+
+            if band.is_fading() {
+                if let Some(i) = band.svf_filter_i {
+                    self.svf_coeffs[i] = P::svf_coeff(band.faded_coeff(self.band_fade_frames));
+                }
+            } else {
+                if self.fixed_band_slots {
+                    // Park the band in its slot.
+                    self.bands_needing_param_sync[band_i] = true;
+                } else {
+                    self.num_filters_changed = true;
+                }
+                self.needs_param_flush = true;
+            }
+        }
+    }
+
+    /// Immediately finish the fades of all bands which are being disabled,
+    /// so that they are removed on the next flush.
+    pub fn finish_band_fades(&mut self) {
+        self.advance_band_fades(usize::MAX);
+    }
+
+    /// Offset the cutoff of the band at the given index by `mod_hz` (e.g.
+    /// from an LFO or an envelope). The coefficients of the band are updated
+    /// in place using [`prewarp_fast`] instead of `tan`, so this is cheap
+    /// enough to call every few frames. The Q, gain, and type of the band
+    /// are unchanged.
+    ///
+    /// The parameters are not touched. The offset lasts until it is set back
+    /// to `0.0`, or until the band is recalculated by a flush. The modulated
+    /// cutoff is clamped to the range of [`BandParams::cutoff_hz`] and kept
+    /// below the Nyquist frequency.
+    ///
+    /// This does nothing if the band is disabled, fading out, or has raw
+    /// coefficients (see [`MeadowEqDspCoeff::set_band_raw_coeff`]).
+    ///
+    /// # Panics
+    /// Panics if `index >= NUM_BANDS`.
+    pub fn set_band_cutoff_mod(&mut self, index: usize, mod_hz: f32) {
+        let band = &self.bands[index];
+        let params = &self.params.bands[index];
+
+        let Some(i) = band.svf_filter_i else {
+            return;
+        };
+        if !params.enabled || band.is_fading() || band.raw_coeff.is_some() {
+            return;
+        }
+
+        if mod_hz == 0.0 {
+            self.svf_coeffs[i] = P::svf_coeff(band.unmodulated_coeff);
+            return;
+        }
+
+        let cutoff_hz = params.cutoff_hz as f64;
+        let max_cutoff_hz =
+            (BandParams::CUTOFF_MAX_HZ as f64).min(MAX_CUTOFF_RATIO * self.sample_rate);
+        let mod_cutoff_hz =
+            (cutoff_hz + mod_hz as f64).clamp(BandParams::CUTOFF_MIN_HZ as f64, max_cutoff_hz);
+
+        // Scale the prewarped cutoff of the band instead of computing it from
+        // scratch, which keeps the cutoff scaling of the shelves.
+        let coeff = &band.unmodulated_coeff;
+        let (g, k) = coeff.g_and_k();
+        let g = g * prewarp_fast(mod_cutoff_hz, self.sample_rate_recip)
+            / prewarp_fast(cutoff_hz, self.sample_rate_recip);
+
+        self.svf_coeffs[i] = P::svf_coeff(SvfCoeffF64::from_g_and_k(
+            g, k, coeff.m0, coeff.m1, coeff.m2,
+        ));
+    }
+
     pub fn params(&self) -> &EqParams<NUM_BANDS> {
         &self.params
     }
@@ -85,37 +363,167 @@ impl<const NUM_BANDS: usize, const NUM_BANDS_PLUS_8: usize>
 
         for i in 0..NUM_BANDS {
             if self.params.bands[i] != params.bands[i] {
-                if self.params.bands[i].enabled != params.bands[i].enabled {
-                    self.num_filters_changed = true;
+                let old = self.params.bands[i];
+                self.params.bands[i] = params.bands[i];
+                let raw_coeff = self.bands[i].raw_coeff.take();
+
+                if old.enabled && !params.bands[i].enabled {
+                    if self.band_fade_frames > 0 && self.bands[i].svf_filter_i.is_some() {
+                        // Keep the band in the cascade until it has faded out.
+                        let from =
+                            raw_coeff.unwrap_or_else(|| band_coeff(&old, self.sample_rate_recip));
+                        self.bands[i].start_fade(from, self.band_fade_frames);
+                        continue;
+                    }
+
+                    if !self.fixed_band_slots {
+                        self.num_filters_changed = true;
+                    }
+                } else if !old.enabled && params.bands[i].enabled {
+                    if self.bands[i].is_fading() {
+                        // The band is still in the cascade.
+                        self.bands[i].fade_frames_left = 0;
+                    } else if !self.fixed_band_slots {
+                        self.num_filters_changed = true;
+                    }
                 }
 
-                self.params.bands[i] = params.bands[i];
                 self.bands_needing_param_sync[i] = true;
                 self.needs_param_flush = true;
             }
         }
     }
 
+    /// The coefficients set with [`MeadowEqDspCoeff::set_band_raw_coeff`]
+    /// for the band at the given index, if any.
+    ///
+    /// # Panics
+    /// Panics if `index >= NUM_BANDS`.
+    pub fn band_raw_coeff(&self, index: usize) -> Option<SvfCoeffF64> {
+        self.bands[index].raw_coeff
+    }
+
+    /// Set the coefficients of the band at the given index directly (e.g. from
+    /// an optimizer which fits a target curve), bypassing the parametric
+    /// computation. This also enables the band.
+    ///
+    /// The band keeps these coefficients until its parameters are changed
+    /// with [`MeadowEqDspCoeff::set_params`] or
+    /// [`MeadowEqDspCoeff::modify_params`], which overwrites them. Until then,
+    /// the type, cutoff, Q, and gain of the band are ignored.
+    ///
+    /// # Panics
+    /// Panics if `index >= NUM_BANDS`. In debug builds, this also panics if
+    /// the coefficients are not stable (see [`SvfCoeffF64::is_stable`]).
+    pub fn set_band_raw_coeff(&mut self, index: usize, coeff: SvfCoeffF64) {
+        debug_assert!(coeff.is_stable(), "unstable raw band coefficients");
+
+        if !self.params.bands[index].enabled {
+            let mut params = self.params;
+            params.bands[index].enabled = true;
+            self.set_params(&params);
+        }
+
+        self.bands[index].raw_coeff = Some(coeff);
+        self.bands_needing_param_sync[index] = true;
+        self.needs_param_flush = true;
+    }
+
+    /// Modify the parameters in place. Only the bands whose parameters were
+    /// actually changed by `f` are marked for recalculation, the same as with
+    /// [`MeadowEqDspCoeff::set_params`].
+    pub fn modify_params(&mut self, f: impl FnOnce(&mut EqParams<NUM_BANDS>)) {
+        let mut params = self.params;
+        f(&mut params);
+        self.set_params(&params);
+    }
+
     pub fn needs_param_flush(&self) -> bool {
         self.needs_param_flush
     }
 
+    /// An estimate of the cost of the next
+    /// [`MeadowEqDspCoeff::flush_param_changes`], so that a scheduler which
+    /// budgets its work can decide whether to flush now or defer it.
+    ///
+    /// The cost is roughly the number of `tan`, `powf`, and similar calls the
+    /// flush makes: `1` for a notch, an allpass, or a multimode band, `2` for
+    /// a bell, a shelf, or a compensated notch, and one per SVF section (or
+    /// one-pole filter) for the lowpass and highpass bands. Matching the
+    /// -3 dB cutoff of a lowpass or highpass band (see
+    /// [`LpOrHpBandParams::matched_cutoff`]) runs a search which adds about
+    /// `64`. Bands which are fading out or have raw coefficients cost
+    /// nothing.
+    ///
+    /// This is `0` if no flush is needed. With the `coeff-cache` feature this
+    /// is an upper bound, since bands whose coefficients are cached cost
+    /// nothing either.
+    pub fn pending_recompute_cost(&self) -> usize {
+        if !self.needs_param_flush {
+            return 0;
+        }
+
+        // If the number of filters changed, every band is re-added.
+        let all = self.num_filters_changed;
+        let mut cost = 0;
+
+        if all || self.lp_band_needs_param_sync {
+            cost += lp_hp_band_cost(&self.params.lp_band);
+        }
+        if all || self.hp_band_needs_param_sync {
+            cost += lp_hp_band_cost(&self.params.hp_band);
+        }
+
+        for (band_i, band) in self.bands.iter().enumerate() {
+            if !(all || self.bands_needing_param_sync[band_i]) || band.is_fading() {
+                continue;
+            }
+
+            let params = &self.params.bands[band_i];
+            let computes_coeff = if params.enabled {
+                band.raw_coeff.is_none()
+            } else {
+                self.fixed_band_slots
+            };
+
+            if computes_coeff {
+                cost += band_cost(params);
+            }
+        }
+
+        cost
+    }
+
+    #[must_use]
     pub fn flush_param_changes(&mut self) -> Option<StateSyncInfo<NUM_BANDS>> {
         if !self.needs_param_flush {
             return None;
         }
         self.needs_param_flush = false;
 
-        if self.num_filters_changed {
+        let num_filters_changed = self.num_filters_changed;
+        if num_filters_changed {
             self.num_filters_changed = false;
+
+            // The filters are packed in order, so every band needs to be re-added.
             self.one_pole_coeffs.clear();
             self.svf_coeffs.clear();
+
+            self.lp_band.clear_filter_indices();
+            self.hp_band.clear_filter_indices();
+            for band in self.bands.iter_mut() {
+                band.svf_filter_i = None;
+            }
+
+            self.lp_band_needs_param_sync = true;
+            self.hp_band_needs_param_sync = true;
+            self.bands_needing_param_sync = [true; NUM_BANDS];
         }
 
         if self.lp_band_needs_param_sync {
             self.lp_band_needs_param_sync = false;
 
-            self.lp_band.sync_params(
+            self.lp_band.sync_params::<P, NUM_BANDS_PLUS_16>(
                 &self.params.lp_band,
                 self.sample_rate_recip,
                 true,
@@ -127,7 +535,7 @@ impl<const NUM_BANDS: usize, const NUM_BANDS_PLUS_8: usize>
         if self.hp_band_needs_param_sync {
             self.hp_band_needs_param_sync = false;
 
-            self.hp_band.sync_params(
+            self.hp_band.sync_params::<P, NUM_BANDS_PLUS_16>(
                 &self.params.hp_band,
                 self.sample_rate_recip,
                 false,
@@ -140,96 +548,586 @@ impl<const NUM_BANDS: usize, const NUM_BANDS_PLUS_8: usize>
             if self.bands_needing_param_sync[band_i] {
                 self.bands_needing_param_sync[band_i] = false;
 
-                self.bands[band_i].sync_params(
+                self.bands[band_i].sync_params::<P, NUM_BANDS_PLUS_16>(
                     &self.params.bands[band_i],
                     self.sample_rate_recip,
+                    self.band_fade_frames,
+                    self.fixed_band_slots,
                     &mut self.svf_coeffs,
                 );
             }
         }
 
-        if self.num_filters_changed {
-            Some(StateSyncInfo {
-                lp_band_enabled: self.params.lp_band.enabled,
-                lp_band_order: self.params.lp_band.order,
-                hp_band_enabled: self.params.hp_band.enabled,
-                hp_band_order: self.params.hp_band.order,
-                bands_enabled: std::array::from_fn(|i| self.params.bands[i].enabled),
-            })
+        if num_filters_changed {
+            Some(self.state_sync_info())
         } else {
             None
         }
     }
 
+    /// Compute the coefficients for the given parameters from scratch,
+    /// without touching `self`.
+    ///
+    /// This is meant to move the expensive coefficient calculations of a full
+    /// preset off of the audio thread: keep a `MeadowEqDspCoeff` with the same
+    /// sample rate and settings on a worker thread, compute the snapshot
+    /// there, send it to the audio thread, and then move it in with
+    /// [`MeadowEqDspCoeff::apply_snapshot`].
+    ///
+    /// The sample rate, the fixed band slots setting, and any raw band
+    /// coefficients are taken from `self`. Bands are never faded out in a
+    /// snapshot.
+    pub fn compute_snapshot(
+        &self,
+        params: &EqParams<NUM_BANDS>,
+    ) -> CoeffSnapshot<NUM_BANDS, NUM_BANDS_PLUS_16, P> {
+        let mut coeff = Self::new(self.sample_rate);
+        coeff.params = *params;
+        coeff.band_fade_frames = 0;
+        coeff.fixed_band_slots = self.fixed_band_slots;
+        for (band, from) in coeff.bands.iter_mut().zip(self.bands.iter()) {
+            band.raw_coeff = from.raw_coeff;
+        }
+
+        coeff.num_filters_changed = true;
+        coeff.needs_param_flush = true;
+        let _ = coeff.flush_param_changes();
+
+        CoeffSnapshot { coeff }
+    }
+
+    /// Replace the parameters and coefficients with those of a snapshot
+    /// computed with [`MeadowEqDspCoeff::compute_snapshot`]. This only moves
+    /// the data in, so it is cheap enough for the audio thread, and it never
+    /// allocates.
+    ///
+    /// Any pending parameter changes and band fades are discarded. The
+    /// duration of band fades is kept, while the fixed band slots setting is
+    /// taken from the snapshot.
+    ///
+    /// Returns the full filter topology, which must be used to sync the
+    /// filter states.
+    ///
+    /// # Panics
+    /// Panics if the snapshot was computed for a different sample rate.
+    pub fn apply_snapshot(
+        &mut self,
+        snapshot: CoeffSnapshot<NUM_BANDS, NUM_BANDS_PLUS_16, P>,
+    ) -> StateSyncInfo<NUM_BANDS> {
+        assert_eq!(snapshot.coeff.sample_rate, self.sample_rate);
+
+        let band_fade_frames = self.band_fade_frames;
+        *self = snapshot.coeff;
+        self.band_fade_frames = band_fade_frames;
+
+        self.state_sync_info()
+    }
+
+    /// The full filter topology.
+    fn state_sync_info(&self) -> StateSyncInfo<NUM_BANDS> {
+        StateSyncInfo {
+            lp_band_enabled: self.params.lp_band.enabled,
+            lp_band_order: self.params.lp_band.order,
+            hp_band_enabled: self.params.hp_band.enabled,
+            hp_band_order: self.params.hp_band.order,
+            bands_enabled: std::array::from_fn(|i| {
+                self.params.bands[i].enabled || self.bands[i].is_fading() || self.fixed_band_slots
+            }),
+        }
+    }
+
+    /// Recompute the coefficients of every band, regardless of which
+    /// parameters have changed. Any pending parameter changes are flushed as
+    /// well.
+    ///
+    /// This is only a forced full flush: it marks every band as changed and
+    /// calls [`MeadowEqDspCoeff::flush_param_changes`], so each band does the
+    /// same coefficient math as it would in a normal flush. It is meant for
+    /// when everything changed anyway (i.e. after loading a preset). Compared
+    /// to changing and flushing the bands one at a time, it does a single
+    /// flush instead of one per band, so it only saves the per-flush overhead.
+    /// The `coeff/recompute_all` and `coeff/individual_flushes` benchmarks
+    /// compare the two.
+    #[must_use]
+    pub fn recompute_all(&mut self) -> Option<StateSyncInfo<NUM_BANDS>> {
+        self.lp_band_needs_param_sync = true;
+        self.hp_band_needs_param_sync = true;
+        self.bands_needing_param_sync = [true; NUM_BANDS];
+        self.needs_param_flush = true;
+
+        self.flush_param_changes()
+    }
+
+    /// The number of SVF filters at the start of the packed SVF coefficients
+    /// that belong to the lowpass and highpass bands. The rest belong to the
+    /// bands.
+    pub fn num_lp_hp_svf_filters(&self) -> usize {
+        self.lp_band.num_svf_filters() + self.hp_band.num_svf_filters()
+    }
+
+    /// Returns the current dirty flags. This is only meant to be used for
+    /// debugging and testing.
+    #[cfg(feature = "debug-introspection")]
+    pub fn dirty_flags(&self) -> DirtyFlags<NUM_BANDS> {
+        DirtyFlags {
+            needs_param_flush: self.needs_param_flush,
+            num_filters_changed: self.num_filters_changed,
+            lp_band_needs_param_sync: self.lp_band_needs_param_sync,
+            hp_band_needs_param_sync: self.hp_band_needs_param_sync,
+            bands_needing_param_sync: self.bands_needing_param_sync,
+        }
+    }
+
+    /// The coefficients of the filters which make up the lowpass band: the
+    /// one-pole filter if the order is [`FilterOrder::X1`], otherwise the
+    /// cascaded SVF sections. Both are empty if the band is disabled.
+    ///
+    /// This reflects the state after the last call to
+    /// [`MeadowEqDspCoeff::flush_param_changes`].
+    pub fn lp_band_sections(&self) -> (Option<&P::OnePoleIirCoeff>, &[P::SvfCoeff]) {
+        self.lp_band
+            .sections(&self.one_pole_coeffs, &self.svf_coeffs)
+    }
+
+    /// The coefficients of the filters which make up the highpass band. See
+    /// [`MeadowEqDspCoeff::lp_band_sections`].
+    pub fn hp_band_sections(&self) -> (Option<&P::OnePoleIirCoeff>, &[P::SvfCoeff]) {
+        self.hp_band
+            .sections(&self.one_pole_coeffs, &self.svf_coeffs)
+    }
+
+    /// The frequency at which the response of the lowpass band crosses
+    /// -3 dB, or `None` if the band is disabled.
+    ///
+    /// Unless `matched_cutoff` is enabled, this can differ from `cutoff_hz`
+    /// when the Q is not that of a Butterworth response. This is useful for
+    /// labeling the true corner frequency in a GUI.
+    pub fn lp_band_corner_hz(&self) -> Option<f32> {
+        self.params
+            .lp_band
+            .enabled
+            .then(|| lp_hp_corner_hz(&self.params.lp_band, self.sample_rate_recip, true) as f32)
+    }
+
+    /// The frequency at which the response of the highpass band crosses
+    /// -3 dB, or `None` if the band is disabled. See
+    /// [`MeadowEqDspCoeff::lp_band_corner_hz`].
+    pub fn hp_band_corner_hz(&self) -> Option<f32> {
+        self.params
+            .hp_band
+            .enabled
+            .then(|| lp_hp_corner_hz(&self.params.hp_band, self.sample_rate_recip, false) as f32)
+    }
+
+    /// An estimate of how many samples it takes for the impulse response of
+    /// the whole EQ to decay below `threshold_db` (e.g. `-90.0`), based on
+    /// the pole closest to the unit circle. This is useful for deciding how
+    /// long of a tail to render after the last input sample.
+    ///
+    /// The estimate errs on the long side, since it ignores how strongly the
+    /// slowest pole actually contributes to the output.
+    ///
+    /// Returns `0` if the EQ is transparent or if `threshold_db >= 0.0`, and
+    /// `usize::MAX` if any filter is unstable.
+    ///
+    /// Note that this uses the coefficients of the last call to
+    /// [`MeadowEqDspCoeff::flush_param_changes`].
+    pub fn estimated_settling_samples(&self, threshold_db: f64) -> usize {
+        if threshold_db >= 0.0 {
+            return 0;
+        }
+
+        let one_pole_radii = self
+            .one_pole_coeffs
+            .iter()
+            .map(|c| P::one_pole_iir_coeff_to_f64(*c))
+            .filter(|c| c.m1 != 0.0)
+            .map(|c| c.b1.abs());
+        let svf_radii = self
+            .svf_coeffs
+            .iter()
+            .map(|c| P::svf_coeff_to_f64(*c))
+            // Filters which only apply a gain (e.g. parked bands) do not
+            // contribute to the tail.
+            .filter(|c| c.m1 != 0.0 || c.m2 != 0.0)
+            .map(|c| {
+                let [p0, p1] = c.poles();
+                p0.norm().max(p1.norm())
+            });
+
+        let max_radius = one_pole_radii.chain(svf_radii).fold(0.0, f64::max);
+
+        if max_radius >= 1.0 || max_radius.is_nan() {
+            usize::MAX
+        } else if max_radius == 0.0 {
+            0
+        } else {
+            // The slowest mode decays by `max_radius` every sample.
+            let threshold = 10.0f64.powf(threshold_db / 20.0);
+            (threshold.ln() / max_radius.ln()).ceil() as usize
+        }
+    }
+
+    /// Write the magnitude response of the filters in decibels at each of
+    /// the frequencies in `freqs_hz` into `out_db`, without allocating. This
+    /// lets GUI code reuse one buffer across frames.
+    ///
+    /// `band_routing` should match the routing of the processor. The input
+    /// and output gains of the processor are not included.
+    ///
+    /// This uses the coefficients of the last call to
+    /// [`MeadowEqDspCoeff::flush_param_changes`], so bands which are fading
+    /// out are shown part way through their fade.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `freqs_hz` and `out_db` have different lengths.
+    pub fn fill_response_curve(
+        &self,
+        band_routing: BandRouting,
+        freqs_hz: &[f64],
+        out_db: &mut [f64],
+    ) {
+        assert_eq!(freqs_hz.len(), out_db.len());
+
+        let sample_rate_recip = self.sample_rate_recip;
+        let (lp_hp_svf_coeffs, band_svf_coeffs) =
+            self.svf_coeffs.split_at(self.num_lp_hp_svf_filters());
+
+        for (&freq_hz, out) in freqs_hz.iter().zip(out_db.iter_mut()) {
+            let lp_hp_magnitude = self
+                .one_pole_coeffs
+                .iter()
+                .map(|c| P::one_pole_iir_coeff_to_f64(*c).magnitude(freq_hz, sample_rate_recip))
+                .chain(
+                    lp_hp_svf_coeffs
+                        .iter()
+                        .map(|c| P::svf_coeff_to_f64(*c).magnitude(freq_hz, sample_rate_recip)),
+                )
+                .product::<f64>();
+
+            let bands_magnitude = match band_routing {
+                BandRouting::Series => band_svf_coeffs
+                    .iter()
+                    .map(|c| P::svf_coeff_to_f64(*c).magnitude(freq_hz, sample_rate_recip))
+                    .product::<f64>(),
+                BandRouting::Parallel => {
+                    // The differences between each band and the input are
+                    // summed together with the input, so the phase matters.
+                    let (re, im) = band_svf_coeffs.iter().fold((1.0, 0.0), |(re, im), c| {
+                        let h = P::svf_coeff_to_f64(*c).response(freq_hz, sample_rate_recip);
+                        (re + h.re - 1.0, im + h.im)
+                    });
+                    re.hypot(im)
+                }
+            };
+
+            *out = amp_to_db(lp_hp_magnitude * bands_magnitude);
+        }
+    }
+
+    /// The magnitude response of the filters in decibels at each of the
+    /// frequencies in `freqs_hz`. See [`MeadowEqDspCoeff::fill_response_curve`]
+    /// for a version which does not allocate.
+    pub fn response_curve(&self, band_routing: BandRouting, freqs_hz: &[f64]) -> Vec<f64> {
+        let mut out_db = vec![0.0; freqs_hz.len()];
+        self.fill_response_curve(band_routing, freqs_hz, &mut out_db);
+        out_db
+    }
+
+    /// The packed coefficients of all active filters, in the order they are
+    /// processed: the lowpass band, the highpass band, and then the bands in
+    /// index order.
+    ///
+    /// (The order is fixed on purpose. Sorting the SVF sections by frequency
+    /// or by Q does not measurably change the f32 noise floor, since the SVF
+    /// is well-conditioned and the error is dominated by rounding the
+    /// coefficients to f32.)
     pub fn coeffs(
         &self,
     ) -> (
-        &ArrayVec<OnePoleIirCoeff, MAX_ONE_POLE_FILTERS>,
-        &ArrayVec<SvfCoeff, NUM_BANDS_PLUS_8>,
+        &ArrayVec<P::OnePoleIirCoeff, MAX_ONE_POLE_FILTERS>,
+        &ArrayVec<P::SvfCoeff, NUM_BANDS_PLUS_16>,
     ) {
         (&self.one_pole_coeffs, &self.svf_coeffs)
     }
+
+    /// Returns `true` if there are no active filters, i.e. every band
+    /// (including the lowpass and highpass bands) is disabled and no band is
+    /// fading out.
+    pub fn is_empty(&self) -> bool {
+        self.one_pole_coeffs.is_empty() && self.svf_coeffs.is_empty()
+    }
+}
+
+impl<const NUM_BANDS: usize, const NUM_BANDS_PLUS_16: usize>
+    MeadowEqDspCoeff<NUM_BANDS, NUM_BANDS_PLUS_16, F32Coeffs>
+{
+    /// Append the coefficients of all active SVF filters to `out` as a flat
+    /// buffer, with six values per filter in the layout of
+    /// [`SvfCoeff::as_array`]. The filters are in the same order as in
+    /// [`MeadowEqDspCoeff::coeffs`].
+    ///
+    /// The one-pole filters (used by the lowpass and highpass bands with an
+    /// order of [`FilterOrder::X1`]) are not included.
+    pub fn pack_coeffs_into(&self, out: &mut Vec<f32>) {
+        out.reserve(self.svf_coeffs.len() * 6);
+
+        for coeff in self.svf_coeffs.iter() {
+            out.extend_from_slice(&coeff.as_array());
+        }
+    }
 }
 
 #[derive(Default, Clone, Copy)]
 struct SecondOrderBand {
     svf_filter_i: Option<usize>,
+
+    /// The number of frames left in the fade of a band which is being
+    /// disabled.
+    fade_frames_left: usize,
+    /// The coefficients of the band before it was disabled.
+    fade_from: SvfCoeffF64,
+    /// Coefficients set directly by the user, which take precedence over the
+    /// parameters.
+    raw_coeff: Option<SvfCoeffF64>,
+    /// The coefficients of the band without any cutoff modulation.
+    unmodulated_coeff: SvfCoeffF64,
+
+    #[cfg(feature = "coeff-cache")]
+    coeff_cache: CoeffCache,
 }
 
 impl SecondOrderBand {
-    fn sync_params<const NUM_BANDS_PLUS_8: usize>(
+    fn is_fading(&self) -> bool {
+        self.fade_frames_left > 0
+    }
+
+    fn start_fade(&mut self, from: SvfCoeffF64, fade_frames: usize) {
+        self.fade_frames_left = fade_frames;
+        self.fade_from = from;
+    }
+
+    /// The coefficients of the band part way through its fade.
+    fn faded_coeff(&self, fade_frames: usize) -> SvfCoeffF64 {
+        let wet = self.fade_frames_left as f64 / fade_frames as f64;
+        mix_with_dry(&self.fade_from, wet)
+    }
+
+    fn sync_params<P: CoeffPrecision, const NUM_BANDS_PLUS_16: usize>(
         &mut self,
         params: &BandParams,
         sample_rate_recip: f64,
-        svf_filter_coeff: &mut ArrayVec<SvfCoeff, NUM_BANDS_PLUS_8>,
+        fade_frames: usize,
+        fixed_slot: bool,
+        svf_filter_coeff: &mut ArrayVec<P::SvfCoeff, NUM_BANDS_PLUS_16>,
     ) {
-        if !params.enabled {
-            self.svf_filter_i = None;
-            return;
+        let coeffs = if self.is_fading() {
+            self.faded_coeff(fade_frames)
+        } else if !params.enabled {
+            if !fixed_slot {
+                self.svf_filter_i = None;
+                return;
+            }
+
+            // The filter keeps tracking the input so that re-enabling the
+            // band starts from a warm state.
+            mix_with_dry(&band_coeff(params, sample_rate_recip), 0.0)
+        } else {
+            self.enabled_coeff(params, sample_rate_recip)
+        };
+        self.unmodulated_coeff = coeffs;
+        let coeffs = P::svf_coeff(coeffs);
+
+        if let Some(i) = self.svf_filter_i {
+            svf_filter_coeff[i] = coeffs;
+        } else {
+            self.svf_filter_i = Some(svf_filter_coeff.len());
+            svf_filter_coeff.push(coeffs);
         }
+    }
 
-        let coeffs = match params.band_type {
-            BandType::Bell => SvfCoeffF64::bell(
-                params.cutoff_hz as f64,
-                params.q as f64,
-                params.gain_db as f64,
-                sample_rate_recip,
-            )
-            .to_f32(),
-            BandType::LowShelf => SvfCoeffF64::low_shelf(
-                params.cutoff_hz as f64,
+    fn enabled_coeff(&mut self, params: &BandParams, sample_rate_recip: f64) -> SvfCoeffF64 {
+        if let Some(coeff) = self.raw_coeff {
+            return coeff;
+        }
+
+        #[cfg(feature = "coeff-cache")]
+        let coeffs = self
+            .coeff_cache
+            .get_or_insert(params, || band_coeff(params, sample_rate_recip));
+        #[cfg(not(feature = "coeff-cache"))]
+        let coeffs = band_coeff(params, sample_rate_recip);
+
+        coeffs
+    }
+}
+
+/// Crossfade the output of the filter towards its input. The rest of the
+/// filter is left untouched, so the filter state stays valid.
+fn mix_with_dry(coeff: &SvfCoeffF64, wet: f64) -> SvfCoeffF64 {
+    SvfCoeffF64 {
+        m0: 1.0 + wet * (coeff.m0 - 1.0),
+        m1: wet * coeff.m1,
+        m2: wet * coeff.m2,
+        ..*coeff
+    }
+}
+
+/// Clamp a cutoff to [`MAX_CUTOFF_RATIO`] times the sample rate.
+fn clamp_cutoff_hz(cutoff_hz: f32, sample_rate_recip: f64) -> f64 {
+    (cutoff_hz as f64).min(MAX_CUTOFF_RATIO / sample_rate_recip)
+}
+
+pub(crate) fn band_coeff(params: &BandParams, sample_rate_recip: f64) -> SvfCoeffF64 {
+    let cutoff_hz = clamp_cutoff_hz(params.cutoff_hz, sample_rate_recip);
+
+    let coeff = match params.band_type {
+        BandType::Bell => match params.q_mode {
+            QMode::Proportional => SvfCoeffF64::bell(
+                cutoff_hz,
                 params.q as f64,
                 params.gain_db as f64,
                 sample_rate_recip,
-            )
-            .to_f32(),
-            BandType::HighShelf => SvfCoeffF64::high_shelf(
-                params.cutoff_hz as f64,
+            ),
+            QMode::Constant => SvfCoeffF64::bell_constant_q(
+                cutoff_hz,
                 params.q as f64,
                 params.gain_db as f64,
                 sample_rate_recip,
-            )
-            .to_f32(),
-            BandType::Notch => {
-                SvfCoeffF64::notch(params.cutoff_hz as f64, params.q as f64, sample_rate_recip)
-                    .to_f32()
-            }
-            BandType::Allpass => {
-                SvfCoeffF64::allpass(params.cutoff_hz as f64, params.q as f64, sample_rate_recip)
-                    .to_f32()
-            }
-        };
+            ),
+        },
+        BandType::LowShelf => SvfCoeffF64::low_shelf(
+            cutoff_hz,
+            params.q as f64,
+            params.gain_db as f64,
+            sample_rate_recip,
+        ),
+        BandType::HighShelf => SvfCoeffF64::high_shelf(
+            cutoff_hz,
+            params.q as f64,
+            params.gain_db as f64,
+            sample_rate_recip,
+        ),
+        BandType::Notch => SvfCoeffF64::notch(cutoff_hz, params.q as f64, sample_rate_recip),
+        BandType::Allpass => SvfCoeffF64::allpass(cutoff_hz, params.q as f64, sample_rate_recip),
+        BandType::NotchCompensated => {
+            SvfCoeffF64::notch_compensated(cutoff_hz, params.q as f64, sample_rate_recip)
+        }
+        BandType::Multimode => SvfCoeffF64::multimode(
+            cutoff_hz,
+            params.q as f64,
+            params.morph as f64,
+            sample_rate_recip,
+        ),
+    };
 
-        if let Some(i) = self.svf_filter_i {
-            svf_filter_coeff[i] = coeffs;
+    debug_assert!(coeff.is_stable(), "unstable band coefficients: {params:?}");
+
+    coeff
+}
+
+/// The estimated cost of computing the coefficients of a band. See
+/// [`MeadowEqDspCoeff::pending_recompute_cost`].
+fn band_cost(params: &BandParams) -> usize {
+    match params.band_type {
+        BandType::Bell | BandType::LowShelf | BandType::HighShelf | BandType::NotchCompensated => 2,
+        BandType::Notch | BandType::Allpass | BandType::Multimode => 1,
+    }
+}
+
+/// The estimated cost of computing the coefficients of a lowpass or highpass
+/// band. See [`MeadowEqDspCoeff::pending_recompute_cost`].
+fn lp_hp_band_cost(params: &LpOrHpBandParams) -> usize {
+    /// The bisection of `SvfCoeffF64::match_3db_cutoff` runs 64 steps.
+    const MATCHED_CUTOFF_COST: usize = 64;
+
+    if !params.enabled {
+        return 0;
+    }
+
+    match params.order {
+        FilterOrder::X1 => 1 + params.shelf_gain_db.is_some() as usize,
+        order if params.matched_cutoff => order.num_svf_filters() + MATCHED_CUTOFF_COST,
+        order => order.num_svf_filters(),
+    }
+}
+
+fn fade_frames(fade_secs: f64, sample_rate_recip: f64) -> usize {
+    (fade_secs / sample_rate_recip).round().max(0.0) as usize
+}
+
+#[cfg(feature = "coeff-cache")]
+const COEFF_CACHE_SIZE: usize = 4;
+
+/// A small fixed-size cache of the most recently computed coefficients of a
+/// band, so that automation which returns to a previous value does not need to
+/// recompute them. A hit skips the coefficient math but still costs a
+/// lookup, so this only pays off when values repeat (see the
+/// `coeff/automation_repeated` benchmark).
+///
+/// The sample rate is not part of the key, so the cache must be cleared if the
+/// sample rate ever changes.
+#[cfg(feature = "coeff-cache")]
+#[derive(Default, Clone, Copy)]
+struct CoeffCache {
+    /// Ordered from most to least recently used.
+    entries: [Option<(CoeffCacheKey, SvfCoeffF64)>; COEFF_CACHE_SIZE],
+}
+
+#[cfg(feature = "coeff-cache")]
+impl CoeffCache {
+    fn get_or_insert(
+        &mut self,
+        params: &BandParams,
+        compute: impl FnOnce() -> SvfCoeffF64,
+    ) -> SvfCoeffF64 {
+        let key = CoeffCacheKey::new(params);
+
+        if let Some(i) = self
+            .entries
+            .iter()
+            .position(|e| e.is_some_and(|(k, _)| k == key))
+        {
+            self.entries[..=i].rotate_right(1);
         } else {
-            self.svf_filter_i = Some(svf_filter_coeff.len());
-            svf_filter_coeff.push(coeffs);
+            self.entries.rotate_right(1);
+            self.entries[0] = Some((key, compute()));
+        }
+
+        self.entries[0].unwrap().1
+    }
+}
+
+/// The parameters which affect the coefficients of a band. The floating point
+/// values are compared bit-for-bit so that a cache hit always returns exactly
+/// the coefficients which would have been computed.
+#[cfg(feature = "coeff-cache")]
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct CoeffCacheKey {
+    band_type: BandType,
+    q_mode: QMode,
+    cutoff_hz: u32,
+    q: u32,
+    gain_db: u32,
+    morph: u32,
+}
+
+#[cfg(feature = "coeff-cache")]
+impl CoeffCacheKey {
+    fn new(params: &BandParams) -> Self {
+        Self {
+            band_type: params.band_type,
+            q_mode: params.q_mode,
+            cutoff_hz: params.cutoff_hz.to_bits(),
+            q: params.q.to_bits(),
+            gain_db: params.gain_db.to_bits(),
+            morph: params.morph.to_bits(),
         }
     }
 }
 
-#[derive(Default)]
+#[derive(Default, Clone, Copy)]
 struct MultiOrderBand {
     order: FilterOrder,
 
@@ -238,13 +1136,40 @@ struct MultiOrderBand {
 }
 
 impl MultiOrderBand {
-    fn sync_params<const NUM_BANDS_PLUS_8: usize>(
+    fn num_svf_filters(&self) -> usize {
+        if self.svf_filter_i.is_some() {
+            self.order.num_svf_filters()
+        } else {
+            0
+        }
+    }
+
+    fn sections<'a, O, S>(
+        &self,
+        one_pole_coeffs: &'a [O],
+        svf_coeffs: &'a [S],
+    ) -> (Option<&'a O>, &'a [S]) {
+        let one_pole = self.one_pole_iir_i.map(|i| &one_pole_coeffs[i]);
+        let svf = match self.svf_filter_i {
+            Some(i) => &svf_coeffs[i..i + self.order.num_svf_filters()],
+            None => &[],
+        };
+
+        (one_pole, svf)
+    }
+
+    fn clear_filter_indices(&mut self) {
+        self.one_pole_iir_i = None;
+        self.svf_filter_i = None;
+    }
+
+    fn sync_params<P: CoeffPrecision, const NUM_BANDS_PLUS_16: usize>(
         &mut self,
         params: &LpOrHpBandParams,
         sample_rate_recip: f64,
         is_lowpass: bool,
-        one_pole_coeffs: &mut ArrayVec<OnePoleIirCoeff, MAX_ONE_POLE_FILTERS>,
-        svf_coeffs: &mut ArrayVec<SvfCoeff, NUM_BANDS_PLUS_8>,
+        one_pole_coeffs: &mut ArrayVec<P::OnePoleIirCoeff, MAX_ONE_POLE_FILTERS>,
+        svf_coeffs: &mut ArrayVec<P::SvfCoeff, NUM_BANDS_PLUS_16>,
     ) {
         if !params.enabled {
             self.one_pole_iir_i = None;
@@ -256,12 +1181,11 @@ impl MultiOrderBand {
 
         match params.order {
             FilterOrder::X1 => {
-                let coeffs = if is_lowpass {
-                    OnePoleIirCoeffF64::lowpass(params.cutoff_hz as f64, sample_rate_recip).to_f32()
-                } else {
-                    OnePoleIirCoeffF64::highpass(params.cutoff_hz as f64, sample_rate_recip)
-                        .to_f32()
-                };
+                let coeffs = P::one_pole_iir_coeff(lp_hp_one_pole_coeff(
+                    params,
+                    sample_rate_recip,
+                    is_lowpass,
+                ));
 
                 if let Some(i) = self.one_pole_iir_i {
                     one_pole_coeffs[i] = coeffs;
@@ -270,112 +1194,194 @@ impl MultiOrderBand {
                     one_pole_coeffs.push(coeffs);
                 }
             }
-            FilterOrder::X2 => {
-                let coeffs = if is_lowpass {
-                    SvfCoeffF64::lowpass_ord2(
-                        params.cutoff_hz as f64,
-                        params.q as f64,
-                        sample_rate_recip,
-                    )
-                    .to_f32()
-                } else {
-                    SvfCoeffF64::highpass_ord2(
-                        params.cutoff_hz as f64,
-                        params.q as f64,
-                        sample_rate_recip,
-                    )
-                    .to_f32()
-                };
+            _ => {
+                let coeffs = lp_hp_svf_coeffs(params, sample_rate_recip, is_lowpass);
 
                 if let Some(i) = self.svf_filter_i {
-                    svf_coeffs[i] = coeffs;
+                    for (j, coeffs) in coeffs.iter().enumerate() {
+                        svf_coeffs[i + j] = P::svf_coeff(*coeffs);
+                    }
                 } else {
                     self.svf_filter_i = Some(svf_coeffs.len());
-                    svf_coeffs.push(coeffs);
+                    for coeffs in coeffs.iter() {
+                        svf_coeffs.push(P::svf_coeff(*coeffs));
+                    }
                 }
             }
-            FilterOrder::X4 => {
-                let coeffs = if is_lowpass {
-                    SvfCoeffF64::lowpass_ord4(
-                        params.cutoff_hz as f64,
-                        params.q as f64,
-                        sample_rate_recip,
-                    )
-                } else {
-                    SvfCoeffF64::highpass_ord4(
-                        params.cutoff_hz as f64,
-                        params.q as f64,
-                        sample_rate_recip,
-                    )
-                };
+        }
+    }
+}
 
-                if let Some(i) = self.svf_filter_i {
-                    svf_coeffs[i] = coeffs[0].to_f32();
-                    svf_coeffs[i + 1] = coeffs[1].to_f32();
-                } else {
-                    self.svf_filter_i = Some(svf_coeffs.len());
-                    svf_coeffs.push(coeffs[0].to_f32());
-                    svf_coeffs.push(coeffs[1].to_f32());
-                }
-            }
-            FilterOrder::X6 => {
-                let coeffs = if is_lowpass {
-                    SvfCoeffF64::lowpass_ord6(
-                        params.cutoff_hz as f64,
-                        params.q as f64,
-                        sample_rate_recip,
-                    )
-                } else {
-                    SvfCoeffF64::highpass_ord6(
-                        params.cutoff_hz as f64,
-                        params.q as f64,
-                        sample_rate_recip,
-                    )
-                };
+/// The coefficients of a lowpass or highpass band with an order of
+/// [`FilterOrder::X1`].
+pub(crate) fn lp_hp_one_pole_coeff(
+    params: &LpOrHpBandParams,
+    sample_rate_recip: f64,
+    is_lowpass: bool,
+) -> OnePoleIirCoeffF64 {
+    let cutoff_hz = clamp_cutoff_hz(params.cutoff_hz, sample_rate_recip);
 
-                if let Some(i) = self.svf_filter_i {
-                    svf_coeffs[i] = coeffs[0].to_f32();
-                    svf_coeffs[i + 1] = coeffs[1].to_f32();
-                    svf_coeffs[i + 2] = coeffs[2].to_f32();
-                } else {
-                    self.svf_filter_i = Some(svf_coeffs.len());
-                    svf_coeffs.push(coeffs[0].to_f32());
-                    svf_coeffs.push(coeffs[1].to_f32());
-                    svf_coeffs.push(coeffs[2].to_f32());
-                }
-            }
-            FilterOrder::X8 => {
-                let coeffs = if is_lowpass {
-                    SvfCoeffF64::lowpass_ord8(
-                        params.cutoff_hz as f64,
-                        params.q as f64,
-                        sample_rate_recip,
-                    )
-                } else {
-                    SvfCoeffF64::highpass_ord8(
-                        params.cutoff_hz as f64,
-                        params.q as f64,
-                        sample_rate_recip,
-                    )
-                };
+    match (params.shelf_gain_db, is_lowpass) {
+        (Some(gain_db), true) => {
+            OnePoleIirCoeffF64::high_shelf(cutoff_hz, gain_db as f64, sample_rate_recip)
+        }
+        (Some(gain_db), false) => {
+            OnePoleIirCoeffF64::low_shelf(cutoff_hz, gain_db as f64, sample_rate_recip)
+        }
+        (None, true) => OnePoleIirCoeffF64::lowpass(cutoff_hz, sample_rate_recip),
+        (None, false) => OnePoleIirCoeffF64::highpass(cutoff_hz, sample_rate_recip),
+    }
+}
 
-                if let Some(i) = self.svf_filter_i {
-                    svf_coeffs[i] = coeffs[0].to_f32();
-                    svf_coeffs[i + 1] = coeffs[1].to_f32();
-                    svf_coeffs[i + 2] = coeffs[2].to_f32();
-                    svf_coeffs[i + 3] = coeffs[3].to_f32();
-                } else {
-                    self.svf_filter_i = Some(svf_coeffs.len());
-                    svf_coeffs.push(coeffs[0].to_f32());
-                    svf_coeffs.push(coeffs[1].to_f32());
-                    svf_coeffs.push(coeffs[2].to_f32());
-                    svf_coeffs.push(coeffs[3].to_f32());
-                }
-            }
+/// The cascaded SVF sections of a lowpass or highpass band with an order of
+/// [`FilterOrder::X2`] or higher. (Empty for [`FilterOrder::X1`].)
+pub(crate) fn lp_hp_svf_coeffs(
+    params: &LpOrHpBandParams,
+    sample_rate_recip: f64,
+    is_lowpass: bool,
+) -> ArrayVec<SvfCoeffF64, MAX_LP_HP_SVF_FILTERS> {
+    let cutoff_hz = clamp_cutoff_hz(params.cutoff_hz, sample_rate_recip);
+    let q = params.q as f64;
+
+    let mut coeffs = ArrayVec::new();
+    match (params.order, is_lowpass) {
+        (FilterOrder::X1, _) => {}
+        (FilterOrder::X2, true) => {
+            coeffs.push(SvfCoeffF64::lowpass_ord2(cutoff_hz, q, sample_rate_recip))
+        }
+        (FilterOrder::X2, false) => {
+            coeffs.push(SvfCoeffF64::highpass_ord2(cutoff_hz, q, sample_rate_recip))
+        }
+        (FilterOrder::X4, true) => {
+            coeffs.extend(SvfCoeffF64::lowpass_ord4(cutoff_hz, q, sample_rate_recip))
         }
+        (FilterOrder::X4, false) => {
+            coeffs.extend(SvfCoeffF64::highpass_ord4(cutoff_hz, q, sample_rate_recip))
+        }
+        (FilterOrder::X6, true) => {
+            coeffs.extend(SvfCoeffF64::lowpass_ord6(cutoff_hz, q, sample_rate_recip))
+        }
+        (FilterOrder::X6, false) => {
+            coeffs.extend(SvfCoeffF64::highpass_ord6(cutoff_hz, q, sample_rate_recip))
+        }
+        (FilterOrder::X8, true) => {
+            coeffs.extend(SvfCoeffF64::lowpass_ord8(cutoff_hz, q, sample_rate_recip))
+        }
+        (FilterOrder::X8, false) => {
+            coeffs.extend(SvfCoeffF64::highpass_ord8(cutoff_hz, q, sample_rate_recip))
+        }
+        (FilterOrder::X12, true) => {
+            coeffs.extend(SvfCoeffF64::lowpass_ord12(cutoff_hz, q, sample_rate_recip))
+        }
+        (FilterOrder::X12, false) => {
+            coeffs.extend(SvfCoeffF64::highpass_ord12(cutoff_hz, q, sample_rate_recip))
+        }
+        (FilterOrder::X16, true) => {
+            coeffs.extend(SvfCoeffF64::lowpass_ord16(cutoff_hz, q, sample_rate_recip))
+        }
+        (FilterOrder::X16, false) => {
+            coeffs.extend(SvfCoeffF64::highpass_ord16(cutoff_hz, q, sample_rate_recip))
+        }
+    }
+
+    if params.matched_cutoff {
+        SvfCoeffF64::match_3db_cutoff(&mut coeffs, is_lowpass);
+    }
+
+    debug_assert!(
+        coeffs.iter().all(|c| c.is_stable()),
+        "unstable lowpass/highpass band coefficients: {params:?}"
+    );
+
+    coeffs
+}
+
+/// Find the frequency at which the response of a lowpass or highpass band
+/// crosses -3 dB by evaluating the magnitude of the whole cascade.
+fn lp_hp_corner_hz(params: &LpOrHpBandParams, sample_rate_recip: f64, is_lowpass: bool) -> f64 {
+    const TARGET: f64 = std::f64::consts::FRAC_1_SQRT_2;
+
+    let one_pole = (params.order == FilterOrder::X1)
+        .then(|| lp_hp_one_pole_coeff(params, sample_rate_recip, is_lowpass));
+    if one_pole.is_some() && params.shelf_gain_db.is_some() {
+        // A shelf does not necessarily cross -3 dB, so use the midpoint of
+        // its transition instead.
+        return params.cutoff_hz as f64;
+    }
+    let svf = lp_hp_svf_coeffs(params, sample_rate_recip, is_lowpass);
+
+    let magnitude = |freq_hz: f64| -> f64 {
+        if let Some(one_pole) = &one_pole {
+            one_pole.magnitude(freq_hz, sample_rate_recip)
+        } else {
+            svf.iter()
+                .map(|f| f.magnitude(freq_hz, sample_rate_recip))
+                .product::<f64>()
+        }
+    };
+
+    bisect_log_hz(sample_rate_recip, |freq_hz| {
+        (magnitude(freq_hz) >= TARGET) == is_lowpass
+    })
+}
+
+/// Bisect in log frequency between 1 Hz and just below Nyquist for the
+/// frequency at which `is_below` switches from `true` to `false`.
+pub(crate) fn bisect_log_hz(sample_rate_recip: f64, mut is_below: impl FnMut(f64) -> bool) -> f64 {
+    let mut lo = 0.0f64;
+    let mut hi = (0.4999 / sample_rate_recip).ln();
+    for _ in 0..64 {
+        let mid = 0.5 * (lo + hi);
+
+        if is_below(mid.exp()) {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+
+    (0.5 * (lo + hi)).exp()
+}
+
+/// The coefficients for a full set of parameters. See
+/// [`MeadowEqDspCoeff::compute_snapshot`].
+#[derive(Clone)]
+pub struct CoeffSnapshot<
+    const NUM_BANDS: usize,
+    const NUM_BANDS_PLUS_16: usize,
+    P: CoeffPrecision = F32Coeffs,
+> {
+    coeff: MeadowEqDspCoeff<NUM_BANDS, NUM_BANDS_PLUS_16, P>,
+}
+
+impl<const NUM_BANDS: usize, const NUM_BANDS_PLUS_16: usize, P: CoeffPrecision>
+    CoeffSnapshot<NUM_BANDS, NUM_BANDS_PLUS_16, P>
+{
+    /// The parameters the snapshot was computed for.
+    pub fn params(&self) -> &EqParams<NUM_BANDS> {
+        self.coeff.params()
     }
+
+    /// The coefficients, i.e. for drawing the response curve before the
+    /// snapshot is applied.
+    pub fn coeff(&self) -> &MeadowEqDspCoeff<NUM_BANDS, NUM_BANDS_PLUS_16, P> {
+        &self.coeff
+    }
+}
+
+/// A snapshot of the flags used to track which coefficients need to be
+/// recalculated on the next flush.
+#[cfg(feature = "debug-introspection")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DirtyFlags<const NUM_BANDS: usize> {
+    pub needs_param_flush: bool,
+    pub num_filters_changed: bool,
+    pub lp_band_needs_param_sync: bool,
+    pub hp_band_needs_param_sync: bool,
+    pub bands_needing_param_sync: [bool; NUM_BANDS],
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct StateSyncInfo<const NUM_BANDS: usize> {
     pub lp_band_enabled: bool,
     pub lp_band_order: FilterOrder,
@@ -396,3 +1402,178 @@ impl<const NUM_BANDS: usize> Default for StateSyncInfo<NUM_BANDS> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use meadow_dsp_mit::filter::svf::{f32::SvfState, f64::SvfState as SvfStateF64};
+
+    use super::*;
+    use crate::test_util::{noise, rms};
+
+    /// The error of processing `input` with the f32 filters in the given
+    /// order, relative to processing it in f64, in dB.
+    fn f32_noise_floor_db(sections: &[SvfCoeffF64], order: &[usize], input: &[f32]) -> f64 {
+        let mut reference = input.to_vec();
+        let mut states = vec![SvfStateF64::default(); sections.len()];
+        for s in reference.iter_mut() {
+            let mut x = *s as f64;
+            for (state, coeff) in states.iter_mut().zip(sections) {
+                x = state.tick(x, coeff);
+            }
+            *s = x as f32;
+        }
+
+        let coeffs: Vec<_> = order.iter().map(|&i| sections[i].to_f32()).collect();
+        let mut states = vec![SvfState::default(); sections.len()];
+        let error: Vec<f32> = input
+            .iter()
+            .zip(&reference)
+            .map(|(&x, &reference)| {
+                let mut x = x;
+                for (state, coeff) in states.iter_mut().zip(&coeffs) {
+                    x = state.tick(x, coeff);
+                }
+                x - reference
+            })
+            .collect();
+
+        amp_to_db(rms(&error) / rms(&reference))
+    }
+
+    /// Justifies keeping the SVF sections in a fixed order (see
+    /// [`MeadowEqDspCoeff::coeffs`]): sorting a high-Q cascade by frequency
+    /// or by Q does not lower the f32 noise floor.
+    #[test]
+    fn section_order_does_not_change_noise_floor() {
+        let sample_rate_recip = 1.0 / 48_000.0;
+
+        let mut sections = lp_hp_svf_coeffs(
+            &LpOrHpBandParams {
+                enabled: true,
+                cutoff_hz: 12_000.0,
+                q: 4.0,
+                order: FilterOrder::X8,
+                ..Default::default()
+            },
+            sample_rate_recip,
+            true,
+        )
+        .to_vec();
+        for (cutoff_hz, gain_db) in [
+            (8_000.0, -12.0),
+            (60.0, 12.0),
+            (2_000.0, 12.0),
+            (25.0, -12.0),
+        ] {
+            sections.push(band_coeff(
+                &BandParams {
+                    enabled: true,
+                    band_type: BandType::Bell,
+                    cutoff_hz,
+                    q: 20.0,
+                    gain_db,
+                    ..Default::default()
+                },
+                sample_rate_recip,
+            ));
+        }
+
+        let input = noise(96_000, 0);
+        let fixed: Vec<usize> = (0..sections.len()).collect();
+        let mut by_freq = fixed.clone();
+        by_freq.sort_by(|&a, &b| sections[a].g_and_k().0.total_cmp(&sections[b].g_and_k().0));
+        let mut by_q = fixed.clone();
+        by_q.sort_by(|&a, &b| sections[b].g_and_k().1.total_cmp(&sections[a].g_and_k().1));
+
+        let fixed_db = f32_noise_floor_db(&sections, &fixed, &input);
+        for (name, order) in [("frequency", by_freq), ("Q", by_q)] {
+            let sorted_db = f32_noise_floor_db(&sections, &order, &input);
+            assert!(
+                sorted_db > fixed_db - 1.0,
+                "sorting by {name} lowers the noise floor from {fixed_db} dB to {sorted_db} dB"
+            );
+        }
+    }
+
+    /// With a shelf gain, an X1 lowpass band becomes a high shelf which cuts
+    /// (or boosts) above the cutoff, and an X1 highpass band becomes a low
+    /// shelf.
+    #[test]
+    fn x1_shelf_option_shelves_the_stopband() {
+        let sample_rate_recip = 1.0 / 48_000.0;
+        let gain_db_at = |coeff: &OnePoleIirCoeffF64, freq_hz: f64| {
+            amp_to_db(coeff.magnitude(freq_hz, sample_rate_recip))
+        };
+
+        for shelf_gain_db in [-12.0, 6.0] {
+            let band = LpOrHpBandParams {
+                enabled: true,
+                cutoff_hz: 1_000.0,
+                order: FilterOrder::X1,
+                shelf_gain_db: Some(shelf_gain_db),
+                ..Default::default()
+            };
+            let lowpass = lp_hp_one_pole_coeff(&band, sample_rate_recip, true);
+            let highpass = lp_hp_one_pole_coeff(&band, sample_rate_recip, false);
+
+            let shelf_gain_db = shelf_gain_db as f64;
+            for (name, coeff, pass_hz, stop_hz) in [
+                ("lowpass", lowpass, 10.0, 20_000.0),
+                ("highpass", highpass, 20_000.0, 10.0),
+            ] {
+                let pass_db = gain_db_at(&coeff, pass_hz);
+                let stop_db = gain_db_at(&coeff, stop_hz);
+                let cutoff_db = gain_db_at(&coeff, 1_000.0);
+                assert!(
+                    pass_db.abs() < 0.1,
+                    "{name} {shelf_gain_db} dB: {pass_db} dB"
+                );
+                assert!(
+                    (stop_db - shelf_gain_db).abs() < 0.1,
+                    "{name} {shelf_gain_db} dB: {stop_db} dB"
+                );
+                assert!(
+                    (cutoff_db - 0.5 * shelf_gain_db).abs() < 0.1,
+                    "{name} {shelf_gain_db} dB: {cutoff_db} dB at the cutoff"
+                );
+            }
+        }
+    }
+
+    /// `set_params` only flags the bands whose parameters actually changed.
+    #[cfg(feature = "debug-introspection")]
+    #[test]
+    fn set_params_only_flags_changed_bands() {
+        let mut params = EqParams::<4>::default();
+        params.lp_band.enabled = true;
+        for band in params.bands.iter_mut() {
+            band.enabled = true;
+        }
+
+        let mut coeff = MeadowEqDspCoeff::<4, 20>::new(48_000.0);
+        coeff.set_params(&params);
+        let _ = coeff.flush_param_changes();
+        let clean = DirtyFlags {
+            needs_param_flush: false,
+            num_filters_changed: false,
+            lp_band_needs_param_sync: false,
+            hp_band_needs_param_sync: false,
+            bands_needing_param_sync: [false; 4],
+        };
+        assert_eq!(coeff.dirty_flags(), clean);
+
+        coeff.set_params(&params);
+        assert_eq!(coeff.dirty_flags(), clean);
+
+        params.bands[2].gain_db += 1.0;
+        coeff.set_params(&params);
+        assert_eq!(
+            coeff.dirty_flags(),
+            DirtyFlags {
+                needs_param_flush: true,
+                bands_needing_param_sync: [false, false, true, false],
+                ..clean
+            }
+        );
+    }
+}
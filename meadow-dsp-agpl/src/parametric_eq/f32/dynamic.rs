@@ -0,0 +1,353 @@
+use arrayvec::ArrayVec;
+use meadow_dsp_mit::{
+    decibel::f32::db_to_amp,
+    filter::{
+        one_pole_iir::f32::{OnePoleIirCoeff, OnePoleIirState},
+        svf::f32::{SvfCoeff, SvfState},
+    },
+    smooth::f32::{OnePoleSmoother, DEFAULT_SMOOTH_SECS},
+};
+
+use super::{
+    coeff::{band_coeff, lp_hp_one_pole_coeff, lp_hp_svf_coeffs, MAX_LP_HP_SVF_FILTERS},
+    mono::apply_gain,
+    BandParams, BandRouting, EqParams, FilterOrder, LpOrHpBandParams,
+};
+
+/// A single channel parametric EQ whose number of bands is chosen at runtime,
+/// for apps where the user can add and remove bands.
+///
+/// This has the same filters as [`MeadowEqDspMono`](super::mono::MeadowEqDspMono)
+/// (a lowpass band, a highpass band, and any number of bands), but the bands
+/// are stored in `Vec`s instead of arrays. Memory is only (re)allocated when a
+/// band is added beyond the current capacity, never while processing or
+/// while changing the parameters of a band. Use
+/// [`MeadowEqDspDynamic::with_capacity`] to avoid allocating when bands are
+/// added on the audio thread.
+///
+/// Unlike the fixed-size EQs, the coefficients are computed as soon as the
+/// parameters are set, and a band which is disabled or removed stops
+/// immediately instead of fading out.
+#[derive(Clone)]
+pub struct MeadowEqDspDynamic {
+    lp_band: LpOrHpBandParams,
+    hp_band: LpOrHpBandParams,
+    lp_filter: LpOrHpFilter,
+    hp_filter: LpOrHpFilter,
+
+    bands: Vec<BandParams>,
+    band_coeffs: Vec<SvfCoeff>,
+    band_states: Vec<SvfState>,
+
+    band_routing: BandRouting,
+
+    gain_db: f32,
+    gain: OnePoleSmoother,
+
+    input_gain_db: f32,
+    input_gain: OnePoleSmoother,
+
+    sample_rate: f64,
+    sample_rate_recip: f64,
+}
+
+impl MeadowEqDspDynamic {
+    pub const LATENCY: u32 = 0;
+
+    /// Create a new EQ with no bands. The lowpass and highpass bands are
+    /// disabled.
+    pub fn new(sample_rate: f64) -> Self {
+        Self::with_capacity(0, sample_rate)
+    }
+
+    /// Create a new EQ with no bands, with room for `capacity` bands before
+    /// adding a band allocates.
+    pub fn with_capacity(capacity: usize, sample_rate: f64) -> Self {
+        let defaults = EqParams::<0>::default();
+
+        Self {
+            lp_band: defaults.lp_band,
+            hp_band: defaults.hp_band,
+            lp_filter: LpOrHpFilter::default(),
+            hp_filter: LpOrHpFilter::default(),
+            bands: Vec::with_capacity(capacity),
+            band_coeffs: Vec::with_capacity(capacity),
+            band_states: Vec::with_capacity(capacity),
+            band_routing: BandRouting::default(),
+            gain_db: 0.0,
+            gain: OnePoleSmoother::new(1.0, DEFAULT_SMOOTH_SECS, sample_rate),
+            input_gain_db: 0.0,
+            input_gain: OnePoleSmoother::new(1.0, DEFAULT_SMOOTH_SECS, sample_rate),
+            sample_rate,
+            sample_rate_recip: sample_rate.recip(),
+        }
+    }
+
+    /// The sample rate this EQ was created with.
+    pub fn sample_rate(&self) -> f64 {
+        self.sample_rate
+    }
+
+    /// The number of bands (not including the lowpass and highpass bands).
+    pub fn num_bands(&self) -> usize {
+        self.bands.len()
+    }
+
+    /// The number of bands that can be added before adding a band allocates.
+    pub fn capacity(&self) -> usize {
+        self.bands.capacity()
+    }
+
+    pub fn band_routing(&self) -> BandRouting {
+        self.band_routing
+    }
+
+    /// Set how the bands (not including the lowpass and highpass bands) are
+    /// combined. See [`BandRouting`] for details.
+    pub fn set_band_routing(&mut self, routing: BandRouting) {
+        self.band_routing = routing;
+    }
+
+    pub fn gain_db(&self) -> f32 {
+        self.gain_db
+    }
+
+    /// Set the gain applied to the output of the EQ in decibels. Changes are
+    /// smoothed to avoid clicks.
+    pub fn set_gain_db(&mut self, gain_db: f32) {
+        self.gain_db = gain_db;
+        self.gain.set(db_to_amp(gain_db));
+    }
+
+    pub fn input_gain_db(&self) -> f32 {
+        self.input_gain_db
+    }
+
+    /// Set the gain applied to the input of the EQ (before any of the
+    /// filters) in decibels. Changes are smoothed to avoid clicks.
+    pub fn set_input_gain_db(&mut self, gain_db: f32) {
+        self.input_gain_db = gain_db;
+        self.input_gain.set(db_to_amp(gain_db));
+    }
+
+    pub fn lp_band(&self) -> &LpOrHpBandParams {
+        &self.lp_band
+    }
+
+    pub fn set_lp_band(&mut self, params: &LpOrHpBandParams) {
+        self.lp_band = *params;
+        self.lp_filter
+            .sync(&self.lp_band, self.sample_rate_recip, true);
+    }
+
+    pub fn hp_band(&self) -> &LpOrHpBandParams {
+        &self.hp_band
+    }
+
+    pub fn set_hp_band(&mut self, params: &LpOrHpBandParams) {
+        self.hp_band = *params;
+        self.hp_filter
+            .sync(&self.hp_band, self.sample_rate_recip, false);
+    }
+
+    /// The parameters of all bands (not including the lowpass and highpass
+    /// bands), in processing order.
+    pub fn bands(&self) -> &[BandParams] {
+        &self.bands
+    }
+
+    /// Add a band after the existing bands, and return its index.
+    ///
+    /// This allocates if the number of bands exceeds the capacity.
+    pub fn add_band(&mut self, params: &BandParams) -> usize {
+        self.bands.push(*params);
+        self.band_coeffs
+            .push(band_svf_coeff(params, self.sample_rate_recip));
+        self.band_states.push(SvfState::default());
+
+        self.bands.len() - 1
+    }
+
+    /// Remove the band at the given index, and return its parameters. The
+    /// bands after it move down by one index.
+    ///
+    /// This never deallocates.
+    ///
+    /// # Panics
+    /// Panics if `index >= self.num_bands()`.
+    pub fn remove_band(&mut self, index: usize) -> BandParams {
+        self.band_coeffs.remove(index);
+        self.band_states.remove(index);
+        self.bands.remove(index)
+    }
+
+    /// Remove all bands. This never deallocates.
+    pub fn clear_bands(&mut self) {
+        self.bands.clear();
+        self.band_coeffs.clear();
+        self.band_states.clear();
+    }
+
+    /// Set the parameters of the band at the given index.
+    ///
+    /// # Panics
+    /// Panics if `index >= self.num_bands()`.
+    pub fn set_band(&mut self, index: usize, params: &BandParams) {
+        if params.enabled && !self.bands[index].enabled {
+            // Start from silence instead of a stale state.
+            self.band_states[index].reset();
+        }
+
+        self.bands[index] = *params;
+        self.band_coeffs[index] = band_svf_coeff(params, self.sample_rate_recip);
+    }
+
+    /// Replace all parameters with the given fixed-size parameters, i.e. to
+    /// load a preset saved from a fixed-size EQ. The bands are replaced by
+    /// those of `params`.
+    ///
+    /// This allocates if `NUM_BANDS` exceeds the capacity.
+    pub fn set_from_params<const NUM_BANDS: usize>(&mut self, params: &EqParams<NUM_BANDS>) {
+        self.set_lp_band(&params.lp_band);
+        self.set_hp_band(&params.hp_band);
+
+        self.clear_bands();
+        for band in params.bands.iter() {
+            self.add_band(band);
+        }
+    }
+
+    /// Reset the state of all filters and finish any gain smoothing. The
+    /// parameters are kept.
+    pub fn reset(&mut self) {
+        self.lp_filter.reset();
+        self.hp_filter.reset();
+        for s in self.band_states.iter_mut() {
+            s.reset();
+        }
+
+        self.gain.finish();
+        self.input_gain.finish();
+    }
+
+    /// Process the given buffer in place.
+    pub fn process(&mut self, buf: &mut [f32]) {
+        apply_gain(&mut self.input_gain, buf);
+
+        // The same order as the fixed-size EQs: the one-pole filters, then
+        // the SVF filters of the lowpass and highpass bands, then the bands.
+        self.lp_filter.process_one_pole(buf);
+        self.hp_filter.process_one_pole(buf);
+        self.lp_filter.process_svf(buf);
+        self.hp_filter.process_svf(buf);
+
+        match self.band_routing {
+            BandRouting::Series => {
+                for ((params, coeff), state) in self
+                    .bands
+                    .iter()
+                    .zip(self.band_coeffs.iter())
+                    .zip(self.band_states.iter_mut())
+                {
+                    if !params.enabled {
+                        continue;
+                    }
+
+                    for out in buf.iter_mut() {
+                        *out = state.tick(*out, coeff);
+                    }
+                }
+            }
+            BandRouting::Parallel => {
+                if self.bands.iter().any(|b| b.enabled) {
+                    for out in buf.iter_mut() {
+                        let input = *out;
+                        let mut s = input;
+
+                        for ((params, coeff), state) in self
+                            .bands
+                            .iter()
+                            .zip(self.band_coeffs.iter())
+                            .zip(self.band_states.iter_mut())
+                        {
+                            if params.enabled {
+                                s += state.tick(input, coeff) - input;
+                            }
+                        }
+
+                        *out = s;
+                    }
+                }
+            }
+        }
+
+        apply_gain(&mut self.gain, buf);
+    }
+}
+
+fn band_svf_coeff(params: &BandParams, sample_rate_recip: f64) -> SvfCoeff {
+    band_coeff(params, sample_rate_recip).to_f32()
+}
+
+/// The filters of a lowpass or highpass band.
+#[derive(Default, Clone)]
+struct LpOrHpFilter {
+    enabled: bool,
+    order: FilterOrder,
+
+    one_pole_coeff: OnePoleIirCoeff,
+    one_pole_state: OnePoleIirState,
+    svf_coeffs: ArrayVec<SvfCoeff, MAX_LP_HP_SVF_FILTERS>,
+    svf_states: [SvfState; MAX_LP_HP_SVF_FILTERS],
+}
+
+impl LpOrHpFilter {
+    fn sync(&mut self, params: &LpOrHpBandParams, sample_rate_recip: f64, is_lowpass: bool) {
+        if params.enabled != self.enabled || params.order != self.order {
+            // The filter topology changed, so start from silence.
+            self.reset();
+        }
+
+        self.enabled = params.enabled;
+        self.order = params.order;
+        self.svf_coeffs.clear();
+
+        if !params.enabled {
+            return;
+        }
+
+        if params.order == FilterOrder::X1 {
+            self.one_pole_coeff =
+                lp_hp_one_pole_coeff(params, sample_rate_recip, is_lowpass).to_f32();
+        } else {
+            self.svf_coeffs.extend(
+                lp_hp_svf_coeffs(params, sample_rate_recip, is_lowpass)
+                    .iter()
+                    .map(|c| c.to_f32()),
+            );
+        }
+    }
+
+    fn process_one_pole(&mut self, buf: &mut [f32]) {
+        if !self.enabled || self.order != FilterOrder::X1 {
+            return;
+        }
+
+        for out in buf.iter_mut() {
+            *out = self.one_pole_state.tick(*out, &self.one_pole_coeff);
+        }
+    }
+
+    fn process_svf(&mut self, buf: &mut [f32]) {
+        for (coeff, state) in self.svf_coeffs.iter().zip(self.svf_states.iter_mut()) {
+            for out in buf.iter_mut() {
+                *out = state.tick(*out, coeff);
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.one_pole_state.reset();
+        self.svf_states = [SvfState::default(); MAX_LP_HP_SVF_FILTERS];
+    }
+}
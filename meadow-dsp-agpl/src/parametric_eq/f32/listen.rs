@@ -0,0 +1,67 @@
+use meadow_dsp_mit::filter::svf::{
+    f32::{SvfCoeff, SvfState},
+    f64::SvfCoeff as SvfCoeffF64,
+};
+
+use super::{coeff::band_coeff, BandParams};
+
+/// A copy of the filter of a single band, used by `process_with_band_listen`
+/// to route the signal that band produces on its own, for one channel.
+#[derive(Clone, Copy)]
+pub(crate) struct BandListen {
+    /// The band which is listened to.
+    band_index: Option<usize>,
+    /// The parameters the coefficients were computed from, or `None` if they
+    /// are raw coefficients.
+    params: Option<BandParams>,
+    coeff: SvfCoeff,
+    state: SvfState,
+}
+
+impl BandListen {
+    pub(crate) fn new() -> Self {
+        Self {
+            band_index: None,
+            params: None,
+            coeff: SvfCoeff::NO_OP,
+            state: SvfState::default(),
+        }
+    }
+
+    /// Write `input` filtered by the band at `band_index` into `out`, using
+    /// `raw_coeff` instead of the parameters if the band has raw
+    /// coefficients. The state is reset when a different band is listened
+    /// to.
+    pub(crate) fn process(
+        &mut self,
+        band_index: usize,
+        params: &BandParams,
+        raw_coeff: Option<SvfCoeffF64>,
+        sample_rate_recip: f64,
+        input: &[f32],
+        out: &mut [f32],
+    ) {
+        if self.band_index != Some(band_index) {
+            self.band_index = Some(band_index);
+            self.params = None;
+            self.state.reset();
+        }
+
+        if let Some(raw_coeff) = raw_coeff {
+            // Converting raw coefficients is cheap, so always use the latest.
+            self.params = None;
+            self.coeff = raw_coeff.to_f32();
+        } else if self.params != Some(*params) {
+            self.params = Some(*params);
+            self.coeff = band_coeff(params, sample_rate_recip).to_f32();
+        }
+
+        for (out, &input) in out.iter_mut().zip(input.iter()) {
+            *out = self.state.tick(input, &self.coeff);
+        }
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.state.reset();
+    }
+}
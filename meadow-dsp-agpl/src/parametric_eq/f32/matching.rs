@@ -0,0 +1,237 @@
+use super::{coeff::band_coeff, BandParams, BandType, EqParams, DEFAULT_Q};
+
+/// The largest gain (boost or cut) that [`match_response`] gives a band.
+pub const MAX_MATCH_GAIN_DB: f32 = 24.0;
+
+/// [`match_response`] stops adding bands once the error at every point is
+/// below this many decibels.
+pub const MATCH_TOLERANCE_DB: f32 = 0.1;
+
+/// The Q values tried for each band.
+const CANDIDATE_QS: [f32; 9] = [0.3, 0.5, DEFAULT_Q, 1.0, 1.5, 2.0, 3.0, 5.0, 8.0];
+
+/// The number of passes which re-fit each band with the others in place.
+const REFINE_PASSES: usize = 2;
+
+/// Fit the bands of an EQ so that its magnitude response approximates the
+/// given target curve, e.g. for a "match EQ" feature.
+///
+/// `target` is a list of `(frequency in Hz, gain in dB)` points in any order.
+/// Points at or above the Nyquist frequency are ignored. The points are
+/// weighted equally, so they should usually be spaced logarithmically.
+///
+/// This is a greedy fit: each band is placed where the remaining error is
+/// largest, choosing whichever bell or shelf (and Q) reduces the squared
+/// error the most. The bands are then refined by re-fitting each one with the
+/// others in place. At most `num_bands.min(NUM_BANDS)` bands are used, and
+/// the lowpass and highpass bands are left disabled.
+///
+/// This allocates, so it should not be used on the audio thread.
+pub fn match_response<const NUM_BANDS: usize>(
+    target: &[(f32, f32)],
+    num_bands: usize,
+    sample_rate: f64,
+) -> EqParams<NUM_BANDS> {
+    let sample_rate_recip = sample_rate.recip();
+    let nyquist_hz = (sample_rate * 0.5) as f32;
+
+    let mut points: Vec<(f32, f32)> = target
+        .iter()
+        .copied()
+        .filter(|&(freq_hz, _)| freq_hz > 0.0 && freq_hz < nyquist_hz)
+        .collect();
+    // The shelves are fit to the lowest and highest points.
+    points.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut params = EqParams::<NUM_BANDS>::default();
+    if points.is_empty() {
+        return params;
+    }
+
+    let num_bands = num_bands.min(NUM_BANDS);
+    let mut responses: Vec<Vec<f32>> = Vec::with_capacity(num_bands);
+
+    for i in 0..num_bands {
+        let residual = residual(&points, &responses, None);
+
+        let max_error = residual.iter().fold(0.0f32, |a, &e| a.max(e.abs()));
+        if max_error < MATCH_TOLERANCE_DB {
+            break;
+        }
+
+        let (band, response) = best_band(&points, &residual, sample_rate_recip);
+        params.bands[i] = band;
+        responses.push(response);
+    }
+
+    for _ in 0..REFINE_PASSES {
+        for i in 0..responses.len() {
+            let residual = residual(&points, &responses, Some(i));
+            let (band, response) = best_band(&points, &residual, sample_rate_recip);
+
+            if squared_error(&residual, &response) < squared_error(&residual, &responses[i]) {
+                params.bands[i] = band;
+                responses[i] = response;
+            }
+        }
+    }
+
+    params
+}
+
+/// The target minus the sum of the responses of all bands except `skip`.
+fn residual(points: &[(f32, f32)], responses: &[Vec<f32>], skip: Option<usize>) -> Vec<f32> {
+    points
+        .iter()
+        .enumerate()
+        .map(|(p, &(_, target_db))| {
+            let total_db: f32 = responses
+                .iter()
+                .enumerate()
+                .filter(|&(i, _)| Some(i) != skip)
+                .map(|(_, response)| response[p])
+                .sum();
+
+            target_db - total_db
+        })
+        .collect()
+}
+
+/// Find the band which best fits the residual, along with its response at
+/// every point.
+fn best_band(
+    points: &[(f32, f32)],
+    residual: &[f32],
+    sample_rate_recip: f64,
+) -> (BandParams, Vec<f32>) {
+    let peak_i = residual
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.abs().total_cmp(&b.1.abs()))
+        .map(|(i, _)| i)
+        .unwrap();
+    let peak_hz = points[peak_i].0;
+
+    let clamp_gain = |gain_db: f32| gain_db.clamp(-MAX_MATCH_GAIN_DB, MAX_MATCH_GAIN_DB);
+
+    let candidates = CANDIDATE_QS.iter().flat_map(|&q| {
+        [
+            (BandType::Bell, q, clamp_gain(residual[peak_i])),
+            (BandType::LowShelf, q, clamp_gain(residual[0])),
+            (
+                BandType::HighShelf,
+                q,
+                clamp_gain(residual[residual.len() - 1]),
+            ),
+        ]
+    });
+
+    candidates
+        .map(|(band_type, q, gain_db)| {
+            let band = BandParams {
+                enabled: true,
+                band_type,
+                cutoff_hz: peak_hz,
+                q,
+                gain_db,
+                ..Default::default()
+            };
+            let response = band_response_db(&band, points, sample_rate_recip);
+
+            (band, response)
+        })
+        .min_by(|a, b| squared_error(residual, &a.1).total_cmp(&squared_error(residual, &b.1)))
+        .unwrap()
+}
+
+fn band_response_db(band: &BandParams, points: &[(f32, f32)], sample_rate_recip: f64) -> Vec<f32> {
+    let coeff = band_coeff(band, sample_rate_recip);
+
+    points
+        .iter()
+        .map(|&(freq_hz, _)| {
+            (20.0 * coeff.magnitude(freq_hz as f64, sample_rate_recip).log10()) as f32
+        })
+        .collect()
+}
+
+fn squared_error(residual: &[f32], response: &[f32]) -> f32 {
+    residual
+        .iter()
+        .zip(response.iter())
+        .map(|(r, b)| (r - b) * (r - b))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f64 = 48_000.0;
+
+    /// Log-spaced points from 20 Hz to 20 kHz, 30 per decade, which include
+    /// 1 kHz.
+    fn target(band: &BandParams) -> Vec<(f32, f32)> {
+        let freqs: Vec<(f32, f32)> = (39..=129)
+            .map(|k| (10f64.powf(k as f64 / 30.0) as f32, 0.0))
+            .collect();
+        let response = band_response_db(band, &freqs, SAMPLE_RATE.recip());
+
+        freqs
+            .iter()
+            .map(|&(freq_hz, _)| freq_hz)
+            .zip(response)
+            .collect()
+    }
+
+    #[test]
+    fn recovers_a_single_bell() {
+        let band = BandParams {
+            enabled: true,
+            band_type: BandType::Bell,
+            cutoff_hz: 1_000.0,
+            q: 2.0,
+            gain_db: 6.0,
+            ..Default::default()
+        };
+        let mut target = target(&band);
+
+        for reversed in [false, true] {
+            if reversed {
+                target.reverse();
+            }
+
+            let params = match_response::<4>(&target, 4, SAMPLE_RATE);
+            let fitted = params.bands[0];
+            assert_eq!(fitted.band_type, BandType::Bell, "reversed: {reversed}");
+            assert!((fitted.cutoff_hz - 1_000.0).abs() < 1.0, "{fitted:?}");
+            assert!((fitted.q - 2.0).abs() < 1e-6, "{fitted:?}");
+            assert!((fitted.gain_db - 6.0).abs() < 0.01, "{fitted:?}");
+
+            // The first band already matches, so no more are added.
+            assert_eq!(params.num_enabled_bands(), 1, "{params:?}");
+        }
+    }
+
+    /// The shelves are fit to the lowest and highest points, so the order of
+    /// the points must not matter.
+    #[test]
+    fn point_order_does_not_matter() {
+        let band = BandParams {
+            enabled: true,
+            band_type: BandType::LowShelf,
+            cutoff_hz: 5_000.0,
+            q: DEFAULT_Q,
+            gain_db: -12.0,
+            ..Default::default()
+        };
+        let sorted = target(&band);
+        let mut shuffled = sorted.clone();
+        shuffled.rotate_left(sorted.len() / 2);
+
+        assert_eq!(
+            match_response::<4>(&shuffled, 4, SAMPLE_RATE),
+            match_response::<4>(&sorted, 4, SAMPLE_RATE)
+        );
+    }
+}
@@ -0,0 +1,591 @@
+use meadow_dsp_mit::{
+    decibel::f32::db_to_amp,
+    dynamics::f32::SoftLimiter,
+    filter::svf::f64::SvfCoeff as SvfCoeffF64,
+    smooth::f32::{OnePoleSmoother, DEFAULT_SMOOTH_SECS},
+};
+
+#[cfg(feature = "debug-introspection")]
+use super::coeff::DirtyFlags;
+use super::{
+    coeff::{
+        CoeffSnapshot, MeadowEqDspCoeff, StateSyncInfo, BAND_FADE_STEP_FRAMES,
+        CUTOFF_MOD_STEP_FRAMES,
+    },
+    listen::BandListen,
+    state::MeadowEqDspState,
+    BandRouting, EqParams,
+};
+
+/// The DSP for a fully-featured parametric EQ. This version has a single
+/// channel, does not make use of SIMD optimizations, and has zero latency.
+///
+/// This is the single channel counterpart of
+/// [`MeadowEqDspStereoLinked`](super::stereo::scalar::MeadowEqDspStereoLinked),
+/// and it has the same API apart from `process`.
+///
+/// TODO: Get rid of `NUM_BANDS_PLUS_16` const generic once const generic expressions
+/// are stabilized. (please rust compiler team)
+#[derive(Clone)]
+pub struct MeadowEqDspMono<const NUM_BANDS: usize, const NUM_BANDS_PLUS_16: usize> {
+    coeff: MeadowEqDspCoeff<NUM_BANDS, NUM_BANDS_PLUS_16>,
+    state: MeadowEqDspState<NUM_BANDS, NUM_BANDS_PLUS_16>,
+
+    band_routing: BandRouting,
+
+    gain_db: f32,
+    gain: OnePoleSmoother,
+
+    input_gain_db: f32,
+    input_gain: OnePoleSmoother,
+
+    output_ceiling_db: Option<f32>,
+    output_limiter: Option<SoftLimiter>,
+
+    /// The mix of the last call to `process_mix`, which the next call ramps
+    /// from.
+    mix: f32,
+
+    listen: BandListen,
+}
+
+impl<const NUM_BANDS: usize, const NUM_BANDS_PLUS_16: usize>
+    MeadowEqDspMono<NUM_BANDS, NUM_BANDS_PLUS_16>
+{
+    pub const LATENCY: u32 = 0;
+
+    pub fn new(sample_rate: f64) -> Self {
+        Self {
+            coeff: MeadowEqDspCoeff::new(sample_rate),
+            state: MeadowEqDspState::new(),
+            band_routing: BandRouting::default(),
+            gain_db: 0.0,
+            gain: OnePoleSmoother::new(1.0, DEFAULT_SMOOTH_SECS, sample_rate),
+            input_gain_db: 0.0,
+            input_gain: OnePoleSmoother::new(1.0, DEFAULT_SMOOTH_SECS, sample_rate),
+            output_ceiling_db: None,
+            output_limiter: None,
+            mix: 1.0,
+            listen: BandListen::new(),
+        }
+    }
+
+    /// The number of bands (not including the lowpass and highpass bands).
+    pub const fn num_bands() -> usize {
+        NUM_BANDS
+    }
+
+    /// The sample rate this EQ was created with.
+    pub fn sample_rate(&self) -> f64 {
+        self.coeff.sample_rate()
+    }
+
+    pub fn band_routing(&self) -> BandRouting {
+        self.band_routing
+    }
+
+    /// Set how the bands (not including the lowpass and highpass bands) are
+    /// combined. See [`BandRouting`] for details.
+    pub fn set_band_routing(&mut self, routing: BandRouting) {
+        self.band_routing = routing;
+    }
+
+    pub fn gain_db(&self) -> f32 {
+        self.gain_db
+    }
+
+    /// Set the gain applied to the output of the EQ in decibels. Changes are
+    /// smoothed to avoid clicks, so this can also be used to fade the output
+    /// out by setting it to `f32::NEG_INFINITY`.
+    pub fn set_gain_db(&mut self, gain_db: f32) {
+        self.gain_db = gain_db;
+        self.gain.set(db_to_amp(gain_db));
+    }
+
+    pub fn input_gain_db(&self) -> f32 {
+        self.input_gain_db
+    }
+
+    /// Set the gain applied to the input of the EQ (before any of the
+    /// filters) in decibels. Changes are smoothed to avoid clicks. This is
+    /// independent of the output gain set with `set_gain_db`.
+    pub fn set_input_gain_db(&mut self, gain_db: f32) {
+        self.input_gain_db = gain_db;
+        self.input_gain.set(db_to_amp(gain_db));
+    }
+
+    pub fn output_ceiling_db(&self) -> Option<f32> {
+        self.output_ceiling_db
+    }
+
+    /// Set the ceiling of a soft limiter on the output (after the output
+    /// gain) in decibels, or `None` to disable it (the default). See
+    /// [`MeadowEqDspStereoLinked::set_output_ceiling_db`](super::stereo::scalar::MeadowEqDspStereoLinked::set_output_ceiling_db).
+    pub fn set_output_ceiling_db(&mut self, ceiling_db: Option<f32>) {
+        self.output_ceiling_db = ceiling_db;
+        self.output_limiter = ceiling_db.map(SoftLimiter::new);
+    }
+
+    pub fn params(&self) -> &EqParams<NUM_BANDS> {
+        self.coeff.params()
+    }
+
+    pub fn set_params(&mut self, params: &EqParams<NUM_BANDS>) {
+        self.coeff.set_params(params);
+    }
+
+    /// Modify the parameters in place. Only the bands whose parameters were
+    /// actually changed by `f` are marked for recalculation.
+    pub fn modify_params(&mut self, f: impl FnOnce(&mut EqParams<NUM_BANDS>)) {
+        self.coeff.modify_params(f);
+    }
+
+    /// Set the coefficients of the band at the given index directly. See
+    /// [`MeadowEqDspCoeff::set_band_raw_coeff`].
+    ///
+    /// # Panics
+    /// Panics if `index >= NUM_BANDS`.
+    pub fn set_band_raw_coeff(&mut self, index: usize, coeff: SvfCoeffF64) {
+        self.coeff.set_band_raw_coeff(index, coeff);
+    }
+
+    pub fn needs_param_flush(&self) -> bool {
+        self.coeff.needs_param_flush()
+    }
+
+    /// An estimate of the cost of the next flush. See
+    /// [`MeadowEqDspCoeff::pending_recompute_cost`].
+    pub fn pending_recompute_cost(&self) -> usize {
+        self.coeff.pending_recompute_cost()
+    }
+
+    /// Returns the current dirty flags of the coefficients. This is only meant
+    /// to be used for debugging and testing.
+    #[cfg(feature = "debug-introspection")]
+    pub fn dirty_flags(&self) -> DirtyFlags<NUM_BANDS> {
+        self.coeff.dirty_flags()
+    }
+
+    /// Flush any pending parameter changes.
+    ///
+    /// The internal filter states are synced automatically. The returned
+    /// info is `Some` if the filter topology changed, so that any external
+    /// mirror of the filter states can be synced as well.
+    pub fn flush_param_changes(&mut self) -> Option<StateSyncInfo<NUM_BANDS>> {
+        let info = self.coeff.flush_param_changes();
+        self.sync_state(info)
+    }
+
+    /// Recompute the coefficients of every band regardless of which
+    /// parameters have changed. See [`MeadowEqDspCoeff::recompute_all`].
+    ///
+    /// The returned info is the same as for
+    /// [`MeadowEqDspMono::flush_param_changes`].
+    pub fn recompute_all(&mut self) -> Option<StateSyncInfo<NUM_BANDS>> {
+        let info = self.coeff.recompute_all();
+        self.sync_state(info)
+    }
+
+    /// Replace the parameters and coefficients with those of a snapshot
+    /// computed on another thread, and sync the filter states. See
+    /// [`MeadowEqDspCoeff::apply_snapshot`].
+    pub fn apply_coeff_snapshot(&mut self, snapshot: CoeffSnapshot<NUM_BANDS, NUM_BANDS_PLUS_16>) {
+        let info = self.coeff.apply_snapshot(snapshot);
+        self.state.sync(&info);
+    }
+
+    fn sync_state(
+        &mut self,
+        info: Option<StateSyncInfo<NUM_BANDS>>,
+    ) -> Option<StateSyncInfo<NUM_BANDS>> {
+        if let Some(info) = &info {
+            self.state.sync(info);
+        }
+
+        info
+    }
+
+    /// Set the duration of the fade applied when a band is disabled. See
+    /// [`MeadowEqDspCoeff::set_band_fade_secs`].
+    pub fn set_band_fade_secs(&mut self, fade_secs: f64) {
+        self.coeff.set_band_fade_secs(fade_secs);
+    }
+
+    /// Whether every band always keeps its filter slot, even while it is
+    /// disabled. See [`MeadowEqDspCoeff::set_fixed_band_slots`].
+    pub fn set_fixed_band_slots(&mut self, fixed: bool) {
+        self.coeff.set_fixed_band_slots(fixed);
+    }
+
+    /// An estimate of how many samples it takes for the impulse response to
+    /// decay below `threshold_db`. See
+    /// [`MeadowEqDspCoeff::estimated_settling_samples`].
+    pub fn estimated_settling_samples(&self, threshold_db: f64) -> usize {
+        self.coeff.estimated_settling_samples(threshold_db)
+    }
+
+    /// Reset the state of all filters, and jump the smoothed gains straight to
+    /// their targets. Bands which are fading out are removed immediately.
+    ///
+    /// The parameters (and so the target coefficients) are left untouched,
+    /// so steady input produces steady output right away.
+    pub fn reset(&mut self) {
+        self.state.reset();
+        self.coeff.finish_band_fades();
+        self.gain.finish();
+        self.input_gain.finish();
+        self.listen.reset();
+    }
+
+    /// Reset the state of only the band at the given index, leaving the
+    /// state of all other bands untouched.
+    ///
+    /// # Panics
+    /// Panics if `index >= NUM_BANDS`.
+    pub fn reset_band(&mut self, index: usize) {
+        self.state.reset_band(index);
+    }
+
+    /// Reset the state of only the lowpass band.
+    pub fn reset_lp(&mut self) {
+        self.state.reset_lp();
+    }
+
+    /// Reset the state of only the highpass band.
+    pub fn reset_hp(&mut self) {
+        self.state.reset_hp();
+    }
+
+    /// Set the state of all filters to the steady state for a constant input
+    /// of `dc_value`, after applying any pending parameter changes. See
+    /// [`MeadowEqDspState::prime_for_dc`].
+    pub fn prime_for_dc(&mut self, dc_value: f32) {
+        let _ = self.flush_param_changes();
+        self.state.prime_for_dc(
+            dc_value * self.input_gain.target(),
+            &self.coeff,
+            self.band_routing,
+        );
+    }
+
+    /// Process the given buffer in place.
+    ///
+    /// Without an output ceiling (see [`Self::set_output_ceiling_db`]) the EQ
+    /// is linear: starting from a [`Self::reset`] state, processing `a * x`
+    /// gives `a` times the output for `x`, apart from floating point
+    /// rounding. The limiter behind the ceiling is nonlinear.
+    pub fn process(&mut self, buf: &mut [f32]) {
+        if !(self.coeff.is_fading_bands()) {
+            self.process_block(buf);
+            return;
+        }
+
+        // Advance the fades of the bands being disabled in small steps.
+        for buf in buf.chunks_mut(BAND_FADE_STEP_FRAMES) {
+            let frames = buf.len();
+            self.coeff.advance_band_fades(frames);
+            self.process_block(buf);
+        }
+    }
+
+    /// Process the given buffer in place like [`Self::process`], but without
+    /// checking for pending parameter changes first. This is for callers which
+    /// call `flush_param_changes` themselves at block boundaries.
+    ///
+    /// The caller must make sure that there are no pending parameter changes
+    /// (see `needs_param_flush`), otherwise the changes are not applied until
+    /// the next flush. This is checked with a debug assertion. While a band is
+    /// fading out, this falls back to [`Self::process`], since the end of the
+    /// fade needs a flush part way through the buffer.
+    pub fn process_no_flush(&mut self, buf: &mut [f32]) {
+        debug_assert!(!self.needs_param_flush());
+
+        if self.coeff.is_fading_bands() {
+            self.process(buf);
+        } else {
+            self.process_block_no_flush(buf);
+        }
+    }
+
+    /// Process the given buffer in place, mixing the processed (wet) signal
+    /// with the unprocessed (dry) signal.
+    ///
+    /// `mix` is in the range `[0.0, 1.0]`, where `0.0` is fully dry and `1.0`
+    /// is fully wet. Changes to `mix` are ramped over the buffer to avoid
+    /// clicks.
+    ///
+    /// `dry_scratch` is a scratch buffer which holds a copy of the dry signal,
+    /// so that this never allocates. It can be any length (the buffer is
+    /// processed in chunks of that length).
+    ///
+    /// # Panics
+    /// Panics if `dry_scratch` is empty.
+    pub fn process_mix(&mut self, buf: &mut [f32], dry_scratch: &mut [f32], mix: f32) {
+        assert!(!dry_scratch.is_empty());
+
+        let from_mix = self.mix;
+        self.mix = mix.clamp(0.0, 1.0);
+
+        if from_mix == 1.0 && self.mix == 1.0 {
+            self.process(buf);
+            return;
+        }
+
+        let frames = buf.len();
+        let mut start = 0;
+        for buf in buf.chunks_mut(dry_scratch.len()) {
+            let end = start + buf.len();
+            let dry = &mut dry_scratch[..buf.len()];
+
+            dry.copy_from_slice(buf);
+            self.process(buf);
+
+            mix_dry(
+                buf,
+                dry,
+                ramp(from_mix, self.mix, start, frames),
+                ramp(from_mix, self.mix, end, frames),
+            );
+            start = end;
+        }
+    }
+
+    /// Process the given buffer in place like [`Self::process_mix`], but with
+    /// a separate mix for every frame (e.g. from host automation) instead of
+    /// a ramp.
+    ///
+    /// The values of `mix` are clamped to the range `[0.0, 1.0]`. They are
+    /// used as is, so they should already be smooth. The next call to
+    /// [`Self::process_mix`] ramps from the last value.
+    ///
+    /// # Panics
+    /// Panics if `dry_scratch` is empty, or if `mix` is not the same length
+    /// as `buf`.
+    pub fn process_mix_automated(&mut self, buf: &mut [f32], dry_scratch: &mut [f32], mix: &[f32]) {
+        assert!(!dry_scratch.is_empty());
+        assert_eq!(mix.len(), buf.len());
+
+        if let Some(&last) = mix.last() {
+            self.mix = last.clamp(0.0, 1.0);
+        }
+
+        for (buf, mix) in buf
+            .chunks_mut(dry_scratch.len())
+            .zip(mix.chunks(dry_scratch.len()))
+        {
+            let dry = &mut dry_scratch[..buf.len()];
+
+            dry.copy_from_slice(buf);
+            self.process(buf);
+
+            mix_dry_automated(buf, dry, mix);
+        }
+    }
+
+    /// Process the given buffer in place like [`Self::process`], and also
+    /// write the signal of the band at `band_index` on its own into
+    /// `listen`. See
+    /// [`MeadowEqDspStereoLinked::process_with_band_listen`](super::stereo::scalar::MeadowEqDspStereoLinked::process_with_band_listen).
+    ///
+    /// # Panics
+    /// Panics if `band_index >= NUM_BANDS`, or if `listen` is shorter than
+    /// `buf`.
+    pub fn process_with_band_listen(
+        &mut self,
+        buf: &mut [f32],
+        band_index: usize,
+        listen: &mut [f32],
+    ) {
+        let frames = buf.len();
+        let sample_rate_recip = self.sample_rate().recip();
+
+        self.listen.process(
+            band_index,
+            &self.coeff.params().bands[band_index],
+            self.coeff.band_raw_coeff(band_index),
+            sample_rate_recip,
+            buf,
+            &mut listen[..frames],
+        );
+
+        self.process(buf);
+    }
+
+    /// Process the given buffer in place like [`Self::process`], while
+    /// offsetting the cutoff of the band at `band_index` by `mod_hz` (in Hz)
+    /// every frame, e.g. for an auto-wah or a modulated filter sweep. See
+    /// [`MeadowEqDspStereoLinked::process_with_cutoff_mod`](super::stereo::scalar::MeadowEqDspStereoLinked::process_with_cutoff_mod).
+    ///
+    /// # Panics
+    /// Panics if `band_index >= NUM_BANDS`, or if `mod_hz` is not the same
+    /// length as `buf`.
+    pub fn process_with_cutoff_mod(&mut self, buf: &mut [f32], band_index: usize, mod_hz: &[f32]) {
+        assert!(band_index < NUM_BANDS);
+        assert_eq!(mod_hz.len(), buf.len());
+
+        for (buf, mod_hz) in buf
+            .chunks_mut(CUTOFF_MOD_STEP_FRAMES)
+            .zip(mod_hz.chunks(CUTOFF_MOD_STEP_FRAMES))
+        {
+            if self.coeff.is_fading_bands() {
+                self.coeff.advance_band_fades(buf.len());
+            }
+            if self.needs_param_flush() {
+                let _ = self.flush_param_changes();
+            }
+
+            self.coeff.set_band_cutoff_mod(band_index, mod_hz[0]);
+            self.process_block_no_flush(buf);
+        }
+
+        self.coeff.set_band_cutoff_mod(band_index, 0.0);
+    }
+
+    fn process_block(&mut self, buf: &mut [f32]) {
+        if self.needs_param_flush() {
+            let _ = self.flush_param_changes();
+        }
+
+        self.process_block_no_flush(buf);
+    }
+
+    fn process_block_no_flush(&mut self, buf: &mut [f32]) {
+        if self.coeff.is_empty()
+            && is_unity_gain(&self.input_gain)
+            && is_unity_gain(&self.gain)
+            && self.output_limiter.is_none()
+        {
+            return;
+        }
+
+        apply_gain(&mut self.input_gain, buf);
+
+        process_channel(buf, &self.coeff, &mut self.state, self.band_routing);
+
+        apply_gain(&mut self.gain, buf);
+
+        if let Some(limiter) = &self.output_limiter {
+            limiter.process(buf);
+        }
+    }
+}
+
+/// Returns `true` if the gain is settled at `1.0`, so applying it is a no-op.
+pub(crate) fn is_unity_gain(gain: &OnePoleSmoother) -> bool {
+    !gain.is_smoothing() && gain.target() == 1.0
+}
+
+/// Multiply the buffer by the (smoothed) gain.
+pub(crate) fn apply_gain(gain: &mut OnePoleSmoother, buf: &mut [f32]) {
+    if gain.is_smoothing() {
+        for out in buf.iter_mut() {
+            *out *= gain.next_value();
+        }
+    } else if gain.target() != 1.0 {
+        let gain = gain.target();
+
+        for out in buf.iter_mut() {
+            *out *= gain;
+        }
+    }
+}
+
+/// Crossfade from the dry signal to the processed signal in `buf` by the given
+/// mix, which is ramped linearly from `from_mix` to `to_mix`.
+pub(crate) fn mix_dry(buf: &mut [f32], dry: &[f32], from_mix: f32, to_mix: f32) {
+    if from_mix == to_mix {
+        for (out, &dry) in buf.iter_mut().zip(dry.iter()) {
+            *out = dry + (*out - dry) * to_mix;
+        }
+        return;
+    }
+
+    let step = (to_mix - from_mix) / buf.len() as f32;
+    let mut mix = from_mix;
+
+    for (out, &dry) in buf.iter_mut().zip(dry.iter()) {
+        mix += step;
+        *out = dry + (*out - dry) * mix;
+    }
+}
+
+/// Mix the processed signal in `buf` with the dry signal, using a separate
+/// mix for every frame.
+pub(crate) fn mix_dry_automated(buf: &mut [f32], dry: &[f32], mix: &[f32]) {
+    for ((out, &dry), &mix) in buf.iter_mut().zip(dry.iter()).zip(mix.iter()) {
+        *out = dry + (*out - dry) * mix.clamp(0.0, 1.0);
+    }
+}
+
+/// The mix `frame` frames into a ramp of `frames` frames from `from_mix` to
+/// `to_mix`.
+pub(crate) fn ramp(from_mix: f32, to_mix: f32, frame: usize, frames: usize) -> f32 {
+    from_mix + (to_mix - from_mix) * (frame as f32 / frames as f32)
+}
+
+/// Process a single channel through the given coefficients and state.
+///
+/// This is useful when the coefficients and states are owned separately,
+/// e.g. with [`coeff_channel`](super::shared::coeff_channel).
+pub fn process_channel<const NUM_BANDS: usize, const NUM_BANDS_PLUS_16: usize>(
+    buf: &mut [f32],
+    coeff: &MeadowEqDspCoeff<NUM_BANDS, NUM_BANDS_PLUS_16>,
+    state: &mut MeadowEqDspState<NUM_BANDS, NUM_BANDS_PLUS_16>,
+    band_routing: BandRouting,
+) {
+    let (one_pole_coeffs, svf_coeffs) = coeff.coeffs();
+    let (one_pole_states, svf_states) = state.states_mut();
+
+    if !one_pole_coeffs.is_empty() {
+        // Hint to compiler to optimize loop;
+        assert_eq!(one_pole_coeffs.len(), one_pole_states.len());
+
+        for out in buf.iter_mut() {
+            let mut s = *out;
+
+            for (i, coeff) in one_pole_coeffs.iter().enumerate() {
+                s = one_pole_states[i].tick(s, coeff);
+            }
+
+            *out = s;
+        }
+    }
+
+    if !svf_coeffs.is_empty() {
+        // Hint to compiler to optimize loop;
+        assert_eq!(svf_coeffs.len(), svf_states.len());
+
+        let num_series_filters = match band_routing {
+            BandRouting::Series => svf_coeffs.len(),
+            BandRouting::Parallel => coeff.num_lp_hp_svf_filters(),
+        };
+
+        let (series_coeffs, parallel_coeffs) = svf_coeffs.split_at(num_series_filters);
+        let (series_states, parallel_states) = svf_states.split_at_mut(num_series_filters);
+
+        if !series_coeffs.is_empty() {
+            for out in buf.iter_mut() {
+                let mut s = *out;
+
+                for (i, coeff) in series_coeffs.iter().enumerate() {
+                    s = series_states[i].tick(s, coeff);
+                }
+
+                *out = s;
+            }
+        }
+
+        if !parallel_coeffs.is_empty() {
+            for out in buf.iter_mut() {
+                let input = *out;
+                let mut s = input;
+
+                for (i, coeff) in parallel_coeffs.iter().enumerate() {
+                    s += parallel_states[i].tick(input, coeff) - input;
+                }
+
+                *out = s;
+            }
+        }
+    }
+}
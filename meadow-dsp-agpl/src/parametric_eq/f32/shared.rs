@@ -0,0 +1,317 @@
+use std::{
+    cell::UnsafeCell,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use meadow_dsp_mit::filter::svf::f64::SvfCoeff as SvfCoeffF64;
+
+use super::{
+    coeff::{MeadowEqDspCoeff, StateSyncInfo},
+    EqParams,
+};
+
+/// Create a producer/consumer pair which shares the coefficients of an EQ
+/// between two threads.
+///
+/// The [`EqCoeffProducer`] lives on a non-realtime thread (i.e. the GUI
+/// thread). It owns the parameters and does all of the coefficient
+/// calculations, and then publishes a full snapshot of the coefficients. The
+/// [`EqCoeffConsumer`] lives on the audio thread and only ever reads the
+/// latest snapshot, so the audio thread never has to recalculate any
+/// coefficients. Snapshots are handed over with a lock-free triple buffer, so
+/// neither side ever blocks or allocates.
+///
+/// The consumer does not own any filter states. The audio thread keeps its
+/// own [`MeadowEqDspState`](super::state::MeadowEqDspState) for each channel,
+/// syncs them whenever [`EqCoeffConsumer::update`] returns `Some`, and then
+/// processes them with [`process_channel`](super::mono::process_channel).
+///
+/// Band fades are not supported (disabled bands are removed immediately),
+/// since they would have to be advanced on the audio thread.
+pub fn coeff_channel<const NUM_BANDS: usize, const NUM_BANDS_PLUS_16: usize>(
+    sample_rate: f64,
+) -> (
+    EqCoeffProducer<NUM_BANDS, NUM_BANDS_PLUS_16>,
+    EqCoeffConsumer<NUM_BANDS, NUM_BANDS_PLUS_16>,
+) {
+    let mut coeff = MeadowEqDspCoeff::new(sample_rate);
+    coeff.set_band_fade_secs(0.0);
+
+    let snapshot = SharedSnapshot {
+        coeff: coeff.clone(),
+        sync_info: StateSyncInfo::default(),
+    };
+
+    let shared = Arc::new(TripleBuffer::new(snapshot));
+
+    (
+        EqCoeffProducer {
+            coeff,
+            sync_info: StateSyncInfo::default(),
+            shared: Arc::clone(&shared),
+            write_i: 1,
+        },
+        EqCoeffConsumer { shared, read_i: 2 },
+    )
+}
+
+#[derive(Clone)]
+struct SharedSnapshot<const NUM_BANDS: usize, const NUM_BANDS_PLUS_16: usize> {
+    coeff: MeadowEqDspCoeff<NUM_BANDS, NUM_BANDS_PLUS_16>,
+
+    /// The filter topology of `coeff`. This is always the full topology (not
+    /// only when it changed), so that the consumer can sync its states even
+    /// if it skipped some snapshots.
+    sync_info: StateSyncInfo<NUM_BANDS>,
+}
+
+/// The non-realtime half of [`coeff_channel`], which calculates and
+/// publishes the coefficients.
+pub struct EqCoeffProducer<const NUM_BANDS: usize, const NUM_BANDS_PLUS_16: usize> {
+    coeff: MeadowEqDspCoeff<NUM_BANDS, NUM_BANDS_PLUS_16>,
+    sync_info: StateSyncInfo<NUM_BANDS>,
+
+    shared: Arc<TripleBuffer<SharedSnapshot<NUM_BANDS, NUM_BANDS_PLUS_16>>>,
+    write_i: usize,
+}
+
+impl<const NUM_BANDS: usize, const NUM_BANDS_PLUS_16: usize>
+    EqCoeffProducer<NUM_BANDS, NUM_BANDS_PLUS_16>
+{
+    /// The producer's own coefficients, i.e. for drawing the response curve.
+    ///
+    /// Unlike the snapshot the consumer sees, the parameters of this include
+    /// any changes made since the last call to [`EqCoeffProducer::publish`].
+    /// The coefficients are only recalculated by `publish`, so they lag
+    /// behind the parameters while
+    /// [`MeadowEqDspCoeff::needs_param_flush`] is `true`.
+    pub fn coeff(&self) -> &MeadowEqDspCoeff<NUM_BANDS, NUM_BANDS_PLUS_16> {
+        &self.coeff
+    }
+
+    pub fn params(&self) -> &EqParams<NUM_BANDS> {
+        self.coeff.params()
+    }
+
+    /// Set the parameters. The changes are not sent to the consumer until
+    /// [`EqCoeffProducer::publish`] is called.
+    pub fn set_params(&mut self, params: &EqParams<NUM_BANDS>) {
+        self.coeff.set_params(params);
+    }
+
+    /// Modify the parameters in place. See
+    /// [`MeadowEqDspCoeff::modify_params`].
+    pub fn modify_params(&mut self, f: impl FnOnce(&mut EqParams<NUM_BANDS>)) {
+        self.coeff.modify_params(f);
+    }
+
+    /// Set the coefficients of the band at the given index directly. See
+    /// [`MeadowEqDspCoeff::set_band_raw_coeff`].
+    ///
+    /// # Panics
+    /// Panics if `index >= NUM_BANDS`.
+    pub fn set_band_raw_coeff(&mut self, index: usize, coeff: SvfCoeffF64) {
+        self.coeff.set_band_raw_coeff(index, coeff);
+    }
+
+    /// Whether every band always keeps its filter slot, even while it is
+    /// disabled. See [`MeadowEqDspCoeff::set_fixed_band_slots`].
+    pub fn set_fixed_band_slots(&mut self, fixed: bool) {
+        self.coeff.set_fixed_band_slots(fixed);
+    }
+
+    /// Calculate the coefficients for any pending parameter changes and send
+    /// a snapshot of them to the consumer.
+    ///
+    /// Returns `false` (and sends nothing) if there were no pending changes.
+    pub fn publish(&mut self) -> bool {
+        if !self.coeff.needs_param_flush() {
+            return false;
+        }
+
+        if let Some(info) = self.coeff.flush_param_changes() {
+            self.sync_info = info;
+        }
+
+        // SAFETY: The slot at `write_i` is owned by the producer until it is
+        // swapped out below.
+        let slot = unsafe { &mut *self.shared.slots[self.write_i].get() };
+        slot.coeff.clone_from(&self.coeff);
+        slot.sync_info = self.sync_info;
+
+        self.write_i = self.shared.publish(self.write_i);
+
+        true
+    }
+}
+
+/// The realtime half of [`coeff_channel`], which reads the latest
+/// coefficients on the audio thread.
+pub struct EqCoeffConsumer<const NUM_BANDS: usize, const NUM_BANDS_PLUS_16: usize> {
+    shared: Arc<TripleBuffer<SharedSnapshot<NUM_BANDS, NUM_BANDS_PLUS_16>>>,
+    read_i: usize,
+}
+
+impl<const NUM_BANDS: usize, const NUM_BANDS_PLUS_16: usize>
+    EqCoeffConsumer<NUM_BANDS, NUM_BANDS_PLUS_16>
+{
+    /// Switch to the latest snapshot published by the producer, if there is a
+    /// new one. This should be called once at the start of every process
+    /// cycle.
+    ///
+    /// If this returns `Some`, then every filter state used with these
+    /// coefficients must be synced with
+    /// [`MeadowEqDspState::sync`](super::state::MeadowEqDspState::sync)
+    /// before processing. (This is always `Some` the first time this is
+    /// called.)
+    pub fn update(&mut self) -> Option<StateSyncInfo<NUM_BANDS>> {
+        self.read_i = self.shared.fetch(self.read_i)?;
+
+        Some(self.snapshot().sync_info)
+    }
+
+    /// The coefficients of the current snapshot.
+    pub fn coeff(&self) -> &MeadowEqDspCoeff<NUM_BANDS, NUM_BANDS_PLUS_16> {
+        &self.snapshot().coeff
+    }
+
+    fn snapshot(&self) -> &SharedSnapshot<NUM_BANDS, NUM_BANDS_PLUS_16> {
+        // SAFETY: The slot at `read_i` is owned by the consumer until it is
+        // swapped out in `update`, which requires `&mut self`.
+        unsafe { &*self.shared.slots[self.read_i].get() }
+    }
+}
+
+/// Set in `TripleBuffer::back` when the back slot holds a snapshot which the
+/// consumer has not read yet.
+const FRESH_BIT: usize = 0b100;
+const INDEX_MASK: usize = 0b011;
+
+/// A lock-free triple buffer.
+///
+/// At any time, one slot is owned by the producer (which writes to it), one
+/// is owned by the consumer (which reads from it), and the third (the "back"
+/// slot) is owned by neither. Publishing and fetching both just swap the
+/// index of the slot they own with the back slot.
+struct TripleBuffer<T> {
+    slots: [UnsafeCell<T>; 3],
+    back: AtomicUsize,
+}
+
+// SAFETY: The slots are only ever accessed by the side which currently owns
+// them, and ownership is handed over with acquire/release swaps.
+unsafe impl<T: Send> Sync for TripleBuffer<T> {}
+
+impl<T: Clone> TripleBuffer<T> {
+    /// Create a new buffer where every slot holds `value`. The back slot is
+    /// index `0`, and it is marked as fresh.
+    fn new(value: T) -> Self {
+        Self {
+            slots: [
+                UnsafeCell::new(value.clone()),
+                UnsafeCell::new(value.clone()),
+                UnsafeCell::new(value),
+            ],
+            back: AtomicUsize::new(FRESH_BIT),
+        }
+    }
+}
+
+impl<T> TripleBuffer<T> {
+    /// Make the slot at `write_i` the back slot, and return the index of the
+    /// slot which the producer now owns.
+    fn publish(&self, write_i: usize) -> usize {
+        self.back.swap(write_i | FRESH_BIT, Ordering::AcqRel) & INDEX_MASK
+    }
+
+    /// If the back slot is fresh, swap it with the slot at `read_i` and
+    /// return the index of the slot which the consumer now owns.
+    fn fetch(&self, read_i: usize) -> Option<usize> {
+        if self.back.load(Ordering::Relaxed) & FRESH_BIT == 0 {
+            return None;
+        }
+
+        Some(self.back.swap(read_i, Ordering::AcqRel) & INDEX_MASK)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parametric_eq::f32::{BandParams, BandType};
+
+    const NUM_PARAMS: usize = 16;
+
+    /// Parameters where the gain of every band is `i`.
+    fn params(i: usize) -> EqParams<4> {
+        let mut params = EqParams::default();
+        for (band_i, band) in params.bands.iter_mut().enumerate() {
+            *band = BandParams {
+                enabled: true,
+                band_type: BandType::Bell,
+                cutoff_hz: 100.0 * (band_i + 1) as f32,
+                gain_db: i as f32,
+                ..Default::default()
+            };
+        }
+        params
+    }
+
+    fn packed(coeff: &MeadowEqDspCoeff<4, 20>) -> Vec<f32> {
+        let mut out = Vec::new();
+        coeff.pack_coeffs_into(&mut out);
+        out
+    }
+
+    /// The consumer only ever sees complete snapshots: the parameters are
+    /// those of a single publish, and the coefficients match them.
+    #[test]
+    fn consumer_sees_complete_snapshots() {
+        const PUBLISHES: usize = 20_000;
+
+        let expected: Vec<Vec<f32>> = (0..NUM_PARAMS)
+            .map(|i| {
+                let mut coeff = MeadowEqDspCoeff::<4, 20>::new(48_000.0);
+                coeff.set_band_fade_secs(0.0);
+                coeff.set_params(&params(i));
+                let _ = coeff.flush_param_changes();
+                packed(&coeff)
+            })
+            .collect();
+
+        let (mut producer, mut consumer) = coeff_channel::<4, 20>(48_000.0);
+        producer.set_params(&params(0));
+        producer.publish();
+
+        std::thread::scope(|scope| {
+            let handle = scope.spawn(move || {
+                for n in 1..PUBLISHES {
+                    producer.set_params(&params(n % NUM_PARAMS));
+                    assert!(producer.publish());
+                }
+            });
+
+            let mut last_i = 0;
+            loop {
+                // Check once more after the producer is done, to read its
+                // last snapshot.
+                let finished = handle.is_finished();
+
+                if consumer.update().is_some() {
+                    let coeff = consumer.coeff();
+                    last_i = coeff.params().bands[0].gain_db as usize;
+                    assert_eq!(*coeff.params(), params(last_i), "mixed parameters");
+                    assert_eq!(packed(coeff), expected[last_i], "coefficients of {last_i}");
+                }
+
+                if finished {
+                    break;
+                }
+            }
+            assert_eq!(last_i, (PUBLISHES - 1) % NUM_PARAMS);
+        });
+    }
+}
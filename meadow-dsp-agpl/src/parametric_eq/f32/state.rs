@@ -2,29 +2,47 @@ use arrayvec::ArrayVec;
 use meadow_dsp_mit::filter::{one_pole_iir::f32::OnePoleIirState, svf::f32::SvfState};
 
 use super::{
-    coeff::{StateSyncInfo, MAX_ONE_POLE_FILTERS},
-    FilterOrder,
+    coeff::{
+        CoeffPrecision, MeadowEqDspCoeff, StateSyncInfo, MAX_LP_HP_SVF_FILTERS,
+        MAX_ONE_POLE_FILTERS,
+    },
+    BandRouting, FilterOrder,
 };
 
 /// The struct that manages the filter states for a fully-featured
 /// parametric equalizer. (For a single channel).
 ///
-/// TODO: Get rid of `NUM_BANDS_PLUS_8` const generic once const generic expressions
+/// See [`MeadowEqDspCoeff`] for the requirements on the const generics.
+///
+/// [`MeadowEqDspCoeff`]: super::coeff::MeadowEqDspCoeff
+///
+/// TODO: Get rid of `NUM_BANDS_PLUS_16` const generic once const generic expressions
 /// are stabilized. (please rust compiler team)
-pub struct MeadowEqDspState<const NUM_BANDS: usize, const NUM_BANDS_PLUS_8: usize> {
+#[derive(Clone)]
+pub struct MeadowEqDspState<const NUM_BANDS: usize, const NUM_BANDS_PLUS_16: usize> {
     lp_band: MultiOrderBand,
     hp_band: MultiOrderBand,
 
     bands: [SecondOrderBand; NUM_BANDS],
 
     one_pole_states: ArrayVec<OnePoleIirState, MAX_ONE_POLE_FILTERS>,
-    svf_states: ArrayVec<SvfState, NUM_BANDS_PLUS_8>,
+    svf_states: ArrayVec<SvfState, NUM_BANDS_PLUS_16>,
 }
 
-impl<const NUM_BANDS: usize, const NUM_BANDS_PLUS_8: usize>
-    MeadowEqDspState<NUM_BANDS, NUM_BANDS_PLUS_8>
+impl<const NUM_BANDS: usize, const NUM_BANDS_PLUS_16: usize> Default
+    for MeadowEqDspState<NUM_BANDS, NUM_BANDS_PLUS_16>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const NUM_BANDS: usize, const NUM_BANDS_PLUS_16: usize>
+    MeadowEqDspState<NUM_BANDS, NUM_BANDS_PLUS_16>
 {
     pub fn new() -> Self {
+        const { assert!(NUM_BANDS_PLUS_16 >= NUM_BANDS + 16) };
+
         Self {
             lp_band: MultiOrderBand::default(),
             hp_band: MultiOrderBand::default(),
@@ -48,6 +66,9 @@ impl<const NUM_BANDS: usize, const NUM_BANDS_PLUS_8: usize>
         } else {
             self.lp_band.reset();
         }
+        if self.lp_band.order != info.lp_band_order {
+            self.lp_band.reset();
+        }
         self.lp_band.enabled = info.lp_band_enabled;
         self.lp_band.order = info.lp_band_order;
 
@@ -61,6 +82,9 @@ impl<const NUM_BANDS: usize, const NUM_BANDS_PLUS_8: usize>
         } else {
             self.hp_band.reset();
         }
+        if self.hp_band.order != info.hp_band_order {
+            self.hp_band.reset();
+        }
         self.hp_band.enabled = info.hp_band_enabled;
         self.hp_band.order = info.hp_band_order;
 
@@ -75,6 +99,9 @@ impl<const NUM_BANDS: usize, const NUM_BANDS_PLUS_8: usize>
             self.bands[i].enabled = info.bands_enabled[i];
         }
 
+        self.one_pole_states.clear();
+        self.svf_states.clear();
+
         if self.lp_band.enabled {
             self.lp_band
                 .add_states(&mut self.one_pole_states, &mut self.svf_states);
@@ -85,10 +112,140 @@ impl<const NUM_BANDS: usize, const NUM_BANDS_PLUS_8: usize>
                 .add_states(&mut self.one_pole_states, &mut self.svf_states);
         }
 
-        for i in 0..NUM_BANDS {
-            if self.bands[i].enabled {
-                self.svf_states[i] = self.bands[i].svf_state;
-                svf_i += 1;
+        for band in self.bands.iter() {
+            if band.enabled {
+                self.svf_states.push(band.svf_state);
+            }
+        }
+    }
+
+    /// Reset the state of all filters.
+    pub fn reset(&mut self) {
+        self.lp_band.reset();
+        self.hp_band.reset();
+        for band in self.bands.iter_mut() {
+            band.reset();
+        }
+
+        for s in self.one_pole_states.iter_mut() {
+            s.reset();
+        }
+        for s in self.svf_states.iter_mut() {
+            s.reset();
+        }
+    }
+
+    /// Reset the state of only the band at the given index, leaving the
+    /// state of all other bands untouched.
+    ///
+    /// This does nothing if the band is currently disabled.
+    ///
+    /// # Panics
+    /// Panics if `index >= NUM_BANDS`.
+    pub fn reset_band(&mut self, index: usize) {
+        self.bands[index].reset();
+
+        if !self.bands[index].enabled {
+            return;
+        }
+
+        let svf_i = self.lp_band.num_svf_filters()
+            + self.hp_band.num_svf_filters()
+            + self.bands[0..index].iter().filter(|b| b.enabled).count();
+
+        self.svf_states[svf_i].reset();
+    }
+
+    /// Reset the state of only the lowpass band, leaving the state of all
+    /// other bands untouched.
+    pub fn reset_lp(&mut self) {
+        self.lp_band.reset();
+
+        if !self.lp_band.enabled {
+            return;
+        }
+
+        if self.lp_band.order == FilterOrder::X1 {
+            self.one_pole_states[0].reset();
+        } else {
+            for s in self.svf_states[0..self.lp_band.num_svf_filters()].iter_mut() {
+                s.reset();
+            }
+        }
+    }
+
+    /// Reset the state of only the highpass band, leaving the state of all
+    /// other bands untouched.
+    pub fn reset_hp(&mut self) {
+        self.hp_band.reset();
+
+        if !self.hp_band.enabled {
+            return;
+        }
+
+        if self.hp_band.order == FilterOrder::X1 {
+            let one_pole_i = if self.lp_band.enabled && self.lp_band.order == FilterOrder::X1 {
+                1
+            } else {
+                0
+            };
+
+            self.one_pole_states[one_pole_i].reset();
+        } else {
+            let start = self.lp_band.num_svf_filters();
+            let end = start + self.hp_band.num_svf_filters();
+
+            for s in self.svf_states[start..end].iter_mut() {
+                s.reset();
+            }
+        }
+    }
+
+    /// Set the state of every filter to the steady state for a constant
+    /// input of `dc_value`, so that processing a signal which starts at (or
+    /// hovers around) that level produces no startup transient. This is
+    /// useful when starting to process in the middle of a signal, e.g. a
+    /// pre-rolled region.
+    ///
+    /// The state must already be synced with `coeff`, i.e. its latest
+    /// [`MeadowEqDspCoeff::flush_param_changes`] must have been passed to
+    /// [`MeadowEqDspState::sync`]. `band_routing` must be the routing used to
+    /// process.
+    pub fn prime_for_dc<P: CoeffPrecision>(
+        &mut self,
+        dc_value: f32,
+        coeff: &MeadowEqDspCoeff<NUM_BANDS, NUM_BANDS_PLUS_16, P>,
+        band_routing: BandRouting,
+    ) {
+        let (one_pole_coeffs, svf_coeffs) = coeff.coeffs();
+        assert_eq!(one_pole_coeffs.len(), self.one_pole_states.len());
+        assert_eq!(svf_coeffs.len(), self.svf_states.len());
+
+        // Follow the DC level through the filters in the same order as they
+        // are processed.
+        let mut dc = dc_value as f64;
+
+        for (state, c) in self.one_pole_states.iter_mut().zip(one_pole_coeffs.iter()) {
+            let c = P::one_pole_iir_coeff_to_f64(*c);
+            state.prime_for_dc_f64_coeff(dc as f32, &c);
+            dc *= c.dc_gain();
+        }
+
+        let num_series_filters = match band_routing {
+            BandRouting::Series => svf_coeffs.len(),
+            BandRouting::Parallel => coeff.num_lp_hp_svf_filters(),
+        };
+
+        for (i, (state, c)) in self
+            .svf_states
+            .iter_mut()
+            .zip(svf_coeffs.iter())
+            .enumerate()
+        {
+            state.prime_for_dc(dc as f32);
+
+            if i < num_series_filters {
+                dc *= P::svf_coeff_to_f64(*c).dc_gain();
             }
         }
     }
@@ -97,7 +254,7 @@ impl<const NUM_BANDS: usize, const NUM_BANDS_PLUS_8: usize>
         &mut self,
     ) -> (
         &mut ArrayVec<OnePoleIirState, MAX_ONE_POLE_FILTERS>,
-        &mut ArrayVec<SvfState, NUM_BANDS_PLUS_8>,
+        &mut ArrayVec<SvfState, NUM_BANDS_PLUS_16>,
     ) {
         (&mut self.one_pole_states, &mut self.svf_states)
     }
@@ -121,79 +278,154 @@ struct MultiOrderBand {
     order: FilterOrder,
 
     one_pole_iir_state: OnePoleIirState,
-    svf_states: [SvfState; 4],
+    svf_states: [SvfState; MAX_LP_HP_SVF_FILTERS],
 }
 
 impl MultiOrderBand {
-    fn sync_states<const NUM_BANDS_PLUS_8: usize>(
+    fn num_svf_filters(&self) -> usize {
+        if self.enabled {
+            self.order.num_svf_filters()
+        } else {
+            0
+        }
+    }
+
+    fn sync_states<const NUM_BANDS_PLUS_16: usize>(
         &mut self,
         one_pole_states: &mut ArrayVec<OnePoleIirState, MAX_ONE_POLE_FILTERS>,
-        svf_states: &mut ArrayVec<SvfState, NUM_BANDS_PLUS_8>,
+        svf_states: &mut ArrayVec<SvfState, NUM_BANDS_PLUS_16>,
         one_pole_iir_i: &mut usize,
         svf_i: &mut usize,
     ) {
-        match self.order {
-            FilterOrder::X1 => {
-                self.one_pole_iir_state = one_pole_states[*one_pole_iir_i];
-                *one_pole_iir_i += 1;
-            }
-            FilterOrder::X2 => {
-                self.svf_states[0] = svf_states[*svf_i];
-                *svf_i += 1;
-            }
-            FilterOrder::X4 => {
-                self.svf_states[0] = svf_states[*svf_i];
-                self.svf_states[1] = svf_states[*svf_i + 1];
-                *svf_i += 2;
-            }
-            FilterOrder::X6 => {
-                self.svf_states[0] = svf_states[*svf_i];
-                self.svf_states[1] = svf_states[*svf_i + 1];
-                self.svf_states[2] = svf_states[*svf_i + 2];
-                *svf_i += 3;
-            }
-            FilterOrder::X8 => {
-                self.svf_states[0] = svf_states[*svf_i];
-                self.svf_states[1] = svf_states[*svf_i + 1];
-                self.svf_states[2] = svf_states[*svf_i + 2];
-                self.svf_states[3] = svf_states[*svf_i + 3];
-                *svf_i += 4;
-            }
+        if self.order == FilterOrder::X1 {
+            self.one_pole_iir_state = one_pole_states[*one_pole_iir_i];
+            *one_pole_iir_i += 1;
+        } else {
+            let n = self.order.num_svf_filters();
+            self.svf_states[..n].copy_from_slice(&svf_states[*svf_i..*svf_i + n]);
+            *svf_i += n;
         }
     }
 
-    fn add_states<const NUM_BANDS_PLUS_8: usize>(
+    fn add_states<const NUM_BANDS_PLUS_16: usize>(
         &self,
         one_pole_states: &mut ArrayVec<OnePoleIirState, MAX_ONE_POLE_FILTERS>,
-        svf_states: &mut ArrayVec<SvfState, NUM_BANDS_PLUS_8>,
+        svf_states: &mut ArrayVec<SvfState, NUM_BANDS_PLUS_16>,
     ) {
-        match self.order {
-            FilterOrder::X1 => {
-                one_pole_states.push(self.one_pole_iir_state);
-            }
-            FilterOrder::X2 => {
-                svf_states.push(self.svf_states[0]);
-            }
-            FilterOrder::X4 => {
-                svf_states.push(self.svf_states[0]);
-                svf_states.push(self.svf_states[1]);
-            }
-            FilterOrder::X6 => {
-                svf_states.push(self.svf_states[0]);
-                svf_states.push(self.svf_states[1]);
-                svf_states.push(self.svf_states[2]);
-            }
-            FilterOrder::X8 => {
-                svf_states.push(self.svf_states[0]);
-                svf_states.push(self.svf_states[1]);
-                svf_states.push(self.svf_states[2]);
-                svf_states.push(self.svf_states[3]);
-            }
+        if self.order == FilterOrder::X1 {
+            one_pole_states.push(self.one_pole_iir_state);
+        } else {
+            let n = self.order.num_svf_filters();
+            svf_states.extend(self.svf_states[..n].iter().copied());
         }
     }
 
     fn reset(&mut self) {
         self.one_pole_iir_state.reset();
-        self.svf_states = [SvfState::default(); 4];
+        self.svf_states = [SvfState::default(); MAX_LP_HP_SVF_FILTERS];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parametric_eq::f32::{coeff::F32Coeffs, BandParams, EqParams, LpOrHpBandParams};
+
+    /// A state synced with the given lowpass and highpass orders and enabled
+    /// bands, with a different nonzero value in every filter.
+    fn filled_state(
+        lp_order: FilterOrder,
+        hp_order: FilterOrder,
+        bands_enabled: [bool; 4],
+    ) -> MeadowEqDspState<4, 20> {
+        let lp_hp_band = |order| LpOrHpBandParams {
+            enabled: true,
+            order,
+            ..Default::default()
+        };
+        let mut params = EqParams::<4> {
+            lp_band: lp_hp_band(lp_order),
+            hp_band: lp_hp_band(hp_order),
+            ..Default::default()
+        };
+        for (band, enabled) in params.bands.iter_mut().zip(bands_enabled) {
+            *band = BandParams {
+                enabled,
+                ..Default::default()
+            };
+        }
+
+        let mut coeff = MeadowEqDspCoeff::<4, 20, F32Coeffs>::new(48_000.0);
+        coeff.set_params(&params);
+        let mut state = MeadowEqDspState::new();
+        state.sync(&coeff.flush_param_changes().unwrap());
+
+        for (i, s) in state.one_pole_states.iter_mut().enumerate() {
+            s.z1 = 1.0 + i as f32;
+        }
+        for (i, s) in state.svf_states.iter_mut().enumerate() {
+            s.ic1eq = 1.0 + i as f32;
+            s.ic2eq = -1.0 - i as f32;
+        }
+
+        state
+    }
+
+    /// The indices of the one-pole and SVF filters whose state is zero.
+    fn reset_filters(state: &MeadowEqDspState<4, 20>) -> (Vec<usize>, Vec<usize>) {
+        let one_pole = (0..state.one_pole_states.len())
+            .filter(|&i| state.one_pole_states[i].z1 == 0.0)
+            .collect();
+        let svf = (0..state.svf_states.len())
+            .filter(|&i| state.svf_states[i].ic1eq == 0.0 && state.svf_states[i].ic2eq == 0.0)
+            .collect();
+
+        (one_pole, svf)
+    }
+
+    #[test]
+    fn reset_band_only_resets_that_band() {
+        // The SVF filters are packed as lowpass (2), highpass (3), then the
+        // enabled bands 0, 2, and 3.
+        let mut state = filled_state(FilterOrder::X4, FilterOrder::X6, [true, false, true, true]);
+        assert_eq!(state.svf_states.len(), 8);
+
+        state.reset_band(2);
+        assert_eq!(reset_filters(&state), (vec![], vec![6]));
+
+        // A disabled band has no filter to reset.
+        let mut state = filled_state(FilterOrder::X4, FilterOrder::X6, [true, false, true, true]);
+        state.reset_band(1);
+        assert_eq!(reset_filters(&state), (vec![], vec![]));
+
+        // Without the lowpass and highpass SVF filters, band 2 comes second.
+        let mut state = filled_state(FilterOrder::X1, FilterOrder::X1, [true, false, true, true]);
+        state.reset_band(2);
+        assert_eq!(reset_filters(&state), (vec![], vec![1]));
+    }
+
+    #[test]
+    fn reset_lp_and_hp_only_reset_that_band() {
+        let mut state = filled_state(FilterOrder::X4, FilterOrder::X6, [true; 4]);
+        state.reset_lp();
+        assert_eq!(reset_filters(&state), (vec![], vec![0, 1]));
+
+        let mut state = filled_state(FilterOrder::X4, FilterOrder::X6, [true; 4]);
+        state.reset_hp();
+        assert_eq!(reset_filters(&state), (vec![], vec![2, 3, 4]));
+
+        // Both first-order bands are one-pole filters, the lowpass first.
+        let mut state = filled_state(FilterOrder::X1, FilterOrder::X1, [true; 4]);
+        state.reset_lp();
+        assert_eq!(reset_filters(&state), (vec![0], vec![]));
+
+        let mut state = filled_state(FilterOrder::X1, FilterOrder::X1, [true; 4]);
+        state.reset_hp();
+        assert_eq!(reset_filters(&state), (vec![1], vec![]));
+
+        // A first-order highpass after a higher-order lowpass.
+        let mut state = filled_state(FilterOrder::X4, FilterOrder::X1, [true; 4]);
+        state.reset_hp();
+        assert_eq!(reset_filters(&state), (vec![0], vec![]));
     }
 }
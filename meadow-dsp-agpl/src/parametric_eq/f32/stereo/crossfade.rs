@@ -0,0 +1,241 @@
+use meadow_dsp_mit::processor::AudioProcessor;
+
+use crate::parametric_eq::f32::{BandRouting, EqParams};
+
+use super::scalar::MeadowEqDspStereoLinked;
+
+/// The default duration of a crossfade in seconds.
+pub const DEFAULT_CROSSFADE_SECS: f64 = 0.05;
+
+/// A stereo EQ which switches between entire sets of parameters (i.e. presets)
+/// by crossfading the audio of two [`MeadowEqDspStereoLinked`] instances.
+///
+/// Unlike smoothing the parameters themselves, this is guaranteed to be
+/// click-free no matter how different the two sets of parameters are, at the
+/// cost of doubling the CPU usage while a crossfade is in progress.
+///
+/// TODO: Get rid of `NUM_BANDS_PLUS_16` const generic once const generic expressions
+/// are stabilized. (please rust compiler team)
+#[derive(Clone)]
+pub struct CrossfadingEq<const NUM_BANDS: usize, const NUM_BANDS_PLUS_16: usize> {
+    eqs: [MeadowEqDspStereoLinked<NUM_BANDS, NUM_BANDS_PLUS_16>; 2],
+    active_i: usize,
+
+    fading: bool,
+    fade_pos: f32,
+    fade_step: f32,
+    pending_params: Option<EqParams<NUM_BANDS>>,
+
+    scratch_l: Vec<f32>,
+    scratch_r: Vec<f32>,
+}
+
+impl<const NUM_BANDS: usize, const NUM_BANDS_PLUS_16: usize>
+    CrossfadingEq<NUM_BANDS, NUM_BANDS_PLUS_16>
+{
+    pub const LATENCY: u32 = 0;
+
+    /// Create a new crossfading EQ.
+    ///
+    /// `max_block_frames` is the size of the scratch buffers that are
+    /// allocated up front. Larger blocks are still supported, they are just
+    /// processed in multiple chunks while crossfading.
+    ///
+    /// # Panics
+    /// Panics if `max_block_frames == 0`.
+    pub fn new(sample_rate: f64, max_block_frames: usize, crossfade_secs: f64) -> Self {
+        assert_ne!(max_block_frames, 0);
+
+        Self {
+            eqs: [
+                MeadowEqDspStereoLinked::new(sample_rate),
+                MeadowEqDspStereoLinked::new(sample_rate),
+            ],
+            active_i: 0,
+            fading: false,
+            fade_pos: 0.0,
+            fade_step: fade_step(crossfade_secs, sample_rate),
+            pending_params: None,
+            scratch_l: vec![0.0; max_block_frames],
+            scratch_r: vec![0.0; max_block_frames],
+        }
+    }
+
+    /// The number of bands (not including the lowpass and highpass bands).
+    pub const fn num_bands() -> usize {
+        NUM_BANDS
+    }
+
+    /// The sample rate this EQ was created with.
+    pub fn sample_rate(&self) -> f64 {
+        self.eqs[0].sample_rate()
+    }
+
+    /// The most recently requested parameters.
+    pub fn params(&self) -> &EqParams<NUM_BANDS> {
+        if let Some(params) = &self.pending_params {
+            params
+        } else if self.fading {
+            self.eqs[1 - self.active_i].params()
+        } else {
+            self.eqs[self.active_i].params()
+        }
+    }
+
+    /// Crossfade to the given parameters.
+    ///
+    /// If a crossfade is already in progress, then the new crossfade starts
+    /// once the current one has finished. (Only the most recent parameters are
+    /// kept.)
+    pub fn set_target_params(&mut self, params: &EqParams<NUM_BANDS>) {
+        if self.fading {
+            self.pending_params = Some(*params);
+        } else if params != self.eqs[self.active_i].params() {
+            self.start_crossfade(params);
+        }
+    }
+
+    /// Set the parameters immediately without crossfading. This cancels any
+    /// crossfade in progress.
+    pub fn set_params_immediate(&mut self, params: &EqParams<NUM_BANDS>) {
+        if self.fading {
+            self.active_i = 1 - self.active_i;
+            self.fading = false;
+        }
+        self.pending_params = None;
+
+        self.eqs[self.active_i].set_params(params);
+    }
+
+    pub fn is_crossfading(&self) -> bool {
+        self.fading
+    }
+
+    pub fn band_routing(&self) -> BandRouting {
+        self.eqs[self.active_i].band_routing()
+    }
+
+    /// Set how the bands (not including the lowpass and highpass bands) are
+    /// combined. See [`BandRouting`] for details.
+    pub fn set_band_routing(&mut self, routing: BandRouting) {
+        for eq in self.eqs.iter_mut() {
+            eq.set_band_routing(routing);
+        }
+    }
+
+    /// Reset the state of all filters, and finish any crossfade in progress
+    /// by jumping straight to the most recently requested parameters. See
+    /// [`MeadowEqDspStereoLinked::reset`].
+    pub fn reset(&mut self) {
+        if self.fading {
+            let params = *self.params();
+            self.set_params_immediate(&params);
+        }
+
+        for eq in self.eqs.iter_mut() {
+            eq.reset();
+        }
+    }
+
+    /// Process the given buffers in place.
+    ///
+    /// `buf_l` and `buf_r` must have the same length. See the
+    /// [module docs](super).
+    pub fn process(&mut self, buf_l: &mut [f32], buf_r: &mut [f32]) {
+        let (buf_l, buf_r) = super::same_len(buf_l, buf_r);
+        let frames = buf_l.len();
+        let max_block_frames = self.scratch_l.len();
+
+        let mut start = 0;
+        while start < frames {
+            if !self.fading {
+                self.eqs[self.active_i]
+                    .process(&mut buf_l[start..frames], &mut buf_r[start..frames]);
+                return;
+            }
+
+            let end = (start + max_block_frames).min(frames);
+            self.process_crossfade(&mut buf_l[start..end], &mut buf_r[start..end]);
+            start = end;
+        }
+    }
+
+    fn process_crossfade(&mut self, buf_l: &mut [f32], buf_r: &mut [f32]) {
+        let frames = buf_l.len();
+        let scratch_l = &mut self.scratch_l[..frames];
+        let scratch_r = &mut self.scratch_r[..frames];
+
+        scratch_l.copy_from_slice(buf_l);
+        scratch_r.copy_from_slice(buf_r);
+
+        let [eq_0, eq_1] = &mut self.eqs;
+        let (out_eq, in_eq) = if self.active_i == 0 {
+            (eq_0, eq_1)
+        } else {
+            (eq_1, eq_0)
+        };
+
+        out_eq.process(buf_l, buf_r);
+        in_eq.process(scratch_l, scratch_r);
+
+        for (((out_l, out_r), &in_l), &in_r) in buf_l
+            .iter_mut()
+            .zip(buf_r.iter_mut())
+            .zip(scratch_l.iter())
+            .zip(scratch_r.iter())
+        {
+            self.fade_pos = (self.fade_pos + self.fade_step).min(1.0);
+
+            *out_l += (in_l - *out_l) * self.fade_pos;
+            *out_r += (in_r - *out_r) * self.fade_pos;
+        }
+
+        if self.fade_pos >= 1.0 {
+            self.active_i = 1 - self.active_i;
+            self.fading = false;
+
+            if let Some(params) = self.pending_params.take() {
+                if &params != self.eqs[self.active_i].params() {
+                    self.start_crossfade(&params);
+                }
+            }
+        }
+    }
+
+    fn start_crossfade(&mut self, params: &EqParams<NUM_BANDS>) {
+        let incoming = &mut self.eqs[1 - self.active_i];
+
+        incoming.set_params(params);
+        let _ = incoming.flush_param_changes();
+        incoming.reset();
+
+        self.fading = true;
+        self.fade_pos = 0.0;
+    }
+}
+
+impl<const NUM_BANDS: usize, const NUM_BANDS_PLUS_16: usize> AudioProcessor
+    for CrossfadingEq<NUM_BANDS, NUM_BANDS_PLUS_16>
+{
+    fn process_stereo(&mut self, buf_l: &mut [f32], buf_r: &mut [f32]) {
+        self.process(buf_l, buf_r);
+    }
+
+    fn reset(&mut self) {
+        self.reset();
+    }
+
+    fn latency(&self) -> u32 {
+        Self::LATENCY
+    }
+}
+
+fn fade_step(crossfade_secs: f64, sample_rate: f64) -> f32 {
+    let frames = crossfade_secs * sample_rate;
+
+    if frames <= 1.0 {
+        1.0
+    } else {
+        (1.0 / frames) as f32
+    }
+}
@@ -0,0 +1,302 @@
+use meadow_dsp_mit::processor::AudioProcessor;
+
+use crate::parametric_eq::f32::{coeff::DEFAULT_BAND_FADE_SECS, EqParams, FilterOrder, DEFAULT_Q};
+
+use super::{same_len, scalar::MeadowEqDspStereoLinked, CONVERT_FRAMES};
+
+/// The cutoff of the lowpass filter just left of center, and of the highpass
+/// filter when the knob is all the way to the right.
+pub const MAX_CUTOFF_HZ: f32 = 20_000.0;
+/// The cutoff of the highpass filter just right of center, and of the lowpass
+/// filter when the knob is all the way to the left.
+pub const MIN_CUTOFF_HZ: f32 = 20.0;
+/// The Q of the filter at a resonance of `1.0`.
+pub const MAX_RESONANCE_Q: f32 = 10.0;
+
+/// How far the knob has to be turned from the center for the resonance to
+/// fully fade in. This avoids a resonant peak near the edge of the audible
+/// range when the knob is barely turned.
+const RESONANCE_FADE_IN_AMOUNT: f32 = 0.1;
+
+/// A resonant DJ-style filter controlled by a single knob.
+///
+/// Turning the knob left of center sweeps a lowpass filter down, and turning
+/// it right of center sweeps a highpass filter up. At the center, both filters
+/// are disabled and the input passes through untouched.
+///
+/// Switching between the lowpass filter, the highpass filter, and no filter
+/// at all would click, so the filter which is active is first crossfaded to
+/// the dry signal (over [`DEFAULT_BAND_FADE_SECS`]), and the next one is
+/// crossfaded in from the dry signal after the switch.
+#[derive(Clone)]
+pub struct DjFilter {
+    eq: MeadowEqDspStereoLinked<0, 16>,
+
+    amount: f32,
+    resonance: f32,
+    order: FilterOrder,
+    sample_rate: f64,
+
+    /// The parameters for the position of the knob, if they switch to
+    /// another filter. These are applied once the active filter has faded
+    /// out.
+    pending_params: Option<EqParams<0>>,
+    /// The current mix of the filtered (wet) signal.
+    mix: f32,
+    fade_frames: usize,
+}
+
+impl DjFilter {
+    pub const LATENCY: u32 = 0;
+
+    pub fn new(sample_rate: f64) -> Self {
+        Self {
+            eq: MeadowEqDspStereoLinked::new(sample_rate),
+            amount: 0.0,
+            resonance: 0.0,
+            order: FilterOrder::X2,
+            sample_rate,
+            pending_params: None,
+            mix: 0.0,
+            fade_frames: ((DEFAULT_BAND_FADE_SECS * sample_rate).round() as usize).max(1),
+        }
+    }
+
+    pub fn amount(&self) -> f32 {
+        self.amount
+    }
+
+    /// Set the position of the knob in the range `[-1.0, 1.0]`, where
+    /// negative values sweep the lowpass filter down, positive values sweep
+    /// the highpass filter up, and `0.0` is off.
+    ///
+    /// The cutoff moves exponentially between [`MAX_CUTOFF_HZ`] and
+    /// [`MIN_CUTOFF_HZ`], so equal movements of the knob are equal intervals
+    /// in pitch.
+    pub fn set_amount(&mut self, amount: f32) {
+        self.amount = amount.clamp(-1.0, 1.0);
+        self.sync_params();
+    }
+
+    pub fn resonance(&self) -> f32 {
+        self.resonance
+    }
+
+    /// Set the resonance in the range `[0.0, 1.0]`, where `0.0` is a
+    /// Butterworth response and `1.0` is a Q of [`MAX_RESONANCE_Q`].
+    pub fn set_resonance(&mut self, resonance: f32) {
+        self.resonance = resonance.clamp(0.0, 1.0);
+        self.sync_params();
+    }
+
+    pub fn order(&self) -> FilterOrder {
+        self.order
+    }
+
+    /// Set the order (steepness) of the filters. The resonance has no effect
+    /// for [`FilterOrder::X1`].
+    pub fn set_order(&mut self, order: FilterOrder) {
+        self.order = order;
+        self.sync_params();
+    }
+
+    /// Reset the state of the filters, and finish any crossfade. See
+    /// [`MeadowEqDspStereoLinked::reset`].
+    pub fn reset(&mut self) {
+        if let Some(params) = self.pending_params.take() {
+            self.eq.set_params(&params);
+        }
+        self.mix = self.target_mix();
+        self.eq.reset();
+    }
+
+    /// Process the given buffers in place.
+    ///
+    /// `buf_l` and `buf_r` must have the same length. See the
+    /// [module docs](super).
+    pub fn process(&mut self, buf_l: &mut [f32], buf_r: &mut [f32]) {
+        let (buf_l, buf_r) = same_len(buf_l, buf_r);
+        let mut dry_l = [0.0f32; CONVERT_FRAMES];
+        let mut dry_r = [0.0f32; CONVERT_FRAMES];
+
+        let mut start = 0;
+        while start < buf_l.len() {
+            if self.mix == 0.0 {
+                if let Some(params) = self.pending_params.take() {
+                    // Switch to the next filter, which fades in from a clean
+                    // state.
+                    self.eq.set_params(&params);
+                    self.eq.reset_lp();
+                    self.eq.reset_hp();
+                }
+            }
+
+            // Process up to the end of the crossfade, if there is one.
+            let target_mix = self.target_mix();
+            let fade_frames_left =
+                ((target_mix - self.mix).abs() * self.fade_frames as f32).ceil() as usize;
+            let remaining = buf_l.len() - start;
+
+            let (frames, mix) = if fade_frames_left == 0 {
+                (remaining, target_mix)
+            } else if fade_frames_left <= remaining {
+                (fade_frames_left, target_mix)
+            } else {
+                let step = (target_mix - self.mix) / fade_frames_left as f32;
+                (remaining, self.mix + step * remaining as f32)
+            };
+
+            let end = start + frames;
+            self.eq.process_mix(
+                &mut buf_l[start..end],
+                &mut buf_r[start..end],
+                &mut dry_l,
+                &mut dry_r,
+                mix,
+            );
+            self.mix = mix;
+            start = end;
+        }
+    }
+
+    /// The mix the crossfade is heading towards.
+    fn target_mix(&self) -> f32 {
+        if self.pending_params.is_none() && is_active(self.eq.params()) {
+            1.0
+        } else {
+            0.0
+        }
+    }
+
+    fn sync_params(&mut self) {
+        let mut params = EqParams::<0>::default();
+
+        let amount = self.amount.abs();
+        if amount > 0.0 {
+            let max_cutoff_hz = MAX_CUTOFF_HZ.min((self.sample_rate * 0.45) as f32);
+            let sweep = (MIN_CUTOFF_HZ / max_cutoff_hz).powf(amount);
+
+            let resonance = self.resonance * (amount / RESONANCE_FADE_IN_AMOUNT).min(1.0);
+            let q = DEFAULT_Q * (MAX_RESONANCE_Q / DEFAULT_Q).powf(resonance);
+
+            let band = if self.amount < 0.0 {
+                &mut params.lp_band
+            } else {
+                &mut params.hp_band
+            };
+
+            band.enabled = true;
+            band.cutoff_hz = if self.amount < 0.0 {
+                max_cutoff_hz * sweep
+            } else {
+                MIN_CUTOFF_HZ / sweep
+            };
+            band.q = q;
+            band.order = self.order;
+        }
+
+        let current = self.eq.params();
+        let switches_filter = params.lp_band.enabled != current.lp_band.enabled
+            || params.hp_band.enabled != current.hp_band.enabled;
+
+        if switches_filter && is_active(current) {
+            // Wait for the active filter to fade out.
+            self.pending_params = Some(params);
+        } else {
+            self.pending_params = None;
+            self.eq.set_params(&params);
+        }
+    }
+}
+
+/// Returns `true` if either filter is enabled.
+fn is_active(params: &EqParams<0>) -> bool {
+    params.lp_band.enabled || params.hp_band.enabled
+}
+
+impl AudioProcessor for DjFilter {
+    fn process_stereo(&mut self, buf_l: &mut [f32], buf_r: &mut [f32]) {
+        self.process(buf_l, buf_r);
+    }
+
+    fn reset(&mut self) {
+        self.reset();
+    }
+
+    fn latency(&self) -> u32 {
+        Self::LATENCY
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{noise, sine};
+
+    const SAMPLE_RATE: f64 = 48_000.0;
+
+    /// The largest second difference of the output, which stays small for a
+    /// smooth signal and spikes at a click.
+    fn max_curvature(buf: &[f32]) -> f32 {
+        buf.windows(3)
+            .map(|w| (w[0] - 2.0 * w[1] + w[2]).abs())
+            .fold(0.0, f32::max)
+    }
+
+    #[test]
+    fn center_is_transparent() {
+        let input = noise(4_800, 0);
+
+        let mut dj = DjFilter::new(SAMPLE_RATE);
+        dj.set_resonance(1.0);
+        dj.set_order(FilterOrder::X4);
+
+        let (mut l, mut r) = (input.clone(), input.clone());
+        dj.process(&mut l, &mut r);
+        assert_eq!(l, input);
+        assert_eq!(r, input);
+
+        // Turn the knob both ways and back to the center.
+        for amount in [-0.5, 0.5, 0.0] {
+            dj.set_amount(amount);
+            let (mut l, mut r) = (input.clone(), input.clone());
+            dj.process(&mut l, &mut r);
+        }
+
+        let (mut l, mut r) = (input.clone(), input.clone());
+        dj.process(&mut l, &mut r);
+        assert_eq!(l, input);
+        assert_eq!(r, input);
+    }
+
+    #[test]
+    fn crossing_the_center_does_not_click() {
+        const BLOCK: usize = 64;
+        let input = sine(1_000.0, SAMPLE_RATE, 9_600);
+
+        for order in [FilterOrder::X2, FilterOrder::X8] {
+            for amounts in [[-0.02, 0.0], [0.0, 0.02], [-0.02, 0.02], [0.02, -0.02]] {
+                let mut dj = DjFilter::new(SAMPLE_RATE);
+                dj.set_order(order);
+                dj.set_amount(amounts[0]);
+
+                let (mut l, mut r) = (input.clone(), input.clone());
+                for (i, (l, r)) in l.chunks_mut(BLOCK).zip(r.chunks_mut(BLOCK)).enumerate() {
+                    if i * BLOCK == input.len() / 2 {
+                        dj.set_amount(amounts[1]);
+                    }
+                    dj.process(l, r);
+                }
+
+                // Skip the start, where the filters fade in.
+                let curvature = max_curvature(&l[input.len() / 4..]);
+                let expected = max_curvature(&input);
+                assert!(
+                    curvature < 1.5 * expected,
+                    "{order:?}, {amounts:?}: {curvature} (expected {expected})"
+                );
+                assert_eq!(l, r);
+            }
+        }
+    }
+}
@@ -0,0 +1,555 @@
+use meadow_dsp_mit::{
+    decibel::f32::db_to_amp,
+    dynamics::f32::SoftLimiter,
+    processor::AudioProcessor,
+    smooth::f32::{OnePoleSmoother, DEFAULT_SMOOTH_SECS},
+};
+
+use crate::parametric_eq::f32::{
+    coeff::{MeadowEqDspCoeff, BAND_FADE_STEP_FRAMES},
+    listen::BandListen,
+    mono::{is_unity_gain, process_channel},
+    state::MeadowEqDspState,
+    BandRouting, EqParams,
+};
+
+/// The DSP for a fully-featured parametric EQ. This version has two channels,
+/// does not make use of SIMD optimizations, and has zero latency. Each channel
+/// has its own parameters.
+///
+/// The channels can be partially linked with [`MeadowEqDspStereoDual::set_link_amount`].
+///
+/// TODO: Get rid of `NUM_BANDS_PLUS_16` const generic once const generic expressions
+/// are stabilized. (please rust compiler team)
+#[derive(Clone)]
+pub struct MeadowEqDspStereoDual<const NUM_BANDS: usize, const NUM_BANDS_PLUS_16: usize> {
+    left_coeff: MeadowEqDspCoeff<NUM_BANDS, NUM_BANDS_PLUS_16>,
+    right_coeff: MeadowEqDspCoeff<NUM_BANDS, NUM_BANDS_PLUS_16>,
+
+    left_state: MeadowEqDspState<NUM_BANDS, NUM_BANDS_PLUS_16>,
+    right_state: MeadowEqDspState<NUM_BANDS, NUM_BANDS_PLUS_16>,
+
+    left_params: EqParams<NUM_BANDS>,
+    right_params: EqParams<NUM_BANDS>,
+    link_amount: f32,
+
+    band_routing: BandRouting,
+
+    gain_db: f32,
+    gain: OnePoleSmoother,
+
+    input_gain_db: f32,
+    input_gain: OnePoleSmoother,
+
+    output_ceiling_db: Option<f32>,
+    output_limiter: Option<SoftLimiter>,
+
+    /// The mix of the last call to `process_mix`, which the next call ramps
+    /// from.
+    mix: f32,
+
+    listen: [BandListen; 2],
+}
+
+impl<const NUM_BANDS: usize, const NUM_BANDS_PLUS_16: usize>
+    MeadowEqDspStereoDual<NUM_BANDS, NUM_BANDS_PLUS_16>
+{
+    pub const LATENCY: u32 = 0;
+
+    pub fn new(sample_rate: f64) -> Self {
+        Self {
+            left_coeff: MeadowEqDspCoeff::new(sample_rate),
+            right_coeff: MeadowEqDspCoeff::new(sample_rate),
+            left_state: MeadowEqDspState::new(),
+            right_state: MeadowEqDspState::new(),
+            left_params: EqParams::default(),
+            right_params: EqParams::default(),
+            link_amount: 0.0,
+            band_routing: BandRouting::default(),
+            gain_db: 0.0,
+            gain: OnePoleSmoother::new(1.0, DEFAULT_SMOOTH_SECS, sample_rate),
+            input_gain_db: 0.0,
+            input_gain: OnePoleSmoother::new(1.0, DEFAULT_SMOOTH_SECS, sample_rate),
+            output_ceiling_db: None,
+            output_limiter: None,
+            mix: 1.0,
+            listen: [BandListen::new(); 2],
+        }
+    }
+
+    /// The number of bands (not including the lowpass and highpass bands).
+    pub const fn num_bands() -> usize {
+        NUM_BANDS
+    }
+
+    /// The sample rate this EQ was created with.
+    pub fn sample_rate(&self) -> f64 {
+        self.left_coeff.sample_rate()
+    }
+
+    pub fn band_routing(&self) -> BandRouting {
+        self.band_routing
+    }
+
+    /// Set how the bands (not including the lowpass and highpass bands) are
+    /// combined. See [`BandRouting`] for details.
+    pub fn set_band_routing(&mut self, routing: BandRouting) {
+        self.band_routing = routing;
+    }
+
+    pub fn gain_db(&self) -> f32 {
+        self.gain_db
+    }
+
+    /// Set the gain applied to the output of the EQ in decibels. Changes are
+    /// smoothed to avoid clicks, so this can also be used to fade the output
+    /// out by setting it to `f32::NEG_INFINITY`.
+    pub fn set_gain_db(&mut self, gain_db: f32) {
+        self.gain_db = gain_db;
+        self.gain.set(db_to_amp(gain_db));
+    }
+
+    pub fn input_gain_db(&self) -> f32 {
+        self.input_gain_db
+    }
+
+    /// Set the gain applied to the input of the EQ (before any of the
+    /// filters) in decibels. Changes are smoothed to avoid clicks. This is
+    /// independent of the output gain set with `set_gain_db`.
+    pub fn set_input_gain_db(&mut self, gain_db: f32) {
+        self.input_gain_db = gain_db;
+        self.input_gain.set(db_to_amp(gain_db));
+    }
+
+    pub fn output_ceiling_db(&self) -> Option<f32> {
+        self.output_ceiling_db
+    }
+
+    /// Set the ceiling of a soft limiter on the output (after the output
+    /// gain) in decibels, or `None` to disable it (the default). See
+    /// [`MeadowEqDspStereoLinked::set_output_ceiling_db`](super::scalar::MeadowEqDspStereoLinked::set_output_ceiling_db).
+    pub fn set_output_ceiling_db(&mut self, ceiling_db: Option<f32>) {
+        self.output_ceiling_db = ceiling_db;
+        self.output_limiter = ceiling_db.map(SoftLimiter::new);
+    }
+
+    /// The parameters of the left channel, as set by the user (before linking
+    /// is applied).
+    pub fn left_params(&self) -> &EqParams<NUM_BANDS> {
+        &self.left_params
+    }
+
+    /// The parameters of the right channel, as set by the user (before linking
+    /// is applied).
+    pub fn right_params(&self) -> &EqParams<NUM_BANDS> {
+        &self.right_params
+    }
+
+    pub fn set_left_params(&mut self, params: &EqParams<NUM_BANDS>) {
+        self.left_params = *params;
+        self.sync_linked_params();
+    }
+
+    pub fn set_right_params(&mut self, params: &EqParams<NUM_BANDS>) {
+        self.right_params = *params;
+        self.sync_linked_params();
+    }
+
+    pub fn link_amount(&self) -> f32 {
+        self.link_amount
+    }
+
+    /// Set how strongly the parameters of the two channels are linked, in the
+    /// range `[0.0, 1.0]`.
+    ///
+    /// Each channel's cutoff, Q, gain, and multimode morph are blended towards
+    /// the average of both channels by this amount (cutoff and Q are blended
+    /// in the log domain). At `0.0` the channels are fully independent, and at `1.0` both
+    /// channels use the same parameters like [`MeadowEqDspStereoLinked`].
+    ///
+    /// Parameters which cannot be blended (whether a band is enabled, the band
+    /// type, the Q mode, the filter order, `matched_cutoff`, and
+    /// `shelf_gain_db`) snap to those of the left channel whenever the link
+    /// amount is above `0.0`.
+    ///
+    /// [`MeadowEqDspStereoLinked`]: super::scalar::MeadowEqDspStereoLinked
+    pub fn set_link_amount(&mut self, link_amount: f32) {
+        self.link_amount = link_amount.clamp(0.0, 1.0);
+        self.sync_linked_params();
+    }
+
+    pub fn needs_param_flush(&self) -> bool {
+        self.left_coeff.needs_param_flush() || self.right_coeff.needs_param_flush()
+    }
+
+    /// An estimate of the cost of the next flush. See
+    /// [`MeadowEqDspCoeff::pending_recompute_cost`].
+    pub fn pending_recompute_cost(&self) -> usize {
+        self.left_coeff.pending_recompute_cost() + self.right_coeff.pending_recompute_cost()
+    }
+
+    pub fn flush_param_changes(&mut self) {
+        if let Some(info) = self.left_coeff.flush_param_changes() {
+            self.left_state.sync(&info);
+        }
+        if let Some(info) = self.right_coeff.flush_param_changes() {
+            self.right_state.sync(&info);
+        }
+    }
+
+    /// Set the duration of the fade applied when a band is disabled. See
+    /// [`MeadowEqDspCoeff::set_band_fade_secs`].
+    pub fn set_band_fade_secs(&mut self, fade_secs: f64) {
+        self.left_coeff.set_band_fade_secs(fade_secs);
+        self.right_coeff.set_band_fade_secs(fade_secs);
+    }
+
+    /// Whether every band always keeps its filter slot, even while it is
+    /// disabled. See [`MeadowEqDspCoeff::set_fixed_band_slots`].
+    pub fn set_fixed_band_slots(&mut self, fixed: bool) {
+        self.left_coeff.set_fixed_band_slots(fixed);
+        self.right_coeff.set_fixed_band_slots(fixed);
+    }
+
+    /// An estimate of how many samples it takes for the impulse response of
+    /// both channels to decay below `threshold_db`. See
+    /// [`MeadowEqDspCoeff::estimated_settling_samples`].
+    pub fn estimated_settling_samples(&self, threshold_db: f64) -> usize {
+        self.left_coeff
+            .estimated_settling_samples(threshold_db)
+            .max(self.right_coeff.estimated_settling_samples(threshold_db))
+    }
+
+    /// Reset the state of all filters, and jump the smoothed gains straight to
+    /// their targets. Bands which are fading out are removed immediately.
+    ///
+    /// The parameters (and so the target coefficients) are left untouched,
+    /// so steady input produces steady output right away.
+    pub fn reset(&mut self) {
+        self.left_state.reset();
+        self.right_state.reset();
+        self.left_coeff.finish_band_fades();
+        self.right_coeff.finish_band_fades();
+        self.gain.finish();
+        self.input_gain.finish();
+        self.listen[0].reset();
+        self.listen[1].reset();
+    }
+
+    /// Reset the state of only the band at the given index (in both channels),
+    /// leaving the state of all other bands untouched.
+    ///
+    /// # Panics
+    /// Panics if `index >= NUM_BANDS`.
+    pub fn reset_band(&mut self, index: usize) {
+        self.left_state.reset_band(index);
+        self.right_state.reset_band(index);
+    }
+
+    /// Reset the state of only the lowpass band.
+    pub fn reset_lp(&mut self) {
+        self.left_state.reset_lp();
+        self.right_state.reset_lp();
+    }
+
+    /// Reset the state of only the highpass band.
+    pub fn reset_hp(&mut self) {
+        self.left_state.reset_hp();
+        self.right_state.reset_hp();
+    }
+
+    /// Set the state of all filters to the steady state for constant inputs
+    /// of `dc_l` and `dc_r`, after applying any pending parameter changes.
+    /// See [`MeadowEqDspState::prime_for_dc`].
+    pub fn prime_for_dc(&mut self, dc_l: f32, dc_r: f32) {
+        self.flush_param_changes();
+        self.left_state.prime_for_dc(
+            dc_l * self.input_gain.target(),
+            &self.left_coeff,
+            self.band_routing,
+        );
+        self.right_state.prime_for_dc(
+            dc_r * self.input_gain.target(),
+            &self.right_coeff,
+            self.band_routing,
+        );
+    }
+
+    /// Process the given buffers in place.
+    ///
+    /// `buf_l` and `buf_r` must have the same length. See the
+    /// [module docs](super).
+    ///
+    /// Without an output ceiling (see [`Self::set_output_ceiling_db`]) the EQ
+    /// is linear: starting from a [`Self::reset`] state, processing `a * x`
+    /// gives `a` times the output for `x`, apart from floating point
+    /// rounding. The limiter behind the ceiling is nonlinear.
+    pub fn process(&mut self, buf_l: &mut [f32], buf_r: &mut [f32]) {
+        let (buf_l, buf_r) = super::same_len(buf_l, buf_r);
+
+        if !(self.left_coeff.is_fading_bands() || self.right_coeff.is_fading_bands()) {
+            self.process_block(buf_l, buf_r);
+            return;
+        }
+
+        // Advance the fades of the bands being disabled in small steps.
+        for (buf_l, buf_r) in buf_l
+            .chunks_mut(BAND_FADE_STEP_FRAMES)
+            .zip(buf_r.chunks_mut(BAND_FADE_STEP_FRAMES))
+        {
+            let frames = buf_l.len();
+            self.left_coeff.advance_band_fades(frames);
+            self.right_coeff.advance_band_fades(frames);
+            self.process_block(buf_l, buf_r);
+        }
+    }
+
+    /// Process the given buffers in place like [`Self::process`], but without
+    /// checking for pending parameter changes first. This is for callers which
+    /// call `flush_param_changes` themselves at block boundaries.
+    ///
+    /// The caller must make sure that there are no pending parameter changes
+    /// (see `needs_param_flush`), otherwise the changes are not applied until
+    /// the next flush. This is checked with a debug assertion. While a band is
+    /// fading out, this falls back to [`Self::process`], since the end of the
+    /// fade needs a flush part way through the buffers.
+    pub fn process_no_flush(&mut self, buf_l: &mut [f32], buf_r: &mut [f32]) {
+        debug_assert!(!self.needs_param_flush());
+
+        if self.left_coeff.is_fading_bands() || self.right_coeff.is_fading_bands() {
+            self.process(buf_l, buf_r);
+        } else {
+            self.process_block_no_flush(buf_l, buf_r);
+        }
+    }
+
+    /// Process the given buffers in place, mixing the processed (wet) signal
+    /// with the unprocessed (dry) signal.
+    ///
+    /// `mix` is in the range `[0.0, 1.0]`, where `0.0` is fully dry and `1.0`
+    /// is fully wet. Changes to `mix` are ramped over the buffer to avoid
+    /// clicks.
+    ///
+    /// `dry_l` and `dry_r` are scratch buffers which hold a copy of the dry
+    /// signal, so that this never allocates. They can be any length (the
+    /// buffers are processed in chunks of that length).
+    ///
+    /// # Panics
+    /// Panics if either scratch buffer is empty.
+    pub fn process_mix(
+        &mut self,
+        buf_l: &mut [f32],
+        buf_r: &mut [f32],
+        dry_l: &mut [f32],
+        dry_r: &mut [f32],
+        mix: f32,
+    ) {
+        let from_mix = self.mix;
+        self.mix = mix.clamp(0.0, 1.0);
+
+        super::process_mix_via(
+            buf_l,
+            buf_r,
+            dry_l,
+            dry_r,
+            from_mix,
+            self.mix,
+            |buf_l, buf_r| self.process(buf_l, buf_r),
+        );
+    }
+
+    /// Process the given buffers in place like [`Self::process_mix`], but
+    /// with a separate mix for every frame (e.g. from host automation)
+    /// instead of a ramp.
+    ///
+    /// The values of `mix` are clamped to the range `[0.0, 1.0]`. They are
+    /// used as is, so they should already be smooth. The next call to
+    /// [`Self::process_mix`] ramps from the last value.
+    ///
+    /// # Panics
+    /// Panics if either scratch buffer is empty, or if `mix` is not the same
+    /// length as the buffers.
+    pub fn process_mix_automated(
+        &mut self,
+        buf_l: &mut [f32],
+        buf_r: &mut [f32],
+        dry_l: &mut [f32],
+        dry_r: &mut [f32],
+        mix: &[f32],
+    ) {
+        if let Some(&last) = mix.last() {
+            self.mix = last.clamp(0.0, 1.0);
+        }
+
+        super::process_mix_automated_via(buf_l, buf_r, dry_l, dry_r, mix, |buf_l, buf_r| {
+            self.process(buf_l, buf_r)
+        });
+    }
+
+    /// Process the given buffers in place like [`Self::process`], and also
+    /// write the signal of the band at `band_index` on its own into
+    /// `listen_l` and `listen_r`, so that the band can be monitored (for
+    /// example to hear the sibilance a de-esser band acts on).
+    ///
+    /// The listen signal is the input (before the input gain) through a copy
+    /// of the band's own filter, so it follows the band's type, cutoff, Q,
+    /// and gain (or its raw coefficients). It does not depend on the other
+    /// bands, or on whether the band is enabled.
+    ///
+    /// This runs one extra SVF filter per channel on top of the EQ itself,
+    /// so it costs about as much as one extra band.
+    ///
+    /// # Panics
+    /// Panics if `band_index >= NUM_BANDS`, or if a listen buffer is shorter
+    /// than the buffers being processed.
+    pub fn process_with_band_listen(
+        &mut self,
+        buf_l: &mut [f32],
+        buf_r: &mut [f32],
+        band_index: usize,
+        listen_l: &mut [f32],
+        listen_r: &mut [f32],
+    ) {
+        let frames = buf_l.len().min(buf_r.len());
+        let sample_rate_recip = self.sample_rate().recip();
+
+        let [listen_filter_l, listen_filter_r] = &mut self.listen;
+        listen_filter_l.process(
+            band_index,
+            &self.left_coeff.params().bands[band_index],
+            self.left_coeff.band_raw_coeff(band_index),
+            sample_rate_recip,
+            &buf_l[..frames],
+            &mut listen_l[..frames],
+        );
+        listen_filter_r.process(
+            band_index,
+            &self.right_coeff.params().bands[band_index],
+            self.right_coeff.band_raw_coeff(band_index),
+            sample_rate_recip,
+            &buf_r[..frames],
+            &mut listen_r[..frames],
+        );
+
+        self.process(buf_l, buf_r);
+    }
+
+    fn process_block(&mut self, buf_l: &mut [f32], buf_r: &mut [f32]) {
+        if self.needs_param_flush() {
+            self.flush_param_changes();
+        }
+
+        self.process_block_no_flush(buf_l, buf_r);
+    }
+
+    fn process_block_no_flush(&mut self, buf_l: &mut [f32], buf_r: &mut [f32]) {
+        if self.left_coeff.is_empty()
+            && self.right_coeff.is_empty()
+            && is_unity_gain(&self.input_gain)
+            && is_unity_gain(&self.gain)
+            && self.output_limiter.is_none()
+        {
+            return;
+        }
+
+        super::apply_gain(&mut self.input_gain, buf_l, buf_r);
+
+        process_channel(
+            buf_l,
+            &self.left_coeff,
+            &mut self.left_state,
+            self.band_routing,
+        );
+        process_channel(
+            buf_r,
+            &self.right_coeff,
+            &mut self.right_state,
+            self.band_routing,
+        );
+
+        super::apply_gain(&mut self.gain, buf_l, buf_r);
+
+        if let Some(limiter) = &self.output_limiter {
+            limiter.process(buf_l);
+            limiter.process(buf_r);
+        }
+    }
+
+    fn sync_linked_params(&mut self) {
+        let left = linked_params(
+            &self.left_params,
+            &self.right_params,
+            &self.left_params,
+            self.link_amount,
+        );
+        let right = linked_params(
+            &self.right_params,
+            &self.left_params,
+            &self.left_params,
+            self.link_amount,
+        );
+
+        self.left_coeff.set_params(&left);
+        self.right_coeff.set_params(&right);
+    }
+}
+
+impl<const NUM_BANDS: usize, const NUM_BANDS_PLUS_16: usize> AudioProcessor
+    for MeadowEqDspStereoDual<NUM_BANDS, NUM_BANDS_PLUS_16>
+{
+    fn process_stereo(&mut self, buf_l: &mut [f32], buf_r: &mut [f32]) {
+        self.process(buf_l, buf_r);
+    }
+
+    fn reset(&mut self) {
+        self.reset();
+    }
+
+    fn latency(&self) -> u32 {
+        Self::LATENCY
+    }
+}
+
+/// Blend `own` towards the average of `own` and `other` by `link_amount`. The
+/// parameters which cannot be blended are taken from `left`.
+fn linked_params<const NUM_BANDS: usize>(
+    own: &EqParams<NUM_BANDS>,
+    other: &EqParams<NUM_BANDS>,
+    left: &EqParams<NUM_BANDS>,
+    link_amount: f32,
+) -> EqParams<NUM_BANDS> {
+    if link_amount <= 0.0 {
+        return *own;
+    }
+
+    let mut params = *left;
+
+    params.lp_band.cutoff_hz =
+        blend_log(own.lp_band.cutoff_hz, other.lp_band.cutoff_hz, link_amount);
+    params.lp_band.q = blend_log(own.lp_band.q, other.lp_band.q, link_amount);
+
+    params.hp_band.cutoff_hz =
+        blend_log(own.hp_band.cutoff_hz, other.hp_band.cutoff_hz, link_amount);
+    params.hp_band.q = blend_log(own.hp_band.q, other.hp_band.q, link_amount);
+
+    for (i, band) in params.bands.iter_mut().enumerate() {
+        band.cutoff_hz = blend_log(
+            own.bands[i].cutoff_hz,
+            other.bands[i].cutoff_hz,
+            link_amount,
+        );
+        band.q = blend_log(own.bands[i].q, other.bands[i].q, link_amount);
+        band.gain_db = blend_linear(own.bands[i].gain_db, other.bands[i].gain_db, link_amount);
+        band.morph = blend_linear(own.bands[i].morph, other.bands[i].morph, link_amount);
+    }
+
+    params
+}
+
+fn blend_linear(own: f32, other: f32, link_amount: f32) -> f32 {
+    own + (0.5 * link_amount) * (other - own)
+}
+
+fn blend_log(own: f32, other: f32, link_amount: f32) -> f32 {
+    own * (other / own).powf(0.5 * link_amount)
+}
@@ -0,0 +1,597 @@
+use meadow_dsp_mit::{
+    decibel::f32::db_to_amp,
+    dynamics::f32::SoftLimiter,
+    filter::svf::f64::SvfCoeff as SvfCoeffF64,
+    processor::AudioProcessor,
+    smooth::f32::{OnePoleSmoother, DEFAULT_SMOOTH_SECS},
+};
+
+#[cfg(feature = "debug-introspection")]
+use crate::parametric_eq::f32::coeff::DirtyFlags;
+use crate::parametric_eq::f32::{
+    coeff::{CoeffSnapshot, F64Coeffs, MeadowEqDspCoeff, StateSyncInfo, BAND_FADE_STEP_FRAMES},
+    listen::BandListen,
+    mono::is_unity_gain,
+    state::MeadowEqDspState,
+    BandRouting, EqParams,
+};
+
+/// The same as [`MeadowEqDspStereoLinked`], except that the filter
+/// coefficients are stored in f64. The state of the filters is still stored in
+/// f32, and each filter promotes its state to f64 while processing.
+///
+/// This is more accurate for long cascades of filters and for filters with a
+/// very low cutoff, at the cost of some speed. With an 8th order highpass and
+/// eight narrow bells all below 100 Hz, the error compared to processing fully
+/// in f64 drops from around -119 dB to -140 dB (see the
+/// `f64_coeff_is_more_accurate` test), while processing is around 1.9x slower
+/// on x86_64 (see the `eq_low_cascade` benchmark).
+///
+/// [`MeadowEqDspStereoLinked`]: super::scalar::MeadowEqDspStereoLinked
+///
+/// TODO: Get rid of `NUM_BANDS_PLUS_16` const generic once const generic expressions
+/// are stabilized. (please rust compiler team)
+#[derive(Clone)]
+pub struct MeadowEqDspStereoLinkedF64Coeff<const NUM_BANDS: usize, const NUM_BANDS_PLUS_16: usize> {
+    coeff: MeadowEqDspCoeff<NUM_BANDS, NUM_BANDS_PLUS_16, F64Coeffs>,
+
+    left_state: MeadowEqDspState<NUM_BANDS, NUM_BANDS_PLUS_16>,
+    right_state: MeadowEqDspState<NUM_BANDS, NUM_BANDS_PLUS_16>,
+
+    band_routing: BandRouting,
+
+    gain_db: f32,
+    gain: OnePoleSmoother,
+
+    input_gain_db: f32,
+    input_gain: OnePoleSmoother,
+
+    output_ceiling_db: Option<f32>,
+    output_limiter: Option<SoftLimiter>,
+
+    /// The mix of the last call to `process_mix`, which the next call ramps
+    /// from.
+    mix: f32,
+
+    listen: [BandListen; 2],
+}
+
+impl<const NUM_BANDS: usize, const NUM_BANDS_PLUS_16: usize>
+    MeadowEqDspStereoLinkedF64Coeff<NUM_BANDS, NUM_BANDS_PLUS_16>
+{
+    pub const LATENCY: u32 = 0;
+
+    pub fn new(sample_rate: f64) -> Self {
+        Self {
+            coeff: MeadowEqDspCoeff::new(sample_rate),
+            left_state: MeadowEqDspState::new(),
+            right_state: MeadowEqDspState::new(),
+            band_routing: BandRouting::default(),
+            gain_db: 0.0,
+            gain: OnePoleSmoother::new(1.0, DEFAULT_SMOOTH_SECS, sample_rate),
+            input_gain_db: 0.0,
+            input_gain: OnePoleSmoother::new(1.0, DEFAULT_SMOOTH_SECS, sample_rate),
+            output_ceiling_db: None,
+            output_limiter: None,
+            mix: 1.0,
+            listen: [BandListen::new(); 2],
+        }
+    }
+
+    /// The number of bands (not including the lowpass and highpass bands).
+    pub const fn num_bands() -> usize {
+        NUM_BANDS
+    }
+
+    /// The sample rate this EQ was created with.
+    pub fn sample_rate(&self) -> f64 {
+        self.coeff.sample_rate()
+    }
+
+    pub fn band_routing(&self) -> BandRouting {
+        self.band_routing
+    }
+
+    /// Set how the bands (not including the lowpass and highpass bands) are
+    /// combined. See [`BandRouting`] for details.
+    pub fn set_band_routing(&mut self, routing: BandRouting) {
+        self.band_routing = routing;
+    }
+
+    pub fn gain_db(&self) -> f32 {
+        self.gain_db
+    }
+
+    /// Set the gain applied to the output of the EQ in decibels. Changes are
+    /// smoothed to avoid clicks, so this can also be used to fade the output
+    /// out by setting it to `f32::NEG_INFINITY`.
+    pub fn set_gain_db(&mut self, gain_db: f32) {
+        self.gain_db = gain_db;
+        self.gain.set(db_to_amp(gain_db));
+    }
+
+    pub fn input_gain_db(&self) -> f32 {
+        self.input_gain_db
+    }
+
+    /// Set the gain applied to the input of the EQ (before any of the
+    /// filters) in decibels. Changes are smoothed to avoid clicks. This is
+    /// independent of the output gain set with `set_gain_db`.
+    pub fn set_input_gain_db(&mut self, gain_db: f32) {
+        self.input_gain_db = gain_db;
+        self.input_gain.set(db_to_amp(gain_db));
+    }
+
+    pub fn output_ceiling_db(&self) -> Option<f32> {
+        self.output_ceiling_db
+    }
+
+    /// Set the ceiling of a soft limiter on the output (after the output
+    /// gain) in decibels, or `None` to disable it (the default). See
+    /// [`MeadowEqDspStereoLinked::set_output_ceiling_db`](super::scalar::MeadowEqDspStereoLinked::set_output_ceiling_db).
+    pub fn set_output_ceiling_db(&mut self, ceiling_db: Option<f32>) {
+        self.output_ceiling_db = ceiling_db;
+        self.output_limiter = ceiling_db.map(SoftLimiter::new);
+    }
+
+    pub fn params(&self) -> &EqParams<NUM_BANDS> {
+        self.coeff.params()
+    }
+
+    pub fn set_params(&mut self, params: &EqParams<NUM_BANDS>) {
+        self.coeff.set_params(params);
+    }
+
+    /// Modify the parameters in place. Only the bands whose parameters were
+    /// actually changed by `f` are marked for recalculation.
+    pub fn modify_params(&mut self, f: impl FnOnce(&mut EqParams<NUM_BANDS>)) {
+        self.coeff.modify_params(f);
+    }
+
+    /// Set the coefficients of the band at the given index directly. See
+    /// [`MeadowEqDspCoeff::set_band_raw_coeff`].
+    ///
+    /// # Panics
+    /// Panics if `index >= NUM_BANDS`.
+    pub fn set_band_raw_coeff(&mut self, index: usize, coeff: SvfCoeffF64) {
+        self.coeff.set_band_raw_coeff(index, coeff);
+    }
+
+    pub fn needs_param_flush(&self) -> bool {
+        self.coeff.needs_param_flush()
+    }
+
+    /// An estimate of the cost of the next flush. See
+    /// [`MeadowEqDspCoeff::pending_recompute_cost`].
+    pub fn pending_recompute_cost(&self) -> usize {
+        self.coeff.pending_recompute_cost()
+    }
+
+    /// Returns the current dirty flags of the coefficients. This is only meant
+    /// to be used for debugging and testing.
+    #[cfg(feature = "debug-introspection")]
+    pub fn dirty_flags(&self) -> DirtyFlags<NUM_BANDS> {
+        self.coeff.dirty_flags()
+    }
+
+    /// Flush any pending parameter changes.
+    ///
+    /// The internal filter states are synced automatically. The returned
+    /// info is `Some` if the filter topology changed (e.g. for logging, or
+    /// for triggering a relayout of a GUI).
+    pub fn flush_param_changes(&mut self) -> Option<StateSyncInfo<NUM_BANDS>> {
+        let info = self.coeff.flush_param_changes();
+        self.sync_states(info)
+    }
+
+    /// Recompute the coefficients of every band regardless of which
+    /// parameters have changed. See [`MeadowEqDspCoeff::recompute_all`].
+    ///
+    /// The returned info is the same as for
+    /// [`MeadowEqDspStereoLinkedF64Coeff::flush_param_changes`].
+    pub fn recompute_all(&mut self) -> Option<StateSyncInfo<NUM_BANDS>> {
+        let info = self.coeff.recompute_all();
+        self.sync_states(info)
+    }
+
+    fn sync_states(
+        &mut self,
+        info: Option<StateSyncInfo<NUM_BANDS>>,
+    ) -> Option<StateSyncInfo<NUM_BANDS>> {
+        if let Some(info) = &info {
+            self.left_state.sync(info);
+            self.right_state.sync(info);
+        }
+
+        info
+    }
+
+    /// Replace the parameters and coefficients with those of a snapshot
+    /// computed on another thread, and sync the filter states. See
+    /// [`MeadowEqDspCoeff::apply_snapshot`].
+    pub fn apply_coeff_snapshot(
+        &mut self,
+        snapshot: CoeffSnapshot<NUM_BANDS, NUM_BANDS_PLUS_16, F64Coeffs>,
+    ) {
+        let info = self.coeff.apply_snapshot(snapshot);
+        self.left_state.sync(&info);
+        self.right_state.sync(&info);
+    }
+
+    /// Set the duration of the fade applied when a band is disabled. See
+    /// [`MeadowEqDspCoeff::set_band_fade_secs`].
+    pub fn set_band_fade_secs(&mut self, fade_secs: f64) {
+        self.coeff.set_band_fade_secs(fade_secs);
+    }
+
+    /// Whether every band always keeps its filter slot, even while it is
+    /// disabled. See [`MeadowEqDspCoeff::set_fixed_band_slots`].
+    pub fn set_fixed_band_slots(&mut self, fixed: bool) {
+        self.coeff.set_fixed_band_slots(fixed);
+    }
+
+    /// An estimate of how many samples it takes for the impulse response to
+    /// decay below `threshold_db`. See
+    /// [`MeadowEqDspCoeff::estimated_settling_samples`].
+    pub fn estimated_settling_samples(&self, threshold_db: f64) -> usize {
+        self.coeff.estimated_settling_samples(threshold_db)
+    }
+
+    /// Reset the state of all filters, and jump the smoothed gains straight to
+    /// their targets. Bands which are fading out are removed immediately.
+    ///
+    /// The parameters (and so the target coefficients) are left untouched,
+    /// so steady input produces steady output right away.
+    pub fn reset(&mut self) {
+        self.left_state.reset();
+        self.right_state.reset();
+        self.coeff.finish_band_fades();
+        self.gain.finish();
+        self.input_gain.finish();
+        self.listen[0].reset();
+        self.listen[1].reset();
+    }
+
+    /// Reset the state of only the band at the given index, leaving the
+    /// state of all other bands untouched.
+    ///
+    /// # Panics
+    /// Panics if `index >= NUM_BANDS`.
+    pub fn reset_band(&mut self, index: usize) {
+        self.left_state.reset_band(index);
+        self.right_state.reset_band(index);
+    }
+
+    /// Reset the state of only the lowpass band.
+    pub fn reset_lp(&mut self) {
+        self.left_state.reset_lp();
+        self.right_state.reset_lp();
+    }
+
+    /// Reset the state of only the highpass band.
+    pub fn reset_hp(&mut self) {
+        self.left_state.reset_hp();
+        self.right_state.reset_hp();
+    }
+
+    /// Set the state of all filters to the steady state for constant inputs
+    /// of `dc_l` and `dc_r`, after applying any pending parameter changes.
+    /// See [`MeadowEqDspState::prime_for_dc`].
+    pub fn prime_for_dc(&mut self, dc_l: f32, dc_r: f32) {
+        let _ = self.flush_param_changes();
+        self.left_state.prime_for_dc(
+            dc_l * self.input_gain.target(),
+            &self.coeff,
+            self.band_routing,
+        );
+        self.right_state.prime_for_dc(
+            dc_r * self.input_gain.target(),
+            &self.coeff,
+            self.band_routing,
+        );
+    }
+
+    /// Process the given buffers in place.
+    ///
+    /// `buf_l` and `buf_r` must have the same length. See the
+    /// [module docs](super).
+    ///
+    /// Without an output ceiling (see [`Self::set_output_ceiling_db`]) the EQ
+    /// is linear: starting from a [`Self::reset`] state, processing `a * x`
+    /// gives `a` times the output for `x`, apart from floating point
+    /// rounding. The limiter behind the ceiling is nonlinear.
+    pub fn process(&mut self, buf_l: &mut [f32], buf_r: &mut [f32]) {
+        let (buf_l, buf_r) = super::same_len(buf_l, buf_r);
+
+        if !(self.coeff.is_fading_bands()) {
+            self.process_block(buf_l, buf_r);
+            return;
+        }
+
+        // Advance the fades of the bands being disabled in small steps.
+        for (buf_l, buf_r) in buf_l
+            .chunks_mut(BAND_FADE_STEP_FRAMES)
+            .zip(buf_r.chunks_mut(BAND_FADE_STEP_FRAMES))
+        {
+            let frames = buf_l.len();
+            self.coeff.advance_band_fades(frames);
+            self.process_block(buf_l, buf_r);
+        }
+    }
+
+    /// Process the given buffers in place like [`Self::process`], but without
+    /// checking for pending parameter changes first. This is for callers which
+    /// call `flush_param_changes` themselves at block boundaries.
+    ///
+    /// The caller must make sure that there are no pending parameter changes
+    /// (see `needs_param_flush`), otherwise the changes are not applied until
+    /// the next flush. This is checked with a debug assertion. While a band is
+    /// fading out, this falls back to [`Self::process`], since the end of the
+    /// fade needs a flush part way through the buffers.
+    pub fn process_no_flush(&mut self, buf_l: &mut [f32], buf_r: &mut [f32]) {
+        debug_assert!(!self.needs_param_flush());
+
+        if self.coeff.is_fading_bands() {
+            self.process(buf_l, buf_r);
+        } else {
+            self.process_block_no_flush(buf_l, buf_r);
+        }
+    }
+
+    /// Process the given buffers in place, mixing the processed (wet) signal
+    /// with the unprocessed (dry) signal.
+    ///
+    /// `mix` is in the range `[0.0, 1.0]`, where `0.0` is fully dry and `1.0`
+    /// is fully wet. Changes to `mix` are ramped over the buffer to avoid
+    /// clicks.
+    ///
+    /// `dry_l` and `dry_r` are scratch buffers which hold a copy of the dry
+    /// signal, so that this never allocates. They can be any length (the
+    /// buffers are processed in chunks of that length).
+    ///
+    /// # Panics
+    /// Panics if either scratch buffer is empty.
+    pub fn process_mix(
+        &mut self,
+        buf_l: &mut [f32],
+        buf_r: &mut [f32],
+        dry_l: &mut [f32],
+        dry_r: &mut [f32],
+        mix: f32,
+    ) {
+        let from_mix = self.mix;
+        self.mix = mix.clamp(0.0, 1.0);
+
+        super::process_mix_via(
+            buf_l,
+            buf_r,
+            dry_l,
+            dry_r,
+            from_mix,
+            self.mix,
+            |buf_l, buf_r| self.process(buf_l, buf_r),
+        );
+    }
+
+    /// Process the given buffers in place like [`Self::process_mix`], but
+    /// with a separate mix for every frame (e.g. from host automation)
+    /// instead of a ramp.
+    ///
+    /// The values of `mix` are clamped to the range `[0.0, 1.0]`. They are
+    /// used as is, so they should already be smooth. The next call to
+    /// [`Self::process_mix`] ramps from the last value.
+    ///
+    /// # Panics
+    /// Panics if either scratch buffer is empty, or if `mix` is not the same
+    /// length as the buffers.
+    pub fn process_mix_automated(
+        &mut self,
+        buf_l: &mut [f32],
+        buf_r: &mut [f32],
+        dry_l: &mut [f32],
+        dry_r: &mut [f32],
+        mix: &[f32],
+    ) {
+        if let Some(&last) = mix.last() {
+            self.mix = last.clamp(0.0, 1.0);
+        }
+
+        super::process_mix_automated_via(buf_l, buf_r, dry_l, dry_r, mix, |buf_l, buf_r| {
+            self.process(buf_l, buf_r)
+        });
+    }
+
+    /// Process the given buffers in place like [`Self::process`], and also
+    /// write the signal of the band at `band_index` on its own into
+    /// `listen_l` and `listen_r`, so that the band can be monitored (for
+    /// example to hear the sibilance a de-esser band acts on).
+    ///
+    /// The listen signal is the input (before the input gain) through a copy
+    /// of the band's own filter, so it follows the band's type, cutoff, Q,
+    /// and gain (or its raw coefficients). It does not depend on the other
+    /// bands, or on whether the band is enabled.
+    ///
+    /// This runs one extra SVF filter per channel on top of the EQ itself,
+    /// so it costs about as much as one extra band.
+    ///
+    /// # Panics
+    /// Panics if `band_index >= NUM_BANDS`, or if a listen buffer is shorter
+    /// than the buffers being processed.
+    pub fn process_with_band_listen(
+        &mut self,
+        buf_l: &mut [f32],
+        buf_r: &mut [f32],
+        band_index: usize,
+        listen_l: &mut [f32],
+        listen_r: &mut [f32],
+    ) {
+        let frames = buf_l.len().min(buf_r.len());
+        let sample_rate_recip = self.sample_rate().recip();
+
+        let [listen_filter_l, listen_filter_r] = &mut self.listen;
+        listen_filter_l.process(
+            band_index,
+            &self.coeff.params().bands[band_index],
+            self.coeff.band_raw_coeff(band_index),
+            sample_rate_recip,
+            &buf_l[..frames],
+            &mut listen_l[..frames],
+        );
+        listen_filter_r.process(
+            band_index,
+            &self.coeff.params().bands[band_index],
+            self.coeff.band_raw_coeff(band_index),
+            sample_rate_recip,
+            &buf_r[..frames],
+            &mut listen_r[..frames],
+        );
+
+        self.process(buf_l, buf_r);
+    }
+
+    /// Process the given f64 buffers in place, for hosts which use f64 audio.
+    ///
+    /// The samples are converted to f32 and processed exactly like
+    /// [`Self::process`], so the precision is the same as for f32 buffers
+    /// (f64 coefficients, f64 arithmetic within each filter, and f32 filter
+    /// states).
+    pub fn process_f64(&mut self, buf_l: &mut [f64], buf_r: &mut [f64]) {
+        super::process_f64_via_f32(buf_l, buf_r, |buf_l, buf_r| self.process(buf_l, buf_r));
+    }
+
+    /// Process an interleaved stereo buffer (`[L, R, L, R, ...]`) in place.
+    ///
+    /// The buffer is deinterleaved in chunks on the stack and processed
+    /// exactly like [`Self::process`], so this never allocates.
+    ///
+    /// # Panics
+    /// Panics if the length of `buf` is odd.
+    pub fn process_interleaved(&mut self, buf: &mut [f32]) {
+        super::process_interleaved_via(buf, |buf_l, buf_r| self.process(buf_l, buf_r));
+    }
+
+    fn process_block(&mut self, buf_l: &mut [f32], buf_r: &mut [f32]) {
+        if self.needs_param_flush() {
+            let _ = self.flush_param_changes();
+        }
+
+        self.process_block_no_flush(buf_l, buf_r);
+    }
+
+    fn process_block_no_flush(&mut self, buf_l: &mut [f32], buf_r: &mut [f32]) {
+        if self.coeff.is_empty()
+            && is_unity_gain(&self.input_gain)
+            && is_unity_gain(&self.gain)
+            && self.output_limiter.is_none()
+        {
+            return;
+        }
+
+        super::apply_gain(&mut self.input_gain, buf_l, buf_r);
+
+        let (one_pole_coeffs, svf_coeffs) = self.coeff.coeffs();
+
+        let (l_one_pole_states, l_svf_states) = self.left_state.states_mut();
+        let (r_one_pole_states, r_svf_states) = self.right_state.states_mut();
+
+        if !one_pole_coeffs.is_empty() {
+            // Hint to compiler to optimize loop;
+            assert_eq!(one_pole_coeffs.len(), l_one_pole_states.len());
+            assert_eq!(one_pole_coeffs.len(), r_one_pole_states.len());
+
+            if one_pole_coeffs.len() == 1 {
+                for (out_l, out_r) in buf_l.iter_mut().zip(buf_r.iter_mut()) {
+                    *out_l = l_one_pole_states[0].tick_f64_coeff(*out_l, &one_pole_coeffs[0]);
+                    *out_r = r_one_pole_states[0].tick_f64_coeff(*out_r, &one_pole_coeffs[0]);
+                }
+            } else {
+                for (out_l, out_r) in buf_l.iter_mut().zip(buf_r.iter_mut()) {
+                    let mut l = *out_l;
+                    let mut r = *out_r;
+
+                    l = l_one_pole_states[0].tick_f64_coeff(l, &one_pole_coeffs[0]);
+                    r = r_one_pole_states[0].tick_f64_coeff(r, &one_pole_coeffs[0]);
+
+                    l = l_one_pole_states[1].tick_f64_coeff(l, &one_pole_coeffs[1]);
+                    r = r_one_pole_states[1].tick_f64_coeff(r, &one_pole_coeffs[1]);
+
+                    *out_l = l;
+                    *out_r = r;
+                }
+            }
+        }
+
+        if !svf_coeffs.is_empty() {
+            // Hint to compiler to optimize loop;
+            assert_eq!(svf_coeffs.len(), l_svf_states.len());
+            assert_eq!(svf_coeffs.len(), r_svf_states.len());
+
+            let num_series_filters = match self.band_routing {
+                BandRouting::Series => svf_coeffs.len(),
+                BandRouting::Parallel => self.coeff.num_lp_hp_svf_filters(),
+            };
+
+            let (series_coeffs, parallel_coeffs) = svf_coeffs.split_at(num_series_filters);
+            let (l_series_states, l_parallel_states) =
+                l_svf_states.split_at_mut(num_series_filters);
+            let (r_series_states, r_parallel_states) =
+                r_svf_states.split_at_mut(num_series_filters);
+
+            if !series_coeffs.is_empty() {
+                for (out_l, out_r) in buf_l.iter_mut().zip(buf_r.iter_mut()) {
+                    let mut l = *out_l;
+                    let mut r = *out_r;
+
+                    for (i, coeff) in series_coeffs.iter().enumerate() {
+                        l = l_series_states[i].tick_f64_coeff(l, coeff);
+                        r = r_series_states[i].tick_f64_coeff(r, coeff);
+                    }
+
+                    *out_l = l;
+                    *out_r = r;
+                }
+            }
+
+            if !parallel_coeffs.is_empty() {
+                for (out_l, out_r) in buf_l.iter_mut().zip(buf_r.iter_mut()) {
+                    let in_l = *out_l;
+                    let in_r = *out_r;
+
+                    let mut l = in_l;
+                    let mut r = in_r;
+
+                    for (i, coeff) in parallel_coeffs.iter().enumerate() {
+                        l += l_parallel_states[i].tick_f64_coeff(in_l, coeff) - in_l;
+                        r += r_parallel_states[i].tick_f64_coeff(in_r, coeff) - in_r;
+                    }
+
+                    *out_l = l;
+                    *out_r = r;
+                }
+            }
+        }
+
+        super::apply_gain(&mut self.gain, buf_l, buf_r);
+
+        if let Some(limiter) = &self.output_limiter {
+            limiter.process(buf_l);
+            limiter.process(buf_r);
+        }
+    }
+}
+
+impl<const NUM_BANDS: usize, const NUM_BANDS_PLUS_16: usize> AudioProcessor
+    for MeadowEqDspStereoLinkedF64Coeff<NUM_BANDS, NUM_BANDS_PLUS_16>
+{
+    fn process_stereo(&mut self, buf_l: &mut [f32], buf_r: &mut [f32]) {
+        self.process(buf_l, buf_r);
+    }
+
+    fn reset(&mut self) {
+        self.reset();
+    }
+
+    fn latency(&self) -> u32 {
+        Self::LATENCY
+    }
+}
@@ -1 +1,206 @@
+//! Stereo processors.
+//!
+//! The left and right buffers passed to the `process*` methods must have the
+//! same length. This is checked with a debug assertion. In release builds
+//! only the frames the buffers have in common are processed, so the two
+//! channels always stay in sync.
+
+pub mod crossfade;
+pub mod dj;
+pub mod dual;
+pub mod f64_coeff;
 pub mod scalar;
+
+use meadow_dsp_mit::smooth::f32::OnePoleSmoother;
+
+use super::mono::{mix_dry, mix_dry_automated, ramp};
+
+/// The number of frames converted at a time by the `process_f64` and
+/// `process_interleaved` methods.
+const CONVERT_FRAMES: usize = 128;
+
+/// Truncate the buffers to the frames they have in common. The lengths are
+/// expected to match, which is checked with a debug assertion.
+fn same_len<'a, T>(buf_l: &'a mut [T], buf_r: &'a mut [T]) -> (&'a mut [T], &'a mut [T]) {
+    debug_assert_eq!(
+        buf_l.len(),
+        buf_r.len(),
+        "the left and right buffers must have the same length"
+    );
+
+    let frames = buf_l.len().min(buf_r.len());
+    (&mut buf_l[..frames], &mut buf_r[..frames])
+}
+
+/// Process f64 buffers by converting them to f32 in chunks on the stack and
+/// passing them to `process`.
+fn process_f64_via_f32(
+    buf_l: &mut [f64],
+    buf_r: &mut [f64],
+    mut process: impl FnMut(&mut [f32], &mut [f32]),
+) {
+    let (buf_l, buf_r) = same_len(buf_l, buf_r);
+    let mut scratch_l = [0.0f32; CONVERT_FRAMES];
+    let mut scratch_r = [0.0f32; CONVERT_FRAMES];
+
+    for (chunk_l, chunk_r) in buf_l
+        .chunks_mut(CONVERT_FRAMES)
+        .zip(buf_r.chunks_mut(CONVERT_FRAMES))
+    {
+        let frames = chunk_l.len().min(chunk_r.len());
+        let scratch_l = &mut scratch_l[..frames];
+        let scratch_r = &mut scratch_r[..frames];
+
+        for (s, &x) in scratch_l.iter_mut().zip(chunk_l.iter()) {
+            *s = x as f32;
+        }
+        for (s, &x) in scratch_r.iter_mut().zip(chunk_r.iter()) {
+            *s = x as f32;
+        }
+
+        process(scratch_l, scratch_r);
+
+        for (x, &s) in chunk_l.iter_mut().zip(scratch_l.iter()) {
+            *x = s as f64;
+        }
+        for (x, &s) in chunk_r.iter_mut().zip(scratch_r.iter()) {
+            *x = s as f64;
+        }
+    }
+}
+
+/// Process an interleaved stereo buffer by deinterleaving it in chunks on the
+/// stack and passing them to `process`.
+fn process_interleaved_via(buf: &mut [f32], mut process: impl FnMut(&mut [f32], &mut [f32])) {
+    assert!(
+        buf.len().is_multiple_of(2),
+        "an interleaved stereo buffer must have an even length"
+    );
+
+    let mut scratch_l = [0.0f32; CONVERT_FRAMES];
+    let mut scratch_r = [0.0f32; CONVERT_FRAMES];
+
+    for chunk in buf.chunks_mut(CONVERT_FRAMES * 2) {
+        let frames = chunk.len() / 2;
+        let scratch_l = &mut scratch_l[..frames];
+        let scratch_r = &mut scratch_r[..frames];
+
+        for ((l, r), frame) in scratch_l
+            .iter_mut()
+            .zip(scratch_r.iter_mut())
+            .zip(chunk.chunks_exact(2))
+        {
+            *l = frame[0];
+            *r = frame[1];
+        }
+
+        process(scratch_l, scratch_r);
+
+        for ((&l, &r), frame) in scratch_l
+            .iter()
+            .zip(scratch_r.iter())
+            .zip(chunk.chunks_exact_mut(2))
+        {
+            frame[0] = l;
+            frame[1] = r;
+        }
+    }
+}
+
+/// Process the buffers with `process` in chunks the size of the dry scratch
+/// buffers, and mix the result with the dry signal. See `process_mix` on the
+/// stereo processors.
+fn process_mix_via(
+    buf_l: &mut [f32],
+    buf_r: &mut [f32],
+    dry_l: &mut [f32],
+    dry_r: &mut [f32],
+    from_mix: f32,
+    to_mix: f32,
+    mut process: impl FnMut(&mut [f32], &mut [f32]),
+) {
+    assert!(!dry_l.is_empty() && !dry_r.is_empty());
+    let (buf_l, buf_r) = same_len(buf_l, buf_r);
+
+    if from_mix == 1.0 && to_mix == 1.0 {
+        process(buf_l, buf_r);
+        return;
+    }
+
+    let frames = buf_l.len().min(buf_r.len());
+    let chunk_frames = dry_l.len().min(dry_r.len());
+
+    let mut start = 0;
+    for (chunk_l, chunk_r) in buf_l[..frames]
+        .chunks_mut(chunk_frames)
+        .zip(buf_r[..frames].chunks_mut(chunk_frames))
+    {
+        let end = start + chunk_l.len();
+        let dry_l = &mut dry_l[..chunk_l.len()];
+        let dry_r = &mut dry_r[..chunk_l.len()];
+
+        dry_l.copy_from_slice(chunk_l);
+        dry_r.copy_from_slice(chunk_r);
+        process(chunk_l, chunk_r);
+
+        let chunk_from_mix = ramp(from_mix, to_mix, start, frames);
+        let chunk_to_mix = ramp(from_mix, to_mix, end, frames);
+        mix_dry(chunk_l, dry_l, chunk_from_mix, chunk_to_mix);
+        mix_dry(chunk_r, dry_r, chunk_from_mix, chunk_to_mix);
+
+        start = end;
+    }
+}
+
+/// Process the buffers with `process` in chunks the size of the dry scratch
+/// buffers, and mix the result with the dry signal using a separate mix for
+/// every frame. See `process_mix_automated` on the stereo processors.
+fn process_mix_automated_via(
+    buf_l: &mut [f32],
+    buf_r: &mut [f32],
+    dry_l: &mut [f32],
+    dry_r: &mut [f32],
+    mix: &[f32],
+    mut process: impl FnMut(&mut [f32], &mut [f32]),
+) {
+    assert!(!dry_l.is_empty() && !dry_r.is_empty());
+    assert_eq!(mix.len(), buf_l.len());
+    let (buf_l, buf_r) = same_len(buf_l, buf_r);
+
+    let chunk_frames = dry_l.len().min(dry_r.len());
+
+    for ((chunk_l, chunk_r), mix) in buf_l
+        .chunks_mut(chunk_frames)
+        .zip(buf_r.chunks_mut(chunk_frames))
+        .zip(mix.chunks(chunk_frames))
+    {
+        let dry_l = &mut dry_l[..chunk_l.len()];
+        let dry_r = &mut dry_r[..chunk_l.len()];
+
+        dry_l.copy_from_slice(chunk_l);
+        dry_r.copy_from_slice(chunk_r);
+        process(chunk_l, chunk_r);
+
+        mix_dry_automated(chunk_l, dry_l, mix);
+        mix_dry_automated(chunk_r, dry_r, mix);
+    }
+}
+
+/// Multiply both buffers by the (smoothed) gain.
+fn apply_gain(gain: &mut OnePoleSmoother, buf_l: &mut [f32], buf_r: &mut [f32]) {
+    if gain.is_smoothing() {
+        for (out_l, out_r) in buf_l.iter_mut().zip(buf_r.iter_mut()) {
+            let gain = gain.next_value();
+
+            *out_l *= gain;
+            *out_r *= gain;
+        }
+    } else if gain.target() != 1.0 {
+        let gain = gain.target();
+
+        for (out_l, out_r) in buf_l.iter_mut().zip(buf_r.iter_mut()) {
+            *out_l *= gain;
+            *out_r *= gain;
+        }
+    }
+}
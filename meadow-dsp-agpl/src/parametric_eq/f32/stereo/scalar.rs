@@ -1,21 +1,84 @@
-use crate::parametric_eq::f32::{coeff::MeadowEqDspCoeff, state::MeadowEqDspState, EqParams};
+use meadow_dsp_mit::{
+    decibel::f32::db_to_amp,
+    dynamics::f32::SoftLimiter,
+    filter::svf::f64::SvfCoeff as SvfCoeffF64,
+    processor::AudioProcessor,
+    smooth::f32::{OnePoleSmoother, DEFAULT_SMOOTH_SECS},
+};
 
-/// The DSP for a fully-featured parametric EQ. This version has two channels,
-/// does not make use of SIMD optimizations (although the left and right channels
-/// may be auto-vectorized together), and has zero latency. Both channels share
-/// the same parameters.
+#[cfg(feature = "portable-simd")]
+use arrayvec::ArrayVec;
+#[cfg(feature = "portable-simd")]
+use meadow_dsp_mit::{
+    filter::{
+        one_pole_iir::f32::{
+            simd::{OnePoleIirCoeffx2, OnePoleIirStatex2},
+            OnePoleIirCoeff, OnePoleIirState,
+        },
+        svf::f32::{
+            simd::{SvfCoeffx2, SvfCoeffx4, SvfStatex2, SvfStatex4},
+            SvfCoeff, SvfState,
+        },
+    },
+    simd::{f32x2, f32x4},
+};
+
+#[cfg(feature = "debug-introspection")]
+use crate::parametric_eq::f32::coeff::DirtyFlags;
+#[cfg(feature = "portable-simd")]
+use crate::parametric_eq::f32::coeff::MAX_ONE_POLE_FILTERS;
+use crate::parametric_eq::f32::{
+    coeff::{
+        CoeffSnapshot, MeadowEqDspCoeff, StateSyncInfo, BAND_FADE_STEP_FRAMES,
+        CUTOFF_MOD_STEP_FRAMES,
+    },
+    listen::BandListen,
+    mono::is_unity_gain,
+    state::MeadowEqDspState,
+    BandRouting, EqParams,
+};
+
+/// The number of frames the filters in series are processed in at a time.
+const SERIES_CHUNK_FRAMES: usize = 4;
+
+/// The DSP for a fully-featured parametric EQ. This version has two channels
+/// and zero latency. Both channels share the same parameters.
 ///
-/// TODO: Get rid of `NUM_BANDS_PLUS_8` const generic once const generic expressions
+/// When the `portable-simd` feature is enabled, the left and right channels
+/// are packed into a 2-lane SIMD vector and processed together. With
+/// [`BandRouting::Parallel`], the bands are independent of each other, so
+/// groups of four bands are processed together instead. Packing the two
+/// channels does not change the output.
+///
+/// TODO: Get rid of `NUM_BANDS_PLUS_16` const generic once const generic expressions
 /// are stabilized. (please rust compiler team)
-pub struct MeadowEqDspStereoLinked<const NUM_BANDS: usize, const NUM_BANDS_PLUS_8: usize> {
-    coeff: MeadowEqDspCoeff<NUM_BANDS, NUM_BANDS_PLUS_8>,
+#[derive(Clone)]
+pub struct MeadowEqDspStereoLinked<const NUM_BANDS: usize, const NUM_BANDS_PLUS_16: usize> {
+    coeff: MeadowEqDspCoeff<NUM_BANDS, NUM_BANDS_PLUS_16>,
+
+    left_state: MeadowEqDspState<NUM_BANDS, NUM_BANDS_PLUS_16>,
+    right_state: MeadowEqDspState<NUM_BANDS, NUM_BANDS_PLUS_16>,
+
+    band_routing: BandRouting,
 
-    left_state: MeadowEqDspState<NUM_BANDS, NUM_BANDS_PLUS_8>,
-    right_state: MeadowEqDspState<NUM_BANDS, NUM_BANDS_PLUS_8>,
+    gain_db: f32,
+    gain: OnePoleSmoother,
+
+    input_gain_db: f32,
+    input_gain: OnePoleSmoother,
+
+    output_ceiling_db: Option<f32>,
+    output_limiter: Option<SoftLimiter>,
+
+    /// The mix of the last call to `process_mix`, which the next call ramps
+    /// from.
+    mix: f32,
+
+    listen: [BandListen; 2],
 }
 
-impl<const NUM_BANDS: usize, const NUM_BANDS_PLUS_8: usize>
-    MeadowEqDspStereoLinked<NUM_BANDS, NUM_BANDS_PLUS_8>
+impl<const NUM_BANDS: usize, const NUM_BANDS_PLUS_16: usize>
+    MeadowEqDspStereoLinked<NUM_BANDS, NUM_BANDS_PLUS_16>
 {
     pub const LATENCY: u32 = 0;
 
@@ -24,38 +87,497 @@ impl<const NUM_BANDS: usize, const NUM_BANDS_PLUS_8: usize>
             coeff: MeadowEqDspCoeff::new(sample_rate),
             left_state: MeadowEqDspState::new(),
             right_state: MeadowEqDspState::new(),
+            band_routing: BandRouting::default(),
+            gain_db: 0.0,
+            gain: OnePoleSmoother::new(1.0, DEFAULT_SMOOTH_SECS, sample_rate),
+            input_gain_db: 0.0,
+            input_gain: OnePoleSmoother::new(1.0, DEFAULT_SMOOTH_SECS, sample_rate),
+            output_ceiling_db: None,
+            output_limiter: None,
+            mix: 1.0,
+            listen: [BandListen::new(); 2],
         }
     }
 
+    /// The number of bands (not including the lowpass and highpass bands).
+    pub const fn num_bands() -> usize {
+        NUM_BANDS
+    }
+
+    /// The sample rate this EQ was created with.
+    pub fn sample_rate(&self) -> f64 {
+        self.coeff.sample_rate()
+    }
+
+    pub fn band_routing(&self) -> BandRouting {
+        self.band_routing
+    }
+
+    /// Set how the bands (not including the lowpass and highpass bands) are
+    /// combined. See [`BandRouting`] for details.
+    pub fn set_band_routing(&mut self, routing: BandRouting) {
+        self.band_routing = routing;
+    }
+
+    pub fn gain_db(&self) -> f32 {
+        self.gain_db
+    }
+
+    /// Set the gain applied to the output of the EQ in decibels. Changes are
+    /// smoothed to avoid clicks, so this can also be used to fade the output
+    /// out by setting it to `f32::NEG_INFINITY`.
+    pub fn set_gain_db(&mut self, gain_db: f32) {
+        self.gain_db = gain_db;
+        self.gain.set(db_to_amp(gain_db));
+    }
+
+    pub fn input_gain_db(&self) -> f32 {
+        self.input_gain_db
+    }
+
+    /// Set the gain applied to the input of the EQ (before any of the
+    /// filters) in decibels. Changes are smoothed to avoid clicks. This is
+    /// independent of the output gain set with `set_gain_db`.
+    pub fn set_input_gain_db(&mut self, gain_db: f32) {
+        self.input_gain_db = gain_db;
+        self.input_gain.set(db_to_amp(gain_db));
+    }
+
+    pub fn output_ceiling_db(&self) -> Option<f32> {
+        self.output_ceiling_db
+    }
+
+    /// Set the ceiling of a soft limiter on the output (after the output
+    /// gain) in decibels, or `None` to disable it (the default).
+    ///
+    /// This is a safety net for resonant lowpass and highpass bands and big
+    /// boosts, which can push the signal well above 0 dBFS. The output never
+    /// exceeds the ceiling, and levels more than about 1.9 dB below it are
+    /// untouched. The limiter is nonlinear, so it aliases when it is driven
+    /// hard. See [`SoftLimiter`] for details.
+    pub fn set_output_ceiling_db(&mut self, ceiling_db: Option<f32>) {
+        self.output_ceiling_db = ceiling_db;
+        self.output_limiter = ceiling_db.map(SoftLimiter::new);
+    }
+
     pub fn params(&self) -> &EqParams<NUM_BANDS> {
-        &self.coeff.params()
+        self.coeff.params()
     }
 
     pub fn set_params(&mut self, params: &EqParams<NUM_BANDS>) {
         self.coeff.set_params(params);
     }
 
+    /// Modify the parameters in place. Only the bands whose parameters were
+    /// actually changed by `f` are marked for recalculation.
+    pub fn modify_params(&mut self, f: impl FnOnce(&mut EqParams<NUM_BANDS>)) {
+        self.coeff.modify_params(f);
+    }
+
+    /// Set the coefficients of the band at the given index directly. See
+    /// [`MeadowEqDspCoeff::set_band_raw_coeff`].
+    ///
+    /// # Panics
+    /// Panics if `index >= NUM_BANDS`.
+    pub fn set_band_raw_coeff(&mut self, index: usize, coeff: SvfCoeffF64) {
+        self.coeff.set_band_raw_coeff(index, coeff);
+    }
+
     pub fn needs_param_flush(&self) -> bool {
         self.coeff.needs_param_flush()
     }
 
-    pub fn flush_param_changes(&mut self) {
-        if let Some(info) = self.coeff.flush_param_changes() {
-            self.left_state.sync(&info);
-            self.right_state.sync(&info);
+    /// An estimate of the cost of the next flush. See
+    /// [`MeadowEqDspCoeff::pending_recompute_cost`].
+    pub fn pending_recompute_cost(&self) -> usize {
+        self.coeff.pending_recompute_cost()
+    }
+
+    /// Returns the current dirty flags of the coefficients. This is only meant
+    /// to be used for debugging and testing.
+    #[cfg(feature = "debug-introspection")]
+    pub fn dirty_flags(&self) -> DirtyFlags<NUM_BANDS> {
+        self.coeff.dirty_flags()
+    }
+
+    /// Flush any pending parameter changes.
+    ///
+    /// The internal filter states are synced automatically. The returned
+    /// info is `Some` if the filter topology changed (e.g. for logging, or
+    /// for triggering a relayout of a GUI).
+    pub fn flush_param_changes(&mut self) -> Option<StateSyncInfo<NUM_BANDS>> {
+        let info = self.coeff.flush_param_changes();
+        self.sync_states(info)
+    }
+
+    /// Recompute the coefficients of every band regardless of which
+    /// parameters have changed. See [`MeadowEqDspCoeff::recompute_all`].
+    ///
+    /// The returned info is the same as for
+    /// [`MeadowEqDspStereoLinked::flush_param_changes`].
+    pub fn recompute_all(&mut self) -> Option<StateSyncInfo<NUM_BANDS>> {
+        let info = self.coeff.recompute_all();
+        self.sync_states(info)
+    }
+
+    fn sync_states(
+        &mut self,
+        info: Option<StateSyncInfo<NUM_BANDS>>,
+    ) -> Option<StateSyncInfo<NUM_BANDS>> {
+        if let Some(info) = &info {
+            self.left_state.sync(info);
+            self.right_state.sync(info);
         }
+
+        info
     }
 
+    /// Replace the parameters and coefficients with those of a snapshot
+    /// computed on another thread, and sync the filter states. See
+    /// [`MeadowEqDspCoeff::apply_snapshot`].
+    pub fn apply_coeff_snapshot(&mut self, snapshot: CoeffSnapshot<NUM_BANDS, NUM_BANDS_PLUS_16>) {
+        let info = self.coeff.apply_snapshot(snapshot);
+        self.left_state.sync(&info);
+        self.right_state.sync(&info);
+    }
+
+    /// Set the duration of the fade applied when a band is disabled. See
+    /// [`MeadowEqDspCoeff::set_band_fade_secs`].
+    pub fn set_band_fade_secs(&mut self, fade_secs: f64) {
+        self.coeff.set_band_fade_secs(fade_secs);
+    }
+
+    /// Whether every band always keeps its filter slot, even while it is
+    /// disabled. See [`MeadowEqDspCoeff::set_fixed_band_slots`].
+    pub fn set_fixed_band_slots(&mut self, fixed: bool) {
+        self.coeff.set_fixed_band_slots(fixed);
+    }
+
+    /// An estimate of how many samples it takes for the impulse response to
+    /// decay below `threshold_db`. See
+    /// [`MeadowEqDspCoeff::estimated_settling_samples`].
+    pub fn estimated_settling_samples(&self, threshold_db: f64) -> usize {
+        self.coeff.estimated_settling_samples(threshold_db)
+    }
+
+    /// Reset the state of all filters, and jump the smoothed gains straight to
+    /// their targets. Bands which are fading out are removed immediately.
+    ///
+    /// The parameters (and so the target coefficients) are left untouched,
+    /// so steady input produces steady output right away.
+    pub fn reset(&mut self) {
+        self.left_state.reset();
+        self.right_state.reset();
+        self.coeff.finish_band_fades();
+        self.gain.finish();
+        self.input_gain.finish();
+        self.listen[0].reset();
+        self.listen[1].reset();
+    }
+
+    /// Reset the state of only the band at the given index, leaving the
+    /// state of all other bands untouched.
+    ///
+    /// # Panics
+    /// Panics if `index >= NUM_BANDS`.
+    pub fn reset_band(&mut self, index: usize) {
+        self.left_state.reset_band(index);
+        self.right_state.reset_band(index);
+    }
+
+    /// Reset the state of only the lowpass band.
+    pub fn reset_lp(&mut self) {
+        self.left_state.reset_lp();
+        self.right_state.reset_lp();
+    }
+
+    /// Reset the state of only the highpass band.
+    pub fn reset_hp(&mut self) {
+        self.left_state.reset_hp();
+        self.right_state.reset_hp();
+    }
+
+    /// Set the state of all filters to the steady state for constant inputs
+    /// of `dc_l` and `dc_r`, after applying any pending parameter changes.
+    /// See [`MeadowEqDspState::prime_for_dc`].
+    pub fn prime_for_dc(&mut self, dc_l: f32, dc_r: f32) {
+        let _ = self.flush_param_changes();
+        self.left_state.prime_for_dc(
+            dc_l * self.input_gain.target(),
+            &self.coeff,
+            self.band_routing,
+        );
+        self.right_state.prime_for_dc(
+            dc_r * self.input_gain.target(),
+            &self.coeff,
+            self.band_routing,
+        );
+    }
+
+    /// Process the given buffers in place.
+    ///
+    /// `buf_l` and `buf_r` must have the same length. See the
+    /// [module docs](super).
+    ///
+    /// Without an output ceiling (see [`Self::set_output_ceiling_db`]) the EQ
+    /// is linear: starting from a [`Self::reset`] state, processing `a * x`
+    /// gives `a` times the output for `x`, apart from floating point
+    /// rounding. The limiter behind the ceiling is nonlinear.
     pub fn process(&mut self, buf_l: &mut [f32], buf_r: &mut [f32]) {
+        let (buf_l, buf_r) = super::same_len(buf_l, buf_r);
+
+        if !(self.coeff.is_fading_bands()) {
+            self.process_block(buf_l, buf_r);
+            return;
+        }
+
+        // Advance the fades of the bands being disabled in small steps.
+        for (buf_l, buf_r) in buf_l
+            .chunks_mut(BAND_FADE_STEP_FRAMES)
+            .zip(buf_r.chunks_mut(BAND_FADE_STEP_FRAMES))
+        {
+            let frames = buf_l.len();
+            self.coeff.advance_band_fades(frames);
+            self.process_block(buf_l, buf_r);
+        }
+    }
+
+    /// Process the given buffers in place like [`Self::process`], but without
+    /// checking for pending parameter changes first. This is for callers which
+    /// call `flush_param_changes` themselves at block boundaries.
+    ///
+    /// The caller must make sure that there are no pending parameter changes
+    /// (see `needs_param_flush`), otherwise the changes are not applied until
+    /// the next flush. This is checked with a debug assertion. While a band is
+    /// fading out, this falls back to [`Self::process`], since the end of the
+    /// fade needs a flush part way through the buffers.
+    pub fn process_no_flush(&mut self, buf_l: &mut [f32], buf_r: &mut [f32]) {
+        debug_assert!(!self.needs_param_flush());
+
+        if self.coeff.is_fading_bands() {
+            self.process(buf_l, buf_r);
+        } else {
+            self.process_block_no_flush(buf_l, buf_r);
+        }
+    }
+
+    /// Process the given buffers in place, mixing the processed (wet) signal
+    /// with the unprocessed (dry) signal.
+    ///
+    /// `mix` is in the range `[0.0, 1.0]`, where `0.0` is fully dry and `1.0`
+    /// is fully wet. Changes to `mix` are ramped over the buffer to avoid
+    /// clicks.
+    ///
+    /// `dry_l` and `dry_r` are scratch buffers which hold a copy of the dry
+    /// signal, so that this never allocates. They can be any length (the
+    /// buffers are processed in chunks of that length).
+    ///
+    /// # Panics
+    /// Panics if either scratch buffer is empty.
+    pub fn process_mix(
+        &mut self,
+        buf_l: &mut [f32],
+        buf_r: &mut [f32],
+        dry_l: &mut [f32],
+        dry_r: &mut [f32],
+        mix: f32,
+    ) {
+        let from_mix = self.mix;
+        self.mix = mix.clamp(0.0, 1.0);
+
+        super::process_mix_via(
+            buf_l,
+            buf_r,
+            dry_l,
+            dry_r,
+            from_mix,
+            self.mix,
+            |buf_l, buf_r| self.process(buf_l, buf_r),
+        );
+    }
+
+    /// Process the given buffers in place like [`Self::process_mix`], but
+    /// with a separate mix for every frame (e.g. from host automation)
+    /// instead of a ramp.
+    ///
+    /// The values of `mix` are clamped to the range `[0.0, 1.0]`. They are
+    /// used as is, so they should already be smooth. The next call to
+    /// [`Self::process_mix`] ramps from the last value.
+    ///
+    /// # Panics
+    /// Panics if either scratch buffer is empty, or if `mix` is not the same
+    /// length as the buffers.
+    pub fn process_mix_automated(
+        &mut self,
+        buf_l: &mut [f32],
+        buf_r: &mut [f32],
+        dry_l: &mut [f32],
+        dry_r: &mut [f32],
+        mix: &[f32],
+    ) {
+        if let Some(&last) = mix.last() {
+            self.mix = last.clamp(0.0, 1.0);
+        }
+
+        super::process_mix_automated_via(buf_l, buf_r, dry_l, dry_r, mix, |buf_l, buf_r| {
+            self.process(buf_l, buf_r)
+        });
+    }
+
+    /// Process the given buffers in place like [`Self::process`], and also
+    /// write the signal of the band at `band_index` on its own into
+    /// `listen_l` and `listen_r`, so that the band can be monitored (for
+    /// example to hear the sibilance a de-esser band acts on).
+    ///
+    /// The listen signal is the input (before the input gain) through a copy
+    /// of the band's own filter, so it follows the band's type, cutoff, Q,
+    /// and gain (or its raw coefficients). It does not depend on the other
+    /// bands, or on whether the band is enabled.
+    ///
+    /// This runs one extra SVF filter per channel on top of the EQ itself,
+    /// so it costs about as much as one extra band.
+    ///
+    /// # Panics
+    /// Panics if `band_index >= NUM_BANDS`, or if a listen buffer is shorter
+    /// than the buffers being processed.
+    pub fn process_with_band_listen(
+        &mut self,
+        buf_l: &mut [f32],
+        buf_r: &mut [f32],
+        band_index: usize,
+        listen_l: &mut [f32],
+        listen_r: &mut [f32],
+    ) {
+        let frames = buf_l.len().min(buf_r.len());
+        let sample_rate_recip = self.sample_rate().recip();
+
+        let [listen_filter_l, listen_filter_r] = &mut self.listen;
+        listen_filter_l.process(
+            band_index,
+            &self.coeff.params().bands[band_index],
+            self.coeff.band_raw_coeff(band_index),
+            sample_rate_recip,
+            &buf_l[..frames],
+            &mut listen_l[..frames],
+        );
+        listen_filter_r.process(
+            band_index,
+            &self.coeff.params().bands[band_index],
+            self.coeff.band_raw_coeff(band_index),
+            sample_rate_recip,
+            &buf_r[..frames],
+            &mut listen_r[..frames],
+        );
+
+        self.process(buf_l, buf_r);
+    }
+
+    /// Process the given buffers in place like [`Self::process`], while
+    /// offsetting the cutoff of the band at `band_index` by `mod_hz` (in Hz)
+    /// every frame, e.g. for an auto-wah or a modulated filter sweep. Both
+    /// channels share the same modulation.
+    ///
+    /// The coefficients of the band are recomputed every
+    /// [`CUTOFF_MOD_STEP_FRAMES`] frames from the first value of `mod_hz` in
+    /// that chunk, so faster modulation is stepped (at 48 kHz that is an
+    /// update rate of 3 kHz). See [`MeadowEqDspCoeff::set_band_cutoff_mod`]
+    /// for the details. Each update costs a handful of multiplies and a few
+    /// divisions, instead of the `tan` of a regular parameter change, so this
+    /// costs little more than [`Self::process`]. Bands which are faded out
+    /// while this is running fade in steps of the same size.
+    ///
+    /// The parameters are not changed, and the unmodulated coefficients are
+    /// restored at the end of the call.
+    ///
+    /// # Panics
+    /// Panics if `band_index >= NUM_BANDS`, or if `mod_hz` is shorter than
+    /// the buffers being processed.
+    pub fn process_with_cutoff_mod(
+        &mut self,
+        buf_l: &mut [f32],
+        buf_r: &mut [f32],
+        band_index: usize,
+        mod_hz: &[f32],
+    ) {
+        assert!(band_index < NUM_BANDS);
+        let (buf_l, buf_r) = super::same_len(buf_l, buf_r);
+        let mod_hz = &mod_hz[..buf_l.len()];
+
+        for ((buf_l, buf_r), mod_hz) in buf_l
+            .chunks_mut(CUTOFF_MOD_STEP_FRAMES)
+            .zip(buf_r.chunks_mut(CUTOFF_MOD_STEP_FRAMES))
+            .zip(mod_hz.chunks(CUTOFF_MOD_STEP_FRAMES))
+        {
+            if self.coeff.is_fading_bands() {
+                self.coeff.advance_band_fades(buf_l.len());
+            }
+            if self.needs_param_flush() {
+                let _ = self.flush_param_changes();
+            }
+
+            self.coeff.set_band_cutoff_mod(band_index, mod_hz[0]);
+            self.process_block_no_flush(buf_l, buf_r);
+        }
+
+        self.coeff.set_band_cutoff_mod(band_index, 0.0);
+    }
+
+    /// Process the given f64 buffers in place, for hosts which use f64 audio.
+    ///
+    /// The samples are converted to f32 and processed exactly like
+    /// [`Self::process`], so the precision is the same as for f32 buffers (f32
+    /// coefficients and f32 filter states).
+    pub fn process_f64(&mut self, buf_l: &mut [f64], buf_r: &mut [f64]) {
+        super::process_f64_via_f32(buf_l, buf_r, |buf_l, buf_r| self.process(buf_l, buf_r));
+    }
+
+    /// Process an interleaved stereo buffer (`[L, R, L, R, ...]`) in place.
+    ///
+    /// The buffer is deinterleaved in chunks on the stack and processed
+    /// exactly like [`Self::process`], so this never allocates.
+    ///
+    /// # Panics
+    /// Panics if the length of `buf` is odd.
+    pub fn process_interleaved(&mut self, buf: &mut [f32]) {
+        super::process_interleaved_via(buf, |buf_l, buf_r| self.process(buf_l, buf_r));
+    }
+
+    fn process_block(&mut self, buf_l: &mut [f32], buf_r: &mut [f32]) {
         if self.needs_param_flush() {
-            self.flush_param_changes();
+            let _ = self.flush_param_changes();
+        }
+
+        self.process_block_no_flush(buf_l, buf_r);
+    }
+
+    fn process_block_no_flush(&mut self, buf_l: &mut [f32], buf_r: &mut [f32]) {
+        if self.coeff.is_empty()
+            && is_unity_gain(&self.input_gain)
+            && is_unity_gain(&self.gain)
+            && self.output_limiter.is_none()
+        {
+            return;
         }
 
+        super::apply_gain(&mut self.input_gain, buf_l, buf_r);
+
         let (one_pole_coeffs, svf_coeffs) = self.coeff.coeffs();
 
         let (l_one_pole_states, l_svf_states) = self.left_state.states_mut();
         let (r_one_pole_states, r_svf_states) = self.right_state.states_mut();
 
+        #[cfg(feature = "portable-simd")]
+        if !one_pole_coeffs.is_empty() {
+            process_one_pole_x2(
+                buf_l,
+                buf_r,
+                one_pole_coeffs,
+                l_one_pole_states,
+                r_one_pole_states,
+            );
+        }
+
+        #[cfg(not(feature = "portable-simd"))]
         if !one_pole_coeffs.is_empty() {
             // Hint to compiler to optimize loop;
             assert_eq!(one_pole_coeffs.len(), l_one_pole_states.len());
@@ -88,18 +610,286 @@ impl<const NUM_BANDS: usize, const NUM_BANDS_PLUS_8: usize>
             assert_eq!(svf_coeffs.len(), l_svf_states.len());
             assert_eq!(svf_coeffs.len(), r_svf_states.len());
 
-            for (out_l, out_r) in buf_l.iter_mut().zip(buf_r.iter_mut()) {
-                let mut l = *out_l;
-                let mut r = *out_r;
+            let num_series_filters = match self.band_routing {
+                BandRouting::Series => svf_coeffs.len(),
+                BandRouting::Parallel => self.coeff.num_lp_hp_svf_filters(),
+            };
+
+            let (series_coeffs, parallel_coeffs) = svf_coeffs.split_at(num_series_filters);
+            let (l_series_states, l_parallel_states) =
+                l_svf_states.split_at_mut(num_series_filters);
+            let (r_series_states, r_parallel_states) =
+                r_svf_states.split_at_mut(num_series_filters);
 
-                for (i, coeff) in svf_coeffs.iter().enumerate() {
-                    l = l_svf_states[i].tick(l, coeff);
-                    r = r_svf_states[i].tick(r, coeff);
+            #[cfg(feature = "portable-simd")]
+            if !series_coeffs.is_empty() {
+                process_series_x2::<NUM_BANDS_PLUS_16>(
+                    buf_l,
+                    buf_r,
+                    series_coeffs,
+                    l_series_states,
+                    r_series_states,
+                );
+            }
+
+            #[cfg(not(feature = "portable-simd"))]
+            if !series_coeffs.is_empty() {
+                // Run each filter over a small chunk of samples before moving
+                // on to the next filter, so that the state of each filter can
+                // stay in registers for the whole chunk. Each sample still
+                // passes through the filters in the same order, so the output
+                // is identical to processing one sample at a time.
+                for (chunk_l, chunk_r) in buf_l
+                    .chunks_mut(SERIES_CHUNK_FRAMES)
+                    .zip(buf_r.chunks_mut(SERIES_CHUNK_FRAMES))
+                {
+                    for (i, coeff) in series_coeffs.iter().enumerate() {
+                        let mut l_state = l_series_states[i];
+                        let mut r_state = r_series_states[i];
+
+                        for (out_l, out_r) in chunk_l.iter_mut().zip(chunk_r.iter_mut()) {
+                            *out_l = l_state.tick(*out_l, coeff);
+                            *out_r = r_state.tick(*out_r, coeff);
+                        }
+
+                        l_series_states[i] = l_state;
+                        r_series_states[i] = r_state;
+                    }
                 }
+            }
+
+            #[cfg(feature = "portable-simd")]
+            if !parallel_coeffs.is_empty() {
+                process_parallel_x4::<NUM_BANDS>(
+                    buf_l,
+                    buf_r,
+                    parallel_coeffs,
+                    l_parallel_states,
+                    r_parallel_states,
+                );
+            }
+
+            #[cfg(not(feature = "portable-simd"))]
+            if !parallel_coeffs.is_empty() {
+                for (out_l, out_r) in buf_l.iter_mut().zip(buf_r.iter_mut()) {
+                    let in_l = *out_l;
+                    let in_r = *out_r;
 
-                *out_l = l;
-                *out_r = r;
+                    let mut l = in_l;
+                    let mut r = in_r;
+
+                    for (i, coeff) in parallel_coeffs.iter().enumerate() {
+                        l += l_parallel_states[i].tick(in_l, coeff) - in_l;
+                        r += r_parallel_states[i].tick(in_r, coeff) - in_r;
+                    }
+
+                    *out_l = l;
+                    *out_r = r;
+                }
             }
         }
+
+        super::apply_gain(&mut self.gain, buf_l, buf_r);
+
+        if let Some(limiter) = &self.output_limiter {
+            limiter.process(buf_l);
+            limiter.process(buf_r);
+        }
+    }
+}
+
+impl<const NUM_BANDS: usize, const NUM_BANDS_PLUS_16: usize> AudioProcessor
+    for MeadowEqDspStereoLinked<NUM_BANDS, NUM_BANDS_PLUS_16>
+{
+    fn process_stereo(&mut self, buf_l: &mut [f32], buf_r: &mut [f32]) {
+        self.process(buf_l, buf_r);
+    }
+
+    fn reset(&mut self) {
+        self.reset();
+    }
+
+    fn latency(&self) -> u32 {
+        Self::LATENCY
+    }
+}
+
+/// Process the one-pole filters in series, with the left and right channels
+/// packed into a 2-lane SIMD vector.
+#[cfg(feature = "portable-simd")]
+fn process_one_pole_x2(
+    buf_l: &mut [f32],
+    buf_r: &mut [f32],
+    coeffs: &[OnePoleIirCoeff],
+    l_states: &mut [OnePoleIirState],
+    r_states: &mut [OnePoleIirState],
+) {
+    let coeffs_x2: ArrayVec<OnePoleIirCoeffx2, MAX_ONE_POLE_FILTERS> = coeffs
+        .iter()
+        .map(|c| OnePoleIirCoeffx2::splat(*c))
+        .collect();
+    let mut states_x2: ArrayVec<OnePoleIirStatex2, MAX_ONE_POLE_FILTERS> = l_states
+        .iter()
+        .zip(r_states.iter())
+        .map(|(l, r)| OnePoleIirStatex2::load(&[*l, *r]))
+        .collect();
+
+    // Hint to compiler to optimize loop;
+    assert_eq!(coeffs_x2.len(), states_x2.len());
+
+    for (out_l, out_r) in buf_l.iter_mut().zip(buf_r.iter_mut()) {
+        let mut x = f32x2::from_array([*out_l, *out_r]);
+
+        for (state, coeff) in states_x2.iter_mut().zip(coeffs_x2.iter()) {
+            x = state.tick(x, coeff);
+        }
+
+        [*out_l, *out_r] = x.to_array();
+    }
+
+    for ((s, l), r) in states_x2
+        .iter()
+        .zip(l_states.iter_mut())
+        .zip(r_states.iter_mut())
+    {
+        let mut states = [*l, *r];
+        s.store(&mut states);
+        [*l, *r] = states;
+    }
+}
+
+/// Process the SVF filters in series, with the left and right channels packed
+/// into a 2-lane SIMD vector.
+///
+/// `MAX_FILTERS` is only used as an upper bound for the number of filters.
+#[cfg(feature = "portable-simd")]
+fn process_series_x2<const MAX_FILTERS: usize>(
+    buf_l: &mut [f32],
+    buf_r: &mut [f32],
+    coeffs: &[SvfCoeff],
+    l_states: &mut [SvfState],
+    r_states: &mut [SvfState],
+) {
+    let coeffs_x2: ArrayVec<SvfCoeffx2, MAX_FILTERS> =
+        coeffs.iter().map(|c| SvfCoeffx2::splat(*c)).collect();
+    let mut states_x2: ArrayVec<SvfStatex2, MAX_FILTERS> = l_states
+        .iter()
+        .zip(r_states.iter())
+        .map(|(l, r)| SvfStatex2::load(&[*l, *r]))
+        .collect();
+
+    // Hint to compiler to optimize loop;
+    assert_eq!(coeffs_x2.len(), states_x2.len());
+
+    // Run each filter over a small chunk of frames before moving on to the
+    // next filter, so that the state of each filter can stay in registers for
+    // the whole chunk. Each frame still passes through the filters in the
+    // same order, so the output is identical to processing one frame at a
+    // time.
+    for (chunk_l, chunk_r) in buf_l
+        .chunks_mut(SERIES_CHUNK_FRAMES)
+        .zip(buf_r.chunks_mut(SERIES_CHUNK_FRAMES))
+    {
+        let frames = chunk_l.len();
+
+        let mut chunk = [f32x2::splat(0.0); SERIES_CHUNK_FRAMES];
+        for ((x, &l), &r) in chunk.iter_mut().zip(chunk_l.iter()).zip(chunk_r.iter()) {
+            *x = f32x2::from_array([l, r]);
+        }
+
+        for (state, coeff) in states_x2.iter_mut().zip(coeffs_x2.iter()) {
+            let mut s = *state;
+
+            for x in chunk[..frames].iter_mut() {
+                *x = s.tick(*x, coeff);
+            }
+
+            *state = s;
+        }
+
+        for ((x, out_l), out_r) in chunk.iter().zip(chunk_l.iter_mut()).zip(chunk_r.iter_mut()) {
+            [*out_l, *out_r] = x.to_array();
+        }
+    }
+
+    for ((s, l), r) in states_x2
+        .iter()
+        .zip(l_states.iter_mut())
+        .zip(r_states.iter_mut())
+    {
+        let mut states = [*l, *r];
+        s.store(&mut states);
+        [*l, *r] = states;
+    }
+}
+
+/// Process bands in parallel, four at a time using SIMD.
+///
+/// `NUM_BANDS` is only used as an upper bound for the number of groups of four.
+#[cfg(feature = "portable-simd")]
+fn process_parallel_x4<const NUM_BANDS: usize>(
+    buf_l: &mut [f32],
+    buf_r: &mut [f32],
+    coeffs: &[SvfCoeff],
+    l_states: &mut [SvfState],
+    r_states: &mut [SvfState],
+) {
+    let num_packed = coeffs.len() - (coeffs.len() % 4);
+
+    let (packed_coeffs, rem_coeffs) = coeffs.split_at(num_packed);
+    let (l_packed_states, l_rem_states) = l_states.split_at_mut(num_packed);
+    let (r_packed_states, r_rem_states) = r_states.split_at_mut(num_packed);
+
+    let coeffs_x4: ArrayVec<SvfCoeffx4, NUM_BANDS> = packed_coeffs
+        .chunks_exact(4)
+        .map(|c| SvfCoeffx4::load(c.try_into().unwrap()))
+        .collect();
+    let mut l_states_x4: ArrayVec<SvfStatex4, NUM_BANDS> = l_packed_states
+        .chunks_exact(4)
+        .map(|s| SvfStatex4::load(s.try_into().unwrap()))
+        .collect();
+    let mut r_states_x4: ArrayVec<SvfStatex4, NUM_BANDS> = r_packed_states
+        .chunks_exact(4)
+        .map(|s| SvfStatex4::load(s.try_into().unwrap()))
+        .collect();
+
+    // Hint to compiler to optimize loop;
+    assert_eq!(coeffs_x4.len(), l_states_x4.len());
+    assert_eq!(coeffs_x4.len(), r_states_x4.len());
+    assert_eq!(rem_coeffs.len(), l_rem_states.len());
+    assert_eq!(rem_coeffs.len(), r_rem_states.len());
+
+    for (out_l, out_r) in buf_l.iter_mut().zip(buf_r.iter_mut()) {
+        let in_l = *out_l;
+        let in_r = *out_r;
+
+        let in_l_x4 = f32x4::splat(in_l);
+        let in_r_x4 = f32x4::splat(in_r);
+
+        let mut l_diff = f32x4::splat(0.0);
+        let mut r_diff = f32x4::splat(0.0);
+
+        for (i, coeff) in coeffs_x4.iter().enumerate() {
+            l_diff += l_states_x4[i].tick(in_l_x4, coeff) - in_l_x4;
+            r_diff += r_states_x4[i].tick(in_r_x4, coeff) - in_r_x4;
+        }
+
+        let mut l = in_l + l_diff.reduce_sum();
+        let mut r = in_r + r_diff.reduce_sum();
+
+        for (i, coeff) in rem_coeffs.iter().enumerate() {
+            l += l_rem_states[i].tick(in_l, coeff) - in_l;
+            r += r_rem_states[i].tick(in_r, coeff) - in_r;
+        }
+
+        *out_l = l;
+        *out_r = r;
+    }
+
+    for (s, states) in l_states_x4.iter().zip(l_packed_states.chunks_exact_mut(4)) {
+        s.store(states.try_into().unwrap());
+    }
+    for (s, states) in r_states_x4.iter().zip(r_packed_states.chunks_exact_mut(4)) {
+        s.store(states.try_into().unwrap());
     }
 }
@@ -0,0 +1,1352 @@
+//! Tests of the EQ processors as a whole.
+
+use meadow_dsp_mit::{
+    decibel::{f32::db_to_amp, f64::amp_to_db},
+    filter::{
+        resonant::f32::ResonantLowpass,
+        svf::f64::{SvfCoeff as SvfCoeffF64, SvfState as SvfStateF64},
+    },
+    smooth::f32::DEFAULT_SMOOTH_SECS,
+};
+
+use super::{
+    coeff::{band_coeff, lp_hp_svf_coeffs, F32Coeffs, MeadowEqDspCoeff, DEFAULT_BAND_FADE_SECS},
+    dynamic::MeadowEqDspDynamic,
+    mono::MeadowEqDspMono,
+    stereo::{
+        crossfade::CrossfadingEq, dj::DjFilter, dual::MeadowEqDspStereoDual,
+        f64_coeff::MeadowEqDspStereoLinkedF64Coeff, scalar::MeadowEqDspStereoLinked,
+    },
+    BandParams, BandRouting, BandType, EqParams, FilterOrder, LpHpMode, LpOrHpBandParams, QMode,
+    DEFAULT_Q,
+};
+use crate::test_util::{noise, peak, rms, sine, sine_gain_db, Rng};
+
+const SAMPLE_RATE: f64 = 48_000.0;
+
+/// Random parameters with every band enabled.
+fn random_params<const NUM_BANDS: usize>(rng: &mut Rng) -> EqParams<NUM_BANDS> {
+    const BAND_TYPES: [BandType; 7] = [
+        BandType::Bell,
+        BandType::LowShelf,
+        BandType::HighShelf,
+        BandType::Notch,
+        BandType::Allpass,
+        BandType::NotchCompensated,
+        BandType::Multimode,
+    ];
+    const ORDERS: [FilterOrder; 7] = [
+        FilterOrder::X1,
+        FilterOrder::X2,
+        FilterOrder::X4,
+        FilterOrder::X6,
+        FilterOrder::X8,
+        FilterOrder::X12,
+        FilterOrder::X16,
+    ];
+
+    let lp_hp_band = |rng: &mut Rng, cutoff_lo: f64, cutoff_hi: f64| LpOrHpBandParams {
+        enabled: true,
+        cutoff_hz: rng.log_range(cutoff_lo, cutoff_hi) as f32,
+        q: rng.log_range(0.5, 2.0) as f32,
+        order: ORDERS[(rng.next_f64() * ORDERS.len() as f64) as usize],
+        ..Default::default()
+    };
+
+    let mut params = EqParams {
+        lp_band: lp_hp_band(rng, 2_000.0, 20_000.0),
+        hp_band: lp_hp_band(rng, 20.0, 500.0),
+        ..Default::default()
+    };
+
+    for band in params.bands.iter_mut() {
+        *band = BandParams {
+            enabled: true,
+            band_type: BAND_TYPES[(rng.next_f64() * BAND_TYPES.len() as f64) as usize],
+            cutoff_hz: rng.log_range(20.0, 20_000.0) as f32,
+            q: rng.log_range(0.2, 10.0) as f32,
+            gain_db: rng.range(-24.0, 24.0) as f32,
+            q_mode: if rng.next_f64() < 0.5 {
+                QMode::Proportional
+            } else {
+                QMode::Constant
+            },
+            morph: rng.next_f64() as f32,
+        };
+    }
+
+    params
+}
+
+/// Scaling by two is exact in floating point, and the EQ is linear, so
+/// processing twice the input must give exactly twice the output.
+#[test]
+fn process_is_linear() {
+    let mut rng = Rng::new(4);
+
+    for seed in 0..50 {
+        let params = random_params::<4>(&mut rng);
+        let gain_db = rng.range(-12.0, 12.0) as f32;
+        let input = noise(512, seed);
+        let input_x2: Vec<f32> = input.iter().map(|x| 2.0 * x).collect();
+        let expected = |out: &[f32], out_x2: &[f32]| {
+            for (&out, &out_x2) in out.iter().zip(out_x2) {
+                assert_eq!(out_x2, 2.0 * out, "{params:?}");
+            }
+        };
+
+        let mut mono = [(); 2].map(|_| {
+            let mut eq = MeadowEqDspMono::<4, 20>::new(SAMPLE_RATE);
+            eq.set_params(&params);
+            eq.set_gain_db(gain_db);
+            eq
+        });
+        let (mut out, mut out_x2) = (input.clone(), input_x2.clone());
+        mono[0].process(&mut out);
+        mono[1].process(&mut out_x2);
+        expected(&out, &out_x2);
+
+        let mut linked = [(); 2].map(|_| {
+            let mut eq = MeadowEqDspStereoLinked::<4, 20>::new(SAMPLE_RATE);
+            eq.set_params(&params);
+            eq.set_gain_db(gain_db);
+            eq
+        });
+        let (mut l, mut r) = (input.clone(), input.clone());
+        let (mut l_x2, mut r_x2) = (input_x2.clone(), input_x2.clone());
+        linked[0].process(&mut l, &mut r);
+        linked[1].process(&mut l_x2, &mut r_x2);
+        expected(&l, &l_x2);
+        expected(&r, &r_x2);
+
+        let mut f64_coeff = [(); 2].map(|_| {
+            let mut eq = MeadowEqDspStereoLinkedF64Coeff::<4, 20>::new(SAMPLE_RATE);
+            eq.set_params(&params);
+            eq.set_gain_db(gain_db);
+            eq
+        });
+        let (mut l, mut r) = (input.clone(), input.clone());
+        let (mut l_x2, mut r_x2) = (input_x2.clone(), input_x2.clone());
+        f64_coeff[0].process(&mut l, &mut r);
+        f64_coeff[1].process(&mut l_x2, &mut r_x2);
+        expected(&l, &l_x2);
+        expected(&r, &r_x2);
+
+        let mut dual = [(); 2].map(|_| {
+            let mut eq = MeadowEqDspStereoDual::<4, 20>::new(SAMPLE_RATE);
+            eq.set_left_params(&params);
+            eq.set_right_params(&params);
+            eq.set_gain_db(gain_db);
+            eq
+        });
+        let (mut l, mut r) = (input.clone(), input);
+        let (mut l_x2, mut r_x2) = (input_x2.clone(), input_x2);
+        dual[0].process(&mut l, &mut r);
+        dual[1].process(&mut l_x2, &mut r_x2);
+        expected(&l, &l_x2);
+        expected(&r, &r_x2);
+    }
+}
+
+/// With the `portable-simd` feature the linked EQ packs both channels into
+/// one vector, which must not change the output of the filters in series.
+/// Without it, the channels are processed side by side with scalar code.
+#[test]
+fn stereo_linked_matches_mono() {
+    let mut rng = Rng::new(9);
+
+    for seed in 0..50 {
+        let params = random_params::<4>(&mut rng);
+        let gain_db = rng.range(-12.0, 12.0) as f32;
+        let input_l = noise(512, seed);
+        let input_r = noise(512, seed + 1_000);
+
+        let mut mono = [(); 2].map(|_| {
+            let mut eq = MeadowEqDspMono::<4, 20>::new(SAMPLE_RATE);
+            eq.set_params(&params);
+            eq.set_gain_db(gain_db);
+            eq
+        });
+        let (mut expected_l, mut expected_r) = (input_l.clone(), input_r.clone());
+        mono[0].process(&mut expected_l);
+        mono[1].process(&mut expected_r);
+
+        let mut linked = MeadowEqDspStereoLinked::<4, 20>::new(SAMPLE_RATE);
+        linked.set_params(&params);
+        linked.set_gain_db(gain_db);
+        let (mut l, mut r) = (input_l, input_r);
+        linked.process(&mut l, &mut r);
+
+        assert_eq!(l, expected_l, "{params:?}");
+        assert_eq!(r, expected_r, "{params:?}");
+    }
+}
+
+#[test]
+fn actual_cutoff_near_nyquist() {
+    let sample_rate = 44_100.0;
+
+    let cases = [
+        (BandType::Bell, 6.0),
+        (BandType::Bell, -6.0),
+        (BandType::LowShelf, 6.0),
+        (BandType::LowShelf, -6.0),
+        (BandType::HighShelf, 6.0),
+        (BandType::HighShelf, -6.0),
+        (BandType::Notch, 0.0),
+        (BandType::NotchCompensated, 0.0),
+    ];
+    for (band_type, gain_db) in cases {
+        for cutoff_hz in [12_000.0, 16_000.0, 20_000.0, 21_000.0] {
+            let band = BandParams {
+                enabled: true,
+                band_type,
+                cutoff_hz,
+                q: 1.0,
+                gain_db,
+                ..Default::default()
+            };
+
+            // The cutoff is prewarped, so it lands where it was asked to even
+            // where the response is cramped.
+            let actual_hz = band.actual_cutoff_hz(sample_rate);
+            assert!(
+                (actual_hz / cutoff_hz - 1.0).abs() < 1e-4,
+                "{band_type:?} {gain_db} dB at {cutoff_hz} Hz: {actual_hz} Hz"
+            );
+        }
+    }
+
+    let far = BandParams {
+        band_type: BandType::Bell,
+        cutoff_hz: 1234.5,
+        gain_db: 6.0,
+        ..Default::default()
+    };
+    assert_eq!(far.actual_cutoff_hz(sample_rate), 1234.5);
+}
+
+/// Cutoffs at or above the Nyquist frequency are clamped instead of tripping
+/// the stability assertion.
+#[test]
+fn cutoff_above_nyquist_is_clamped() {
+    let sample_rate = 32_000.0;
+
+    let mut params = EqParams::<4>::default();
+    params.lp_band.enabled = true;
+    params.bands[0] = BandParams {
+        enabled: true,
+        band_type: BandType::Bell,
+        cutoff_hz: BandParams::CUTOFF_MAX_HZ,
+        gain_db: 6.0,
+        ..Default::default()
+    };
+    for order in [FilterOrder::X1, FilterOrder::X2, FilterOrder::X16] {
+        params.lp_band.order = order;
+
+        let mut eq = MeadowEqDspMono::<4, 20>::new(sample_rate);
+        eq.set_params(&params);
+
+        let mut buf = noise(512, 0);
+        eq.process(&mut buf);
+        assert!(buf.iter().all(|s| s.is_finite()), "{order:?}");
+    }
+}
+
+/// The attenuation of a 60 Hz sine once the transient of a notch with a Q
+/// of up to 30 has decayed.
+fn hum_gain_db(mut process: impl FnMut(&mut [f32])) -> f64 {
+    let frames = SAMPLE_RATE as usize;
+    let mut buf = sine(60.0, SAMPLE_RATE, 4 * frames);
+    process(&mut buf);
+
+    amp_to_db(rms(&buf[3 * frames..]) * std::f64::consts::SQRT_2)
+}
+
+#[test]
+fn notch_removes_hum() {
+    for q in [0.5, 10.0, 30.0] {
+        let mut params = EqParams::<4>::default();
+        params.bands[0] = BandParams {
+            enabled: true,
+            band_type: BandType::Notch,
+            cutoff_hz: 60.0,
+            q,
+            ..Default::default()
+        };
+
+        let mut mono = MeadowEqDspMono::<4, 20>::new(SAMPLE_RATE);
+        mono.set_params(&params);
+        let mono_db = hum_gain_db(|buf| mono.process(buf));
+
+        let mut f64_coeff = MeadowEqDspStereoLinkedF64Coeff::<4, 20>::new(SAMPLE_RATE);
+        f64_coeff.set_params(&params);
+        let f64_coeff_db = hum_gain_db(|buf| {
+            let mut r = buf.to_vec();
+            f64_coeff.process(buf, &mut r);
+        });
+
+        assert!(mono_db < -60.0, "q {q}: mono {mono_db} dB");
+        assert!(f64_coeff_db < -60.0, "q {q}: f64 coeff {f64_coeff_db} dB");
+    }
+}
+
+/// The flat side of a shelf reads 0 dB well away from the corner.
+#[test]
+fn shelf_flat_side_is_unity() {
+    for (band_type, cutoff_hz, freq_hz) in [
+        (BandType::LowShelf, 200.0, 10_000.0),
+        (BandType::HighShelf, 5_000.0, 100.0),
+    ] {
+        let mut params = EqParams::<4>::default();
+        params.bands[0] = BandParams {
+            enabled: true,
+            band_type,
+            cutoff_hz,
+            gain_db: 6.0,
+            ..Default::default()
+        };
+
+        let mut eq = MeadowEqDspMono::<4, 20>::new(SAMPLE_RATE);
+        eq.set_params(&params);
+        let gain_db = sine_gain_db(freq_hz, SAMPLE_RATE, |buf| eq.process(buf));
+
+        assert!(
+            gain_db.abs() < 0.01,
+            "{band_type:?} at {cutoff_hz} Hz: {gain_db} dB at {freq_hz} Hz"
+        );
+    }
+}
+
+#[test]
+fn output_gain_halves_a_tone() {
+    let mut eq = MeadowEqDspMono::<4, 20>::new(SAMPLE_RATE);
+    eq.set_gain_db(-6.0);
+    let gain_db = sine_gain_db(1_000.0, SAMPLE_RATE, |buf| eq.process(buf));
+
+    // -6 dB is an amplitude of 0.501.
+    let amplitude = 10f64.powf(gain_db / 20.0);
+    assert!((amplitude - 0.5).abs() < 0.005, "amplitude {amplitude}");
+}
+
+/// An 8th order highpass and eight narrow bells, all below 100 Hz, which is
+/// where f32 coefficients are least accurate.
+fn low_cascade_params() -> EqParams<8> {
+    EqParams::builder()
+        .highpass(30.0, FilterOrder::X8)
+        .bell(35.0, 8.0, 6.0)
+        .bell(42.0, 8.0, -6.0)
+        .bell(50.0, 8.0, 6.0)
+        .bell(58.0, 8.0, -6.0)
+        .bell(67.0, 8.0, 6.0)
+        .bell(76.0, 8.0, -6.0)
+        .bell(87.0, 8.0, 6.0)
+        .bell(98.0, 8.0, -6.0)
+        .build()
+}
+
+/// The error of `out` relative to processing `input` with `params` fully in
+/// f64, in dB.
+fn error_vs_f64_db(params: &EqParams<8>, input: &[f32], out: &[f32]) -> f64 {
+    let sample_rate_recip = SAMPLE_RATE.recip();
+    let mut sections = lp_hp_svf_coeffs(&params.hp_band, sample_rate_recip, false).to_vec();
+    sections.extend(
+        params
+            .bands
+            .iter()
+            .map(|band| band_coeff(band, sample_rate_recip)),
+    );
+    let mut states = vec![SvfStateF64::default(); sections.len()];
+
+    let (mut error, mut reference) = (Vec::new(), Vec::new());
+    for (&x, &out) in input.iter().zip(out) {
+        let mut x = x as f64;
+        for (state, coeff) in states.iter_mut().zip(&sections) {
+            x = state.tick(x, coeff);
+        }
+        error.push((out as f64 - x) as f32);
+        reference.push(x as f32);
+    }
+
+    amp_to_db(rms(&error) / rms(&reference))
+}
+
+#[test]
+fn f64_coeff_is_more_accurate() {
+    let params = low_cascade_params();
+    let input = noise(SAMPLE_RATE as usize, 0);
+
+    let mut linked = MeadowEqDspStereoLinked::<8, 24>::new(SAMPLE_RATE);
+    linked.set_params(&params);
+    let (mut l, mut r) = (input.clone(), input.clone());
+    linked.process(&mut l, &mut r);
+    let linked_db = error_vs_f64_db(&params, &input, &l);
+
+    let mut f64_coeff = MeadowEqDspStereoLinkedF64Coeff::<8, 24>::new(SAMPLE_RATE);
+    f64_coeff.set_params(&params);
+    let (mut l, mut r) = (input.clone(), input.clone());
+    f64_coeff.process(&mut l, &mut r);
+    let f64_coeff_db = error_vs_f64_db(&params, &input, &l);
+
+    // About -119 dB and -140 dB.
+    assert!(linked_db < -110.0, "f32 coefficients: {linked_db} dB");
+    assert!(f64_coeff_db < -130.0, "f64 coefficients: {f64_coeff_db} dB");
+    assert!(f64_coeff_db < linked_db - 15.0);
+}
+
+/// Regression test for the default cutoffs of the lowpass and highpass
+/// bands, which used to be swapped (a 20 Hz lowpass and a 21,480 Hz
+/// highpass), so that enabling both silenced the EQ.
+#[test]
+fn default_lp_hp_bands_pass_the_audible_range() {
+    let mut params = EqParams::<4>::default();
+    assert_eq!(params.lp_band.cutoff_hz, 21_480.0);
+    assert_eq!(params.hp_band.cutoff_hz, 20.0);
+
+    params.lp_band.enabled = true;
+    params.hp_band.enabled = true;
+    assert_eq!(params.lp_hp_mode(), Some(LpHpMode::Bandpass));
+
+    let mut eq = MeadowEqDspMono::<4, 20>::new(SAMPLE_RATE);
+    eq.set_params(&params);
+    let gain_db = sine_gain_db(1_000.0, SAMPLE_RATE, |buf| eq.process(buf));
+    assert!(gain_db.abs() < 0.01, "{gain_db} dB at 1 kHz");
+}
+
+/// The listen signal is the band's own filter, so it matches an EQ with only
+/// that band enabled, even while the band itself is disabled.
+#[test]
+fn band_listen_is_the_band_on_its_own() {
+    let band = BandParams {
+        enabled: true,
+        band_type: BandType::Bell,
+        cutoff_hz: 6_000.0,
+        q: 2.0,
+        gain_db: 12.0,
+        ..Default::default()
+    };
+    let input = noise(4096, 0);
+
+    let mut solo_params = EqParams::<4>::default();
+    solo_params.bands[2] = band;
+    let mut solo = MeadowEqDspMono::<4, 20>::new(SAMPLE_RATE);
+    solo.set_params(&solo_params);
+    let mut expected = input.clone();
+    solo.process(&mut expected);
+
+    let mut params = random_params::<4>(&mut Rng::new(5));
+    params.bands[2] = BandParams {
+        enabled: false,
+        ..band
+    };
+
+    let mut mono = MeadowEqDspMono::<4, 20>::new(SAMPLE_RATE);
+    mono.set_params(&params);
+    let mut listen = vec![0.0; input.len()];
+    mono.process_with_band_listen(&mut input.clone(), 2, &mut listen);
+    assert_eq!(listen, expected);
+
+    let mut linked = MeadowEqDspStereoLinked::<4, 20>::new(SAMPLE_RATE);
+    linked.set_params(&params);
+    let (mut listen_l, mut listen_r) = (vec![0.0; input.len()], vec![0.0; input.len()]);
+    linked.process_with_band_listen(
+        &mut input.clone(),
+        &mut input.clone(),
+        2,
+        &mut listen_l,
+        &mut listen_r,
+    );
+    assert_eq!(listen_l, expected);
+    assert_eq!(listen_r, expected);
+
+    // The band's gain is respected.
+    let mut listen = vec![0.0; 2 * SAMPLE_RATE as usize];
+    let gain_db = sine_gain_db(6_000.0, SAMPLE_RATE, |buf| {
+        mono.process_with_band_listen(buf, 2, &mut listen);
+        buf.copy_from_slice(&listen);
+    });
+    assert!((gain_db - 12.0).abs() < 0.01, "{gain_db} dB");
+}
+
+/// The largest second difference of the output of `process` when a +12 dB
+/// bell on a 1 kHz tone is disabled half way through (`process` is passed
+/// whether the band should be disabled), and the same before the change.
+///
+/// A click shows up as a jump in the second difference, while the second
+/// difference of the tone itself is small.
+fn disable_spike(mut process: impl FnMut(&mut [f32], bool)) -> (f32, f32) {
+    const BLOCK_FRAMES: usize = 128;
+
+    let frames = SAMPLE_RATE as usize;
+    let mut buf = sine(1_000.0, SAMPLE_RATE, frames);
+    for (i, block) in buf.chunks_mut(BLOCK_FRAMES).enumerate() {
+        process(block, i * BLOCK_FRAMES >= frames / 2);
+    }
+
+    let max_second_diff = |buf: &[f32]| {
+        buf.windows(3)
+            .map(|w| (w[0] - 2.0 * w[1] + w[2]).abs())
+            .fold(0.0, f32::max)
+    };
+
+    (
+        max_second_diff(&buf[frames / 4..frames / 2]),
+        max_second_diff(&buf[frames / 2..]),
+    )
+}
+
+#[test]
+fn disabling_a_band_does_not_click() {
+    let mut params = EqParams::<4>::default();
+    params.bands[1] = BandParams {
+        enabled: true,
+        band_type: BandType::Bell,
+        cutoff_hz: 1_000.0,
+        q: 1.0,
+        gain_db: 12.0,
+        ..Default::default()
+    };
+    let disabled = |disable: bool| {
+        let mut params = params;
+        params.bands[1].enabled = !disable;
+        params
+    };
+
+    for fixed_slots in [false, true] {
+        // Without the fade, disabling the band clicks (a second difference
+        // of about 2.7, against 0.07 before the change). The fade is
+        // advanced in small steps, which leaves about 0.11.
+        for (fade_secs, clicks) in [(DEFAULT_BAND_FADE_SECS, false), (0.0, true)] {
+            let check = |name: &str, (before, after): (f32, f32)| {
+                assert_eq!(
+                    after > 10.0 * before,
+                    clicks,
+                    "{name}, fixed slots {fixed_slots}, fade {fade_secs} s: {before} before, {after} after"
+                );
+                if !clicks {
+                    assert!(
+                        after < 2.0 * before,
+                        "{name}, fixed slots {fixed_slots}: {before} before, {after} after"
+                    );
+                }
+            };
+
+            let mut mono = MeadowEqDspMono::<4, 20>::new(SAMPLE_RATE);
+            mono.set_fixed_band_slots(fixed_slots);
+            mono.set_band_fade_secs(fade_secs);
+            check(
+                "mono",
+                disable_spike(|buf, disable| {
+                    mono.set_params(&disabled(disable));
+                    mono.process(buf);
+                }),
+            );
+
+            let mut linked = MeadowEqDspStereoLinked::<4, 20>::new(SAMPLE_RATE);
+            linked.set_fixed_band_slots(fixed_slots);
+            linked.set_band_fade_secs(fade_secs);
+            check(
+                "linked",
+                disable_spike(|buf, disable| {
+                    linked.set_params(&disabled(disable));
+                    let mut r = buf.to_vec();
+                    linked.process(buf, &mut r);
+                }),
+            );
+
+            let mut dual = MeadowEqDspStereoDual::<4, 20>::new(SAMPLE_RATE);
+            dual.set_fixed_band_slots(fixed_slots);
+            dual.set_band_fade_secs(fade_secs);
+            check(
+                "dual",
+                disable_spike(|buf, disable| {
+                    dual.set_left_params(&disabled(disable));
+                    dual.set_right_params(&disabled(disable));
+                    let mut r = buf.to_vec();
+                    dual.process(buf, &mut r);
+                }),
+            );
+        }
+    }
+}
+
+/// An EQ with no bands is just the lowpass and highpass bands (e.g. one side
+/// of a crossover). Two 96 dB/octave bands fill the whole
+/// `NUM_BANDS_PLUS_16` budget.
+#[test]
+fn lp_hp_only_eq() {
+    let band = |cutoff_hz| LpOrHpBandParams {
+        enabled: true,
+        cutoff_hz,
+        order: FilterOrder::X16,
+        ..Default::default()
+    };
+    let params = EqParams::<0> {
+        lp_band: band(2_000.0),
+        hp_band: band(200.0),
+        ..Default::default()
+    };
+
+    let mut coeff = MeadowEqDspCoeff::<0, 16, F32Coeffs>::new(SAMPLE_RATE);
+    coeff.set_params(&params);
+    let _ = coeff.flush_param_changes();
+    let (one_pole_coeffs, svf_coeffs) = coeff.coeffs();
+    assert!(one_pole_coeffs.is_empty());
+    assert_eq!(svf_coeffs.len(), 16);
+
+    // A quarter of a second is plenty for these cutoffs to settle (a full
+    // `sine_gain_db` makes the test slow in debug builds).
+    let gain_db = |freq_hz: f64, process: &mut dyn FnMut(&mut [f32])| {
+        let frames = SAMPLE_RATE as usize / 2;
+        let mut buf = sine(freq_hz, SAMPLE_RATE, frames);
+        process(&mut buf);
+        amp_to_db(rms(&buf[frames / 2..]) * std::f64::consts::SQRT_2)
+    };
+
+    let mut mono = MeadowEqDspMono::<0, 16>::new(SAMPLE_RATE);
+    mono.set_params(&params);
+    let mut linked = MeadowEqDspStereoLinked::<0, 16>::new(SAMPLE_RATE);
+    linked.set_params(&params);
+
+    for (freq_hz, expect_pass) in [(100.0, false), (632.0, true), (4_000.0, false)] {
+        mono.reset();
+        let mono_db = gain_db(freq_hz, &mut |buf| mono.process(buf));
+        linked.reset();
+        let linked_db = gain_db(freq_hz, &mut |buf| {
+            let mut r = buf.to_vec();
+            linked.process(buf, &mut r);
+        });
+
+        for (name, gain_db) in [("mono", mono_db), ("linked", linked_db)] {
+            if expect_pass {
+                assert!(gain_db.abs() < 0.01, "{name} {freq_hz} Hz: {gain_db} dB");
+            } else {
+                // About -96 dB an octave past the cutoff.
+                assert!(gain_db < -80.0, "{name} {freq_hz} Hz: {gain_db} dB");
+            }
+        }
+    }
+}
+
+/// The lowpass and highpass bands roll off at 6 dB/octave per order: about
+/// 72 dB/octave for [`FilterOrder::X12`] and 96 dB/octave for
+/// [`FilterOrder::X16`].
+#[test]
+fn lp_hp_slopes() {
+    let sample_rate_recip = SAMPLE_RATE.recip();
+    let gain_db = |sections: &[SvfCoeffF64], freq_hz: f64| {
+        amp_to_db(
+            sections
+                .iter()
+                .map(|c| c.magnitude(freq_hz, sample_rate_recip))
+                .product(),
+        )
+    };
+
+    for (order, slope_db) in [(FilterOrder::X12, 72.0), (FilterOrder::X16, 96.0)] {
+        let band = |cutoff_hz| LpOrHpBandParams {
+            enabled: true,
+            cutoff_hz,
+            order,
+            ..Default::default()
+        };
+
+        // Measure well past the cutoff, where the slope is constant, but
+        // well below the Nyquist frequency, where the bilinear transform
+        // makes the lowpass steeper.
+        let lp = lp_hp_svf_coeffs(&band(100.0), sample_rate_recip, true);
+        let lp_slope_db = gain_db(&lp, 800.0) - gain_db(&lp, 1_600.0);
+
+        let hp = lp_hp_svf_coeffs(&band(8_000.0), sample_rate_recip, false);
+        let hp_slope_db = gain_db(&hp, 1_000.0) - gain_db(&hp, 500.0);
+
+        for (name, sections, cutoff_hz, measured_db) in [
+            ("lowpass", &lp, 100.0, lp_slope_db),
+            ("highpass", &hp, 8_000.0, hp_slope_db),
+        ] {
+            assert_eq!(sections.len(), order.num_svf_filters());
+            assert!(
+                (measured_db - slope_db).abs() < 1.0,
+                "{order:?} {name}: {measured_db} dB/octave"
+            );
+
+            let corner_db = gain_db(sections, cutoff_hz);
+            assert!(
+                (corner_db + 3.0).abs() < 0.02,
+                "{order:?} {name}: {corner_db} dB at the cutoff"
+            );
+        }
+    }
+}
+
+/// With `matched_cutoff`, the -3 dB point of the lowpass and highpass bands
+/// lands on the cutoff for any Q and order, while without it only a
+/// Butterworth Q does.
+#[test]
+fn matched_cutoff_puts_corner_on_cutoff() {
+    let sample_rate_recip = SAMPLE_RATE.recip();
+
+    // Bisect in log frequency for the point where the cascade crosses -3 dB.
+    let corner_hz = |sections: &[SvfCoeffF64], cutoff_hz: f64, is_lowpass: bool| {
+        let passes = |freq_hz: f64| {
+            let magnitude: f64 = sections
+                .iter()
+                .map(|c| c.magnitude(freq_hz, sample_rate_recip))
+                .product();
+            amp_to_db(magnitude) >= -3.0103
+        };
+
+        let mut lo = (cutoff_hz / 8.0).ln();
+        let mut hi = (cutoff_hz * 8.0).min(0.49 * SAMPLE_RATE).ln();
+        for _ in 0..64 {
+            let mid = 0.5 * (lo + hi);
+            if passes(mid.exp()) == is_lowpass {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        (0.5 * (lo + hi)).exp()
+    };
+
+    for order in [
+        FilterOrder::X2,
+        FilterOrder::X4,
+        FilterOrder::X6,
+        FilterOrder::X8,
+        FilterOrder::X12,
+        FilterOrder::X16,
+    ] {
+        for q in [0.4, DEFAULT_Q, 1.5, 4.0] {
+            for (cutoff_hz, is_lowpass) in [(200.0, true), (5_000.0, true), (200.0, false)] {
+                for matched_cutoff in [false, true] {
+                    let band = LpOrHpBandParams {
+                        enabled: true,
+                        cutoff_hz,
+                        q,
+                        order,
+                        matched_cutoff,
+                        ..Default::default()
+                    };
+                    let sections = lp_hp_svf_coeffs(&band, sample_rate_recip, is_lowpass);
+                    let error =
+                        corner_hz(&sections, cutoff_hz as f64, is_lowpass) / cutoff_hz as f64 - 1.0;
+
+                    if matched_cutoff {
+                        assert!(
+                            error.abs() < 0.005,
+                            "{order:?}, q = {q}, {cutoff_hz} Hz, lowpass = {is_lowpass}: {error}"
+                        );
+                    } else if q != DEFAULT_Q {
+                        // Make sure there is something to correct.
+                        assert!(
+                            error.abs() > 0.005,
+                            "{order:?}, q = {q}, {cutoff_hz} Hz, lowpass = {is_lowpass}: {error}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A multimode band is a plain lowpass at a morph of `0.0` and a plain
+/// highpass at a morph of `1.0`.
+#[test]
+fn multimode_ends_are_lowpass_and_highpass() {
+    let sample_rate_recip = SAMPLE_RATE.recip();
+    let fields = |c: &SvfCoeffF64| [c.a1, c.a2, c.a3, c.m0, c.m1, c.m2];
+
+    for q in [0.5, DEFAULT_Q, 4.0] {
+        let band = |morph| BandParams {
+            enabled: true,
+            band_type: BandType::Multimode,
+            cutoff_hz: 1_000.0,
+            q,
+            morph,
+            ..Default::default()
+        };
+
+        let lowpass = SvfCoeffF64::lowpass_ord2(1_000.0, q as f64, sample_rate_recip);
+        let highpass = SvfCoeffF64::highpass_ord2(1_000.0, q as f64, sample_rate_recip);
+        assert_eq!(
+            fields(&band_coeff(&band(0.0), sample_rate_recip)),
+            fields(&lowpass)
+        );
+        assert_eq!(
+            fields(&band_coeff(&band(1.0), sample_rate_recip)),
+            fields(&highpass)
+        );
+
+        // Check the processed output as well, three octaves from the cutoff.
+        for (morph, pass_hz, stop_hz) in [(0.0, 125.0, 8_000.0), (1.0, 8_000.0, 125.0)] {
+            let mut params = EqParams::<1>::default();
+            params.bands[0] = band(morph);
+
+            let gain_db = |freq_hz| {
+                let mut eq = MeadowEqDspMono::<1, 17>::new(SAMPLE_RATE);
+                eq.set_params(&params);
+                sine_gain_db(freq_hz, SAMPLE_RATE, |buf| eq.process(buf))
+            };
+
+            let pass_db = gain_db(pass_hz);
+            let stop_db = gain_db(stop_hz);
+            assert!(
+                pass_db.abs() < 0.5,
+                "q = {q}, morph = {morph}: {pass_db} dB"
+            );
+            assert!(stop_db < -30.0, "q = {q}, morph = {morph}: {stop_db} dB");
+        }
+    }
+}
+
+/// With a link amount of `1.0`, the morph of a multimode band is blended to
+/// the average of both channels, like the gain.
+#[test]
+fn linked_dual_blends_multimode_morph() {
+    let band = |morph| BandParams {
+        enabled: true,
+        band_type: BandType::Multimode,
+        cutoff_hz: 1_000.0,
+        morph,
+        ..Default::default()
+    };
+    let mut left = EqParams::<1>::default();
+    left.bands[0] = band(0.0);
+    let mut right = EqParams::<1>::default();
+    right.bands[0] = band(1.0);
+    let mut average = EqParams::<1>::default();
+    average.bands[0] = band(0.5);
+
+    let input = noise(512, 19);
+
+    let mut expected = input.clone();
+    let mut mono = MeadowEqDspMono::<1, 17>::new(SAMPLE_RATE);
+    mono.set_params(&average);
+    mono.process(&mut expected);
+
+    let mut dual = MeadowEqDspStereoDual::<1, 17>::new(SAMPLE_RATE);
+    dual.set_left_params(&left);
+    dual.set_right_params(&right);
+    dual.set_link_amount(1.0);
+    let (mut l, mut r) = (input.clone(), input);
+    dual.process(&mut l, &mut r);
+
+    assert_eq!(l, expected);
+    assert_eq!(r, expected);
+}
+
+/// An EQ with every band disabled returns early from `process`, so it must
+/// not change the input at all, both when it starts out disabled and once
+/// the bands of a previously active EQ have faded out.
+#[test]
+fn all_disabled_is_transparent() {
+    let mut rng = Rng::new(12);
+    let active = random_params::<4>(&mut rng);
+    let mut disabled = active;
+    disabled.lp_band.enabled = false;
+    disabled.hp_band.enabled = false;
+    for band in disabled.bands.iter_mut() {
+        band.enabled = false;
+    }
+
+    let input_l = noise(1024, 0);
+    let input_r = noise(1024, 1);
+    // Long enough for the fades of the disabled bands to finish.
+    let warm_up = noise(4096, 2);
+
+    for was_active in [false, true] {
+        let first = if was_active { &active } else { &disabled };
+
+        let mut mono = MeadowEqDspMono::<4, 20>::new(SAMPLE_RATE);
+        mono.set_params(first);
+        mono.process(&mut warm_up.clone());
+        mono.set_params(&disabled);
+        mono.process(&mut warm_up.clone());
+        let mut l = input_l.clone();
+        mono.process(&mut l);
+        assert_eq!(l, input_l, "mono, was active: {was_active}");
+
+        let mut linked = MeadowEqDspStereoLinked::<4, 20>::new(SAMPLE_RATE);
+        linked.set_params(first);
+        linked.process(&mut warm_up.clone(), &mut warm_up.clone());
+        linked.set_params(&disabled);
+        linked.process(&mut warm_up.clone(), &mut warm_up.clone());
+        let (mut l, mut r) = (input_l.clone(), input_r.clone());
+        linked.process(&mut l, &mut r);
+        assert_eq!(l, input_l, "linked, was active: {was_active}");
+        assert_eq!(r, input_r, "linked, was active: {was_active}");
+
+        let mut f64_coeff = MeadowEqDspStereoLinkedF64Coeff::<4, 20>::new(SAMPLE_RATE);
+        f64_coeff.set_params(first);
+        f64_coeff.process(&mut warm_up.clone(), &mut warm_up.clone());
+        f64_coeff.set_params(&disabled);
+        f64_coeff.process(&mut warm_up.clone(), &mut warm_up.clone());
+        let (mut l, mut r) = (input_l.clone(), input_r.clone());
+        f64_coeff.process(&mut l, &mut r);
+        assert_eq!(l, input_l, "f64 coefficients, was active: {was_active}");
+        assert_eq!(r, input_r, "f64 coefficients, was active: {was_active}");
+
+        let mut dual = MeadowEqDspStereoDual::<4, 20>::new(SAMPLE_RATE);
+        dual.set_left_params(first);
+        dual.set_right_params(first);
+        dual.process(&mut warm_up.clone(), &mut warm_up.clone());
+        dual.set_left_params(&disabled);
+        dual.set_right_params(&disabled);
+        dual.process(&mut warm_up.clone(), &mut warm_up.clone());
+        let (mut l, mut r) = (input_l.clone(), input_r.clone());
+        dual.process(&mut l, &mut r);
+        assert_eq!(l, input_l, "dual, was active: {was_active}");
+        assert_eq!(r, input_r, "dual, was active: {was_active}");
+    }
+}
+
+/// Resetting band 2 must leave every other filter (including band 1) as it
+/// was. With parallel routing the bands do not feed each other, so this is
+/// the same as band 2 only being enabled after the warm-up, which starts it
+/// from a zero state.
+#[test]
+fn reset_band_does_not_disturb_other_bands() {
+    let mut params = random_params::<4>(&mut Rng::new(13));
+    params.bands[1].enabled = false;
+    let mut warm_up_params = params;
+    warm_up_params.bands[2].enabled = false;
+
+    let warm_up = noise(2048, 0);
+    let input = noise(1024, 1);
+
+    let mut expected = MeadowEqDspMono::<4, 20>::new(SAMPLE_RATE);
+    expected.set_band_routing(BandRouting::Parallel);
+    expected.set_params(&warm_up_params);
+    expected.process(&mut warm_up.clone());
+    expected.set_params(&params);
+    let mut expected_out = input.clone();
+    expected.process(&mut expected_out);
+
+    let mut mono = MeadowEqDspMono::<4, 20>::new(SAMPLE_RATE);
+    mono.set_band_routing(BandRouting::Parallel);
+    mono.set_params(&params);
+    mono.process(&mut warm_up.clone());
+    mono.reset_band(2);
+    let mut out = input.clone();
+    mono.process(&mut out);
+    assert_eq!(out, expected_out);
+
+    let mut linked = MeadowEqDspStereoLinked::<4, 20>::new(SAMPLE_RATE);
+    linked.set_band_routing(BandRouting::Parallel);
+    linked.set_params(&params);
+    linked.process(&mut warm_up.clone(), &mut warm_up.clone());
+    linked.reset_band(2);
+    let (mut l, mut r) = (input.clone(), input);
+    linked.process(&mut l, &mut r);
+    assert_eq!(l, expected_out);
+    assert_eq!(r, expected_out);
+}
+
+/// After `prime_for_dc`, a constant input gives a constant output right
+/// away, with either routing. Without priming the filters ring for a while.
+#[test]
+fn prime_for_dc_gives_steady_output() {
+    let mut rng = Rng::new(14);
+
+    // The largest deviation of the output from its final value, relative
+    // to the input level.
+    let deviation = |out: &[f32], dc: f32| {
+        let last = *out.last().unwrap();
+        out.iter().fold(0.0f32, |d, &x| d.max((x - last).abs())) / dc.abs()
+    };
+
+    for routing in [BandRouting::Series, BandRouting::Parallel] {
+        for _ in 0..10 {
+            let mut params = random_params::<4>(&mut rng);
+            // The highpass band would block DC.
+            params.hp_band.enabled = false;
+            let dc = rng.range(0.1, 1.0) as f32;
+
+            let mut mono = MeadowEqDspMono::<4, 20>::new(SAMPLE_RATE);
+            mono.set_band_routing(routing);
+            mono.set_params(&params);
+            let mut unprimed = vec![dc; 2048];
+            mono.process(&mut unprimed);
+
+            mono.prime_for_dc(dc);
+            let mut primed = vec![dc; 2048];
+            mono.process(&mut primed);
+
+            let mut linked = MeadowEqDspStereoLinked::<4, 20>::new(SAMPLE_RATE);
+            linked.set_band_routing(routing);
+            linked.set_params(&params);
+            linked.prime_for_dc(dc, -dc);
+            let (mut l, mut r) = (vec![dc; 2048], vec![-dc; 2048]);
+            linked.process(&mut l, &mut r);
+
+            assert!(deviation(&unprimed, dc) > 0.01, "{routing:?}: {params:?}");
+            for (name, out) in [("mono", &primed), ("left", &l), ("right", &r)] {
+                let deviation = deviation(out, dc);
+                assert!(
+                    deviation < 1e-5,
+                    "{name} {routing:?}: {deviation}, {params:?}"
+                );
+            }
+        }
+    }
+}
+
+/// A self-oscillating resonant lowpass driven hard, boosted by the EQ, still
+/// never exceeds the output ceiling of the EQ.
+#[test]
+fn output_ceiling_catches_a_resonant_filter() {
+    const CEILING_DB: f32 = -3.0;
+
+    let mut input: Vec<f32> = noise(SAMPLE_RATE as usize, 0)
+        .iter()
+        .map(|x| 2.0 * x)
+        .collect();
+    let mut resonant = ResonantLowpass::new(1_000.0, 1.0, SAMPLE_RATE);
+    resonant.process(&mut input);
+
+    let mut params = EqParams::<4>::default();
+    params.bands[0] = BandParams {
+        enabled: true,
+        band_type: BandType::Bell,
+        cutoff_hz: 1_000.0,
+        q: 2.0,
+        gain_db: 18.0,
+        ..Default::default()
+    };
+
+    for ceiling_db in [None, Some(CEILING_DB)] {
+        let mut mono = MeadowEqDspMono::<4, 20>::new(SAMPLE_RATE);
+        mono.set_params(&params);
+        mono.set_gain_db(6.0);
+        mono.set_output_ceiling_db(ceiling_db);
+        let mut mono_out = input.clone();
+        mono.process(&mut mono_out);
+
+        let mut linked = MeadowEqDspStereoLinked::<4, 20>::new(SAMPLE_RATE);
+        linked.set_params(&params);
+        linked.set_gain_db(6.0);
+        linked.set_output_ceiling_db(ceiling_db);
+        let (mut l, mut r) = (input.clone(), input.clone());
+        linked.process(&mut l, &mut r);
+
+        let mut dual = MeadowEqDspStereoDual::<4, 20>::new(SAMPLE_RATE);
+        dual.set_left_params(&params);
+        dual.set_right_params(&params);
+        dual.set_gain_db(6.0);
+        dual.set_output_ceiling_db(ceiling_db);
+        let (mut dual_l, mut dual_r) = (input.clone(), input.clone());
+        dual.process(&mut dual_l, &mut dual_r);
+
+        let mut f64_coeff = MeadowEqDspStereoLinkedF64Coeff::<4, 20>::new(SAMPLE_RATE);
+        f64_coeff.set_params(&params);
+        f64_coeff.set_gain_db(6.0);
+        f64_coeff.set_output_ceiling_db(ceiling_db);
+        let (mut f64_l, mut f64_r) = (input.clone(), input.clone());
+        f64_coeff.process(&mut f64_l, &mut f64_r);
+
+        for (name, out) in [
+            ("mono", &mono_out),
+            ("linked left", &l),
+            ("linked right", &r),
+            ("dual left", &dual_l),
+            ("dual right", &dual_r),
+            ("f64 coefficients left", &f64_l),
+            ("f64 coefficients right", &f64_r),
+        ] {
+            let peak = peak(out);
+            match ceiling_db {
+                // Make sure the limiter actually has something to catch.
+                None => assert!(peak > 2.0, "{name}: {peak}"),
+                Some(ceiling_db) => {
+                    assert!(peak <= db_to_amp(ceiling_db), "{name}: {peak}")
+                }
+            }
+        }
+    }
+}
+
+/// `reset` jumps the smoothed gains, band fades, and crossfades straight to
+/// their targets, so a steady input gives a steady output right away.
+#[test]
+fn reset_gives_steady_output_right_away() {
+    const FRAMES: usize = 480;
+
+    let mut rng = Rng::new(14);
+    let active = random_params::<4>(&mut rng);
+    let mut disabled = active;
+    disabled.lp_band.enabled = false;
+    disabled.hp_band.enabled = false;
+    for band in disabled.bands.iter_mut() {
+        band.enabled = false;
+    }
+
+    let input = noise(FRAMES, 15);
+    let dc = [0.5f32; FRAMES];
+    let expected = 0.5 * db_to_amp(6.0) * db_to_amp(-12.0);
+    let check = |name: &str, out: &[f32], expected: f32| {
+        for (i, &s) in out.iter().enumerate() {
+            assert!((s - expected).abs() < 1e-6, "{name}: {s} at frame {i}");
+        }
+    };
+
+    // Every change below would otherwise still be in progress.
+    let mut mono = MeadowEqDspMono::<4, 20>::new(SAMPLE_RATE);
+    mono.set_params(&active);
+    mono.process(&mut input.clone());
+    mono.set_params(&disabled);
+    mono.set_input_gain_db(6.0);
+    mono.set_gain_db(-12.0);
+    mono.reset();
+    let mut out = dc;
+    mono.process(&mut out);
+    check("mono", &out, expected);
+
+    let mut linked = MeadowEqDspStereoLinked::<4, 20>::new(SAMPLE_RATE);
+    linked.set_params(&active);
+    linked.process(&mut input.clone(), &mut input.clone());
+    linked.set_params(&disabled);
+    linked.set_input_gain_db(6.0);
+    linked.set_gain_db(-12.0);
+    linked.reset();
+    let (mut l, mut r) = (dc, dc);
+    linked.process(&mut l, &mut r);
+    check("linked left", &l, expected);
+    check("linked right", &r, expected);
+
+    let mut f64_coeff = MeadowEqDspStereoLinkedF64Coeff::<4, 20>::new(SAMPLE_RATE);
+    f64_coeff.set_params(&active);
+    f64_coeff.process(&mut input.clone(), &mut input.clone());
+    f64_coeff.set_params(&disabled);
+    f64_coeff.set_input_gain_db(6.0);
+    f64_coeff.set_gain_db(-12.0);
+    f64_coeff.reset();
+    let (mut l, mut r) = (dc, dc);
+    f64_coeff.process(&mut l, &mut r);
+    check("f64 coefficients left", &l, expected);
+    check("f64 coefficients right", &r, expected);
+
+    let mut dual = MeadowEqDspStereoDual::<4, 20>::new(SAMPLE_RATE);
+    dual.set_left_params(&active);
+    dual.set_right_params(&active);
+    dual.process(&mut input.clone(), &mut input.clone());
+    dual.set_left_params(&disabled);
+    dual.set_right_params(&disabled);
+    dual.set_input_gain_db(6.0);
+    dual.set_gain_db(-12.0);
+    dual.reset();
+    let (mut l, mut r) = (dc, dc);
+    dual.process(&mut l, &mut r);
+    check("dual left", &l, expected);
+    check("dual right", &r, expected);
+
+    let mut crossfading = CrossfadingEq::<4, 20>::new(SAMPLE_RATE, FRAMES, 0.1);
+    crossfading.set_params_immediate(&active);
+    crossfading.process(&mut input.clone(), &mut input.clone());
+    crossfading.set_target_params(&disabled);
+    assert!(crossfading.is_crossfading());
+    crossfading.reset();
+    let (mut l, mut r) = (dc, dc);
+    crossfading.process(&mut l, &mut r);
+    check("crossfading left", &l, 0.5);
+    check("crossfading right", &r, 0.5);
+}
+
+/// A clone taken part way through a band fade, a gain ramp, or a crossfade
+/// carries on exactly like the original.
+#[test]
+fn clone_produces_identical_output() {
+    const FRAMES: usize = 480;
+
+    let mut rng = Rng::new(16);
+    let first = random_params::<4>(&mut rng);
+    let mut second = random_params::<4>(&mut rng);
+    second.bands[1].enabled = false;
+
+    let input = noise(FRAMES, 17);
+    let stereo = |process: &mut dyn FnMut(&mut [f32], &mut [f32])| {
+        let (mut l, mut r) = (input.clone(), input.clone());
+        process(&mut l, &mut r);
+        (l, r)
+    };
+
+    let mut mono = MeadowEqDspMono::<4, 20>::new(SAMPLE_RATE);
+    mono.set_params(&first);
+    mono.process(&mut input.clone());
+    mono.set_params(&second);
+    mono.set_gain_db(-6.0);
+    let mut mono_clone = mono.clone();
+    let (mut out, mut clone_out) = (input.clone(), input.clone());
+    mono.process(&mut out);
+    mono_clone.process(&mut clone_out);
+    assert_eq!(out, clone_out);
+
+    let mut linked = MeadowEqDspStereoLinked::<4, 20>::new(SAMPLE_RATE);
+    linked.set_params(&first);
+    stereo(&mut |l, r| linked.process(l, r));
+    linked.set_params(&second);
+    linked.set_gain_db(-6.0);
+    let mut linked_clone = linked.clone();
+    assert_eq!(
+        stereo(&mut |l, r| linked.process(l, r)),
+        stereo(&mut |l, r| linked_clone.process(l, r))
+    );
+
+    let mut f64_coeff = MeadowEqDspStereoLinkedF64Coeff::<4, 20>::new(SAMPLE_RATE);
+    f64_coeff.set_params(&first);
+    stereo(&mut |l, r| f64_coeff.process(l, r));
+    f64_coeff.set_params(&second);
+    f64_coeff.set_input_gain_db(3.0);
+    let mut f64_coeff_clone = f64_coeff.clone();
+    assert_eq!(
+        stereo(&mut |l, r| f64_coeff.process(l, r)),
+        stereo(&mut |l, r| f64_coeff_clone.process(l, r))
+    );
+
+    let mut dual = MeadowEqDspStereoDual::<4, 20>::new(SAMPLE_RATE);
+    dual.set_left_params(&first);
+    dual.set_right_params(&second);
+    stereo(&mut |l, r| dual.process(l, r));
+    dual.set_left_params(&second);
+    dual.set_right_params(&first);
+    let mut dual_clone = dual.clone();
+    assert_eq!(
+        stereo(&mut |l, r| dual.process(l, r)),
+        stereo(&mut |l, r| dual_clone.process(l, r))
+    );
+
+    let mut crossfading = CrossfadingEq::<4, 20>::new(SAMPLE_RATE, FRAMES, 0.1);
+    crossfading.set_params_immediate(&first);
+    stereo(&mut |l, r| crossfading.process(l, r));
+    crossfading.set_target_params(&second);
+    stereo(&mut |l, r| crossfading.process(l, r));
+    assert!(crossfading.is_crossfading());
+    let mut crossfading_clone = crossfading.clone();
+    assert_eq!(
+        stereo(&mut |l, r| crossfading.process(l, r)),
+        stereo(&mut |l, r| crossfading_clone.process(l, r))
+    );
+
+    let mut dj = DjFilter::new(SAMPLE_RATE);
+    dj.set_amount(-0.5);
+    stereo(&mut |l, r| dj.process(l, r));
+    dj.set_amount(0.5);
+    let mut dj_clone = dj.clone();
+    assert_eq!(
+        stereo(&mut |l, r| dj.process(l, r)),
+        stereo(&mut |l, r| dj_clone.process(l, r))
+    );
+
+    let mut dynamic = MeadowEqDspDynamic::new(SAMPLE_RATE);
+    dynamic.set_from_params(&first);
+    dynamic.process(&mut input.clone());
+    dynamic.set_from_params(&second);
+    dynamic.set_gain_db(-6.0);
+    let mut dynamic_clone = dynamic.clone();
+    let (mut out, mut clone_out) = (input.clone(), input.clone());
+    dynamic.process(&mut out);
+    dynamic_clone.process(&mut clone_out);
+    assert_eq!(out, clone_out);
+}
+
+/// Setting the output gain to `-inf` dB fades the output out smoothly
+/// instead of cutting it off, and ends in exact silence.
+#[test]
+fn gain_step_to_silence_ramps_smoothly() {
+    const BLOCK: usize = 64;
+    const FRAMES: usize = 4_800;
+
+    // The largest step of the smoothed gain, at the start of the fade.
+    let max_step = 1.0 - (-1.0 / (DEFAULT_SMOOTH_SECS * SAMPLE_RATE)).exp() as f32 + 1e-6;
+    let check = |name: &str, out: &[f32]| {
+        assert!(
+            out[0] < 1.0 && out[0] >= 1.0 - max_step,
+            "{name}: {}",
+            out[0]
+        );
+        for pair in out.windows(2) {
+            assert!(
+                pair[1] <= pair[0] && pair[0] - pair[1] <= max_step,
+                "{name}: {pair:?}"
+            );
+        }
+        // The smoothed gain snaps to exactly zero after about 12 time
+        // constants.
+        assert!(out[3 * FRAMES / 4..].iter().all(|&s| s == 0.0), "{name}");
+    };
+
+    let mut mono = MeadowEqDspMono::<4, 20>::new(SAMPLE_RATE);
+    mono.set_gain_db(f32::NEG_INFINITY);
+    let mut out = vec![1.0; FRAMES];
+    for block in out.chunks_mut(BLOCK) {
+        mono.process(block);
+    }
+    check("mono", &out);
+
+    let mut linked = MeadowEqDspStereoLinked::<4, 20>::new(SAMPLE_RATE);
+    let mut f64_coeff = MeadowEqDspStereoLinkedF64Coeff::<4, 20>::new(SAMPLE_RATE);
+    let mut dual = MeadowEqDspStereoDual::<4, 20>::new(SAMPLE_RATE);
+    linked.set_gain_db(f32::NEG_INFINITY);
+    f64_coeff.set_gain_db(f32::NEG_INFINITY);
+    dual.set_gain_db(f32::NEG_INFINITY);
+
+    let check_stereo = |name: &str, process: &mut dyn FnMut(&mut [f32], &mut [f32])| {
+        let (mut l, mut r) = (vec![1.0; FRAMES], vec![1.0; FRAMES]);
+        for (l, r) in l.chunks_mut(BLOCK).zip(r.chunks_mut(BLOCK)) {
+            process(l, r);
+        }
+        check(name, &l);
+        assert_eq!(l, r, "{name}");
+    };
+    check_stereo("linked", &mut |l, r| linked.process(l, r));
+    check_stereo("f64 coefficients", &mut |l, r| f64_coeff.process(l, r));
+    check_stereo("dual", &mut |l, r| dual.process(l, r));
+}
+
+/// Enabling or disabling a band, or changing the order of the lowpass or
+/// highpass band, repacks the filters. Every band must keep its own
+/// coefficients and state through the repack, so after a `reset` the EQ
+/// processes exactly like a fresh one with the same parameters.
+#[test]
+fn changing_the_filter_count_keeps_every_band() {
+    let mut rng = Rng::new(17);
+    let full = random_params::<4>(&mut rng);
+    let input = noise(256, 18);
+
+    let mut mono = MeadowEqDspMono::<4, 20>::new(SAMPLE_RATE);
+    let mut linked = MeadowEqDspStereoLinked::<4, 20>::new(SAMPLE_RATE);
+
+    for step in 0..12 {
+        let mut params = full;
+        params.lp_band.enabled = step % 3 != 0;
+        params.hp_band.enabled = step % 4 != 1;
+        params.lp_band.order = [FilterOrder::X1, FilterOrder::X4, FilterOrder::X8][step % 3];
+        params.hp_band.order = [FilterOrder::X2, FilterOrder::X6][step % 2];
+        for (i, band) in params.bands.iter_mut().enumerate() {
+            band.enabled = (step >> i) & 1 == 0;
+        }
+
+        let mut fresh_mono = MeadowEqDspMono::<4, 20>::new(SAMPLE_RATE);
+        fresh_mono.set_params(&params);
+        let mut expected = input.clone();
+        fresh_mono.process(&mut expected);
+
+        mono.set_params(&params);
+        mono.reset();
+        let mut out = input.clone();
+        mono.process(&mut out);
+        assert_eq!(out, expected, "mono, step {step}");
+
+        let mut fresh_linked = MeadowEqDspStereoLinked::<4, 20>::new(SAMPLE_RATE);
+        fresh_linked.set_params(&params);
+        let (mut expected_l, mut expected_r) = (input.clone(), input.clone());
+        fresh_linked.process(&mut expected_l, &mut expected_r);
+
+        linked.set_params(&params);
+        linked.reset();
+        let (mut l, mut r) = (input.clone(), input.clone());
+        linked.process(&mut l, &mut r);
+        assert_eq!(l, expected_l, "linked left, step {step}");
+        assert_eq!(r, expected_r, "linked right, step {step}");
+    }
+}
@@ -0,0 +1,16 @@
+//! Re-exports of the most commonly used types, so that they can be imported
+//! with `use meadow_dsp_agpl::prelude::*;`. This includes the
+//! [`meadow_dsp_mit` prelude](meadow_dsp_mit::prelude).
+
+pub use meadow_dsp_mit::prelude::*;
+
+pub use crate::parametric_eq::f32::{
+    coeff::MeadowEqDspCoeff,
+    dynamic::MeadowEqDspDynamic,
+    mono::MeadowEqDspMono,
+    stereo::{
+        dual::MeadowEqDspStereoDual, f64_coeff::MeadowEqDspStereoLinkedF64Coeff,
+        scalar::MeadowEqDspStereoLinked,
+    },
+    BandParams, BandRouting, BandType, EqParams, FilterOrder, LpOrHpBandParams, QMode,
+};
@@ -0,0 +1,67 @@
+//! Helpers shared by the unit tests.
+
+// Not every test uses every helper.
+#![allow(dead_code)]
+
+use std::f64::consts::TAU;
+
+/// A small deterministic random number generator (a 64-bit LCG), so that the
+/// tests do not need a random number crate and always see the same input.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15).wrapping_add(1))
+    }
+
+    /// A uniformly distributed value in `[0.0, 1.0)`.
+    pub fn next_f64(&mut self) -> f64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        (self.0 >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// A uniformly distributed value in `[lo, hi)`.
+    pub fn range(&mut self, lo: f64, hi: f64) -> f64 {
+        lo + (hi - lo) * self.next_f64()
+    }
+
+    /// A value whose logarithm is uniformly distributed in `[lo, hi)`, for
+    /// frequencies and Q values.
+    pub fn log_range(&mut self, lo: f64, hi: f64) -> f64 {
+        self.range(lo.ln(), hi.ln()).exp()
+    }
+}
+
+/// White noise in `[-1.0, 1.0)`.
+pub fn noise(frames: usize, seed: u64) -> Vec<f32> {
+    let mut rng = Rng::new(seed);
+    (0..frames).map(|_| rng.range(-1.0, 1.0) as f32).collect()
+}
+
+/// A sine with an amplitude of `1.0`.
+pub fn sine(freq_hz: f64, sample_rate: f64, frames: usize) -> Vec<f32> {
+    (0..frames)
+        .map(|i| (TAU * freq_hz * i as f64 / sample_rate).sin() as f32)
+        .collect()
+}
+
+pub fn peak(buf: &[f32]) -> f32 {
+    buf.iter().fold(0.0, |p, s| p.max(s.abs()))
+}
+
+pub fn rms(buf: &[f32]) -> f64 {
+    (buf.iter().map(|&s| s as f64 * s as f64).sum::<f64>() / buf.len() as f64).sqrt()
+}
+
+/// The gain in decibels of `process` at the given frequency, measured with a
+/// sine of one second after letting the output settle for one second.
+pub fn sine_gain_db(freq_hz: f64, sample_rate: f64, mut process: impl FnMut(&mut [f32])) -> f64 {
+    let frames = sample_rate as usize;
+    let mut buf = sine(freq_hz, sample_rate, 2 * frames);
+    process(&mut buf);
+
+    20.0 * (rms(&buf[frames..]) * std::f64::consts::SQRT_2).log10()
+}
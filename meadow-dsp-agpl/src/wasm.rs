@@ -0,0 +1,378 @@
+//! A flat C ABI for the parametric EQ, meant to be called from JavaScript
+//! when compiled to WASM.
+//!
+//! Calling across the JS boundary is expensive, so processing is always done
+//! on whole blocks. A typical host allocates a buffer inside the WASM memory
+//! with [`meadow_eq_alloc_buffer`], creates a single `Float32Array` view over
+//! it, and then calls [`meadow_eq_process`] once per block.
+//!
+//! Both a stereo EQ ([`MeadowEqWasm`], with the `meadow_eq_*` functions) and
+//! a mono EQ ([`MeadowEqMonoWasm`], with the `meadow_eq_mono_*` functions)
+//! are exposed. The buffer functions are shared by both.
+//!
+//! This module does not make use of `std::simd` (the `portable-simd` feature
+//! of `meadow-dsp-mit` is not enabled by this crate).
+//!
+//! # Ownership
+//!
+//! * A handle returned by [`meadow_eq_new`] (or [`meadow_eq_mono_new`]) is
+//!   owned by the caller, and it must be freed exactly once with
+//!   [`meadow_eq_free`] (or [`meadow_eq_mono_free`]).
+//! * A buffer returned by [`meadow_eq_alloc_buffer`] is owned by the caller,
+//!   and it must be freed exactly once with [`meadow_eq_free_buffer`] using
+//!   the same length it was allocated with.
+//! * The library never holds on to a buffer pointer after a function returns.
+
+use crate::parametric_eq::f32::{
+    mono::MeadowEqDspMono, stereo::scalar::MeadowEqDspStereoLinked, BandRouting, BandType,
+    EqParams, FilterOrder, LpOrHpBandParams,
+};
+
+/// The number of bands (not including the lowpass and highpass bands) in
+/// [`MeadowEqWasm`] and [`MeadowEqMonoWasm`].
+pub const NUM_BANDS: usize = 8;
+
+/// An opaque handle to a stereo parametric EQ with [`NUM_BANDS`] bands.
+pub struct MeadowEqWasm {
+    eq: MeadowEqDspStereoLinked<NUM_BANDS, { NUM_BANDS + 16 }>,
+}
+
+/// Create a new EQ handle. All bands are initially disabled.
+#[no_mangle]
+pub extern "C" fn meadow_eq_new(sample_rate: f64) -> *mut MeadowEqWasm {
+    Box::into_raw(Box::new(MeadowEqWasm {
+        eq: MeadowEqDspStereoLinked::new(sample_rate),
+    }))
+}
+
+/// Free an EQ handle.
+///
+/// # Safety
+/// `eq` must be null or a handle returned by [`meadow_eq_new`] that has not
+/// already been freed.
+#[no_mangle]
+pub unsafe extern "C" fn meadow_eq_free(eq: *mut MeadowEqWasm) {
+    if !eq.is_null() {
+        drop(Box::from_raw(eq));
+    }
+}
+
+/// Allocate a zeroed buffer of `len` samples.
+#[no_mangle]
+pub extern "C" fn meadow_eq_alloc_buffer(len: usize) -> *mut f32 {
+    Box::into_raw(vec![0.0f32; len].into_boxed_slice()) as *mut f32
+}
+
+/// Free a buffer allocated with [`meadow_eq_alloc_buffer`].
+///
+/// # Safety
+/// `buf` must be null or a buffer returned by [`meadow_eq_alloc_buffer`] that
+/// has not already been freed, and `len` must be the length it was allocated
+/// with.
+#[no_mangle]
+pub unsafe extern "C" fn meadow_eq_free_buffer(buf: *mut f32, len: usize) {
+    if !buf.is_null() {
+        drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(buf, len)));
+    }
+}
+
+/// Set the parameters of the band at the given index. `band_type` is the
+/// value passed to [`BandType::from_u32`].
+///
+/// Out-of-range indices are ignored.
+///
+/// # Safety
+/// `eq` must be a valid handle returned by [`meadow_eq_new`].
+#[no_mangle]
+pub unsafe extern "C" fn meadow_eq_set_band(
+    eq: *mut MeadowEqWasm,
+    index: usize,
+    enabled: bool,
+    band_type: u32,
+    cutoff_hz: f32,
+    q: f32,
+    gain_db: f32,
+) {
+    (*eq)
+        .eq
+        .modify_params(|p| set_band(p, index, enabled, band_type, cutoff_hz, q, gain_db));
+}
+
+/// Set the parameters of the lowpass band. `order` is the value passed to
+/// [`FilterOrder::from_u32`].
+///
+/// # Safety
+/// `eq` must be a valid handle returned by [`meadow_eq_new`].
+#[no_mangle]
+pub unsafe extern "C" fn meadow_eq_set_lp_band(
+    eq: *mut MeadowEqWasm,
+    enabled: bool,
+    cutoff_hz: f32,
+    q: f32,
+    order: u32,
+) {
+    (*eq)
+        .eq
+        .modify_params(|p| set_lp_hp_band(&mut p.lp_band, enabled, cutoff_hz, q, order));
+}
+
+/// Set the parameters of the highpass band. `order` is the value passed to
+/// [`FilterOrder::from_u32`].
+///
+/// # Safety
+/// `eq` must be a valid handle returned by [`meadow_eq_new`].
+#[no_mangle]
+pub unsafe extern "C" fn meadow_eq_set_hp_band(
+    eq: *mut MeadowEqWasm,
+    enabled: bool,
+    cutoff_hz: f32,
+    q: f32,
+    order: u32,
+) {
+    (*eq)
+        .eq
+        .modify_params(|p| set_lp_hp_band(&mut p.hp_band, enabled, cutoff_hz, q, order));
+}
+
+/// Set how the bands are combined. `routing` is the value passed to
+/// [`BandRouting::from_u32`].
+///
+/// # Safety
+/// `eq` must be a valid handle returned by [`meadow_eq_new`].
+#[no_mangle]
+pub unsafe extern "C" fn meadow_eq_set_band_routing(eq: *mut MeadowEqWasm, routing: u32) {
+    (*eq).eq.set_band_routing(BandRouting::from_u32(routing));
+}
+
+/// Reset the state of all filters.
+///
+/// # Safety
+/// `eq` must be a valid handle returned by [`meadow_eq_new`].
+#[no_mangle]
+pub unsafe extern "C" fn meadow_eq_reset(eq: *mut MeadowEqWasm) {
+    (*eq).eq.reset();
+}
+
+/// Process a planar stereo buffer in place. `buf` holds `2 * frames` samples,
+/// with all of the left channel first followed by all of the right channel.
+///
+/// # Safety
+/// `eq` must be a valid handle returned by [`meadow_eq_new`], and `buf` must
+/// point to `2 * frames` valid and initialized samples (it may be null if
+/// `frames == 0`).
+#[no_mangle]
+pub unsafe extern "C" fn meadow_eq_process(eq: *mut MeadowEqWasm, buf: *mut f32, frames: usize) {
+    if frames == 0 {
+        return;
+    }
+
+    let buf = std::slice::from_raw_parts_mut(buf, frames * 2);
+    let (buf_l, buf_r) = buf.split_at_mut(frames);
+
+    (*eq).eq.process(buf_l, buf_r);
+}
+
+/// Process two separate channel buffers of `frames` samples each in place.
+///
+/// # Safety
+/// `eq` must be a valid handle returned by [`meadow_eq_new`], and `buf_l` and
+/// `buf_r` must each point to `frames` valid and initialized samples which do
+/// not overlap (they may be null if `frames == 0`).
+#[no_mangle]
+pub unsafe extern "C" fn meadow_eq_process_stereo(
+    eq: *mut MeadowEqWasm,
+    buf_l: *mut f32,
+    buf_r: *mut f32,
+    frames: usize,
+) {
+    if frames == 0 {
+        return;
+    }
+
+    let buf_l = std::slice::from_raw_parts_mut(buf_l, frames);
+    let buf_r = std::slice::from_raw_parts_mut(buf_r, frames);
+
+    (*eq).eq.process(buf_l, buf_r);
+}
+
+/// An opaque handle to a mono parametric EQ with [`NUM_BANDS`] bands.
+pub struct MeadowEqMonoWasm {
+    eq: MeadowEqDspMono<NUM_BANDS, { NUM_BANDS + 16 }>,
+}
+
+/// Create a new mono EQ handle. All bands are initially disabled.
+#[no_mangle]
+pub extern "C" fn meadow_eq_mono_new(sample_rate: f64) -> *mut MeadowEqMonoWasm {
+    Box::into_raw(Box::new(MeadowEqMonoWasm {
+        eq: MeadowEqDspMono::new(sample_rate),
+    }))
+}
+
+/// Free a mono EQ handle.
+///
+/// # Safety
+/// `eq` must be null or a handle returned by [`meadow_eq_mono_new`] that has
+/// not already been freed.
+#[no_mangle]
+pub unsafe extern "C" fn meadow_eq_mono_free(eq: *mut MeadowEqMonoWasm) {
+    if !eq.is_null() {
+        drop(Box::from_raw(eq));
+    }
+}
+
+/// The same as [`meadow_eq_set_band`], for a mono EQ.
+///
+/// # Safety
+/// `eq` must be a valid handle returned by [`meadow_eq_mono_new`].
+#[no_mangle]
+pub unsafe extern "C" fn meadow_eq_mono_set_band(
+    eq: *mut MeadowEqMonoWasm,
+    index: usize,
+    enabled: bool,
+    band_type: u32,
+    cutoff_hz: f32,
+    q: f32,
+    gain_db: f32,
+) {
+    (*eq)
+        .eq
+        .modify_params(|p| set_band(p, index, enabled, band_type, cutoff_hz, q, gain_db));
+}
+
+/// The same as [`meadow_eq_set_lp_band`], for a mono EQ.
+///
+/// # Safety
+/// `eq` must be a valid handle returned by [`meadow_eq_mono_new`].
+#[no_mangle]
+pub unsafe extern "C" fn meadow_eq_mono_set_lp_band(
+    eq: *mut MeadowEqMonoWasm,
+    enabled: bool,
+    cutoff_hz: f32,
+    q: f32,
+    order: u32,
+) {
+    (*eq)
+        .eq
+        .modify_params(|p| set_lp_hp_band(&mut p.lp_band, enabled, cutoff_hz, q, order));
+}
+
+/// The same as [`meadow_eq_set_hp_band`], for a mono EQ.
+///
+/// # Safety
+/// `eq` must be a valid handle returned by [`meadow_eq_mono_new`].
+#[no_mangle]
+pub unsafe extern "C" fn meadow_eq_mono_set_hp_band(
+    eq: *mut MeadowEqMonoWasm,
+    enabled: bool,
+    cutoff_hz: f32,
+    q: f32,
+    order: u32,
+) {
+    (*eq)
+        .eq
+        .modify_params(|p| set_lp_hp_band(&mut p.hp_band, enabled, cutoff_hz, q, order));
+}
+
+/// The same as [`meadow_eq_set_band_routing`], for a mono EQ.
+///
+/// # Safety
+/// `eq` must be a valid handle returned by [`meadow_eq_mono_new`].
+#[no_mangle]
+pub unsafe extern "C" fn meadow_eq_mono_set_band_routing(eq: *mut MeadowEqMonoWasm, routing: u32) {
+    (*eq).eq.set_band_routing(BandRouting::from_u32(routing));
+}
+
+/// Reset the state of all filters of a mono EQ.
+///
+/// # Safety
+/// `eq` must be a valid handle returned by [`meadow_eq_mono_new`].
+#[no_mangle]
+pub unsafe extern "C" fn meadow_eq_mono_reset(eq: *mut MeadowEqMonoWasm) {
+    (*eq).eq.reset();
+}
+
+/// Process a mono buffer of `frames` samples in place.
+///
+/// # Safety
+/// `eq` must be a valid handle returned by [`meadow_eq_mono_new`], and `buf`
+/// must point to `frames` valid and initialized samples (it may be null if
+/// `frames == 0`).
+#[no_mangle]
+pub unsafe extern "C" fn meadow_eq_mono_process(
+    eq: *mut MeadowEqMonoWasm,
+    buf: *mut f32,
+    frames: usize,
+) {
+    if frames == 0 {
+        return;
+    }
+
+    (*eq)
+        .eq
+        .process(std::slice::from_raw_parts_mut(buf, frames));
+}
+
+/// Out-of-range indices are ignored.
+fn set_band(
+    params: &mut EqParams<NUM_BANDS>,
+    index: usize,
+    enabled: bool,
+    band_type: u32,
+    cutoff_hz: f32,
+    q: f32,
+    gain_db: f32,
+) {
+    if let Some(band) = params.bands.get_mut(index) {
+        band.enabled = enabled;
+        band.band_type = BandType::from_u32(band_type);
+        band.cutoff_hz = cutoff_hz;
+        band.q = q;
+        band.gain_db = gain_db;
+    }
+}
+
+fn set_lp_hp_band(band: &mut LpOrHpBandParams, enabled: bool, cutoff_hz: f32, q: f32, order: u32) {
+    band.enabled = enabled;
+    band.cutoff_hz = cutoff_hz;
+    band.q = q;
+    band.order = FilterOrder::from_u32(order);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::noise;
+
+    /// The mono bindings process like the stereo ones.
+    #[test]
+    fn mono_matches_stereo() {
+        let input = noise(1024, 0);
+
+        unsafe {
+            let stereo = meadow_eq_new(48_000.0);
+            let mono = meadow_eq_mono_new(48_000.0);
+
+            meadow_eq_set_band(stereo, 0, true, 0, 1_000.0, 2.0, 6.0);
+            meadow_eq_mono_set_band(mono, 0, true, 0, 1_000.0, 2.0, 6.0);
+            meadow_eq_set_hp_band(stereo, true, 80.0, 0.7, 2);
+            meadow_eq_mono_set_hp_band(mono, true, 80.0, 0.7, 2);
+            meadow_eq_set_lp_band(stereo, true, 12_000.0, 0.7, 1);
+            meadow_eq_mono_set_lp_band(mono, true, 12_000.0, 0.7, 1);
+
+            let mut stereo_buf = [input.as_slice(), input.as_slice()].concat();
+            meadow_eq_process(stereo, stereo_buf.as_mut_ptr(), input.len());
+
+            let buf = meadow_eq_alloc_buffer(input.len());
+            std::slice::from_raw_parts_mut(buf, input.len()).copy_from_slice(&input);
+            meadow_eq_mono_process(mono, buf, input.len());
+            let mono_out = std::slice::from_raw_parts(buf, input.len()).to_vec();
+
+            assert_ne!(mono_out, input);
+            assert_eq!(mono_out, stereo_buf[..input.len()]);
+
+            meadow_eq_free_buffer(buf, input.len());
+            meadow_eq_mono_free(mono);
+            meadow_eq_free(stereo);
+        }
+    }
+}
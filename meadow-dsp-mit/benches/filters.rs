@@ -0,0 +1,202 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use meadow_dsp_mit::filter::{
+    one_pole_iir::f32::{OnePoleIirCoeff, OnePoleIirState},
+    svf::f32::{SvfCoeff, SvfState},
+};
+
+const SAMPLE_RATE_RECIP: f32 = 1.0 / 48_000.0;
+const FRAMES: usize = 512;
+
+/// Filter `N` channels of `FRAMES` samples each with a different filter per
+/// channel, once with a scalar filter per channel and once (with the
+/// `portable-simd` feature) with all channels packed into one SIMD filter.
+fn svf(c: &mut Criterion) {
+    let mut group = c.benchmark_group("svf");
+
+    let coeff = SvfCoeff::bell(1_000.0, 0.7, 6.0, SAMPLE_RATE_RECIP);
+
+    group.throughput(Throughput::Elements(FRAMES as u64));
+    group.bench_function("tick", |b| {
+        let mut state = SvfState::default();
+        let mut buf = input::<1>();
+
+        b.iter(|| {
+            for s in buf[0].iter_mut() {
+                *s = state.tick(*s, &coeff);
+            }
+            black_box(&mut buf);
+        });
+    });
+
+    svf_scalar::<4>(&mut group, coeff);
+    svf_scalar::<8>(&mut group, coeff);
+
+    #[cfg(feature = "portable-simd")]
+    {
+        use meadow_dsp_mit::filter::svf::f32::simd::{
+            SvfCoeffx4, SvfCoeffx8, SvfStatex4, SvfStatex8,
+        };
+        use meadow_dsp_mit::simd::{f32x4, f32x8};
+
+        group.throughput(Throughput::Elements((FRAMES * 4) as u64));
+        group.bench_function(BenchmarkId::new("simd", 4), |b| {
+            let coeff = SvfCoeffx4::splat(coeff);
+            let mut state = SvfStatex4::splat(SvfState::default());
+            let mut buf = interleaved_input::<4>();
+
+            b.iter(|| {
+                for frame in buf.iter_mut() {
+                    *frame = state.tick(f32x4::from_array(*frame), &coeff).to_array();
+                }
+                black_box(&mut buf);
+            });
+        });
+
+        group.throughput(Throughput::Elements((FRAMES * 8) as u64));
+        group.bench_function(BenchmarkId::new("simd", 8), |b| {
+            let coeff = SvfCoeffx8::splat(coeff);
+            let mut state = SvfStatex8::splat(SvfState::default());
+            let mut buf = interleaved_input::<8>();
+
+            b.iter(|| {
+                for frame in buf.iter_mut() {
+                    *frame = state.tick(f32x8::from_array(*frame), &coeff).to_array();
+                }
+                black_box(&mut buf);
+            });
+        });
+    }
+
+    group.finish();
+}
+
+fn svf_scalar<const N: usize>(
+    group: &mut criterion::BenchmarkGroup<criterion::measurement::WallTime>,
+    coeff: SvfCoeff,
+) {
+    group.throughput(Throughput::Elements((FRAMES * N) as u64));
+    group.bench_function(BenchmarkId::new("scalar", N), |b| {
+        let mut states = [SvfState::default(); N];
+        let mut buf = input::<N>();
+
+        b.iter(|| {
+            for (state, buf) in states.iter_mut().zip(buf.iter_mut()) {
+                for s in buf.iter_mut() {
+                    *s = state.tick(*s, &coeff);
+                }
+            }
+            black_box(&mut buf);
+        });
+    });
+}
+
+/// The same as [`svf`], but for the one-pole IIR filters.
+fn one_pole_iir(c: &mut Criterion) {
+    let mut group = c.benchmark_group("one_pole_iir");
+
+    let coeff = OnePoleIirCoeff::lowpass(1_000.0, SAMPLE_RATE_RECIP);
+
+    group.throughput(Throughput::Elements(FRAMES as u64));
+    group.bench_function("tick", |b| {
+        let mut state = OnePoleIirState::default();
+        let mut buf = input::<1>();
+
+        b.iter(|| {
+            for s in buf[0].iter_mut() {
+                *s = state.tick(*s, &coeff);
+            }
+            black_box(&mut buf);
+        });
+    });
+
+    one_pole_iir_scalar::<4>(&mut group, coeff);
+    one_pole_iir_scalar::<8>(&mut group, coeff);
+
+    #[cfg(feature = "portable-simd")]
+    {
+        use meadow_dsp_mit::filter::one_pole_iir::f32::simd::{
+            OnePoleIirCoeffx4, OnePoleIirCoeffx8, OnePoleIirStatex4, OnePoleIirStatex8,
+        };
+        use meadow_dsp_mit::simd::{f32x4, f32x8};
+
+        group.throughput(Throughput::Elements((FRAMES * 4) as u64));
+        group.bench_function(BenchmarkId::new("simd", 4), |b| {
+            let coeff = OnePoleIirCoeffx4::splat(coeff);
+            let mut state = OnePoleIirStatex4::default();
+            let mut buf = interleaved_input::<4>();
+
+            b.iter(|| {
+                for frame in buf.iter_mut() {
+                    *frame = state.tick(f32x4::from_array(*frame), &coeff).to_array();
+                }
+                black_box(&mut buf);
+            });
+        });
+
+        group.throughput(Throughput::Elements((FRAMES * 8) as u64));
+        group.bench_function(BenchmarkId::new("simd", 8), |b| {
+            let coeff = OnePoleIirCoeffx8::splat(coeff);
+            let mut state = OnePoleIirStatex8::default();
+            let mut buf = interleaved_input::<8>();
+
+            b.iter(|| {
+                for frame in buf.iter_mut() {
+                    *frame = state.tick(f32x8::from_array(*frame), &coeff).to_array();
+                }
+                black_box(&mut buf);
+            });
+        });
+    }
+
+    group.finish();
+}
+
+fn one_pole_iir_scalar<const N: usize>(
+    group: &mut criterion::BenchmarkGroup<criterion::measurement::WallTime>,
+    coeff: OnePoleIirCoeff,
+) {
+    group.throughput(Throughput::Elements((FRAMES * N) as u64));
+    group.bench_function(BenchmarkId::new("scalar", N), |b| {
+        let mut states = [OnePoleIirState::default(); N];
+        let mut buf = input::<N>();
+
+        b.iter(|| {
+            for (state, buf) in states.iter_mut().zip(buf.iter_mut()) {
+                for s in buf.iter_mut() {
+                    *s = state.tick(*s, &coeff);
+                }
+            }
+            black_box(&mut buf);
+        });
+    });
+}
+
+/// `N` channels of white-ish noise.
+fn input<const N: usize>() -> Vec<[f32; FRAMES]> {
+    let mut seed = 1u32;
+
+    (0..N)
+        .map(|_| {
+            std::array::from_fn(|_| {
+                // A simple LCG, so that the input is not trivially predictable.
+                seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                (seed >> 8) as f32 / (1 << 23) as f32 - 1.0
+            })
+        })
+        .collect()
+}
+
+/// The same as [`input`], but with the channels interleaved into frames.
+#[cfg(feature = "portable-simd")]
+fn interleaved_input<const N: usize>() -> Vec<[f32; N]> {
+    let channels = input::<N>();
+
+    (0..FRAMES)
+        .map(|i| std::array::from_fn(|ch| channels[ch][i]))
+        .collect()
+}
+
+criterion_group!(benches, svf, one_pole_iir);
+criterion_main!(benches);
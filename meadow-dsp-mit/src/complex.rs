@@ -0,0 +1,28 @@
+//! A minimal complex number type, used for analysis of the filters.
+
+/// A complex number with f64 components.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Complex64 {
+    pub re: f64,
+    pub im: f64,
+}
+
+impl Complex64 {
+    pub const fn new(re: f64, im: f64) -> Self {
+        Self { re, im }
+    }
+
+    /// The magnitude (absolute value).
+    pub fn norm(&self) -> f64 {
+        self.re.hypot(self.im)
+    }
+
+    /// The angle in radians, in the range `[-pi, pi]`.
+    pub fn arg(&self) -> f64 {
+        self.im.atan2(self.re)
+    }
+
+    pub const fn conj(&self) -> Self {
+        Self::new(self.re, -self.im)
+    }
+}
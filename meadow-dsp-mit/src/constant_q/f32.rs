@@ -0,0 +1,190 @@
+use std::f32::consts::FRAC_PI_2;
+
+use crate::filter::{
+    one_pole_iir::{
+        f32::{OnePoleIirCoeff, OnePoleIirState},
+        f64::OnePoleIirCoeff as OnePoleIirCoeffF64,
+    },
+    svf::{
+        f32::{SvfCoeff, SvfState},
+        f64::{SvfCoeff as SvfCoeffF64, Q_BUTTERWORTH_ORD2},
+    },
+};
+
+/// The default cutoff of the one-pole envelope followers in Hz.
+pub const DEFAULT_ENVELOPE_CUTOFF_HZ: f32 = 10.0;
+
+/// A bank of bandpass filters at log-spaced center frequencies, each followed
+/// by an envelope follower.
+///
+/// For low bin counts this produces a magnitude spectrum much more cheaply than
+/// an FFT, and it has zero latency. This is useful for real-time visualizers and
+/// vocoders.
+pub struct ConstantQBank {
+    center_freqs_hz: Vec<f32>,
+
+    svf_coeffs: Vec<SvfCoeff>,
+    svf_states: Vec<SvfState>,
+
+    envelope_coeff: OnePoleIirCoeff,
+    envelope_states: Vec<OnePoleIirState>,
+
+    magnitudes: Vec<f32>,
+
+    sample_rate_recip: f64,
+}
+
+impl ConstantQBank {
+    /// Create a new bank with `num_bins` bins log-spaced between `min_hz` and
+    /// `max_hz` (inclusive).
+    ///
+    /// The Q of every bin is chosen so that neighboring bins cross at their
+    /// -3 dB points.
+    ///
+    /// # Panics
+    /// Panics if `num_bins == 0`.
+    pub fn new(num_bins: usize, min_hz: f32, max_hz: f32, sample_rate: f64) -> Self {
+        assert_ne!(num_bins, 0);
+
+        let sample_rate_recip = sample_rate.recip();
+
+        let center_freqs_hz = log_spaced_freqs(num_bins, min_hz, max_hz);
+        let q = constant_q(num_bins, min_hz, max_hz);
+
+        let svf_coeffs = center_freqs_hz
+            .iter()
+            .map(|&hz| SvfCoeffF64::bandpass(hz as f64, q as f64, sample_rate_recip).to_f32())
+            .collect();
+
+        Self {
+            center_freqs_hz,
+            svf_coeffs,
+            svf_states: vec![SvfState::default(); num_bins],
+            envelope_coeff: OnePoleIirCoeffF64::lowpass(
+                DEFAULT_ENVELOPE_CUTOFF_HZ as f64,
+                sample_rate_recip,
+            )
+            .to_f32(),
+            envelope_states: vec![OnePoleIirState::default(); num_bins],
+            magnitudes: vec![0.0; num_bins],
+            sample_rate_recip,
+        }
+    }
+
+    pub fn num_bins(&self) -> usize {
+        self.center_freqs_hz.len()
+    }
+
+    /// The center frequency of each bin in Hz.
+    pub fn center_freqs_hz(&self) -> &[f32] {
+        &self.center_freqs_hz
+    }
+
+    /// Set the cutoff of the envelope followers in Hz. Lower values give a
+    /// smoother but slower-reacting spectrum.
+    pub fn set_envelope_cutoff_hz(&mut self, cutoff_hz: f32) {
+        self.envelope_coeff =
+            OnePoleIirCoeffF64::lowpass(cutoff_hz as f64, self.sample_rate_recip).to_f32();
+    }
+
+    /// Process a block of samples and return the magnitude of each bin at the
+    /// end of the block.
+    ///
+    /// The magnitudes are raw amplitudes (not decibels), where a sine wave of
+    /// amplitude `1.0` at a bin's center frequency reads as roughly `1.0`.
+    pub fn process_block(&mut self, input: &[f32]) -> &[f32] {
+        // Hint to compiler to optimize loop;
+        assert_eq!(self.svf_coeffs.len(), self.svf_states.len());
+        assert_eq!(self.svf_coeffs.len(), self.envelope_states.len());
+        assert_eq!(self.svf_coeffs.len(), self.magnitudes.len());
+
+        for i in 0..self.svf_coeffs.len() {
+            let coeff = &self.svf_coeffs[i];
+            let svf_state = &mut self.svf_states[i];
+            let envelope_state = &mut self.envelope_states[i];
+
+            let mut env = envelope_state.z1;
+            for &s in input.iter() {
+                let band = svf_state.tick(s, coeff);
+                env = envelope_state.tick(band.abs(), &self.envelope_coeff);
+            }
+
+            // The mean of a rectified sine wave is `2/pi` times its amplitude.
+            self.magnitudes[i] = env * FRAC_PI_2;
+        }
+
+        &self.magnitudes
+    }
+
+    /// The magnitudes computed in the last call to `process_block`.
+    pub fn magnitudes(&self) -> &[f32] {
+        &self.magnitudes
+    }
+
+    pub fn reset(&mut self) {
+        for s in self.svf_states.iter_mut() {
+            s.reset();
+        }
+        for s in self.envelope_states.iter_mut() {
+            s.reset();
+        }
+        self.magnitudes.fill(0.0);
+    }
+}
+
+/// Returns `num_bins` frequencies log-spaced between `min_hz` and `max_hz`
+/// (inclusive).
+pub fn log_spaced_freqs(num_bins: usize, min_hz: f32, max_hz: f32) -> Vec<f32> {
+    if num_bins == 1 {
+        return vec![(min_hz * max_hz).sqrt()];
+    }
+
+    let ratio = (max_hz / min_hz).powf(1.0 / (num_bins - 1) as f32);
+
+    (0..num_bins)
+        .map(|i| min_hz * ratio.powi(i as i32))
+        .collect()
+}
+
+/// Returns the Q for `num_bins` log-spaced bandpass filters between `min_hz`
+/// and `max_hz` so that neighboring bands cross at their -3 dB points.
+pub fn constant_q(num_bins: usize, min_hz: f32, max_hz: f32) -> f32 {
+    if num_bins == 1 {
+        return Q_BUTTERWORTH_ORD2 as f32;
+    }
+
+    let ratio = (max_hz / min_hz).powf(1.0 / (num_bins - 1) as f32);
+
+    ratio.sqrt() / (ratio - 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::sine;
+
+    /// A sine at a bin's center frequency reads as about `1.0` in that bin,
+    /// and louder there than in any other bin.
+    #[test]
+    fn sine_at_center_peaks_in_its_bin() {
+        const SAMPLE_RATE: f64 = 48_000.0;
+
+        let mut bank = ConstantQBank::new(16, 50.0, 10_000.0, SAMPLE_RATE);
+
+        for i in 0..bank.num_bins() {
+            let freq_hz = bank.center_freqs_hz()[i];
+            bank.reset();
+            let magnitudes = bank.process_block(&sine(freq_hz as f64, SAMPLE_RATE, 24_000));
+
+            let loudest = (0..magnitudes.len())
+                .max_by(|&a, &b| magnitudes[a].total_cmp(&magnitudes[b]))
+                .unwrap();
+            assert_eq!(loudest, i, "{freq_hz} Hz: {magnitudes:?}");
+            assert!(
+                (0.8..1.2).contains(&magnitudes[i]),
+                "{freq_hz} Hz: {}",
+                magnitudes[i]
+            );
+        }
+    }
+}
@@ -44,6 +44,28 @@ pub mod f32 {
         }
     }
 
+    /// Returns the decibel value from the given raw amplitude.
+    ///
+    /// Unlike [`amp_to_db`], this never returns `f32::NEG_INFINITY`. Any value
+    /// below `floor_db` (including silence) is returned as `floor_db`.
+    #[inline]
+    pub fn amp_to_db_floored(amp: f32, floor_db: f32) -> f32 {
+        if amp <= 0.0 {
+            floor_db
+        } else {
+            amp_to_db(amp).max(floor_db)
+        }
+    }
+
+    /// Returns the raw amplitude from the given decibel value.
+    ///
+    /// Any value `<= floor_db` (including `f32::NEG_INFINITY`) is treated as
+    /// `floor_db`, so this never returns `0.0`.
+    #[inline]
+    pub fn db_to_amp_floored(db: f32, floor_db: f32) -> f32 {
+        db_to_amp(db.max(floor_db))
+    }
+
     /// Map the linear volume (where `0.0` means mute and `1.0` means unity
     /// gain) to the corresponding raw amplitude value (not decibels) for use in
     /// DSP. Values above `1.0` are allowed.
@@ -121,6 +143,28 @@ pub mod f64 {
         }
     }
 
+    /// Returns the decibel value from the given raw amplitude.
+    ///
+    /// Unlike [`amp_to_db`], this never returns `f64::NEG_INFINITY`. Any value
+    /// below `floor_db` (including silence) is returned as `floor_db`.
+    #[inline]
+    pub fn amp_to_db_floored(amp: f64, floor_db: f64) -> f64 {
+        if amp <= 0.0 {
+            floor_db
+        } else {
+            amp_to_db(amp).max(floor_db)
+        }
+    }
+
+    /// Returns the raw amplitude from the given decibel value.
+    ///
+    /// Any value `<= floor_db` (including `f64::NEG_INFINITY`) is treated as
+    /// `floor_db`, so this never returns `0.0`.
+    #[inline]
+    pub fn db_to_amp_floored(db: f64, floor_db: f64) -> f64 {
+        db_to_amp(db.max(floor_db))
+    }
+
     /// Map the linear volume (where `0.0` means mute and `1.0` means unity
     /// gain) to the corresponding raw amplitude value (not decibels) for use in
     /// DSP. Values above `1.0` are allowed.
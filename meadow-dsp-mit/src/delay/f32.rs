@@ -0,0 +1,152 @@
+/// A delay line which stores the last `CAPACITY` samples in a ring buffer.
+///
+/// The history is stored inline, so this never allocates (but note that a
+/// large `CAPACITY` makes the struct itself large).
+#[derive(Debug, Clone, Copy)]
+pub struct DelayLine<const CAPACITY: usize> {
+    buffer: [f32; CAPACITY],
+    /// The index of the most recently pushed sample.
+    write_i: usize,
+}
+
+impl<const CAPACITY: usize> DelayLine<CAPACITY> {
+    /// The longest delay in samples which can be tapped.
+    pub const MAX_DELAY: usize = CAPACITY - 1;
+
+    /// Create a new delay line filled with silence.
+    pub fn new() -> Self {
+        const { assert!(CAPACITY > 0) };
+
+        Self {
+            buffer: [0.0; CAPACITY],
+            write_i: 0,
+        }
+    }
+
+    /// Push a new sample into the delay line, replacing the oldest sample.
+    #[inline(always)]
+    pub fn push(&mut self, sample: f32) {
+        self.write_i += 1;
+        if self.write_i == CAPACITY {
+            self.write_i = 0;
+        }
+
+        self.buffer[self.write_i] = sample;
+    }
+
+    /// Read the sample from `delay_samples` samples ago, where a delay of
+    /// `0.0` is the most recently pushed sample. Fractional delays are
+    /// linearly interpolated.
+    ///
+    /// `delay_samples` is clamped to the range `[0.0, MAX_DELAY]`, and a NaN
+    /// delay is treated as `0.0`.
+    #[inline]
+    pub fn tap(&self, delay_samples: f32) -> f32 {
+        // Unlike `clamp`, `max` returns the other operand for a NaN.
+        let delay = delay_samples.max(0.0).min(Self::MAX_DELAY as f32);
+
+        let delay_int = delay as usize;
+        let frac = delay - delay_int as f32;
+
+        let a = self.tap_int(delay_int);
+        if frac == 0.0 {
+            return a;
+        }
+
+        let b = self.tap_int((delay_int + 1).min(Self::MAX_DELAY));
+        a + (b - a) * frac
+    }
+
+    /// Read the sample from exactly `delay_samples` samples ago, without
+    /// interpolation.
+    ///
+    /// # Panics
+    /// Panics if `delay_samples > MAX_DELAY`.
+    #[inline]
+    pub fn tap_int(&self, delay_samples: usize) -> f32 {
+        assert!(delay_samples <= Self::MAX_DELAY);
+
+        let i = if delay_samples <= self.write_i {
+            self.write_i - delay_samples
+        } else {
+            self.write_i + CAPACITY - delay_samples
+        };
+
+        self.buffer[i]
+    }
+
+    /// Fill the delay line with silence.
+    pub fn reset(&mut self) {
+        self.buffer = [0.0; CAPACITY];
+        self.write_i = 0;
+    }
+}
+
+impl<const CAPACITY: usize> Default for DelayLine<CAPACITY> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CAPACITY: usize = 16;
+
+    /// A delay line holding a ramp, where the sample pushed `n` samples ago
+    /// has the value `latest - n`.
+    fn ramp(num_pushed: usize) -> (DelayLine<CAPACITY>, f32) {
+        let mut delay_line = DelayLine::new();
+        for i in 0..num_pushed {
+            delay_line.push(i as f32);
+        }
+
+        (delay_line, (num_pushed - 1) as f32)
+    }
+
+    #[test]
+    fn integer_taps() {
+        // Including pushing enough samples to wrap around the ring buffer
+        // more than once.
+        for num_pushed in [CAPACITY, CAPACITY + 1, 2 * CAPACITY + 5] {
+            let (delay_line, latest) = ramp(num_pushed);
+
+            for delay in 0..=DelayLine::<CAPACITY>::MAX_DELAY {
+                let expected = latest - delay as f32;
+                assert_eq!(delay_line.tap_int(delay), expected);
+                assert_eq!(delay_line.tap(delay as f32), expected);
+            }
+        }
+    }
+
+    /// Linear interpolation of a ramp is exact.
+    #[test]
+    fn fractional_taps_on_a_ramp() {
+        for num_pushed in [CAPACITY, 2 * CAPACITY + 5] {
+            let (delay_line, latest) = ramp(num_pushed);
+
+            for delay in 0..DelayLine::<CAPACITY>::MAX_DELAY {
+                for frac in [0.25, 0.5, 0.75] {
+                    let delay = delay as f32 + frac;
+                    assert_eq!(delay_line.tap(delay), latest - delay);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn out_of_range_taps_are_clamped() {
+        let (delay_line, _) = ramp(2 * CAPACITY + 5);
+        let max_delay = DelayLine::<CAPACITY>::MAX_DELAY;
+        let oldest = delay_line.tap_int(max_delay);
+        let latest = delay_line.tap_int(0);
+
+        assert_eq!(delay_line.tap(max_delay as f32 + 0.5), oldest);
+        assert_eq!(delay_line.tap(1_000.0), oldest);
+        assert_eq!(delay_line.tap(f32::INFINITY), oldest);
+        assert_eq!(delay_line.tap(-0.5), latest);
+        assert_eq!(delay_line.tap(f32::NEG_INFINITY), latest);
+        assert_eq!(delay_line.tap(f32::NAN), latest);
+    }
+}
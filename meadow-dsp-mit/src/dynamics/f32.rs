@@ -0,0 +1,360 @@
+use std::f64::consts::PI;
+
+use crate::{
+    decibel::f32::{amp_to_db_floored, db_to_amp},
+    filter::one_pole_iir::{
+        f32::{OnePoleIirCoeff, OnePoleIirState},
+        f64::OnePoleIirCoeff as OnePoleIirCoeffF64,
+    },
+    smooth::f32::{OnePoleSmoother, DEFAULT_SMOOTH_SECS},
+};
+
+/// The default attack time of an [`EnvelopeFollower`] in milliseconds.
+pub const DEFAULT_ATTACK_MS: f32 = 10.0;
+/// The default release time of an [`EnvelopeFollower`] in milliseconds.
+pub const DEFAULT_RELEASE_MS: f32 = 100.0;
+
+/// The time constant of the averaging used by [`DetectionMode::Rms`] in
+/// milliseconds.
+pub const RMS_WINDOW_MS: f32 = 10.0;
+
+/// How the level of the signal is detected by an [`EnvelopeFollower`].
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DetectionMode {
+    /// Follow the absolute value of the signal.
+    #[default]
+    Peak,
+    /// Follow the RMS level of the signal, averaged over about
+    /// [`RMS_WINDOW_MS`].
+    Rms,
+}
+
+/// An "analog-style" envelope follower.
+///
+/// The detected level is smoothed with a one-pole lowpass filter, using one
+/// set of coefficients while the level is rising (attack) and another while
+/// it is falling (release).
+#[derive(Clone, Copy)]
+pub struct EnvelopeFollower {
+    mode: DetectionMode,
+
+    attack_ms: f32,
+    release_ms: f32,
+
+    attack_coeff: OnePoleIirCoeff,
+    release_coeff: OnePoleIirCoeff,
+    state: OnePoleIirState,
+
+    rms_coeff: OnePoleIirCoeff,
+    rms_state: OnePoleIirState,
+
+    sample_rate_recip: f64,
+}
+
+impl EnvelopeFollower {
+    pub fn new(attack_ms: f32, release_ms: f32, mode: DetectionMode, sample_rate: f64) -> Self {
+        let sample_rate_recip = sample_rate.recip();
+
+        Self {
+            mode,
+            attack_ms,
+            release_ms,
+            attack_coeff: time_coeff(attack_ms, sample_rate_recip),
+            release_coeff: time_coeff(release_ms, sample_rate_recip),
+            state: OnePoleIirState::default(),
+            rms_coeff: time_coeff(RMS_WINDOW_MS, sample_rate_recip),
+            rms_state: OnePoleIirState::default(),
+            sample_rate_recip,
+        }
+    }
+
+    pub fn mode(&self) -> DetectionMode {
+        self.mode
+    }
+
+    /// Set the detection mode. This resets the envelope.
+    pub fn set_mode(&mut self, mode: DetectionMode) {
+        if self.mode != mode {
+            self.mode = mode;
+            self.reset();
+        }
+    }
+
+    pub fn attack_ms(&self) -> f32 {
+        self.attack_ms
+    }
+
+    /// Set the attack time in milliseconds, i.e. the time it takes the
+    /// envelope to rise about 63% of the way towards a louder level.
+    pub fn set_attack_ms(&mut self, attack_ms: f32) {
+        self.attack_ms = attack_ms;
+        self.attack_coeff = time_coeff(attack_ms, self.sample_rate_recip);
+    }
+
+    pub fn release_ms(&self) -> f32 {
+        self.release_ms
+    }
+
+    /// Set the release time in milliseconds, i.e. the time it takes the
+    /// envelope to fall about 63% of the way towards a quieter level.
+    pub fn set_release_ms(&mut self, release_ms: f32) {
+        self.release_ms = release_ms;
+        self.release_coeff = time_coeff(release_ms, self.sample_rate_recip);
+    }
+
+    /// The current value of the envelope (in raw amplitude).
+    pub fn envelope(&self) -> f32 {
+        self.state.z1
+    }
+
+    /// Advance by one sample and return the new value of the envelope (in
+    /// raw amplitude).
+    #[inline(always)]
+    pub fn process(&mut self, sample: f32) -> f32 {
+        let level = match self.mode {
+            DetectionMode::Peak => sample.abs(),
+            DetectionMode::Rms => self.rms_state.tick(sample * sample, &self.rms_coeff).sqrt(),
+        };
+
+        let coeff = if level > self.state.z1 {
+            &self.attack_coeff
+        } else {
+            &self.release_coeff
+        };
+        self.state.tick(level, coeff)
+    }
+
+    pub fn reset(&mut self) {
+        self.state.reset();
+        self.rms_state.reset();
+    }
+}
+
+pub const DEFAULT_THRESHOLD_DB: f32 = -18.0;
+pub const DEFAULT_RATIO: f32 = 4.0;
+pub const DEFAULT_KNEE_DB: f32 = 6.0;
+
+/// Levels below this are treated as this value by the gain computer of a
+/// [`Compressor`].
+const LEVEL_FLOOR_DB: f32 = -120.0;
+
+/// A feed-forward compressor with a soft knee.
+///
+/// The level of the input is detected with a peak [`EnvelopeFollower`], and
+/// the gain reduction is then computed in the decibel domain.
+pub struct Compressor {
+    threshold_db: f32,
+    ratio: f32,
+    knee_db: f32,
+    makeup_db: f32,
+
+    envelope: EnvelopeFollower,
+    makeup: OnePoleSmoother,
+
+    gain_reduction_db: f32,
+}
+
+impl Compressor {
+    pub const LATENCY: u32 = 0;
+
+    pub fn new(sample_rate: f64) -> Self {
+        Self {
+            threshold_db: DEFAULT_THRESHOLD_DB,
+            ratio: DEFAULT_RATIO,
+            knee_db: DEFAULT_KNEE_DB,
+            makeup_db: 0.0,
+            envelope: EnvelopeFollower::new(
+                DEFAULT_ATTACK_MS,
+                DEFAULT_RELEASE_MS,
+                DetectionMode::Peak,
+                sample_rate,
+            ),
+            makeup: OnePoleSmoother::new(0.0, DEFAULT_SMOOTH_SECS, sample_rate),
+            gain_reduction_db: 0.0,
+        }
+    }
+
+    pub fn threshold_db(&self) -> f32 {
+        self.threshold_db
+    }
+
+    pub fn set_threshold_db(&mut self, threshold_db: f32) {
+        self.threshold_db = threshold_db;
+    }
+
+    pub fn ratio(&self) -> f32 {
+        self.ratio
+    }
+
+    /// Set the compression ratio. Values below `1.0` are clamped to `1.0`
+    /// (no compression), and `f32::INFINITY` turns the compressor into a
+    /// limiter.
+    pub fn set_ratio(&mut self, ratio: f32) {
+        self.ratio = ratio.max(1.0);
+    }
+
+    pub fn knee_db(&self) -> f32 {
+        self.knee_db
+    }
+
+    /// Set the width of the soft knee in decibels, centered on the
+    /// threshold. `0.0` gives a hard knee.
+    pub fn set_knee_db(&mut self, knee_db: f32) {
+        self.knee_db = knee_db.max(0.0);
+    }
+
+    pub fn attack_ms(&self) -> f32 {
+        self.envelope.attack_ms()
+    }
+
+    pub fn set_attack_ms(&mut self, attack_ms: f32) {
+        self.envelope.set_attack_ms(attack_ms);
+    }
+
+    pub fn release_ms(&self) -> f32 {
+        self.envelope.release_ms()
+    }
+
+    pub fn set_release_ms(&mut self, release_ms: f32) {
+        self.envelope.set_release_ms(release_ms);
+    }
+
+    pub fn makeup_db(&self) -> f32 {
+        self.makeup_db
+    }
+
+    /// Set the makeup gain in decibels. Changes are smoothed to avoid clicks.
+    pub fn set_makeup_db(&mut self, makeup_db: f32) {
+        self.makeup_db = makeup_db;
+        self.makeup.set(makeup_db);
+    }
+
+    /// The gain reduction applied to the most recently processed sample in
+    /// decibels (a positive value, not including the makeup gain). This is
+    /// meant to be used for metering.
+    pub fn gain_reduction_db(&self) -> f32 {
+        self.gain_reduction_db
+    }
+
+    /// Process the given buffer in place.
+    pub fn process_block(&mut self, buf: &mut [f32]) {
+        for out in buf.iter_mut() {
+            let env = self.envelope.process(*out);
+            let level_db = amp_to_db_floored(env, LEVEL_FLOOR_DB);
+
+            self.gain_reduction_db = self.compute_gain_reduction_db(level_db);
+
+            let makeup_db = if self.makeup.is_smoothing() {
+                self.makeup.next_value()
+            } else {
+                self.makeup.target()
+            };
+
+            *out *= db_to_amp(makeup_db - self.gain_reduction_db);
+        }
+    }
+
+    /// Reset the envelope and jump the makeup gain straight to its target.
+    /// The parameters are left untouched.
+    pub fn reset(&mut self) {
+        self.envelope.reset();
+        self.makeup.finish();
+        self.gain_reduction_db = 0.0;
+    }
+
+    /// The static curve of the compressor.
+    fn compute_gain_reduction_db(&self, level_db: f32) -> f32 {
+        let over_db = level_db - self.threshold_db;
+        let slope = 1.0 - self.ratio.recip();
+
+        if 2.0 * over_db <= -self.knee_db {
+            0.0
+        } else if 2.0 * over_db < self.knee_db {
+            let x = over_db + 0.5 * self.knee_db;
+            slope * x * x / (2.0 * self.knee_db)
+        } else {
+            slope * over_db
+        }
+    }
+}
+
+/// A one-pole lowpass with the given time constant in milliseconds.
+fn time_coeff(time_ms: f32, sample_rate_recip: f64) -> OnePoleIirCoeff {
+    if time_ms <= 0.0 {
+        // Follow the level instantly.
+        return OnePoleIirCoeff {
+            a0: 1.0,
+            b1: 0.0,
+            m0: 0.0,
+            m1: 1.0,
+        };
+    }
+
+    let cutoff_hz = 1_000.0 / (2.0 * PI * time_ms as f64);
+    OnePoleIirCoeffF64::lowpass(cutoff_hz, sample_rate_recip).to_f32()
+}
+
+/// Levels below this fraction of the ceiling of a [`SoftLimiter`] (about
+/// 1.9 dB below the ceiling) pass through untouched.
+pub const SOFT_LIMITER_KNEE: f32 = 0.8;
+
+/// A soft limiter without any memory (a waveshaper rather than a
+/// compressor), whose output never exceeds its ceiling.
+///
+/// Levels below [`SOFT_LIMITER_KNEE`] times the ceiling are untouched, and
+/// higher levels are bent smoothly towards the ceiling with a `tanh` curve
+/// whose slope matches at the knee. This is meant as a safety net to catch
+/// stray peaks.
+///
+/// Since this is nonlinear, it adds harmonics to the peaks it limits, and
+/// those above the Nyquist frequency alias back down. This is inaudible for
+/// the occasional peak, but if the limiter is driven hard on purpose the
+/// signal should be oversampled around it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SoftLimiter {
+    knee: f32,
+    ceiling: f32,
+}
+
+impl SoftLimiter {
+    pub const LATENCY: u32 = 0;
+
+    /// Create a limiter with the given ceiling in decibels.
+    pub fn new(ceiling_db: f32) -> Self {
+        Self::from_ceiling(db_to_amp(ceiling_db))
+    }
+
+    /// Create a limiter with the given ceiling as a raw amplitude.
+    pub const fn from_ceiling(ceiling: f32) -> Self {
+        Self {
+            knee: ceiling * SOFT_LIMITER_KNEE,
+            ceiling,
+        }
+    }
+
+    /// The ceiling as a raw amplitude.
+    pub fn ceiling(&self) -> f32 {
+        self.ceiling
+    }
+
+    #[inline(always)]
+    pub fn tick(&self, x: f32) -> f32 {
+        let a = x.abs();
+
+        if a <= self.knee {
+            x
+        } else {
+            let range = self.ceiling - self.knee;
+            (self.knee + range * ((a - self.knee) / range).tanh())
+                .min(self.ceiling)
+                .copysign(x)
+        }
+    }
+
+    /// Process the given buffer in place.
+    pub fn process(&self, buf: &mut [f32]) {
+        for s in buf.iter_mut() {
+            *s = self.tick(*s);
+        }
+    }
+}
@@ -0,0 +1,175 @@
+//! A C ABI for the core filters.
+//!
+//! Each filter is exposed as an opaque handle which holds both the
+//! coefficients and the state of the filter.
+//!
+//! # Ownership
+//!
+//! * A handle returned by a `*_new` function is owned by the caller, and it
+//!   must be freed exactly once with the matching `*_free` function.
+//! * A handle must not be used after it has been freed.
+//! * A handle is not thread-safe. It may be moved between threads, but it must
+//!   not be used from multiple threads at the same time.
+//! * The library never holds on to a buffer pointer after a function returns.
+
+use crate::filter::{
+    one_pole_iir::f32::{OnePoleIirCoeff, OnePoleIirState},
+    svf::f32::{SvfCoeff, SvfState},
+};
+
+/// An opaque handle to an SVF (state variable filter).
+pub struct MeadowSvf {
+    coeff: SvfCoeff,
+    state: SvfState,
+}
+
+/// Create a new SVF handle. The filter initially passes the signal through
+/// unchanged.
+#[no_mangle]
+pub extern "C" fn meadow_svf_new() -> *mut MeadowSvf {
+    Box::into_raw(Box::new(MeadowSvf {
+        coeff: SvfCoeff::NO_OP,
+        state: SvfState::default(),
+    }))
+}
+
+/// Free an SVF handle.
+///
+/// # Safety
+/// `svf` must be null or a handle returned by [`meadow_svf_new`] that has not
+/// already been freed.
+#[no_mangle]
+pub unsafe extern "C" fn meadow_svf_free(svf: *mut MeadowSvf) {
+    if !svf.is_null() {
+        drop(Box::from_raw(svf));
+    }
+}
+
+/// Set the raw coefficients of the SVF.
+///
+/// # Safety
+/// `svf` must be a valid handle returned by [`meadow_svf_new`].
+#[no_mangle]
+pub unsafe extern "C" fn meadow_svf_set_coeffs(
+    svf: *mut MeadowSvf,
+    a1: f32,
+    a2: f32,
+    a3: f32,
+    m0: f32,
+    m1: f32,
+    m2: f32,
+) {
+    (*svf).coeff = SvfCoeff {
+        a1,
+        a2,
+        a3,
+        m0,
+        m1,
+        m2,
+    };
+}
+
+/// Reset the state of the SVF.
+///
+/// # Safety
+/// `svf` must be a valid handle returned by [`meadow_svf_new`].
+#[no_mangle]
+pub unsafe extern "C" fn meadow_svf_reset(svf: *mut MeadowSvf) {
+    (*svf).state.reset();
+}
+
+/// Process a buffer of `len` samples in place.
+///
+/// # Safety
+/// `svf` must be a valid handle returned by [`meadow_svf_new`], and `buf` must
+/// point to `len` valid and initialized samples (it may be null if
+/// `len == 0`).
+#[no_mangle]
+pub unsafe extern "C" fn meadow_svf_process(svf: *mut MeadowSvf, buf: *mut f32, len: usize) {
+    if len == 0 {
+        return;
+    }
+
+    let svf = &mut *svf;
+    let buf = std::slice::from_raw_parts_mut(buf, len);
+
+    for s in buf.iter_mut() {
+        *s = svf.state.tick(*s, &svf.coeff);
+    }
+}
+
+/// An opaque handle to a one-pole IIR filter.
+pub struct MeadowOnePole {
+    coeff: OnePoleIirCoeff,
+    state: OnePoleIirState,
+}
+
+/// Create a new one-pole IIR filter handle. The filter initially passes the
+/// signal through unchanged.
+#[no_mangle]
+pub extern "C" fn meadow_one_pole_new() -> *mut MeadowOnePole {
+    Box::into_raw(Box::new(MeadowOnePole {
+        coeff: OnePoleIirCoeff::NO_OP,
+        state: OnePoleIirState::default(),
+    }))
+}
+
+/// Free a one-pole IIR filter handle.
+///
+/// # Safety
+/// `one_pole` must be null or a handle returned by [`meadow_one_pole_new`]
+/// that has not already been freed.
+#[no_mangle]
+pub unsafe extern "C" fn meadow_one_pole_free(one_pole: *mut MeadowOnePole) {
+    if !one_pole.is_null() {
+        drop(Box::from_raw(one_pole));
+    }
+}
+
+/// Set the raw coefficients of the one-pole IIR filter.
+///
+/// # Safety
+/// `one_pole` must be a valid handle returned by [`meadow_one_pole_new`].
+#[no_mangle]
+pub unsafe extern "C" fn meadow_one_pole_set_coeffs(
+    one_pole: *mut MeadowOnePole,
+    a0: f32,
+    b1: f32,
+    m0: f32,
+    m1: f32,
+) {
+    (*one_pole).coeff = OnePoleIirCoeff { a0, b1, m0, m1 };
+}
+
+/// Reset the state of the one-pole IIR filter.
+///
+/// # Safety
+/// `one_pole` must be a valid handle returned by [`meadow_one_pole_new`].
+#[no_mangle]
+pub unsafe extern "C" fn meadow_one_pole_reset(one_pole: *mut MeadowOnePole) {
+    (*one_pole).state.reset();
+}
+
+/// Process a buffer of `len` samples in place.
+///
+/// # Safety
+/// `one_pole` must be a valid handle returned by [`meadow_one_pole_new`], and
+/// `buf` must point to `len` valid and initialized samples (it may be null if
+/// `len == 0`).
+#[no_mangle]
+pub unsafe extern "C" fn meadow_one_pole_process(
+    one_pole: *mut MeadowOnePole,
+    buf: *mut f32,
+    len: usize,
+) {
+    if len == 0 {
+        return;
+    }
+
+    let one_pole = &mut *one_pole;
+    let buf = std::slice::from_raw_parts_mut(buf, len);
+
+    for s in buf.iter_mut() {
+        *s = one_pole.state.tick(*s, &one_pole.coeff);
+    }
+}
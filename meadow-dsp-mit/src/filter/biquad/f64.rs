@@ -0,0 +1,270 @@
+//! A direct form I biquad filter using the coefficient formulas from Robert
+//! Bristow-Johnson's "Audio EQ Cookbook".
+//! https://webaudio.github.io/Audio-EQ-Cookbook/audio-eq-cookbook.html
+//!
+//! This is meant to be used as a reference to cross-validate the other filter
+//! models against. Prefer the SVF model for actual processing since it behaves
+//! much better under modulation.
+
+use std::f64::consts::PI;
+
+/// The coefficients for a biquad filter, normalized so that `a0 == 1.0`.
+#[derive(Default, Clone, Copy, PartialEq)]
+pub struct BiquadCoeff {
+    pub b0: f64,
+    pub b1: f64,
+    pub b2: f64,
+
+    pub a1: f64,
+    pub a2: f64,
+}
+
+impl BiquadCoeff {
+    pub const NO_OP: Self = Self {
+        b0: 1.0,
+        b1: 0.0,
+        b2: 0.0,
+        a1: 0.0,
+        a2: 0.0,
+    };
+
+    pub fn lowpass(cutoff_hz: f64, q: f64, sample_rate_recip: f64) -> Self {
+        let (cos_w0, alpha) = cos_w0_and_alpha(cutoff_hz, q, sample_rate_recip);
+
+        Self::from_unnormalized(
+            (1.0 - cos_w0) * 0.5,
+            1.0 - cos_w0,
+            (1.0 - cos_w0) * 0.5,
+            1.0 + alpha,
+            -2.0 * cos_w0,
+            1.0 - alpha,
+        )
+    }
+
+    pub fn highpass(cutoff_hz: f64, q: f64, sample_rate_recip: f64) -> Self {
+        let (cos_w0, alpha) = cos_w0_and_alpha(cutoff_hz, q, sample_rate_recip);
+
+        Self::from_unnormalized(
+            (1.0 + cos_w0) * 0.5,
+            -(1.0 + cos_w0),
+            (1.0 + cos_w0) * 0.5,
+            1.0 + alpha,
+            -2.0 * cos_w0,
+            1.0 - alpha,
+        )
+    }
+
+    /// A bandpass filter normalized to have a gain of `1.0` (0 dB) at
+    /// `cutoff_hz`.
+    pub fn bandpass(cutoff_hz: f64, q: f64, sample_rate_recip: f64) -> Self {
+        let (cos_w0, alpha) = cos_w0_and_alpha(cutoff_hz, q, sample_rate_recip);
+
+        Self::from_unnormalized(alpha, 0.0, -alpha, 1.0 + alpha, -2.0 * cos_w0, 1.0 - alpha)
+    }
+
+    pub fn notch(cutoff_hz: f64, q: f64, sample_rate_recip: f64) -> Self {
+        let (cos_w0, alpha) = cos_w0_and_alpha(cutoff_hz, q, sample_rate_recip);
+
+        Self::from_unnormalized(
+            1.0,
+            -2.0 * cos_w0,
+            1.0,
+            1.0 + alpha,
+            -2.0 * cos_w0,
+            1.0 - alpha,
+        )
+    }
+
+    pub fn bell(cutoff_hz: f64, q: f64, gain_db: f64, sample_rate_recip: f64) -> Self {
+        let a = gain_db_to_a(gain_db);
+        let (cos_w0, alpha) = cos_w0_and_alpha(cutoff_hz, q, sample_rate_recip);
+
+        Self::from_unnormalized(
+            1.0 + alpha * a,
+            -2.0 * cos_w0,
+            1.0 - alpha * a,
+            1.0 + alpha / a,
+            -2.0 * cos_w0,
+            1.0 - alpha / a,
+        )
+    }
+
+    pub fn low_shelf(cutoff_hz: f64, q: f64, gain_db: f64, sample_rate_recip: f64) -> Self {
+        let a = gain_db_to_a(gain_db);
+        let (cos_w0, alpha) = cos_w0_and_alpha(cutoff_hz, q, sample_rate_recip);
+        let two_sqrt_a_alpha = 2.0 * a.sqrt() * alpha;
+
+        Self::from_unnormalized(
+            a * ((a + 1.0) - (a - 1.0) * cos_w0 + two_sqrt_a_alpha),
+            2.0 * a * ((a - 1.0) - (a + 1.0) * cos_w0),
+            a * ((a + 1.0) - (a - 1.0) * cos_w0 - two_sqrt_a_alpha),
+            (a + 1.0) + (a - 1.0) * cos_w0 + two_sqrt_a_alpha,
+            -2.0 * ((a - 1.0) + (a + 1.0) * cos_w0),
+            (a + 1.0) + (a - 1.0) * cos_w0 - two_sqrt_a_alpha,
+        )
+    }
+
+    pub fn high_shelf(cutoff_hz: f64, q: f64, gain_db: f64, sample_rate_recip: f64) -> Self {
+        let a = gain_db_to_a(gain_db);
+        let (cos_w0, alpha) = cos_w0_and_alpha(cutoff_hz, q, sample_rate_recip);
+        let two_sqrt_a_alpha = 2.0 * a.sqrt() * alpha;
+
+        Self::from_unnormalized(
+            a * ((a + 1.0) + (a - 1.0) * cos_w0 + two_sqrt_a_alpha),
+            -2.0 * a * ((a - 1.0) + (a + 1.0) * cos_w0),
+            a * ((a + 1.0) + (a - 1.0) * cos_w0 - two_sqrt_a_alpha),
+            (a + 1.0) - (a - 1.0) * cos_w0 + two_sqrt_a_alpha,
+            2.0 * ((a - 1.0) - (a + 1.0) * cos_w0),
+            (a + 1.0) - (a - 1.0) * cos_w0 - two_sqrt_a_alpha,
+        )
+    }
+
+    pub fn allpass(cutoff_hz: f64, q: f64, sample_rate_recip: f64) -> Self {
+        let (cos_w0, alpha) = cos_w0_and_alpha(cutoff_hz, q, sample_rate_recip);
+
+        Self::from_unnormalized(
+            1.0 - alpha,
+            -2.0 * cos_w0,
+            1.0 + alpha,
+            1.0 + alpha,
+            -2.0 * cos_w0,
+            1.0 - alpha,
+        )
+    }
+
+    pub fn from_unnormalized(b0: f64, b1: f64, b2: f64, a0: f64, a1: f64, a2: f64) -> Self {
+        let a0_recip = a0.recip();
+
+        Self {
+            b0: b0 * a0_recip,
+            b1: b1 * a0_recip,
+            b2: b2 * a0_recip,
+            a1: a1 * a0_recip,
+            a2: a2 * a0_recip,
+        }
+    }
+
+    /// Returns the magnitude (raw amplitude, not decibels) of the frequency
+    /// response of this filter at the given frequency.
+    pub fn magnitude(&self, freq_hz: f64, sample_rate_recip: f64) -> f64 {
+        let w = 2.0 * PI * freq_hz * sample_rate_recip;
+        let (sin_w, cos_w) = w.sin_cos();
+        let (sin_2w, cos_2w) = (2.0 * w).sin_cos();
+
+        let num_re = self.b0 + self.b1 * cos_w + self.b2 * cos_2w;
+        let num_im = -(self.b1 * sin_w + self.b2 * sin_2w);
+        let den_re = 1.0 + self.a1 * cos_w + self.a2 * cos_2w;
+        let den_im = -(self.a1 * sin_w + self.a2 * sin_2w);
+
+        ((num_re * num_re + num_im * num_im) / (den_re * den_re + den_im * den_im)).sqrt()
+    }
+}
+
+/// The state of a direct form I biquad filter.
+#[derive(Default, Clone, Copy, PartialEq)]
+pub struct BiquadState {
+    pub x1: f64,
+    pub x2: f64,
+    pub y1: f64,
+    pub y2: f64,
+}
+
+impl BiquadState {
+    #[inline(always)]
+    pub fn tick(&mut self, input: f64, coeff: &BiquadCoeff) -> f64 {
+        let out = coeff.b0 * input + coeff.b1 * self.x1 + coeff.b2 * self.x2
+            - coeff.a1 * self.y1
+            - coeff.a2 * self.y2;
+
+        self.x2 = self.x1;
+        self.x1 = input;
+        self.y2 = self.y1;
+        self.y1 = out;
+
+        out
+    }
+
+    #[inline(always)]
+    pub fn reset(&mut self) {
+        self.x1 = 0.0;
+        self.x2 = 0.0;
+        self.y1 = 0.0;
+        self.y2 = 0.0;
+    }
+}
+
+fn cos_w0_and_alpha(cutoff_hz: f64, q: f64, sample_rate_recip: f64) -> (f64, f64) {
+    let (sin_w0, cos_w0) = (2.0 * PI * cutoff_hz * sample_rate_recip).sin_cos();
+
+    (cos_w0, sin_w0 / (2.0 * q))
+}
+
+fn gain_db_to_a(gain_db: f64) -> f64 {
+    10.0f64.powf(gain_db * (1.0 / 40.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filter::svf::f64::SvfCoeff;
+
+    const SAMPLE_RATE_RECIP: f64 = 1.0 / 48_000.0;
+
+    /// The SVF and the RBJ biquad are both the bilinear transform of the
+    /// same analog prototypes, so their magnitude responses must agree.
+    fn assert_same_magnitude(svf: &SvfCoeff, biquad: &BiquadCoeff, what: &str) {
+        let mut freq_hz = 10.0;
+        while freq_hz < 23_900.0 {
+            let svf_db = 20.0 * svf.magnitude(freq_hz, SAMPLE_RATE_RECIP).log10();
+            let biquad_db = 20.0 * biquad.magnitude(freq_hz, SAMPLE_RATE_RECIP).log10();
+
+            assert!(
+                (svf_db - biquad_db).abs() < 1e-6,
+                "{what} at {freq_hz} Hz: SVF {svf_db} dB, biquad {biquad_db} dB"
+            );
+
+            freq_hz *= 1.05;
+        }
+    }
+
+    #[test]
+    fn svf_matches_biquad() {
+        for cutoff_hz in [30.0, 1_000.0, 12_000.0, 20_000.0] {
+            for q in [0.5, 0.707, 2.0, 8.0] {
+                let what = format!("{cutoff_hz} Hz, q {q}");
+                let r = SAMPLE_RATE_RECIP;
+
+                assert_same_magnitude(
+                    &SvfCoeff::lowpass_ord2(cutoff_hz, q, r),
+                    &BiquadCoeff::lowpass(cutoff_hz, q, r),
+                    &format!("lowpass {what}"),
+                );
+                assert_same_magnitude(
+                    &SvfCoeff::highpass_ord2(cutoff_hz, q, r),
+                    &BiquadCoeff::highpass(cutoff_hz, q, r),
+                    &format!("highpass {what}"),
+                );
+
+                for gain_db in [-18.0, -6.0, 3.0, 12.0] {
+                    let what = format!("{what}, {gain_db} dB");
+
+                    assert_same_magnitude(
+                        &SvfCoeff::bell(cutoff_hz, q, gain_db, r),
+                        &BiquadCoeff::bell(cutoff_hz, q, gain_db, r),
+                        &format!("bell {what}"),
+                    );
+                    assert_same_magnitude(
+                        &SvfCoeff::low_shelf(cutoff_hz, q, gain_db, r),
+                        &BiquadCoeff::low_shelf(cutoff_hz, q, gain_db, r),
+                        &format!("low shelf {what}"),
+                    );
+                    assert_same_magnitude(
+                        &SvfCoeff::high_shelf(cutoff_hz, q, gain_db, r),
+                        &BiquadCoeff::high_shelf(cutoff_hz, q, gain_db, r),
+                        &format!("high shelf {what}"),
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use crate::filter::svf::{f32::SvfState, f64::SvfCoeff as SvfCoeffF64};
+
+/// The default Q of each notch in a [`HumRemover`].
+pub const DEFAULT_HUM_Q: f32 = 20.0;
+
+/// The default number of harmonics notched by a [`HumRemover`] (counting the
+/// fundamental).
+pub const DEFAULT_NUM_HARMONICS: usize = 8;
+
+/// Harmonics above this fraction of the sample rate are not notched.
+const MAX_HARMONIC_FRACTION: f64 = 0.45;
+
+/// Removes mains hum and buzz by placing a narrow notch at a fundamental
+/// (usually 50 or 60 Hz) and at each of its harmonics, up to `MAX_HARMONICS`
+/// notches in total.
+///
+/// Each notch is an SVF notch with a true zero at its frequency (see
+/// [`SvfCoeffF64::notch`]), and the notches are run in series. The
+/// coefficients are kept in f64 since they are very close to each other at
+/// low frequencies, which makes the notches deeper than with f32
+/// coefficients.
+///
+/// All notches share the same Q, so the notches at higher harmonics are wider
+/// in Hz. Harmonics above `0.45` times the sample rate are skipped.
+pub struct HumRemover<const MAX_HARMONICS: usize> {
+    fundamental_hz: f32,
+    num_harmonics: usize,
+    q: f32,
+
+    coeffs: [SvfCoeffF64; MAX_HARMONICS],
+    states: [SvfState; MAX_HARMONICS],
+    num_active: usize,
+
+    sample_rate: f64,
+    sample_rate_recip: f64,
+}
+
+impl<const MAX_HARMONICS: usize> HumRemover<MAX_HARMONICS> {
+    pub const LATENCY: u32 = 0;
+
+    /// Create a new hum remover which notches [`DEFAULT_NUM_HARMONICS`]
+    /// harmonics (clamped to `MAX_HARMONICS`) of `fundamental_hz` with a Q
+    /// of [`DEFAULT_HUM_Q`].
+    ///
+    /// # Panics
+    /// Panics if `MAX_HARMONICS == 0`.
+    pub fn new(fundamental_hz: f32, sample_rate: f64) -> Self {
+        assert_ne!(MAX_HARMONICS, 0);
+
+        let mut new_self = Self {
+            fundamental_hz,
+            num_harmonics: DEFAULT_NUM_HARMONICS.min(MAX_HARMONICS),
+            q: DEFAULT_HUM_Q,
+            coeffs: [SvfCoeffF64::NO_OP; MAX_HARMONICS],
+            states: [SvfState::default(); MAX_HARMONICS],
+            num_active: 0,
+            sample_rate,
+            sample_rate_recip: sample_rate.recip(),
+        };
+
+        new_self.set_fundamental(fundamental_hz);
+
+        new_self
+    }
+
+    pub fn fundamental(&self) -> f32 {
+        self.fundamental_hz
+    }
+
+    /// Set the frequency of the fundamental in Hz (usually `50.0` or `60.0`).
+    pub fn set_fundamental(&mut self, fundamental_hz: f32) {
+        self.fundamental_hz = fundamental_hz.max(1.0);
+        self.update_coeffs();
+    }
+
+    /// The number of harmonics which are notched, counting the fundamental as
+    /// the first one.
+    pub fn num_harmonics(&self) -> usize {
+        self.num_harmonics
+    }
+
+    /// Set the number of harmonics to notch, counting the fundamental as the
+    /// first one. (So `1` only notches the fundamental, and `3` notches the
+    /// fundamental and the 2nd and 3rd harmonics.)
+    ///
+    /// This is clamped to the range `[1, MAX_HARMONICS]`.
+    pub fn set_num_harmonics(&mut self, num_harmonics: usize) {
+        self.num_harmonics = num_harmonics.clamp(1, MAX_HARMONICS);
+        self.update_coeffs();
+    }
+
+    /// The number of notches actually in use. This can be less than
+    /// [`HumRemover::num_harmonics`] if some of the harmonics lie too close to
+    /// the Nyquist frequency.
+    pub fn num_active_notches(&self) -> usize {
+        self.num_active
+    }
+
+    pub fn q(&self) -> f32 {
+        self.q
+    }
+
+    /// Set the Q of every notch. Higher values give narrower notches, which
+    /// remove less of the wanted signal but take longer to settle.
+    pub fn set_q(&mut self, q: f32) {
+        self.q = q.max(0.1);
+        self.update_coeffs();
+    }
+
+    pub fn reset(&mut self) {
+        for s in self.states.iter_mut() {
+            s.reset();
+        }
+    }
+
+    /// Process the given buffer in place.
+    pub fn process(&mut self, buf: &mut [f32]) {
+        for (state, coeff) in self.states[..self.num_active]
+            .iter_mut()
+            .zip(self.coeffs[..self.num_active].iter())
+        {
+            for s in buf.iter_mut() {
+                *s = state.tick_f64_coeff(*s, coeff);
+            }
+        }
+    }
+
+    fn update_coeffs(&mut self) {
+        let max_hz = self.sample_rate * MAX_HARMONIC_FRACTION;
+        let prev_num_active = self.num_active;
+
+        self.num_active = 0;
+        for harmonic in 1..=self.num_harmonics {
+            let freq_hz = self.fundamental_hz as f64 * harmonic as f64;
+            if freq_hz > max_hz {
+                break;
+            }
+
+            self.coeffs[self.num_active] =
+                SvfCoeffF64::notch(freq_hz, self.q as f64, self.sample_rate_recip);
+            self.num_active += 1;
+        }
+
+        // Notches which were just enabled start from silence.
+        for s in self.states[prev_num_active.min(self.num_active)..self.num_active].iter_mut() {
+            s.reset();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::sine_gain_db;
+
+    const SAMPLE_RATE: f64 = 48_000.0;
+
+    #[test]
+    fn removes_fundamental_and_harmonics() {
+        for fundamental_hz in [50.0, 60.0] {
+            let gain_db = |num_harmonics: usize, freq_hz: f64| {
+                let mut hum = HumRemover::<8>::new(fundamental_hz, SAMPLE_RATE);
+                hum.set_num_harmonics(num_harmonics);
+                sine_gain_db(freq_hz, SAMPLE_RATE, |buf| hum.process(buf))
+            };
+            let fundamental_hz = fundamental_hz as f64;
+
+            for harmonic in [1.0, 3.0] {
+                let notched_db = gain_db(DEFAULT_NUM_HARMONICS, harmonic * fundamental_hz);
+                assert!(
+                    notched_db < -40.0,
+                    "{fundamental_hz} Hz, harmonic {harmonic}: {notched_db} dB"
+                );
+            }
+
+            // Only the harmonics which are asked for are notched.
+            let unnotched_db = gain_db(1, 3.0 * fundamental_hz);
+            assert!(
+                unnotched_db.abs() < 0.1,
+                "{fundamental_hz} Hz: {unnotched_db} dB"
+            );
+
+            // Between the harmonics, and far above them, the signal passes.
+            for freq_hz in [1.5 * fundamental_hz, 2.5 * fundamental_hz, 5_000.0] {
+                let passed_db = gain_db(DEFAULT_NUM_HARMONICS, freq_hz);
+                assert!(
+                    passed_db.abs() < 0.5,
+                    "{fundamental_hz} Hz, {freq_hz} Hz: {passed_db} dB"
+                );
+            }
+        }
+    }
+}
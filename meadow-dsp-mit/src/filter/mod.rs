@@ -1,2 +1,5 @@
+pub mod biquad;
+pub mod hum;
 pub mod one_pole_iir;
+pub mod resonant;
 pub mod svf;
@@ -1,5 +1,8 @@
 use std::f32::consts::PI;
 
+use super::f64::OnePoleIirCoeff as OnePoleIirCoeffF64;
+use crate::decibel::f32::db_to_amp;
+
 /// The coefficients for a single-pole IIR filter.
 #[derive(Default, Clone, Copy, PartialEq)]
 pub struct OnePoleIirCoeff {
@@ -23,8 +26,8 @@ impl OnePoleIirCoeff {
         let a0 = 1.0 - b1;
 
         Self {
-            a0: a0 as f32,
-            b1: b1 as f32,
+            a0,
+            b1,
             m0: 0.0,
             m1: 1.0,
         }
@@ -35,15 +38,99 @@ impl OnePoleIirCoeff {
         let a0 = 1.0 - b1;
 
         Self {
-            a0: a0 as f32,
-            b1: b1 as f32,
+            a0,
+            b1,
             m0: 1.0,
             m1: -1.0,
         }
     }
+
+    /// A first-order (6 dB/octave) low shelf filter, for a gentle tilt.
+    ///
+    /// `cutoff_hz` is the midpoint of the transition, where the gain is half
+    /// of `gain_db` (in decibels). The pole and zero are placed with the
+    /// matched z-transform (like [`OnePoleIirCoeff::lowpass`]), so the gain
+    /// above the transition is only approximately 0 dB close to the Nyquist
+    /// frequency.
+    pub fn low_shelf(cutoff_hz: f32, gain_db: f32, sample_rate_recip: f32) -> Self {
+        let a = db_to_amp(gain_db).sqrt();
+
+        Self::shelf(cutoff_hz / a, cutoff_hz * a, a * a, sample_rate_recip)
+    }
+
+    /// A first-order (6 dB/octave) high shelf filter, for a gentle tilt. See
+    /// [`OnePoleIirCoeff::low_shelf`].
+    pub fn high_shelf(cutoff_hz: f32, gain_db: f32, sample_rate_recip: f32) -> Self {
+        let a = db_to_amp(gain_db).sqrt();
+
+        Self::shelf(cutoff_hz * a, cutoff_hz / a, 1.0, sample_rate_recip)
+    }
+
+    /// A first-order allpass filter, which delays the phase by 90 degrees at
+    /// `cutoff_hz`. See [`OnePoleIirCoeffF64::allpass`].
+    ///
+    /// The coefficients get large close to a quarter of the sample rate, and
+    /// the rounding error of an `f32` state grows with them, so prefer the
+    /// `f64` filter if the cutoff can get close to it.
+    pub fn allpass(cutoff_hz: f32, sample_rate_recip: f32) -> Self {
+        OnePoleIirCoeffF64::allpass(cutoff_hz as f64, sample_rate_recip as f64).to_f32()
+    }
+
+    /// A filter with a single pole and a single zero at the given
+    /// frequencies, with the given gain at DC.
+    fn shelf(pole_hz: f32, zero_hz: f32, dc_gain: f32, sample_rate_recip: f32) -> Self {
+        let b1 = ((-2.0 * PI) * pole_hz * sample_rate_recip).exp();
+        let zero = ((-2.0 * PI) * zero_hz * sample_rate_recip).exp();
+        let a0 = 1.0 - b1;
+
+        // The transfer function is `m0 + m1 * a0 / (1 - b1 * z^-1)`, so the
+        // zero is at `m0 * b1 / (m0 + m1 * a0)`.
+        let gain = dc_gain * (1.0 - b1) / (1.0 - zero);
+        let m0 = gain * zero / b1;
+
+        Self {
+            a0,
+            b1,
+            m0,
+            m1: (gain - m0) / a0,
+        }
+    }
+
+    pub fn to_f64(self) -> OnePoleIirCoeffF64 {
+        OnePoleIirCoeffF64 {
+            a0: self.a0 as f64,
+            b1: self.b1 as f64,
+            m0: self.m0 as f64,
+            m1: self.m1 as f64,
+        }
+    }
+
+    /// Returns `true` if every coefficient is within `epsilon` of the matching
+    /// coefficient of `other`. This is `false` if any coefficient is NaN.
+    pub fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        [
+            self.a0 - other.a0,
+            self.b1 - other.b1,
+            self.m0 - other.m0,
+            self.m1 - other.m1,
+        ]
+        .iter()
+        .all(|d| d.abs() <= epsilon)
+    }
+}
+
+impl From<OnePoleIirCoeff> for OnePoleIirCoeffF64 {
+    /// Widen the coefficients to f64. This is lossless.
+    fn from(coeff: OnePoleIirCoeff) -> Self {
+        coeff.to_f64()
+    }
 }
 
 /// The state of a single-pole IIR filter.
+///
+/// The filter is linear and time-invariant. Scaling the input by a constant
+/// scales the output by the same constant only if `z1` starts at zero or is
+/// scaled along with the input, since it holds the response to past input.
 #[derive(Default, Clone, Copy, PartialEq)]
 pub struct OnePoleIirState {
     pub z1: f32,
@@ -56,21 +143,91 @@ impl OnePoleIirState {
         coeff.m0 * input + coeff.m1 * self.z1
     }
 
+    /// The same as [`OnePoleIirState::tick`], but using coefficients stored
+    /// in f64.
+    ///
+    /// The computation is done in f64, and only the state is rounded back to
+    /// f32.
+    #[inline(always)]
+    pub fn tick_f64_coeff(&mut self, input: f32, coeff: &OnePoleIirCoeffF64) -> f32 {
+        let input = input as f64;
+        let z1 = (coeff.a0 * input) + (coeff.b1 * self.z1 as f64);
+        self.z1 = z1 as f32;
+
+        (coeff.m0 * input + coeff.m1 * z1) as f32
+    }
+
     #[inline(always)]
     pub fn reset(&mut self) {
         self.z1 = 0.0;
     }
+
+    /// Set the state to the steady state for a constant input of `input`,
+    /// i.e. the state the filter converges to once the input has been
+    /// constant for a long time.
+    pub fn prime_for_dc(&mut self, input: f32, coeff: &OnePoleIirCoeff) {
+        self.prime_for_dc_f64_coeff(input, &coeff.to_f64());
+    }
+
+    /// The same as [`OnePoleIirState::prime_for_dc`], but using coefficients
+    /// stored in f64.
+    pub fn prime_for_dc_f64_coeff(&mut self, input: f32, coeff: &OnePoleIirCoeffF64) {
+        self.z1 = (coeff.dc_state_gain() * input as f64) as f32;
+    }
+
+    /// Returns `true` if the state is within `epsilon` of that of `other`.
+    /// This is `false` if either of them is NaN.
+    pub fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        (self.z1 - other.z1).abs() <= epsilon
+    }
+}
+
+/// The same as [`OnePoleIirState::tick`], but as a free function so that it
+/// can be referenced by a function pointer.
+#[inline(always)]
+pub fn tick(state: &mut OnePoleIirState, input: f32, coeff: &OnePoleIirCoeff) -> f32 {
+    state.tick(input, coeff)
 }
 
-#[cfg(feature = "portable-simd")]
+/// Several filters packed into SIMD vectors. Without the `portable-simd`
+/// feature the vectors are emulated with scalar code (see [`crate::simd`]).
 pub mod simd {
-    use std::{
-        array,
-        simd::{f32x4, f32x8},
-    };
+    use std::array;
+
+    use crate::simd::{f32x2, f32x4, f32x8};
 
     use super::{OnePoleIirCoeff, OnePoleIirState};
 
+    /// The coefficients of two one-pole IIR filters packed into an SIMD vector.
+    #[derive(Default, Clone, Copy)]
+    pub struct OnePoleIirCoeffx2 {
+        pub a0: f32x2,
+        pub b1: f32x2,
+
+        pub m0: f32x2,
+        pub m1: f32x2,
+    }
+
+    impl OnePoleIirCoeffx2 {
+        pub const fn splat(coeffs: OnePoleIirCoeff) -> Self {
+            Self {
+                a0: f32x2::splat(coeffs.a0),
+                b1: f32x2::splat(coeffs.b1),
+                m0: f32x2::splat(coeffs.m0),
+                m1: f32x2::splat(coeffs.m1),
+            }
+        }
+
+        pub fn load(coeffs: &[OnePoleIirCoeff; 2]) -> Self {
+            Self {
+                a0: f32x2::from_array(array::from_fn(|i| coeffs[i].a0)),
+                b1: f32x2::from_array(array::from_fn(|i| coeffs[i].b1)),
+                m0: f32x2::from_array(array::from_fn(|i| coeffs[i].m0)),
+                m1: f32x2::from_array(array::from_fn(|i| coeffs[i].m1)),
+            }
+        }
+    }
+
     /// The coefficients of four one-pole IIR filters packed into an SIMD vector.
     #[derive(Default, Clone, Copy)]
     pub struct OnePoleIirCoeffx4 {
@@ -131,6 +288,45 @@ pub mod simd {
         }
     }
 
+    /// The state of two single-pole IIR filters packed into an SIMD vector.
+    #[derive(Default, Clone, Copy)]
+    pub struct OnePoleIirStatex2 {
+        z1: f32x2,
+    }
+
+    impl OnePoleIirStatex2 {
+        pub const fn splat(state: OnePoleIirState) -> Self {
+            Self {
+                z1: f32x2::splat(state.z1),
+            }
+        }
+
+        pub fn load(states: &[OnePoleIirState; 2]) -> Self {
+            Self {
+                z1: f32x2::from_array(array::from_fn(|i| states[i].z1)),
+            }
+        }
+
+        pub fn store(&self, states: &mut [OnePoleIirState; 2]) {
+            let z1 = self.z1.to_array();
+
+            for (i, s) in states.iter_mut().enumerate() {
+                s.z1 = z1[i];
+            }
+        }
+
+        #[inline(always)]
+        pub fn tick(&mut self, input: f32x2, coeff: &OnePoleIirCoeffx2) -> f32x2 {
+            self.z1 = (coeff.a0 * input) + (coeff.b1 * self.z1);
+            coeff.m0 * input + coeff.m1 * self.z1
+        }
+
+        #[inline(always)]
+        pub fn reset(&mut self) {
+            self.z1 = f32x2::splat(0.0);
+        }
+    }
+
     /// The state of four single-pole IIR filters packed into an SIMD vector.
     #[derive(Default, Clone, Copy)]
     pub struct OnePoleIirStatex4 {
@@ -193,3 +389,85 @@ pub mod simd {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{noise, Rng};
+
+    #[test]
+    fn tick_is_linear() {
+        let mut rng = Rng::new(3);
+
+        for seed in 0..500 {
+            let cutoff_hz = rng.log_range(10.0, 23_000.0) as f32;
+            let coeff = OnePoleIirCoeff {
+                m0: rng.range(-4.0, 4.0) as f32,
+                m1: rng.range(-4.0, 4.0) as f32,
+                ..OnePoleIirCoeff::lowpass(cutoff_hz, 1.0 / 48_000.0)
+            };
+            let mut state = OnePoleIirState::default();
+            let mut state_x2 = OnePoleIirState::default();
+
+            // Scaling by two is exact in floating point, so the outputs must
+            // match bit for bit.
+            for x in noise(256, seed) {
+                let out = tick(&mut state, x, &coeff);
+                let out_x2 = state_x2.tick(2.0 * x, &coeff);
+                assert_eq!(out_x2, 2.0 * out);
+            }
+        }
+    }
+
+    /// The packed filters (emulated with scalar code without the
+    /// `portable-simd` feature) must match the scalar filters bit for bit.
+    #[test]
+    fn packed_tick_matches_scalar() {
+        use crate::simd::{f32x2, f32x4, f32x8};
+        use simd::{
+            OnePoleIirCoeffx2, OnePoleIirCoeffx4, OnePoleIirCoeffx8, OnePoleIirStatex2,
+            OnePoleIirStatex4, OnePoleIirStatex8,
+        };
+
+        let mut rng = Rng::new(4);
+
+        macro_rules! check {
+            ($lanes:literal, $vector:ident, $coeff:ident, $state:ident) => {
+                for seed in 0..50 {
+                    let coeffs: [OnePoleIirCoeff; $lanes] =
+                        std::array::from_fn(|_| OnePoleIirCoeff {
+                            m0: rng.range(-4.0, 4.0) as f32,
+                            m1: rng.range(-4.0, 4.0) as f32,
+                            ..OnePoleIirCoeff::lowpass(
+                                rng.log_range(10.0, 23_000.0) as f32,
+                                1.0 / 48_000.0,
+                            )
+                        });
+                    let inputs: [Vec<f32>; $lanes] =
+                        std::array::from_fn(|i| noise(256, seed * 8 + i as u64));
+
+                    let coeff_packed = $coeff::load(&coeffs);
+                    let mut state_packed = $state::default();
+                    let mut states = [OnePoleIirState::default(); $lanes];
+
+                    for frame in 0..256 {
+                        let out = state_packed
+                            .tick(
+                                $vector::from_array(std::array::from_fn(|i| inputs[i][frame])),
+                                &coeff_packed,
+                            )
+                            .to_array();
+
+                        for (i, state) in states.iter_mut().enumerate() {
+                            assert_eq!(out[i], state.tick(inputs[i][frame], &coeffs[i]));
+                        }
+                    }
+                }
+            };
+        }
+
+        check!(2, f32x2, OnePoleIirCoeffx2, OnePoleIirStatex2);
+        check!(4, f32x4, OnePoleIirCoeffx4, OnePoleIirStatex4);
+        check!(8, f32x8, OnePoleIirCoeffx8, OnePoleIirStatex8);
+    }
+}
@@ -1,6 +1,7 @@
 use std::f64::consts::PI;
 
 use super::f32::OnePoleIirCoeff as OnePoleIirCoeffF32;
+use crate::decibel::f64::db_to_amp;
 
 /// The coefficients for a single-pole IIR filter.
 #[derive(Default, Clone, Copy)]
@@ -25,8 +26,8 @@ impl OnePoleIirCoeff {
         let a0 = 1.0 - b1;
 
         Self {
-            a0: a0 as f64,
-            b1: b1 as f64,
+            a0,
+            b1,
             m0: 0.0,
             m1: 1.0,
         }
@@ -37,13 +38,118 @@ impl OnePoleIirCoeff {
         let a0 = 1.0 - b1;
 
         Self {
-            a0: a0 as f64,
-            b1: b1 as f64,
+            a0,
+            b1,
             m0: 1.0,
             m1: -1.0,
         }
     }
 
+    /// A first-order (6 dB/octave) low shelf filter, for a gentle tilt.
+    ///
+    /// `cutoff_hz` is the midpoint of the transition, where the gain is half
+    /// of `gain_db` (in decibels). The pole and zero are placed with the
+    /// matched z-transform (like [`OnePoleIirCoeff::lowpass`]), so the gain
+    /// above the transition is only approximately 0 dB close to the Nyquist
+    /// frequency.
+    pub fn low_shelf(cutoff_hz: f64, gain_db: f64, sample_rate_recip: f64) -> Self {
+        let a = db_to_amp(gain_db).sqrt();
+
+        Self::shelf(cutoff_hz / a, cutoff_hz * a, a * a, sample_rate_recip)
+    }
+
+    /// A first-order (6 dB/octave) high shelf filter, for a gentle tilt. See
+    /// [`OnePoleIirCoeff::low_shelf`].
+    pub fn high_shelf(cutoff_hz: f64, gain_db: f64, sample_rate_recip: f64) -> Self {
+        let a = db_to_amp(gain_db).sqrt();
+
+        Self::shelf(cutoff_hz * a, cutoff_hz / a, 1.0, sample_rate_recip)
+    }
+
+    /// A first-order allpass filter, which delays the phase by 90 degrees at
+    /// `cutoff_hz` (and by between 0 and 180 degrees at other frequencies)
+    /// without changing the magnitude at any frequency.
+    ///
+    /// This is the bilinear transform of `(1 - s/wc) / (1 + s/wc)` with a
+    /// prewarped cutoff, so the 90 degree point is exact. Since the output is
+    /// computed as `m0 * input + m1 * z1`, where `m0 = -1 / b1` and
+    /// `m1 = -m0 * (1 + b1)`, the two terms mostly cancel when the pole is
+    /// close to zero (i.e. when the cutoff is close to a quarter of the
+    /// sample rate), which amplifies rounding errors. To bound this, the pole
+    /// is kept at least `1e-6` away from zero.
+    pub fn allpass(cutoff_hz: f64, sample_rate_recip: f64) -> Self {
+        const MIN_POLE: f64 = 1e-6;
+
+        let wc = (PI * cutoff_hz * sample_rate_recip).tan();
+        let mut b1 = (1.0 - wc) / (1.0 + wc);
+        if b1.abs() < MIN_POLE {
+            b1 = MIN_POLE.copysign(b1);
+        }
+        let a0 = 1.0 - b1;
+
+        // The transfer function is `m0 + m1 * a0 / (1 - b1 * z^-1)`, which is
+        // `(-b1 + z^-1) / (1 - b1 * z^-1)` for these values.
+        let m0 = -1.0 / b1;
+
+        Self {
+            a0,
+            b1,
+            m0,
+            m1: -m0 * (1.0 + b1),
+        }
+    }
+
+    /// A filter with a single pole and a single zero at the given
+    /// frequencies, with the given gain at DC.
+    fn shelf(pole_hz: f64, zero_hz: f64, dc_gain: f64, sample_rate_recip: f64) -> Self {
+        let b1 = ((-2.0 * PI) * pole_hz * sample_rate_recip).exp();
+        let zero = ((-2.0 * PI) * zero_hz * sample_rate_recip).exp();
+        let a0 = 1.0 - b1;
+
+        // The transfer function is `m0 + m1 * a0 / (1 - b1 * z^-1)`, so the
+        // zero is at `m0 * b1 / (m0 + m1 * a0)`.
+        let gain = dc_gain * (1.0 - b1) / (1.0 - zero);
+        let m0 = gain * zero / b1;
+
+        Self {
+            a0,
+            b1,
+            m0,
+            m1: (gain - m0) / a0,
+        }
+    }
+
+    /// Returns the magnitude (raw amplitude, not decibels) of the frequency
+    /// response of this filter at the given frequency.
+    pub fn magnitude(&self, freq_hz: f64, sample_rate_recip: f64) -> f64 {
+        let w = 2.0 * PI * freq_hz * sample_rate_recip;
+        let (sin_w, cos_w) = w.sin_cos();
+
+        let den_re = 1.0 - self.b1 * cos_w;
+        let den_im = self.b1 * sin_w;
+        let num_re = self.m0 * den_re + self.m1 * self.a0;
+        let num_im = self.m0 * den_im;
+
+        ((num_re * num_re + num_im * num_im) / (den_re * den_re + den_im * den_im)).sqrt()
+    }
+
+    /// The gain of the filter for a constant (DC) input. Unlike
+    /// [`OnePoleIirCoeff::magnitude`], this keeps the sign.
+    pub fn dc_gain(&self) -> f64 {
+        self.m0 + self.m1 * self.dc_state_gain()
+    }
+
+    /// The ratio of the steady-state internal value of the filter to a
+    /// constant input.
+    pub(crate) fn dc_state_gain(&self) -> f64 {
+        if self.b1 == 1.0 {
+            // The pole is at DC, so the filter never charges up.
+            0.0
+        } else {
+            self.a0 / (1.0 - self.b1)
+        }
+    }
+
     pub fn to_f32(self) -> OnePoleIirCoeffF32 {
         OnePoleIirCoeffF32 {
             a0: self.a0 as f32,
@@ -52,9 +158,26 @@ impl OnePoleIirCoeff {
             m1: self.m1 as f32,
         }
     }
+
+    /// Returns `true` if every coefficient is within `epsilon` of the matching
+    /// coefficient of `other`. This is `false` if any coefficient is NaN.
+    pub fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        [
+            self.a0 - other.a0,
+            self.b1 - other.b1,
+            self.m0 - other.m0,
+            self.m1 - other.m1,
+        ]
+        .iter()
+        .all(|d| d.abs() <= epsilon)
+    }
 }
 
 /// The state of a single-pole IIR filter.
+///
+/// The filter is linear: from a [`OnePoleIirState::reset`] state, scaling the
+/// input by a constant scales the output by the same constant. A non-zero
+/// state would have to be scaled as well.
 #[derive(Default, Clone, Copy)]
 pub struct OnePoleIirState {
     z1: f64,
@@ -71,14 +194,34 @@ impl OnePoleIirState {
     pub fn reset(&mut self) {
         self.z1 = 0.0;
     }
+
+    /// Set the state to the steady state for a constant input of `input`,
+    /// i.e. the state the filter converges to once the input has been
+    /// constant for a long time.
+    pub fn prime_for_dc(&mut self, input: f64, coeff: &OnePoleIirCoeff) {
+        self.z1 = coeff.dc_state_gain() * input;
+    }
+
+    /// Returns `true` if the state is within `epsilon` of that of `other`.
+    /// This is `false` if either of them is NaN.
+    pub fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        (self.z1 - other.z1).abs() <= epsilon
+    }
+}
+
+/// The same as [`OnePoleIirState::tick`], but as a free function so that it
+/// can be referenced by a function pointer.
+#[inline(always)]
+pub fn tick(state: &mut OnePoleIirState, input: f64, coeff: &OnePoleIirCoeff) -> f64 {
+    state.tick(input, coeff)
 }
 
-#[cfg(feature = "portable-simd")]
+/// Several filters packed into SIMD vectors. Without the `portable-simd`
+/// feature the vectors are emulated with scalar code (see [`crate::simd`]).
 pub mod simd {
-    use std::{
-        array,
-        simd::{f64x2, f64x4},
-    };
+    use std::array;
+
+    use crate::simd::{f64x2, f64x4};
 
     use super::{OnePoleIirCoeff, OnePoleIirState};
 
@@ -204,3 +347,102 @@ pub mod simd {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{noise, Rng};
+
+    #[test]
+    fn tick_is_linear() {
+        let mut rng = Rng::new(3);
+
+        for seed in 0..500 {
+            let cutoff_hz = rng.log_range(10.0, 23_000.0);
+            let coeff = OnePoleIirCoeff {
+                m0: rng.range(-4.0, 4.0),
+                m1: rng.range(-4.0, 4.0),
+                ..OnePoleIirCoeff::lowpass(cutoff_hz, 1.0 / 48_000.0)
+            };
+            let mut state = OnePoleIirState::default();
+            let mut state_x2 = OnePoleIirState::default();
+
+            // Scaling by two is exact in floating point, so the outputs must
+            // match bit for bit.
+            for x in noise(256, seed) {
+                let x = x as f64;
+                let out = tick(&mut state, x, &coeff);
+                let out_x2 = state_x2.tick(2.0 * x, &coeff);
+                assert_eq!(out_x2, 2.0 * out);
+            }
+        }
+    }
+
+    #[test]
+    fn allpass_is_flat() {
+        let sample_rate_recip = 1.0 / 48_000.0;
+
+        // Including a quarter of the sample rate, where the pole is at zero.
+        for cutoff_hz in [20.0, 1_000.0, 11_999.0, 12_000.0, 20_000.0] {
+            let coeff = OnePoleIirCoeff::allpass(cutoff_hz, sample_rate_recip);
+
+            for freq_hz in [10.0, 100.0, 1_000.0, 10_000.0, 23_000.0] {
+                let magnitude = coeff.magnitude(freq_hz, sample_rate_recip);
+                assert!(
+                    (magnitude - 1.0).abs() < 1e-9,
+                    "{cutoff_hz} Hz: {magnitude} at {freq_hz} Hz"
+                );
+            }
+        }
+    }
+
+    /// The shelves reach `gain_db` on the shelved side, pass 0 dB on the
+    /// other side, and are halfway (in decibels) at the cutoff. For a big
+    /// gain the transition in between is close to a 6 dB/octave slope (the
+    /// pole and the zero of a 36 dB shelf are only about 6 octaves apart,
+    /// so the slope at the cutoff is about 5.8 dB/octave).
+    #[test]
+    fn shelf_slope_and_plateau() {
+        let sample_rate_recip = 1.0 / 48_000.0;
+        let cutoff_hz = 300.0;
+        let gain_db_at = |coeff: &OnePoleIirCoeff, freq_hz: f64| {
+            20.0 * coeff.magnitude(freq_hz, sample_rate_recip).log10()
+        };
+
+        for gain_db in [-36.0, 36.0] {
+            let low = OnePoleIirCoeff::low_shelf(cutoff_hz, gain_db, sample_rate_recip);
+            let high = OnePoleIirCoeff::high_shelf(cutoff_hz, gain_db, sample_rate_recip);
+
+            // The plateaus, well past the pole and the zero. The matched
+            // z-transform is slightly off close to Nyquist.
+            let low_plateau_db = gain_db_at(&low, 1.0);
+            let high_plateau_db = gain_db_at(&high, 23_999.0);
+            assert!(
+                (low_plateau_db - gain_db).abs() < 0.05,
+                "low shelf {gain_db} dB: {low_plateau_db} dB plateau"
+            );
+            assert!(
+                (high_plateau_db - gain_db).abs() < 0.1,
+                "high shelf {gain_db} dB: {high_plateau_db} dB plateau"
+            );
+            assert!(gain_db_at(&low, 23_999.0).abs() < 0.1);
+            assert!(gain_db_at(&high, 1.0).abs() < 0.05);
+
+            for (name, coeff, sign) in [("low", low, -1.0), ("high", high, 1.0)] {
+                let midpoint_db = gain_db_at(&coeff, cutoff_hz);
+                assert!(
+                    (midpoint_db - 0.5 * gain_db).abs() < 0.1,
+                    "{name} shelf {gain_db} dB: {midpoint_db} dB at the cutoff"
+                );
+
+                let slope_db = gain_db_at(&coeff, cutoff_hz * 2.0f64.sqrt())
+                    - gain_db_at(&coeff, cutoff_hz / 2.0f64.sqrt());
+                let expected_db = sign * 6.02f64.copysign(gain_db);
+                assert!(
+                    (slope_db - expected_db).abs() < 0.3,
+                    "{name} shelf {gain_db} dB: {slope_db} dB/octave"
+                );
+            }
+        }
+    }
+}
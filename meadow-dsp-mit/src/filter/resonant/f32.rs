@@ -0,0 +1,202 @@
+use crate::{
+    dynamics::f32::SoftLimiter,
+    filter::svf::f32::{prewarp, SvfState},
+};
+
+/// The resonance at which the damping of a [`ResonantLowpass`] reaches zero,
+/// and the filter starts to self-oscillate.
+pub const SELF_OSCILLATION_RESONANCE: f32 = 2.0 / MAX_RESONANCE_K_RANGE;
+
+/// `k` goes from `2.0` at a resonance of `0.0` to `2.0 - MAX_RESONANCE_K_RANGE`
+/// (slightly negative) at a resonance of `1.0`.
+const MAX_RESONANCE_K_RANGE: f32 = 2.1;
+
+/// How much the damping increases with the energy of the filter states. This
+/// is what sets the amplitude of the self-oscillation (see
+/// [`ResonantLowpass`]).
+const NONLINEAR_DAMPING: f32 = 0.6;
+
+/// Output levels above `0.8` are softly limited so that they never exceed
+/// `1.0`.
+const LIMITER: SoftLimiter = SoftLimiter::from_ceiling(1.0);
+
+/// A resonant 12 dB/octave lowpass filter which can self-oscillate, for
+/// "acid" synth sounds.
+///
+/// This is the same SVF model as
+/// [`SvfCoeff::lowpass_ord2`](crate::filter::svf::f32::SvfCoeff::lowpass_ord2),
+/// but with an extended resonance range. The resonance is mapped to the damping
+/// `k = 1.0 / q` as `k = 2.0 - 2.1 * resonance`, so:
+///
+/// * `0.0` gives `q = 0.5` (no resonance).
+/// * About `0.62` gives a Butterworth response (`q = 0.707`).
+/// * [`SELF_OSCILLATION_RESONANCE`] (about `0.952`) gives `k = 0.0`
+///   (infinite `q`), where the filter rings forever.
+/// * Above that the damping is negative, so the filter self-oscillates at the
+///   cutoff frequency, even with a silent input once it has been excited.
+///
+/// To keep the self-oscillation under control, the damping increases with the
+/// energy stored in the filter (the sum of the squares of its two states),
+/// so the oscillation settles where the extra damping cancels the negative
+/// damping. While the filter oscillates, the two states are a quadrature
+/// pair, so their energy is constant over a cycle and the damping does not
+/// distort the oscillation. The energy is scaled by `cos(PI * cutoff /
+/// sample_rate)^2` to undo the gain of the trapezoidal integrators at the
+/// cutoff, so the oscillation settles at the same level at any cutoff (about
+/// `0.41` at a resonance of `1.0`). This also makes the resonance "compress"
+/// gently for loud input, like an analog filter. Finally, the output is
+/// passed through a soft limiter which never exceeds `1.0`. Below a level of
+/// `0.8` the output is untouched.
+#[derive(Clone, Copy)]
+pub struct ResonantLowpass {
+    cutoff_hz: f32,
+    resonance: f32,
+
+    g: f32,
+    k: f32,
+    /// `NONLINEAR_DAMPING * cos(PI * cutoff / sample_rate)^2`
+    energy_damping: f32,
+    state: SvfState,
+
+    sample_rate_recip: f32,
+}
+
+impl ResonantLowpass {
+    pub const LATENCY: u32 = 0;
+
+    pub fn new(cutoff_hz: f32, resonance: f32, sample_rate: f64) -> Self {
+        let mut new_self = Self {
+            cutoff_hz,
+            resonance,
+            g: 0.0,
+            k: 2.0,
+            energy_damping: NONLINEAR_DAMPING,
+            state: SvfState::default(),
+            sample_rate_recip: sample_rate.recip() as f32,
+        };
+
+        new_self.set_cutoff_hz(cutoff_hz);
+        new_self.set_resonance(resonance);
+
+        new_self
+    }
+
+    pub fn cutoff_hz(&self) -> f32 {
+        self.cutoff_hz
+    }
+
+    /// Set the cutoff frequency in Hz. This is clamped to just below the
+    /// Nyquist frequency.
+    pub fn set_cutoff_hz(&mut self, cutoff_hz: f32) {
+        self.cutoff_hz = cutoff_hz.clamp(1.0, 0.49 / self.sample_rate_recip);
+        self.g = prewarp(self.cutoff_hz, self.sample_rate_recip);
+        // `cos(x)^2 = 1 / (1 + tan(x)^2)`
+        self.energy_damping = NONLINEAR_DAMPING / (1.0 + self.g * self.g);
+    }
+
+    pub fn resonance(&self) -> f32 {
+        self.resonance
+    }
+
+    /// Set the resonance in the range `[0.0, 1.0]`. See the struct docs for
+    /// how this maps to Q.
+    pub fn set_resonance(&mut self, resonance: f32) {
+        self.resonance = resonance.clamp(0.0, 1.0);
+        self.k = 2.0 - MAX_RESONANCE_K_RANGE * self.resonance;
+    }
+
+    /// The Q of the filter for the current resonance, or `f32::INFINITY`
+    /// once the filter self-oscillates.
+    pub fn q(&self) -> f32 {
+        if self.k > 0.0 {
+            self.k.recip()
+        } else {
+            f32::INFINITY
+        }
+    }
+
+    /// Returns `true` if the resonance is high enough for the filter to
+    /// self-oscillate.
+    pub fn is_self_oscillating(&self) -> bool {
+        self.k <= 0.0
+    }
+
+    pub fn reset(&mut self) {
+        self.state.reset();
+    }
+
+    /// Process a single sample.
+    #[inline]
+    pub fn tick(&mut self, input: f32) -> f32 {
+        let s = &mut self.state;
+        let g = self.g;
+        let k = self.k + self.energy_damping * (s.ic1eq * s.ic1eq + s.ic2eq * s.ic2eq);
+
+        // The same as `SvfCoeff::from_g_and_k`, but with the damping changing
+        // every sample.
+        let a1 = 1.0 / (1.0 + g * (g + k));
+        let a2 = g * a1;
+        let a3 = g * a2;
+
+        let v3 = input - s.ic2eq;
+        let v1 = a1 * s.ic1eq + a2 * v3;
+        let v2 = s.ic2eq + a2 * s.ic1eq + a3 * v3;
+        s.ic1eq = 2.0 * v1 - s.ic1eq;
+        s.ic2eq = 2.0 * v2 - s.ic2eq;
+
+        LIMITER.tick(v2)
+    }
+
+    /// Process the given buffer in place.
+    pub fn process(&mut self, buf: &mut [f32]) {
+        for s in buf.iter_mut() {
+            *s = self.tick(*s);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::rms;
+
+    #[test]
+    fn self_oscillation_level_does_not_depend_on_cutoff() {
+        let sample_rate = 48_000.0;
+        let frames = (1.5 * sample_rate) as usize;
+        let quarter_second = sample_rate as usize / 4;
+
+        for cutoff_hz in [100.0, 1_000.0, 5_000.0, 10_000.0, 15_000.0, 20_000.0] {
+            let mut filter = ResonantLowpass::new(cutoff_hz, 1.0, sample_rate);
+            assert!(filter.is_self_oscillating());
+
+            // A tiny seed, and then silence.
+            let mut buf = vec![0.0; frames];
+            buf[0] = 1e-6;
+            filter.process(&mut buf);
+
+            let level = |buf: &[f32]| rms(buf) * std::f64::consts::SQRT_2;
+            let last = level(&buf[frames - quarter_second..]);
+            let before = level(&buf[frames - 2 * quarter_second..frames - quarter_second]);
+
+            assert!((last - 0.41).abs() < 0.01, "{cutoff_hz} Hz: {last}");
+            assert!(
+                (last / before - 1.0).abs() < 0.001,
+                "{cutoff_hz} Hz: {before} then {last}"
+            );
+        }
+    }
+
+    #[test]
+    fn rings_out_below_self_oscillation() {
+        let mut filter = ResonantLowpass::new(1_000.0, 0.9, 48_000.0);
+        assert!(!filter.is_self_oscillating());
+
+        let mut buf = vec![0.0; 48_000];
+        buf[0] = 1.0;
+        filter.process(&mut buf);
+
+        let tail = rms(&buf[24_000..]);
+        assert!(tail < 1e-6, "{tail}");
+    }
+}
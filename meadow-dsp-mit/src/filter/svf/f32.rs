@@ -1,7 +1,8 @@
-use std::f32::consts::PI;
+use std::f32::consts::{FRAC_PI_2, PI};
 
 use super::f64::{
-    ORD4_Q_SCALE, ORD6_Q_SCALE, ORD8_Q_SCALE, Q_BUTTERWORTH_ORD2, Q_BUTTERWORTH_ORD4,
+    SvfCoeff as SvfCoeffF64, ORD12_Q_SCALE, ORD16_Q_SCALE, ORD4_Q_SCALE, ORD6_Q_SCALE,
+    ORD8_Q_SCALE, Q_BUTTERWORTH_ORD12, Q_BUTTERWORTH_ORD16, Q_BUTTERWORTH_ORD2, Q_BUTTERWORTH_ORD4,
     Q_BUTTERWORTH_ORD6, Q_BUTTERWORTH_ORD8,
 };
 
@@ -28,14 +29,14 @@ impl SvfCoeff {
     };
 
     pub fn lowpass_ord2(cutoff_hz: f32, q: f32, sample_rate_recip: f32) -> Self {
-        let g = g(cutoff_hz, sample_rate_recip);
+        let g = prewarp(cutoff_hz, sample_rate_recip);
         let k = 1.0 / q;
 
         Self::from_g_and_k(g, k, 0.0, 0.0, 1.0)
     }
 
     pub fn lowpass_ord4(cutoff_hz: f32, q: f32, sample_rate_recip: f32) -> [Self; 2] {
-        let g = g(cutoff_hz, sample_rate_recip);
+        let g = prewarp(cutoff_hz, sample_rate_recip);
         let q_norm = scale_q_norm_for_order(q_norm(q), ORD4_Q_SCALE as f32);
 
         std::array::from_fn(|i| {
@@ -47,7 +48,7 @@ impl SvfCoeff {
     }
 
     pub fn lowpass_ord6(cutoff_hz: f32, q: f32, sample_rate_recip: f32) -> [Self; 3] {
-        let g = g(cutoff_hz, sample_rate_recip);
+        let g = prewarp(cutoff_hz, sample_rate_recip);
         let q_norm = scale_q_norm_for_order(q_norm(q), ORD4_Q_SCALE as f32);
 
         std::array::from_fn(|i| {
@@ -59,7 +60,7 @@ impl SvfCoeff {
     }
 
     pub fn lowpass_ord8(cutoff_hz: f32, q: f32, sample_rate_recip: f32) -> [Self; 4] {
-        let g = g(cutoff_hz, sample_rate_recip);
+        let g = prewarp(cutoff_hz, sample_rate_recip);
         let q_norm = scale_q_norm_for_order(q_norm(q), ORD8_Q_SCALE as f32);
 
         std::array::from_fn(|i| {
@@ -70,15 +71,39 @@ impl SvfCoeff {
         })
     }
 
+    pub fn lowpass_ord12(cutoff_hz: f32, q: f32, sample_rate_recip: f32) -> [Self; 6] {
+        let g = prewarp(cutoff_hz, sample_rate_recip);
+        let q_norm = scale_q_norm_for_order(q_norm(q), ORD12_Q_SCALE as f32);
+
+        std::array::from_fn(|i| {
+            let q = q_norm * Q_BUTTERWORTH_ORD12[i] as f32;
+            let k = 1.0 / q;
+
+            Self::from_g_and_k(g, k, 0.0, 0.0, 1.0)
+        })
+    }
+
+    pub fn lowpass_ord16(cutoff_hz: f32, q: f32, sample_rate_recip: f32) -> [Self; 8] {
+        let g = prewarp(cutoff_hz, sample_rate_recip);
+        let q_norm = scale_q_norm_for_order(q_norm(q), ORD16_Q_SCALE as f32);
+
+        std::array::from_fn(|i| {
+            let q = q_norm * Q_BUTTERWORTH_ORD16[i] as f32;
+            let k = 1.0 / q;
+
+            Self::from_g_and_k(g, k, 0.0, 0.0, 1.0)
+        })
+    }
+
     pub fn highpass_ord2(cutoff_hz: f32, q: f32, sample_rate_recip: f32) -> Self {
-        let g = g(cutoff_hz, sample_rate_recip);
+        let g = prewarp(cutoff_hz, sample_rate_recip);
         let k = 1.0 / q;
 
         Self::from_g_and_k(g, k, 1.0, -k, -1.0)
     }
 
     pub fn highpass_ord4(cutoff_hz: f32, q: f32, sample_rate_recip: f32) -> [Self; 2] {
-        let g = g(cutoff_hz, sample_rate_recip);
+        let g = prewarp(cutoff_hz, sample_rate_recip);
         let q_norm = scale_q_norm_for_order(q_norm(q), ORD4_Q_SCALE as f32);
 
         std::array::from_fn(|i| {
@@ -90,7 +115,7 @@ impl SvfCoeff {
     }
 
     pub fn highpass_ord6(cutoff_hz: f32, q: f32, sample_rate_recip: f32) -> [Self; 3] {
-        let g = g(cutoff_hz, sample_rate_recip);
+        let g = prewarp(cutoff_hz, sample_rate_recip);
         let q_norm = scale_q_norm_for_order(q_norm(q), ORD6_Q_SCALE as f32);
 
         std::array::from_fn(|i| {
@@ -102,7 +127,7 @@ impl SvfCoeff {
     }
 
     pub fn highpass_ord8(cutoff_hz: f32, q: f32, sample_rate_recip: f32) -> [Self; 4] {
-        let g = g(cutoff_hz, sample_rate_recip);
+        let g = prewarp(cutoff_hz, sample_rate_recip);
         let q_norm = scale_q_norm_for_order(q_norm(q), ORD8_Q_SCALE as f32);
 
         std::array::from_fn(|i| {
@@ -113,22 +138,147 @@ impl SvfCoeff {
         })
     }
 
+    pub fn highpass_ord12(cutoff_hz: f32, q: f32, sample_rate_recip: f32) -> [Self; 6] {
+        let g = prewarp(cutoff_hz, sample_rate_recip);
+        let q_norm = scale_q_norm_for_order(q_norm(q), ORD12_Q_SCALE as f32);
+
+        std::array::from_fn(|i| {
+            let q = q_norm * Q_BUTTERWORTH_ORD12[i] as f32;
+            let k = 1.0 / q;
+
+            Self::from_g_and_k(g, k, 1.0, -k, -1.0)
+        })
+    }
+
+    pub fn highpass_ord16(cutoff_hz: f32, q: f32, sample_rate_recip: f32) -> [Self; 8] {
+        let g = prewarp(cutoff_hz, sample_rate_recip);
+        let q_norm = scale_q_norm_for_order(q_norm(q), ORD16_Q_SCALE as f32);
+
+        std::array::from_fn(|i| {
+            let q = q_norm * Q_BUTTERWORTH_ORD16[i] as f32;
+            let k = 1.0 / q;
+
+            Self::from_g_and_k(g, k, 1.0, -k, -1.0)
+        })
+    }
+
+    /// A bandpass filter normalized to have a gain of `1.0` (0 dB) at
+    /// `cutoff_hz`.
+    pub fn bandpass(cutoff_hz: f32, q: f32, sample_rate_recip: f32) -> Self {
+        let g = prewarp(cutoff_hz, sample_rate_recip);
+        let k = 1.0 / q;
+
+        Self::from_g_and_k(g, k, 0.0, k, 0.0)
+    }
+
+    /// A filter which morphs between a lowpass at `morph = 0.0`, a bandpass
+    /// (normalized like [`SvfCoeff::bandpass`]) at `morph = 0.5`, and a
+    /// highpass at `morph = 1.0`, crossfading linearly in between. `morph` is
+    /// clamped to the range `[0.0, 1.0]`.
+    ///
+    /// This gives the same output as blending the outputs of
+    /// [`SvfState::tick_multi`], since each of them is a mix of the same
+    /// internal values.
+    pub fn multimode(cutoff_hz: f32, q: f32, morph: f32, sample_rate_recip: f32) -> Self {
+        let g = prewarp(cutoff_hz, sample_rate_recip);
+        let k = 1.0 / q;
+
+        let morph = morph.clamp(0.0, 1.0);
+        let lp_mix = (1.0 - 2.0 * morph).max(0.0);
+        let hp_mix = (2.0 * morph - 1.0).max(0.0);
+        let bp_mix = 1.0 - lp_mix - hp_mix;
+
+        // lp = v2, bp = k * v1, hp = input - k * v1 - v2
+        Self::from_g_and_k(g, k, hp_mix, k * (bp_mix - hp_mix), lp_mix - hp_mix)
+    }
+
+    /// A notch filter with a true zero (infinite attenuation) at `cutoff_hz`.
+    ///
+    /// The analog prototype `(s^2 + 1) / (s^2 + s / q + 1)` has its zeros on
+    /// the imaginary axis, and since the cutoff is prewarped the bilinear
+    /// transform maps them onto the unit circle at exactly `cutoff_hz`. `q`
+    /// only sets the width of the notch, not its depth. In practice the depth
+    /// is limited by the precision of the coefficients and the filter state.
     pub fn notch(cutoff_hz: f32, q: f32, sample_rate_recip: f32) -> Self {
-        let g = g(cutoff_hz, sample_rate_recip);
+        let g = prewarp(cutoff_hz, sample_rate_recip);
         let k = 1.0 / q;
 
         Self::from_g_and_k(g, k, 1.0, -k, 0.0)
     }
 
+    /// A notch filter with a broadband makeup gain applied so that the
+    /// perceived loudness stays roughly constant.
+    ///
+    /// This is a heuristic. The makeup gain compensates for the energy the
+    /// notch removes from pink noise over the audible range (20Hz - 20kHz),
+    /// assuming the notch lies well within that range.
+    pub fn notch_compensated(cutoff_hz: f32, q: f32, sample_rate_recip: f32) -> Self {
+        let g = prewarp(cutoff_hz, sample_rate_recip);
+        let k = 1.0 / q;
+
+        let removed = (notch_removed_log_bandwidth(k) * (1.0 / AUDIBLE_LOG_BANDWIDTH)).min(0.9);
+        let makeup = 1.0 / (1.0 - removed).sqrt();
+
+        Self::from_g_and_k(g, k, makeup, -k * makeup, 0.0)
+    }
+
+    /// A bell filter with "proportional Q".
+    ///
+    /// `q` is the center frequency divided by the bandwidth between the points
+    /// where the gain is half of `gain_db` (in decibels). This bandwidth stays
+    /// the same at any gain, so the bell looks narrower the more it is boosted
+    /// or cut. A cut is the exact inverse of a boost by the same amount.
     pub fn bell(cutoff_hz: f32, q: f32, gain_db: f32, sample_rate_recip: f32) -> Self {
         let a = gain_db_to_a(gain_db);
 
-        let g = g(cutoff_hz, sample_rate_recip);
+        let g = prewarp(cutoff_hz, sample_rate_recip);
         let k = 1.0 / (q * a);
 
         Self::from_g_and_k(g, k, 1.0, k * (a * a - 1.0), 0.0)
     }
 
+    /// A bell filter with "constant Q".
+    ///
+    /// For gains of at least +/-6 dB, `q` is the center frequency divided by
+    /// the bandwidth between the points 3 dB below the peak (or above the
+    /// dip), so the width of the bell stays the same as the gain changes.
+    /// Below +/-6 dB those points do not exist (or lie far away), so the
+    /// response falls back to that of [`SvfCoeff::bell`], which is identical
+    /// at exactly +/-6 dB. A cut is the exact inverse of a boost by the same
+    /// amount.
+    pub fn bell_constant_q(cutoff_hz: f32, q: f32, gain_db: f32, sample_rate_recip: f32) -> Self {
+        let a = gain_db_to_a(gain_db);
+        let a2 = a * a;
+
+        let g = prewarp(cutoff_hz, sample_rate_recip);
+        let k = if a2 >= 2.0 {
+            (a2 * a2 - 2.0).sqrt() / (q * a2)
+        } else if a2 <= 0.5 {
+            // The inverse of the boost with gain `1 / a`.
+            let a2_recip = a2.recip();
+            (a2_recip * a2_recip - 2.0).sqrt() / q
+        } else {
+            1.0 / (q * a)
+        };
+
+        Self::from_g_and_k(g, k, 1.0, k * (a2 - 1.0), 0.0)
+    }
+
+    /// A low shelf filter.
+    ///
+    /// `q` shapes the transition between the two levels. At
+    /// `Q_BUTTERWORTH_ORD2` (and below) the transition is monotonic. Raising `q`
+    /// makes the transition steeper and adds a resonant overshoot on the
+    /// shelved side and a matching undershoot on the other side (for a +6 dB
+    /// shelf, roughly +2.9/-2.9 dB at `q = 2.0` and +7/-7 dB at `q = 4.0`),
+    /// like many analog shelving EQs.
+    ///
+    /// The flat side of the shelf is exactly 0 dB at the Nyquist frequency for any cutoff
+    /// and sample rate, since the SVF is equivalent to the bilinear transform
+    /// (cramping only squeezes the transition). Towards the cutoff it
+    /// approaches 0 dB like the analog shelf: a decade above the cutoff it
+    /// is within 0.002 dB at `Q_BUTTERWORTH_ORD2`, and within about 0.06 dB
+    /// for a 6 dB shelf at a `q` of `0.5` or `2.0`.
     pub fn low_shelf(cutoff_hz: f32, q: f32, gain_db: f32, sample_rate_recip: f32) -> Self {
         let a = gain_db_to_a(gain_db);
 
@@ -138,22 +288,40 @@ impl SvfCoeff {
         Self::from_g_and_k(g, k, 1.0, k * (a - 1.0), a * a - 1.0)
     }
 
+    /// A high shelf filter.
+    ///
+    /// `q` shapes the transition between the two levels. At
+    /// `Q_BUTTERWORTH_ORD2` (and below) the transition is monotonic. Raising `q`
+    /// makes the transition steeper and adds a resonant overshoot on the
+    /// shelved side and a matching undershoot on the other side (for a +6 dB
+    /// shelf, roughly +2.9/-2.9 dB at `q = 2.0` and +7/-7 dB at `q = 4.0`),
+    /// like many analog shelving EQs.
+    ///
+    /// The flat side of the shelf is exactly 0 dB at DC for any cutoff
+    /// and sample rate, since the SVF is equivalent to the bilinear transform
+    /// (cramping only squeezes the transition). Towards the cutoff it
+    /// approaches 0 dB like the analog shelf: a decade below the cutoff it
+    /// is within 0.002 dB at `Q_BUTTERWORTH_ORD2`, and within about 0.06 dB
+    /// for a 6 dB shelf at a `q` of `0.5` or `2.0`.
     pub fn high_shelf(cutoff_hz: f32, q: f32, gain_db: f32, sample_rate_recip: f32) -> Self {
         let a = gain_db_to_a(gain_db);
 
-        let g = (PI * cutoff_hz * sample_rate_recip).tan() / a.sqrt();
+        let g = (PI * cutoff_hz * sample_rate_recip).tan() * a.sqrt();
         let k = 1.0 / q;
 
         Self::from_g_and_k(g, k, a * a, k * (1.0 - a) * a, 1.0 - a * a)
     }
 
     pub fn allpass(cutoff_hz: f32, q: f32, sample_rate_recip: f32) -> Self {
-        let g = g(cutoff_hz, sample_rate_recip);
+        let g = prewarp(cutoff_hz, sample_rate_recip);
         let k = 1.0 / q;
 
         Self::from_g_and_k(g, k, 1.0, -2.0 * k, 0.0)
     }
 
+    /// Create the coefficients from the prewarped cutoff `g` (see
+    /// [`prewarp`]), the damping `k`, and the output mix. See
+    /// [`SvfCoeff::from_g_and_k`](super::f64::SvfCoeff::from_g_and_k).
     pub fn from_g_and_k(g: f32, k: f32, m0: f32, m1: f32, m2: f32) -> Self {
         let a1 = 1.0 / (1.0 + g * (g + k));
         let a2 = g * a1;
@@ -168,9 +336,148 @@ impl SvfCoeff {
             m2,
         }
     }
+
+    /// The same as [`SvfCoeff::from_g_and_k`], but from a cutoff in Hz using
+    /// the cheaper [`prewarp_fast`]. See
+    /// [`SvfCoeff::from_cutoff_fast`](super::f64::SvfCoeff::from_cutoff_fast).
+    #[inline]
+    pub fn from_cutoff_fast(
+        cutoff_hz: f32,
+        k: f32,
+        m0: f32,
+        m1: f32,
+        m2: f32,
+        sample_rate_recip: f32,
+    ) -> Self {
+        Self::from_g_and_k(prewarp_fast(cutoff_hz, sample_rate_recip), k, m0, m1, m2)
+    }
+
+    /// Whether both poles of the filter lie strictly inside the unit circle.
+    ///
+    /// The coefficients constructed by this type from a positive cutoff
+    /// below the Nyquist frequency and a positive Q are always stable, but
+    /// coefficients from [`SvfCoeff::from_g_and_k`] or ones that were edited
+    /// directly might not be.
+    pub fn is_stable(&self) -> bool {
+        if self.a2 == 0.0 {
+            // The filter only passes the input through.
+            return true;
+        }
+
+        // The denominator of the transfer function normalized to
+        // `z^2 + b1*z + b2`.
+        let (a1, a3) = (self.a1 as f64, self.a3 as f64);
+        let b1 = 2.0 * (a3 - a1);
+        let b2 = 2.0 * (a1 + a3) - 1.0;
+
+        b2.abs() < 1.0 && b1.abs() < 1.0 + b2
+    }
+
+    pub fn to_f64(self) -> SvfCoeffF64 {
+        SvfCoeffF64 {
+            a1: self.a1 as f64,
+            a2: self.a2 as f64,
+            a3: self.a3 as f64,
+            m0: self.m0 as f64,
+            m1: self.m1 as f64,
+            m2: self.m2 as f64,
+        }
+    }
+
+    /// The coefficients as a flat array in the order
+    /// `[a1, a2, a3, m0, m1, m2]`.
+    ///
+    /// This layout is stable, so it can be used for serialization or for
+    /// uploading the coefficients to a GPU.
+    pub const fn as_array(&self) -> [f32; 6] {
+        [self.a1, self.a2, self.a3, self.m0, self.m1, self.m2]
+    }
+
+    /// Create the coefficients from a flat array in the order
+    /// `[a1, a2, a3, m0, m1, m2]`. See [`SvfCoeff::as_array`].
+    pub const fn from_array(a: [f32; 6]) -> Self {
+        Self {
+            a1: a[0],
+            a2: a[1],
+            a3: a[2],
+            m0: a[3],
+            m1: a[4],
+            m2: a[5],
+        }
+    }
+
+    /// A key which can be used to look up these coefficients in a hash map
+    /// or set. See [`SvfCoeffKey`].
+    pub fn key(&self) -> SvfCoeffKey {
+        SvfCoeffKey(self.as_array().map(canonical_bits))
+    }
+
+    /// Returns `true` if every coefficient is within `epsilon` of the matching
+    /// coefficient of `other`. This is `false` if any coefficient is NaN.
+    pub fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        self.as_array()
+            .iter()
+            .zip(other.as_array().iter())
+            .all(|(a, b)| (a - b).abs() <= epsilon)
+    }
+}
+
+/// A hashable key for an [`SvfCoeff`], for use in caches.
+///
+/// `SvfCoeff` can't implement `Eq` or `Hash` because its fields are floats.
+/// Hashing the raw bits of floats is unsafe for two reasons: `NaN != NaN`
+/// breaks the reflexivity that `Eq` requires (so an entry with a NaN key could
+/// be inserted but never found again), and `0.0 == -0.0` even though their
+/// bits differ (so equal values could hash differently). This key compares
+/// the bits instead, after mapping `-0.0` to `0.0` and every NaN to a single
+/// NaN. Two coefficients have equal keys exactly when all of their fields are
+/// equal, treating NaN as equal to itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SvfCoeffKey([u32; 6]);
+
+impl From<SvfCoeff> for SvfCoeffKey {
+    fn from(coeff: SvfCoeff) -> Self {
+        coeff.key()
+    }
+}
+
+/// The bits of `x`, with `-0.0` mapped to `0.0` and every NaN mapped to the
+/// same NaN.
+fn canonical_bits(x: f32) -> u32 {
+    if x.is_nan() {
+        f32::NAN.to_bits()
+    } else if x == 0.0 {
+        0
+    } else {
+        x.to_bits()
+    }
+}
+
+impl From<SvfCoeff> for SvfCoeffF64 {
+    /// Widen the coefficients to f64. This is lossless.
+    fn from(coeff: SvfCoeff) -> Self {
+        coeff.to_f64()
+    }
+}
+
+/// The lowpass, bandpass, and highpass outputs of an SVF (state variable
+/// filter) model, as returned by [`SvfState::tick_multi`].
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub struct SvfOutputs {
+    /// The same response as [`SvfCoeff::lowpass_ord2`].
+    pub lp: f32,
+    /// The same response as [`SvfCoeff::bandpass`] (unity gain at the
+    /// cutoff).
+    pub bp: f32,
+    /// The same response as [`SvfCoeff::highpass_ord2`].
+    pub hp: f32,
 }
 
 /// The state of an SVF (state variable filter) model.
+///
+/// The filter is linear and time-invariant. `ic1eq` and `ic2eq` carry the
+/// response to past input, so scaling the input by a constant scales the
+/// output by the same constant only if they start at zero or are scaled too.
 #[derive(Default, Clone, Copy)]
 pub struct SvfState {
     pub ic1eq: f32,
@@ -189,21 +496,135 @@ impl SvfState {
         coeff.m0 * input + coeff.m1 * v1 + coeff.m2 * v2
     }
 
+    /// Process a single sample, and return the lowpass, bandpass, and
+    /// highpass outputs all at once. This is useful for morphing between
+    /// filter modes without running a separate filter for each mode.
+    ///
+    /// Only the cutoff and Q of `coeff` are used (its output mix is
+    /// ignored), so any of the second order constructors with the desired
+    /// cutoff and Q can be used. The three outputs always sum to the input.
+    #[inline(always)]
+    pub fn tick_multi(&mut self, input: f32, coeff: &SvfCoeff) -> SvfOutputs {
+        let v3 = input - self.ic2eq;
+        let v1 = coeff.a1 * self.ic1eq + coeff.a2 * v3;
+        let v2 = self.ic2eq + coeff.a2 * self.ic1eq + coeff.a3 * v3;
+
+        // The bandpass integrator gives `v1 = ic1eq + g * hp`, where
+        // `g = a2 / a1`.
+        let hp = if coeff.a2 == 0.0 {
+            // The filter only passes the input through.
+            input
+        } else {
+            (v1 - self.ic1eq) * coeff.a1 / coeff.a2
+        };
+
+        self.ic1eq = 2.0 * v1 - self.ic1eq;
+        self.ic2eq = 2.0 * v2 - self.ic2eq;
+
+        SvfOutputs {
+            lp: v2,
+            bp: input - v2 - hp,
+            hp,
+        }
+    }
+
+    /// The same as [`SvfState::tick`], but using coefficients stored in f64.
+    ///
+    /// The computation is done in f64, and only the state is rounded back to
+    /// f32. This avoids the error from rounding the coefficients (which is
+    /// significant for filters with a very low cutoff) while keeping the
+    /// memory footprint of the state small.
+    #[inline(always)]
+    pub fn tick_f64_coeff(&mut self, input: f32, coeff: &SvfCoeffF64) -> f32 {
+        let input = input as f64;
+        let ic1eq = self.ic1eq as f64;
+        let ic2eq = self.ic2eq as f64;
+
+        let v3 = input - ic2eq;
+        let v1 = coeff.a1 * ic1eq + coeff.a2 * v3;
+        let v2 = ic2eq + coeff.a2 * ic1eq + coeff.a3 * v3;
+        self.ic1eq = (2.0 * v1 - ic1eq) as f32;
+        self.ic2eq = (2.0 * v2 - ic2eq) as f32;
+
+        (coeff.m0 * input + coeff.m1 * v1 + coeff.m2 * v2) as f32
+    }
+
     #[inline(always)]
     pub fn reset(&mut self) {
         self.ic1eq = 0.0;
         self.ic2eq = 0.0;
     }
+
+    /// Set the state to the steady state for a constant input of `input`,
+    /// i.e. the state the filter converges to once the input has been
+    /// constant for a long time. This avoids a startup transient when the
+    /// input does not start at zero.
+    ///
+    /// The steady state of a (stable) SVF does not depend on its
+    /// coefficients.
+    pub fn prime_for_dc(&mut self, input: f32) {
+        self.ic1eq = 0.0;
+        self.ic2eq = input;
+    }
+
+    /// Returns `true` if both state variables are within `epsilon` of those
+    /// of `other`. This is `false` if any of them is NaN.
+    pub fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        (self.ic1eq - other.ic1eq).abs() <= epsilon && (self.ic2eq - other.ic2eq).abs() <= epsilon
+    }
 }
 
-fn g(cutoff_hz: f32, sample_rate_recip: f32) -> f32 {
+/// The same as [`SvfState::tick`], but as a free function so that it can be
+/// referenced by a function pointer.
+#[inline(always)]
+pub fn tick(state: &mut SvfState, input: f32, coeff: &SvfCoeff) -> f32 {
+    state.tick(input, coeff)
+}
+
+/// The prewarped cutoff. See [`prewarp`](super::f64::prewarp).
+pub fn prewarp(cutoff_hz: f32, sample_rate_recip: f32) -> f32 {
     (PI * cutoff_hz * sample_rate_recip).tan()
 }
 
+/// A cheaper approximation of [`prewarp`]. See
+/// [`prewarp_fast`](super::f64::prewarp_fast) for the accuracy.
+#[inline]
+pub fn prewarp_fast(cutoff_hz: f32, sample_rate_recip: f32) -> f32 {
+    let x = PI * cutoff_hz * sample_rate_recip;
+    let x2 = x * x;
+
+    x * (15.0 - x2) / (15.0 - 6.0 * x2)
+}
+
 fn q_norm(q: f32) -> f32 {
     q * (1.0 / Q_BUTTERWORTH_ORD2 as f32)
 }
 
+/// `ln(20_000 / 20)`
+const AUDIBLE_LOG_BANDWIDTH: f32 = 6.907_755;
+
+/// The bandwidth (in natural-log frequency units) of the energy removed from
+/// pink noise by a notch filter with the given damping, i.e. the integral of
+/// `1 - |H(jw)|^2` over `ln(w)`.
+fn notch_removed_log_bandwidth(k: f32) -> f32 {
+    let k2 = k * k;
+
+    let j = if k < 2.0 {
+        let d = (4.0 - k2).sqrt();
+        let j = FRAC_PI_2 + ((2.0 - k2) / (k * d)).atan();
+        2.0 * j / (k * d)
+    } else if k == 2.0 {
+        1.0
+    } else {
+        let b = k2 - 2.0;
+        let p = (b + (b * b - 4.0).sqrt()) * 0.5;
+        let r = p.recip();
+        (p / r).ln() / (p - r)
+    };
+
+    k2 * 0.5 * j
+}
+
 fn gain_db_to_a(gain_db: f32) -> f32 {
     10.0f32.powf(gain_db * (1.0 / 40.0))
 }
@@ -216,15 +637,51 @@ fn scale_q_norm_for_order(q_norm: f32, scale: f32) -> f32 {
     }
 }
 
-#[cfg(feature = "portable-simd")]
+/// Several filters packed into SIMD vectors. Without the `portable-simd`
+/// feature the vectors are emulated with scalar code (see [`crate::simd`]).
 pub mod simd {
-    use std::{
-        array,
-        simd::{f32x4, f32x8},
-    };
+    use std::array;
+
+    use crate::simd::{f32x2, f32x4, f32x8};
 
     use super::{SvfCoeff, SvfState};
 
+    /// The coefficients of two SVF (state variable filter) models packed
+    /// into an SIMD vector.
+    pub struct SvfCoeffx2 {
+        pub a1: f32x2,
+        pub a2: f32x2,
+        pub a3: f32x2,
+
+        pub m0: f32x2,
+        pub m1: f32x2,
+        pub m2: f32x2,
+    }
+
+    impl SvfCoeffx2 {
+        pub const fn splat(coeffs: SvfCoeff) -> Self {
+            Self {
+                a1: f32x2::splat(coeffs.a1),
+                a2: f32x2::splat(coeffs.a2),
+                a3: f32x2::splat(coeffs.a3),
+                m0: f32x2::splat(coeffs.m0),
+                m1: f32x2::splat(coeffs.m1),
+                m2: f32x2::splat(coeffs.m2),
+            }
+        }
+
+        pub fn load(coeffs: &[SvfCoeff; 2]) -> Self {
+            Self {
+                a1: f32x2::from_array(array::from_fn(|i| coeffs[i].a1)),
+                a2: f32x2::from_array(array::from_fn(|i| coeffs[i].a2)),
+                a3: f32x2::from_array(array::from_fn(|i| coeffs[i].a3)),
+                m0: f32x2::from_array(array::from_fn(|i| coeffs[i].m0)),
+                m1: f32x2::from_array(array::from_fn(|i| coeffs[i].m1)),
+                m2: f32x2::from_array(array::from_fn(|i| coeffs[i].m2)),
+            }
+        }
+    }
+
     /// The coefficients of four SVF (state variable filter) models packed
     /// into an SIMD vector.
     pub struct SvfCoeffx4 {
@@ -297,6 +754,59 @@ pub mod simd {
         }
     }
 
+    /// The state of two SVF (state variable filter) models packed into an
+    /// SIMD vector.
+    #[derive(Default, Clone, Copy)]
+    pub struct SvfStatex2 {
+        pub ic1eq: f32x2,
+        pub ic2eq: f32x2,
+    }
+
+    impl SvfStatex2 {
+        pub const fn splat(state: SvfState) -> Self {
+            Self {
+                ic1eq: f32x2::splat(state.ic1eq),
+                ic2eq: f32x2::splat(state.ic2eq),
+            }
+        }
+
+        pub fn load(states: &[SvfState; 2]) -> Self {
+            Self {
+                ic1eq: f32x2::from_array(array::from_fn(|i| states[i].ic1eq)),
+                ic2eq: f32x2::from_array(array::from_fn(|i| states[i].ic2eq)),
+            }
+        }
+
+        pub fn store(&self, states: &mut [SvfState; 2]) {
+            let ic1eq = self.ic1eq.to_array();
+            let ic2eq = self.ic2eq.to_array();
+
+            for (i, s) in states.iter_mut().enumerate() {
+                s.ic1eq = ic1eq[i];
+                s.ic2eq = ic2eq[i];
+            }
+        }
+
+        #[inline(always)]
+        pub fn tick(&mut self, input: f32x2, coeff: &SvfCoeffx2) -> f32x2 {
+            const V_2: f32x2 = f32x2::from_array([2.0; 2]);
+
+            let v3 = input - self.ic2eq;
+            let v1 = coeff.a1 * self.ic1eq + coeff.a2 * v3;
+            let v2 = self.ic2eq + coeff.a2 * self.ic1eq + coeff.a3 * v3;
+            self.ic1eq = V_2 * v1 - self.ic1eq;
+            self.ic2eq = V_2 * v2 - self.ic2eq;
+
+            coeff.m0 * input + coeff.m1 * v1 + coeff.m2 * v2
+        }
+
+        #[inline(always)]
+        pub fn reset(&mut self) {
+            self.ic1eq = f32x2::splat(0.0);
+            self.ic2eq = f32x2::splat(0.0);
+        }
+    }
+
     /// The state of four SVF (state variable filter) models packed into an
     /// SIMD vector.
     #[derive(Default, Clone, Copy)]
@@ -313,7 +823,7 @@ pub mod simd {
             }
         }
 
-        pub fn load(&mut self, states: &[SvfState; 4]) -> Self {
+        pub fn load(states: &[SvfState; 4]) -> Self {
             Self {
                 ic1eq: f32x4::from_array(array::from_fn(|i| states[i].ic1eq)),
                 ic2eq: f32x4::from_array(array::from_fn(|i| states[i].ic2eq)),
@@ -366,7 +876,7 @@ pub mod simd {
             }
         }
 
-        pub fn load(&mut self, states: &[SvfState; 8]) -> Self {
+        pub fn load(states: &[SvfState; 8]) -> Self {
             Self {
                 ic1eq: f32x8::from_array(array::from_fn(|i| states[i].ic1eq)),
                 ic2eq: f32x8::from_array(array::from_fn(|i| states[i].ic2eq)),
@@ -403,3 +913,169 @@ pub mod simd {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{noise, pink_noise, rms, Rng};
+
+    /// A random stable filter, with random output mix coefficients.
+    fn random_coeff(rng: &mut Rng) -> SvfCoeff {
+        let cutoff_hz = rng.log_range(10.0, 23_000.0) as f32;
+        let k = rng.log_range(0.02, 20.0) as f32;
+
+        SvfCoeff::from_g_and_k(
+            prewarp(cutoff_hz, 1.0 / 48_000.0),
+            k,
+            rng.range(-4.0, 4.0) as f32,
+            rng.range(-4.0, 4.0) as f32,
+            rng.range(-4.0, 4.0) as f32,
+        )
+    }
+
+    #[test]
+    fn tick_is_linear() {
+        let mut rng = Rng::new(1);
+
+        for seed in 0..500 {
+            let coeff = random_coeff(&mut rng);
+            let mut state = SvfState::default();
+            let mut state_x2 = SvfState::default();
+
+            // Scaling by two is exact in floating point, so the outputs must
+            // match bit for bit.
+            for x in noise(256, seed) {
+                let out = tick(&mut state, x, &coeff);
+                let out_x2 = state_x2.tick(2.0 * x, &coeff);
+                assert_eq!(out_x2, 2.0 * out);
+            }
+        }
+    }
+
+    /// The packed filters (emulated with scalar code without the
+    /// `portable-simd` feature) must match the scalar filters bit for bit.
+    #[test]
+    fn packed_tick_matches_scalar() {
+        use crate::simd::{f32x2, f32x4, f32x8};
+        use simd::{SvfCoeffx2, SvfCoeffx4, SvfCoeffx8, SvfStatex2, SvfStatex4, SvfStatex8};
+
+        let mut rng = Rng::new(2);
+
+        macro_rules! check {
+            ($lanes:literal, $vector:ident, $coeff:ident, $state:ident) => {
+                for seed in 0..50 {
+                    let coeffs: [SvfCoeff; $lanes] =
+                        std::array::from_fn(|_| random_coeff(&mut rng));
+                    let inputs: [Vec<f32>; $lanes] =
+                        std::array::from_fn(|i| noise(256, seed * 8 + i as u64));
+
+                    let coeff_packed = $coeff::load(&coeffs);
+                    let mut state_packed = $state::default();
+                    let mut states = [SvfState::default(); $lanes];
+
+                    for frame in 0..256 {
+                        let out = state_packed
+                            .tick(
+                                $vector::from_array(std::array::from_fn(|i| inputs[i][frame])),
+                                &coeff_packed,
+                            )
+                            .to_array();
+
+                        for (i, state) in states.iter_mut().enumerate() {
+                            assert_eq!(out[i], state.tick(inputs[i][frame], &coeffs[i]));
+                        }
+                    }
+
+                    let mut stored = [SvfState::default(); $lanes];
+                    state_packed.store(&mut stored);
+                    for (stored, state) in stored.iter().zip(states.iter()) {
+                        assert_eq!((stored.ic1eq, stored.ic2eq), (state.ic1eq, state.ic2eq));
+                    }
+                }
+            };
+        }
+
+        check!(2, f32x2, SvfCoeffx2, SvfStatex2);
+        check!(4, f32x4, SvfCoeffx4, SvfStatex4);
+        check!(8, f32x8, SvfCoeffx8, SvfStatex8);
+    }
+
+    /// Regression test for the high shelf, which used to divide `g` by
+    /// `sqrt(A)` instead of multiplying it, which moved its midpoint away
+    /// from the cutoff by the shelf gain.
+    #[test]
+    fn shelf_midpoint_is_at_cutoff() {
+        let sample_rate_recip = 1.0 / 48_000.0;
+
+        for cutoff_hz in [100.0, 1_000.0, 10_000.0] {
+            for gain_db in [-24.0, -6.0, 6.0, 24.0] {
+                for (name, coeff) in [
+                    (
+                        "low shelf",
+                        SvfCoeff::low_shelf(cutoff_hz, 0.707, gain_db, sample_rate_recip),
+                    ),
+                    (
+                        "high shelf",
+                        SvfCoeff::high_shelf(cutoff_hz, 0.707, gain_db, sample_rate_recip),
+                    ),
+                ] {
+                    let magnitude = coeff
+                        .to_f64()
+                        .magnitude(cutoff_hz as f64, sample_rate_recip as f64);
+                    let midpoint_db = 20.0 * magnitude.log10();
+
+                    assert!(
+                        (midpoint_db - 0.5 * gain_db as f64).abs() < 1e-3,
+                        "{name} {cutoff_hz} Hz {gain_db} dB: {midpoint_db} dB at the cutoff"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn notch_compensated_keeps_pink_noise_loudness() {
+        const SAMPLE_RATE: f32 = 48_000.0;
+        let sr_recip = SAMPLE_RATE.recip();
+
+        // The compensation is for the energy in the audible range, so only
+        // measure that range.
+        let audible_band: Vec<SvfCoeff> =
+            SvfCoeff::highpass_ord4(20.0, Q_BUTTERWORTH_ORD2 as f32, sr_recip)
+                .into_iter()
+                .chain(SvfCoeff::lowpass_ord4(
+                    20_000.0,
+                    Q_BUTTERWORTH_ORD2 as f32,
+                    sr_recip,
+                ))
+                .collect();
+        let audible_rms = |coeffs: &[SvfCoeff]| {
+            let mut buf = pink_noise(2 * SAMPLE_RATE as usize, 0);
+            for coeff in coeffs.iter().chain(audible_band.iter()) {
+                let mut state = SvfState::default();
+                for s in buf.iter_mut() {
+                    *s = state.tick(*s, coeff);
+                }
+            }
+            rms(&buf)
+        };
+
+        let input_rms = audible_rms(&[]);
+        let rms_change_db = |coeff: SvfCoeff| 20.0 * (audible_rms(&[coeff]) / input_rms).log10();
+
+        // Notches which lie well within the audible range.
+        for cutoff_hz in [200.0, 1_000.0, 4_000.0] {
+            for q in [0.5, 1.0, 2.0] {
+                let plain = rms_change_db(SvfCoeff::notch(cutoff_hz, q, sr_recip));
+                let compensated =
+                    rms_change_db(SvfCoeff::notch_compensated(cutoff_hz, q, sr_recip));
+
+                assert!(plain < -0.1, "{cutoff_hz} Hz, q = {q}: {plain} dB");
+                assert!(
+                    compensated.abs() < 0.5 * plain.abs(),
+                    "{cutoff_hz} Hz, q = {q}: {compensated} dB (plain {plain} dB)"
+                );
+            }
+        }
+    }
+}
@@ -1,27 +1,56 @@
 //! An implementation of Andrew Simper's SVF (state variable filter) model (f64 version).
 //! https://cytomic.com/files/dsp/SvfLinearTrapOptimised2.pdf
+//!
+//! The model is zero-delay-feedback (trapezoidal integration), and the cutoff
+//! is prewarped, so the response at the cutoff frequency is exactly that of
+//! the analog prototype at any sample rate. For example, a Butterworth lowpass
+//! at 1 kHz is exactly -3 dB at 1 kHz whether it runs at 44.1 kHz or 192 kHz.
+//! The only requirement is that the cutoff lies below the Nyquist frequency.
 
-use std::f64::consts::PI;
+use std::f64::consts::{FRAC_1_SQRT_2, FRAC_PI_2, PI};
 
 use super::f32::SvfCoeff as SvfCoeffF32;
+use crate::complex::Complex64;
 
-pub const Q_BUTTERWORTH_ORD2: f64 = 0.70710678118654752440;
+pub const Q_BUTTERWORTH_ORD2: f64 = FRAC_1_SQRT_2;
+#[allow(clippy::excessive_precision)]
 pub const Q_BUTTERWORTH_ORD4: [f64; 2] = [0.54119610014619698440, 1.3065629648763765279];
-pub const Q_BUTTERWORTH_ORD6: [f64; 3] = [
-    0.51763809020504152470,
-    0.70710678118654752440,
-    1.9318516525781365735,
-];
+#[allow(clippy::excessive_precision)]
+pub const Q_BUTTERWORTH_ORD6: [f64; 3] =
+    [0.51763809020504152470, FRAC_1_SQRT_2, 1.9318516525781365735];
+#[allow(clippy::excessive_precision)]
 pub const Q_BUTTERWORTH_ORD8: [f64; 4] = [
     0.50979557910415916894,
     0.60134488693504528054,
     0.89997622313641570464,
     2.5629154477415061788,
 ];
+#[allow(clippy::excessive_precision)]
+pub const Q_BUTTERWORTH_ORD12: [f64; 6] = [
+    0.50431448029007636036,
+    0.54119610014619698440,
+    0.63023620700513223342,
+    0.82133981585229078570,
+    1.3065629648763765279,
+    3.8306487877701943345,
+];
+#[allow(clippy::excessive_precision)]
+pub const Q_BUTTERWORTH_ORD16: [f64; 8] = [
+    0.50241928618815570551,
+    0.52249861493968888063,
+    0.56694403481635770368,
+    0.64682178335999012955,
+    0.78815462345125022473,
+    1.0606776859903474713,
+    1.7224470982383339278,
+    5.1011486186891638581,
+];
 
 pub const ORD4_Q_SCALE: f64 = 0.35;
 pub const ORD6_Q_SCALE: f64 = 0.2;
 pub const ORD8_Q_SCALE: f64 = 0.14;
+pub const ORD12_Q_SCALE: f64 = 0.08;
+pub const ORD16_Q_SCALE: f64 = 0.06;
 
 /// The coefficients for an SVF (state variable filter) model.
 #[derive(Default, Clone, Copy)]
@@ -46,14 +75,14 @@ impl SvfCoeff {
     };
 
     pub fn lowpass_ord2(cutoff_hz: f64, q: f64, sample_rate_recip: f64) -> Self {
-        let g = g(cutoff_hz, sample_rate_recip);
+        let g = prewarp(cutoff_hz, sample_rate_recip);
         let k = 1.0 / q;
 
         Self::from_g_and_k(g, k, 0.0, 0.0, 1.0)
     }
 
     pub fn lowpass_ord4(cutoff_hz: f64, q: f64, sample_rate_recip: f64) -> [Self; 2] {
-        let g = g(cutoff_hz, sample_rate_recip);
+        let g = prewarp(cutoff_hz, sample_rate_recip);
         let q_norm = scale_q_norm_for_order(q_norm(q), ORD4_Q_SCALE);
 
         std::array::from_fn(|i| {
@@ -65,7 +94,7 @@ impl SvfCoeff {
     }
 
     pub fn lowpass_ord6(cutoff_hz: f64, q: f64, sample_rate_recip: f64) -> [Self; 3] {
-        let g = g(cutoff_hz, sample_rate_recip);
+        let g = prewarp(cutoff_hz, sample_rate_recip);
         let q_norm = scale_q_norm_for_order(q_norm(q), ORD6_Q_SCALE);
 
         std::array::from_fn(|i| {
@@ -77,7 +106,7 @@ impl SvfCoeff {
     }
 
     pub fn lowpass_ord8(cutoff_hz: f64, q: f64, sample_rate_recip: f64) -> [Self; 4] {
-        let g = g(cutoff_hz, sample_rate_recip);
+        let g = prewarp(cutoff_hz, sample_rate_recip);
         let q_norm = scale_q_norm_for_order(q_norm(q), ORD8_Q_SCALE);
 
         std::array::from_fn(|i| {
@@ -88,15 +117,39 @@ impl SvfCoeff {
         })
     }
 
+    pub fn lowpass_ord12(cutoff_hz: f64, q: f64, sample_rate_recip: f64) -> [Self; 6] {
+        let g = prewarp(cutoff_hz, sample_rate_recip);
+        let q_norm = scale_q_norm_for_order(q_norm(q), ORD12_Q_SCALE);
+
+        std::array::from_fn(|i| {
+            let q = q_norm * Q_BUTTERWORTH_ORD12[i];
+            let k = 1.0 / q;
+
+            Self::from_g_and_k(g, k, 0.0, 0.0, 1.0)
+        })
+    }
+
+    pub fn lowpass_ord16(cutoff_hz: f64, q: f64, sample_rate_recip: f64) -> [Self; 8] {
+        let g = prewarp(cutoff_hz, sample_rate_recip);
+        let q_norm = scale_q_norm_for_order(q_norm(q), ORD16_Q_SCALE);
+
+        std::array::from_fn(|i| {
+            let q = q_norm * Q_BUTTERWORTH_ORD16[i];
+            let k = 1.0 / q;
+
+            Self::from_g_and_k(g, k, 0.0, 0.0, 1.0)
+        })
+    }
+
     pub fn highpass_ord2(cutoff_hz: f64, q: f64, sample_rate_recip: f64) -> Self {
-        let g = g(cutoff_hz, sample_rate_recip);
+        let g = prewarp(cutoff_hz, sample_rate_recip);
         let k = 1.0 / q;
 
         Self::from_g_and_k(g, k, 1.0, -k, -1.0)
     }
 
     pub fn highpass_ord4(cutoff_hz: f64, q: f64, sample_rate_recip: f64) -> [Self; 2] {
-        let g = g(cutoff_hz, sample_rate_recip);
+        let g = prewarp(cutoff_hz, sample_rate_recip);
         let q_norm = scale_q_norm_for_order(q_norm(q), ORD4_Q_SCALE);
 
         std::array::from_fn(|i| {
@@ -108,7 +161,7 @@ impl SvfCoeff {
     }
 
     pub fn highpass_ord6(cutoff_hz: f64, q: f64, sample_rate_recip: f64) -> [Self; 3] {
-        let g = g(cutoff_hz, sample_rate_recip);
+        let g = prewarp(cutoff_hz, sample_rate_recip);
         let q_norm = scale_q_norm_for_order(q_norm(q), ORD6_Q_SCALE);
 
         std::array::from_fn(|i| {
@@ -120,7 +173,7 @@ impl SvfCoeff {
     }
 
     pub fn highpass_ord8(cutoff_hz: f64, q: f64, sample_rate_recip: f64) -> [Self; 4] {
-        let g = g(cutoff_hz, sample_rate_recip);
+        let g = prewarp(cutoff_hz, sample_rate_recip);
         let q_norm = scale_q_norm_for_order(q_norm(q), ORD8_Q_SCALE);
 
         std::array::from_fn(|i| {
@@ -131,22 +184,147 @@ impl SvfCoeff {
         })
     }
 
+    pub fn highpass_ord12(cutoff_hz: f64, q: f64, sample_rate_recip: f64) -> [Self; 6] {
+        let g = prewarp(cutoff_hz, sample_rate_recip);
+        let q_norm = scale_q_norm_for_order(q_norm(q), ORD12_Q_SCALE);
+
+        std::array::from_fn(|i| {
+            let q = q_norm * Q_BUTTERWORTH_ORD12[i];
+            let k = 1.0 / q;
+
+            Self::from_g_and_k(g, k, 1.0, -k, -1.0)
+        })
+    }
+
+    pub fn highpass_ord16(cutoff_hz: f64, q: f64, sample_rate_recip: f64) -> [Self; 8] {
+        let g = prewarp(cutoff_hz, sample_rate_recip);
+        let q_norm = scale_q_norm_for_order(q_norm(q), ORD16_Q_SCALE);
+
+        std::array::from_fn(|i| {
+            let q = q_norm * Q_BUTTERWORTH_ORD16[i];
+            let k = 1.0 / q;
+
+            Self::from_g_and_k(g, k, 1.0, -k, -1.0)
+        })
+    }
+
+    /// A bandpass filter normalized to have a gain of `1.0` (0 dB) at
+    /// `cutoff_hz`.
+    pub fn bandpass(cutoff_hz: f64, q: f64, sample_rate_recip: f64) -> Self {
+        let g = prewarp(cutoff_hz, sample_rate_recip);
+        let k = 1.0 / q;
+
+        Self::from_g_and_k(g, k, 0.0, k, 0.0)
+    }
+
+    /// A filter which morphs between a lowpass at `morph = 0.0`, a bandpass
+    /// (normalized like [`SvfCoeff::bandpass`]) at `morph = 0.5`, and a
+    /// highpass at `morph = 1.0`, crossfading linearly in between. `morph` is
+    /// clamped to the range `[0.0, 1.0]`.
+    ///
+    /// This gives the same output as blending the outputs of
+    /// [`SvfState::tick_multi`], since each of them is a mix of the same
+    /// internal values.
+    pub fn multimode(cutoff_hz: f64, q: f64, morph: f64, sample_rate_recip: f64) -> Self {
+        let g = prewarp(cutoff_hz, sample_rate_recip);
+        let k = 1.0 / q;
+
+        let morph = morph.clamp(0.0, 1.0);
+        let lp_mix = (1.0 - 2.0 * morph).max(0.0);
+        let hp_mix = (2.0 * morph - 1.0).max(0.0);
+        let bp_mix = 1.0 - lp_mix - hp_mix;
+
+        // lp = v2, bp = k * v1, hp = input - k * v1 - v2
+        Self::from_g_and_k(g, k, hp_mix, k * (bp_mix - hp_mix), lp_mix - hp_mix)
+    }
+
+    /// A notch filter with a true zero (infinite attenuation) at `cutoff_hz`.
+    ///
+    /// The analog prototype `(s^2 + 1) / (s^2 + s / q + 1)` has its zeros on
+    /// the imaginary axis, and since the cutoff is prewarped the bilinear
+    /// transform maps them onto the unit circle at exactly `cutoff_hz`. `q`
+    /// only sets the width of the notch, not its depth. In practice the depth
+    /// is limited by the precision of the coefficients and the filter state.
     pub fn notch(cutoff_hz: f64, q: f64, sample_rate_recip: f64) -> Self {
-        let g = g(cutoff_hz, sample_rate_recip);
+        let g = prewarp(cutoff_hz, sample_rate_recip);
         let k = 1.0 / q;
 
         Self::from_g_and_k(g, k, 1.0, -k, 0.0)
     }
 
+    /// A notch filter with a broadband makeup gain applied so that the
+    /// perceived loudness stays roughly constant.
+    ///
+    /// This is a heuristic. The makeup gain compensates for the energy the
+    /// notch removes from pink noise over the audible range (20Hz - 20kHz),
+    /// assuming the notch lies well within that range.
+    pub fn notch_compensated(cutoff_hz: f64, q: f64, sample_rate_recip: f64) -> Self {
+        let g = prewarp(cutoff_hz, sample_rate_recip);
+        let k = 1.0 / q;
+
+        let removed = (notch_removed_log_bandwidth(k) * (1.0 / AUDIBLE_LOG_BANDWIDTH)).min(0.9);
+        let makeup = 1.0 / (1.0 - removed).sqrt();
+
+        Self::from_g_and_k(g, k, makeup, -k * makeup, 0.0)
+    }
+
+    /// A bell filter with "proportional Q".
+    ///
+    /// `q` is the center frequency divided by the bandwidth between the points
+    /// where the gain is half of `gain_db` (in decibels). This bandwidth stays
+    /// the same at any gain, so the bell looks narrower the more it is boosted
+    /// or cut. A cut is the exact inverse of a boost by the same amount.
     pub fn bell(cutoff_hz: f64, q: f64, gain_db: f64, sample_rate_recip: f64) -> Self {
         let a = gain_db_to_a(gain_db);
 
-        let g = g(cutoff_hz, sample_rate_recip);
+        let g = prewarp(cutoff_hz, sample_rate_recip);
         let k = 1.0 / (q * a);
 
         Self::from_g_and_k(g, k, 1.0, k * (a * a - 1.0), 0.0)
     }
 
+    /// A bell filter with "constant Q".
+    ///
+    /// For gains of at least +/-6 dB, `q` is the center frequency divided by
+    /// the bandwidth between the points 3 dB below the peak (or above the
+    /// dip), so the width of the bell stays the same as the gain changes.
+    /// Below +/-6 dB those points do not exist (or lie far away), so the
+    /// response falls back to that of [`SvfCoeff::bell`], which is identical
+    /// at exactly +/-6 dB. A cut is the exact inverse of a boost by the same
+    /// amount.
+    pub fn bell_constant_q(cutoff_hz: f64, q: f64, gain_db: f64, sample_rate_recip: f64) -> Self {
+        let a = gain_db_to_a(gain_db);
+        let a2 = a * a;
+
+        let g = prewarp(cutoff_hz, sample_rate_recip);
+        let k = if a2 >= 2.0 {
+            (a2 * a2 - 2.0).sqrt() / (q * a2)
+        } else if a2 <= 0.5 {
+            // The inverse of the boost with gain `1 / a`.
+            let a2_recip = a2.recip();
+            (a2_recip * a2_recip - 2.0).sqrt() / q
+        } else {
+            1.0 / (q * a)
+        };
+
+        Self::from_g_and_k(g, k, 1.0, k * (a2 - 1.0), 0.0)
+    }
+
+    /// A low shelf filter.
+    ///
+    /// `q` shapes the transition between the two levels. At
+    /// `Q_BUTTERWORTH_ORD2` (and below) the transition is monotonic. Raising `q`
+    /// makes the transition steeper and adds a resonant overshoot on the
+    /// shelved side and a matching undershoot on the other side (for a +6 dB
+    /// shelf, roughly +2.9/-2.9 dB at `q = 2.0` and +7/-7 dB at `q = 4.0`),
+    /// like many analog shelving EQs.
+    ///
+    /// The flat side of the shelf is exactly 0 dB at the Nyquist frequency for any cutoff
+    /// and sample rate, since the SVF is equivalent to the bilinear transform
+    /// (cramping only squeezes the transition). Towards the cutoff it
+    /// approaches 0 dB like the analog shelf: a decade above the cutoff it
+    /// is within 0.002 dB at `Q_BUTTERWORTH_ORD2`, and within about 0.06 dB
+    /// for a 6 dB shelf at a `q` of `0.5` or `2.0`.
     pub fn low_shelf(cutoff_hz: f64, q: f64, gain_db: f64, sample_rate_recip: f64) -> Self {
         let a = gain_db_to_a(gain_db);
 
@@ -156,22 +334,41 @@ impl SvfCoeff {
         Self::from_g_and_k(g, k, 1.0, k * (a - 1.0), a * a - 1.0)
     }
 
+    /// A high shelf filter.
+    ///
+    /// `q` shapes the transition between the two levels. At
+    /// `Q_BUTTERWORTH_ORD2` (and below) the transition is monotonic. Raising `q`
+    /// makes the transition steeper and adds a resonant overshoot on the
+    /// shelved side and a matching undershoot on the other side (for a +6 dB
+    /// shelf, roughly +2.9/-2.9 dB at `q = 2.0` and +7/-7 dB at `q = 4.0`),
+    /// like many analog shelving EQs.
+    ///
+    /// The flat side of the shelf is exactly 0 dB at DC for any cutoff
+    /// and sample rate, since the SVF is equivalent to the bilinear transform
+    /// (cramping only squeezes the transition). Towards the cutoff it
+    /// approaches 0 dB like the analog shelf: a decade below the cutoff it
+    /// is within 0.002 dB at `Q_BUTTERWORTH_ORD2`, and within about 0.06 dB
+    /// for a 6 dB shelf at a `q` of `0.5` or `2.0`.
     pub fn high_shelf(cutoff_hz: f64, q: f64, gain_db: f64, sample_rate_recip: f64) -> Self {
         let a = gain_db_to_a(gain_db);
 
-        let g = (PI * cutoff_hz * sample_rate_recip).tan() / a.sqrt();
+        let g = (PI * cutoff_hz * sample_rate_recip).tan() * a.sqrt();
         let k = 1.0 / q;
 
         Self::from_g_and_k(g, k, a * a, k * (1.0 - a) * a, 1.0 - a * a)
     }
 
     pub fn allpass(cutoff_hz: f64, q: f64, sample_rate_recip: f64) -> Self {
-        let g = g(cutoff_hz, sample_rate_recip);
+        let g = prewarp(cutoff_hz, sample_rate_recip);
         let k = 1.0 / q;
 
         Self::from_g_and_k(g, k, 1.0, -2.0 * k, 0.0)
     }
 
+    /// Create the coefficients from the prewarped cutoff `g` (see
+    /// [`prewarp`]), the damping `k` (`1.0 / q`), and the output mix of the
+    /// input (`m0`), the bandpass output (`m1`), and the lowpass output
+    /// (`m2`).
     pub fn from_g_and_k(g: f64, k: f64, m0: f64, m1: f64, m2: f64) -> Self {
         let a1 = 1.0 / (1.0 + g * (g + k));
         let a2 = g * a1;
@@ -187,6 +384,207 @@ impl SvfCoeff {
         }
     }
 
+    /// The same as [`SvfCoeff::from_g_and_k`], but from a cutoff in Hz using
+    /// the cheaper [`prewarp_fast`] instead of [`prewarp`]. This is meant for
+    /// modulating the cutoff every sample (e.g. for a synth filter), where a
+    /// slightly flat cutoff matters less than CPU. See [`prewarp_fast`] for
+    /// the accuracy.
+    #[inline]
+    pub fn from_cutoff_fast(
+        cutoff_hz: f64,
+        k: f64,
+        m0: f64,
+        m1: f64,
+        m2: f64,
+        sample_rate_recip: f64,
+    ) -> Self {
+        Self::from_g_and_k(prewarp_fast(cutoff_hz, sample_rate_recip), k, m0, m1, m2)
+    }
+
+    /// Returns the magnitude (raw amplitude, not decibels) of the frequency
+    /// response of this filter at the given frequency.
+    pub fn magnitude(&self, freq_hz: f64, sample_rate_recip: f64) -> f64 {
+        if self.a2 == 0.0 {
+            // The filter only passes the input through.
+            return self.m0.abs();
+        }
+
+        // The trapezoidal integrators are equivalent to the bilinear transform,
+        // so the response is that of the analog prototype evaluated at the
+        // prewarped frequency.
+        let (g, _) = self.g_and_k();
+        let w = (PI * freq_hz * sample_rate_recip).tan() / g;
+
+        self.analog_magnitude(w)
+    }
+
+    /// Returns the complex frequency response of this filter at the given
+    /// frequency. Unlike [`SvfCoeff::magnitude`] this includes the phase,
+    /// which is needed to sum the responses of filters run in parallel.
+    pub fn response(&self, freq_hz: f64, sample_rate_recip: f64) -> Complex64 {
+        if self.a2 == 0.0 {
+            // The filter only passes the input through.
+            return Complex64::new(self.m0, 0.0);
+        }
+
+        let (g, k) = self.g_and_k();
+        let w = (PI * freq_hz * sample_rate_recip).tan() / g;
+
+        let den_re = 1.0 - w * w;
+        let den_im = k * w;
+        let num_re = self.m0 * den_re + self.m2;
+        let num_im = self.m0 * den_im + self.m1 * w;
+
+        let den_norm = den_re * den_re + den_im * den_im;
+        Complex64::new(
+            (num_re * den_re + num_im * den_im) / den_norm,
+            (num_im * den_re - num_re * den_im) / den_norm,
+        )
+    }
+
+    /// Adjust the cutoff of a cascade of lowpass or highpass filters (as
+    /// returned by the `lowpass_*` and `highpass_*` constructors) so that the
+    /// -3 dB point of the whole cascade lands exactly on the cutoff frequency
+    /// the filters were created with.
+    ///
+    /// The bilinear transform used by this filter model maps the cutoff
+    /// exactly, so the -3 dB point already lands on the cutoff for a
+    /// Butterworth response. For any other Q, the -3 dB point of the analog
+    /// prototype itself lies away from the cutoff, and this corrects for that.
+    ///
+    /// All of the given filters must share the same cutoff.
+    pub fn match_3db_cutoff(filters: &mut [Self], is_lowpass: bool) {
+        if filters.is_empty() || filters[0].a2 == 0.0 {
+            return;
+        }
+
+        const TARGET: f64 = FRAC_1_SQRT_2;
+        let cascade_magnitude = |w: f64| -> f64 {
+            filters
+                .iter()
+                .map(|f| f.analog_magnitude(w))
+                .product::<f64>()
+        };
+
+        // Bisect in log frequency (relative to the cutoff of the analog
+        // prototype).
+        let mut lo = -10.0f64;
+        let mut hi = 10.0f64;
+        for _ in 0..64 {
+            let mid = 0.5 * (lo + hi);
+            let passes = cascade_magnitude(mid.exp()) >= TARGET;
+
+            if passes == is_lowpass {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        let ratio = (0.5 * (lo + hi)).exp();
+
+        for f in filters.iter_mut() {
+            let (g, k) = f.g_and_k();
+            *f = Self::from_g_and_k(g / ratio, k, f.m0, f.m1, f.m2);
+        }
+    }
+
+    /// Whether both poles of the filter lie strictly inside the unit circle.
+    ///
+    /// The coefficients constructed by this type from a positive cutoff
+    /// below the Nyquist frequency and a positive Q are always stable, but
+    /// coefficients from [`SvfCoeff::from_g_and_k`] or ones that were edited
+    /// directly might not be.
+    pub fn is_stable(&self) -> bool {
+        if self.a2 == 0.0 {
+            // The filter only passes the input through.
+            return true;
+        }
+
+        // The denominator of the transfer function normalized to
+        // `z^2 + b1*z + b2`.
+        let b1 = 2.0 * (self.a3 - self.a1);
+        let b2 = 2.0 * (self.a1 + self.a3) - 1.0;
+
+        b2.abs() < 1.0 && b1.abs() < 1.0 + b2
+    }
+
+    /// The two poles of the filter in the z-plane.
+    ///
+    /// The filter is stable if both poles lie inside the unit circle. For a
+    /// filter which passes the input through unchanged (i.e.
+    /// [`SvfCoeff::NO_OP`]), both poles are at the origin.
+    pub fn poles(&self) -> [Complex64; 2] {
+        if self.a2 == 0.0 {
+            return [Complex64::default(); 2];
+        }
+
+        // The trapezoidal integrators are equivalent to the bilinear
+        // transform `s = (z - 1) / (g * (z + 1))` of the analog prototype
+        // `s^2 + k*s + 1`.
+        let (g, k) = self.g_and_k();
+        let g2 = g * g;
+
+        quadratic_roots(1.0 + k * g + g2, 2.0 * (g2 - 1.0), 1.0 - k * g + g2)
+    }
+
+    /// The two zeros of the filter in the z-plane.
+    ///
+    /// A zero which the bilinear transform maps to infinity (this happens if
+    /// the analog prototype has fewer than two zeros at a finite frequency
+    /// and the digital filter has a zero at Nyquist instead) is returned
+    /// with infinite components. For a filter which passes the input through
+    /// unchanged (i.e. [`SvfCoeff::NO_OP`]), both zeros are at the origin.
+    pub fn zeros(&self) -> [Complex64; 2] {
+        if self.a2 == 0.0 {
+            return [Complex64::default(); 2];
+        }
+
+        // The numerator of the analog prototype is
+        // `m0 * (s^2 + k*s + 1) + m1 * s + m2`.
+        let (g, k) = self.g_and_k();
+        let g2 = g * g;
+
+        quadratic_roots(
+            self.m0 * (1.0 + k * g + g2) + self.m1 * g + self.m2 * g2,
+            2.0 * self.m0 * (g2 - 1.0) + 2.0 * self.m2 * g2,
+            self.m0 * (1.0 - k * g + g2) - self.m1 * g + self.m2 * g2,
+        )
+    }
+
+    /// Recover the prewarped cutoff `g` and the damping `k` from the
+    /// coefficients (the inverse of [`SvfCoeff::from_g_and_k`]). This is not
+    /// meaningful for coefficients which only pass the input through.
+    pub fn g_and_k(&self) -> (f64, f64) {
+        let g = self.a2 / self.a1;
+        let k = (self.a1.recip() - 1.0 - g * g) / g;
+
+        (g, k)
+    }
+
+    /// The magnitude of the analog prototype at the frequency `w` (relative to
+    /// the cutoff).
+    fn analog_magnitude(&self, w: f64) -> f64 {
+        let (_, k) = self.g_and_k();
+
+        let den_re = 1.0 - w * w;
+        let den_im = k * w;
+        let num_re = self.m0 * den_re + self.m2;
+        let num_im = self.m0 * den_im + self.m1 * w;
+
+        ((num_re * num_re + num_im * num_im) / (den_re * den_re + den_im * den_im)).sqrt()
+    }
+
+    /// The gain of the filter for a constant (DC) input. Unlike
+    /// [`SvfCoeff::magnitude`], this keeps the sign.
+    pub fn dc_gain(&self) -> f64 {
+        if self.a2 == 0.0 {
+            // The filter only passes the input through.
+            self.m0
+        } else {
+            self.m0 + self.m2
+        }
+    }
+
     pub fn to_f32(self) -> SvfCoeffF32 {
         SvfCoeffF32 {
             a1: self.a1 as f32,
@@ -197,9 +595,94 @@ impl SvfCoeff {
             m2: self.m2 as f32,
         }
     }
+
+    /// The coefficients as a flat array in the order
+    /// `[a1, a2, a3, m0, m1, m2]`.
+    ///
+    /// This layout is stable, so it can be used for serialization or for
+    /// uploading the coefficients to a GPU.
+    pub const fn as_array(&self) -> [f64; 6] {
+        [self.a1, self.a2, self.a3, self.m0, self.m1, self.m2]
+    }
+
+    /// Create the coefficients from a flat array in the order
+    /// `[a1, a2, a3, m0, m1, m2]`. See [`SvfCoeff::as_array`].
+    pub const fn from_array(a: [f64; 6]) -> Self {
+        Self {
+            a1: a[0],
+            a2: a[1],
+            a3: a[2],
+            m0: a[3],
+            m1: a[4],
+            m2: a[5],
+        }
+    }
+
+    /// A key which can be used to look up these coefficients in a hash map
+    /// or set. See [`SvfCoeffKey`].
+    pub fn key(&self) -> SvfCoeffKey {
+        SvfCoeffKey(self.as_array().map(canonical_bits))
+    }
+
+    /// Returns `true` if every coefficient is within `epsilon` of the matching
+    /// coefficient of `other`. This is `false` if any coefficient is NaN.
+    pub fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.as_array()
+            .iter()
+            .zip(other.as_array().iter())
+            .all(|(a, b)| (a - b).abs() <= epsilon)
+    }
+}
+
+/// A hashable key for an [`SvfCoeff`], for use in caches.
+///
+/// `SvfCoeff` can't implement `Eq` or `Hash` because its fields are floats.
+/// Hashing the raw bits of floats is unsafe for two reasons: `NaN != NaN`
+/// breaks the reflexivity that `Eq` requires (so an entry with a NaN key could
+/// be inserted but never found again), and `0.0 == -0.0` even though their
+/// bits differ (so equal values could hash differently). This key compares
+/// the bits instead, after mapping `-0.0` to `0.0` and every NaN to a single
+/// NaN. Two coefficients have equal keys exactly when all of their fields are
+/// equal, treating NaN as equal to itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SvfCoeffKey([u64; 6]);
+
+impl From<SvfCoeff> for SvfCoeffKey {
+    fn from(coeff: SvfCoeff) -> Self {
+        coeff.key()
+    }
+}
+
+/// The bits of `x`, with `-0.0` mapped to `0.0` and every NaN mapped to the
+/// same NaN.
+fn canonical_bits(x: f64) -> u64 {
+    if x.is_nan() {
+        f64::NAN.to_bits()
+    } else if x == 0.0 {
+        0
+    } else {
+        x.to_bits()
+    }
+}
+
+/// The lowpass, bandpass, and highpass outputs of an SVF (state variable
+/// filter) model, as returned by [`SvfState::tick_multi`].
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub struct SvfOutputs {
+    /// The same response as [`SvfCoeff::lowpass_ord2`].
+    pub lp: f64,
+    /// The same response as [`SvfCoeff::bandpass`] (unity gain at the
+    /// cutoff).
+    pub bp: f64,
+    /// The same response as [`SvfCoeff::highpass_ord2`].
+    pub hp: f64,
 }
 
 /// The state of an SVF (state variable filter) model.
+///
+/// The filter is linear: with `ic1eq` and `ic2eq` at zero (see
+/// [`SvfState::reset`]), scaling the input by a constant scales the output by
+/// the same constant. From any other state, the state must be scaled as well.
 #[derive(Default, Clone, Copy)]
 pub struct SvfState {
     pub ic1eq: f64,
@@ -218,21 +701,161 @@ impl SvfState {
         coeff.m0 * input + coeff.m1 * v1 + coeff.m2 * v2
     }
 
+    /// Process a single sample, and return the lowpass, bandpass, and
+    /// highpass outputs all at once. This is useful for morphing between
+    /// filter modes without running a separate filter for each mode.
+    ///
+    /// Only the cutoff and Q of `coeff` are used (its output mix is
+    /// ignored), so any of the second order constructors with the desired
+    /// cutoff and Q can be used. The three outputs always sum to the input.
+    #[inline(always)]
+    pub fn tick_multi(&mut self, input: f64, coeff: &SvfCoeff) -> SvfOutputs {
+        let v3 = input - self.ic2eq;
+        let v1 = coeff.a1 * self.ic1eq + coeff.a2 * v3;
+        let v2 = self.ic2eq + coeff.a2 * self.ic1eq + coeff.a3 * v3;
+
+        // The bandpass integrator gives `v1 = ic1eq + g * hp`, where
+        // `g = a2 / a1`.
+        let hp = if coeff.a2 == 0.0 {
+            // The filter only passes the input through.
+            input
+        } else {
+            (v1 - self.ic1eq) * coeff.a1 / coeff.a2
+        };
+
+        self.ic1eq = 2.0 * v1 - self.ic1eq;
+        self.ic2eq = 2.0 * v2 - self.ic2eq;
+
+        SvfOutputs {
+            lp: v2,
+            bp: input - v2 - hp,
+            hp,
+        }
+    }
+
     #[inline(always)]
     pub fn reset(&mut self) {
         self.ic1eq = 0.0;
         self.ic2eq = 0.0;
     }
+
+    /// Set the state to the steady state for a constant input of `input`,
+    /// i.e. the state the filter converges to once the input has been
+    /// constant for a long time. This avoids a startup transient when the
+    /// input does not start at zero.
+    ///
+    /// The steady state of a (stable) SVF does not depend on its
+    /// coefficients.
+    pub fn prime_for_dc(&mut self, input: f64) {
+        self.ic1eq = 0.0;
+        self.ic2eq = input;
+    }
+
+    /// Returns `true` if both state variables are within `epsilon` of those
+    /// of `other`. This is `false` if any of them is NaN.
+    pub fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        (self.ic1eq - other.ic1eq).abs() <= epsilon && (self.ic2eq - other.ic2eq).abs() <= epsilon
+    }
+}
+
+/// The same as [`SvfState::tick`], but as a free function so that it can be
+/// referenced by a function pointer.
+#[inline(always)]
+pub fn tick(state: &mut SvfState, input: f64, coeff: &SvfCoeff) -> f64 {
+    state.tick(input, coeff)
 }
 
-fn g(cutoff_hz: f64, sample_rate_recip: f64) -> f64 {
+/// The prewarped cutoff `g = tan(PI * cutoff_hz * sample_rate_recip)`, as
+/// used by all of the filter constructors. Pass this to
+/// [`SvfCoeff::from_g_and_k`] to build custom filters which are tuned exactly
+/// like the built-in ones at any sample rate.
+///
+/// No clamping is done. `cutoff_hz` must be positive and below the Nyquist
+/// frequency (`0.5 / sample_rate_recip`): `g` grows without bound as the
+/// cutoff approaches the Nyquist frequency, and is negative above it, which
+/// gives an unstable filter.
+pub fn prewarp(cutoff_hz: f64, sample_rate_recip: f64) -> f64 {
     (PI * cutoff_hz * sample_rate_recip).tan()
 }
 
+/// A cheaper approximation of [`prewarp`] for modulating the cutoff every
+/// sample, using a rational (Pade) approximation of `tan` which only needs a
+/// few multiplies and one division.
+///
+/// The approximation is always slightly low, so the cutoff of the filter
+/// lands slightly flat. The error is below 0.01 cents up to an eighth of the
+/// sample rate, below 0.25 cents up to a quarter of the sample rate, and
+/// below 7 cents up to 0.45 times the sample rate. It stays positive and
+/// finite all the way up to the Nyquist frequency, so the filter is always
+/// stable. `cutoff_hz` must be positive and below the Nyquist frequency.
+#[inline]
+pub fn prewarp_fast(cutoff_hz: f64, sample_rate_recip: f64) -> f64 {
+    let x = PI * cutoff_hz * sample_rate_recip;
+    let x2 = x * x;
+
+    x * (15.0 - x2) / (15.0 - 6.0 * x2)
+}
+
 fn q_norm(q: f64) -> f64 {
     q * (1.0 / Q_BUTTERWORTH_ORD2)
 }
 
+/// `ln(20_000 / 20)`
+const AUDIBLE_LOG_BANDWIDTH: f64 = 6.907_755;
+
+/// The bandwidth (in natural-log frequency units) of the energy removed from
+/// pink noise by a notch filter with the given damping, i.e. the integral of
+/// `1 - |H(jw)|^2` over `ln(w)`.
+fn notch_removed_log_bandwidth(k: f64) -> f64 {
+    let k2 = k * k;
+
+    let j = if k < 2.0 {
+        let d = (4.0 - k2).sqrt();
+        let j = FRAC_PI_2 + ((2.0 - k2) / (k * d)).atan();
+        2.0 * j / (k * d)
+    } else if k == 2.0 {
+        1.0
+    } else {
+        let b = k2 - 2.0;
+        let p = (b + (b * b - 4.0).sqrt()) * 0.5;
+        let r = p.recip();
+        (p / r).ln() / (p - r)
+    };
+
+    k2 * 0.5 * j
+}
+
+/// The roots of `a*x^2 + b*x + c`.
+fn quadratic_roots(a: f64, b: f64, c: f64) -> [Complex64; 2] {
+    if a == 0.0 {
+        let inf = Complex64::new(f64::INFINITY, 0.0);
+
+        return if b == 0.0 {
+            [inf, inf]
+        } else {
+            [Complex64::new(-c / b, 0.0), inf]
+        };
+    }
+
+    let disc = b * b - 4.0 * a * c;
+
+    if disc < 0.0 {
+        let re = -b / (2.0 * a);
+        let im = (-disc).sqrt() / (2.0 * a);
+
+        [Complex64::new(re, im), Complex64::new(re, -im)]
+    } else {
+        // Avoid cancellation by computing the larger root first.
+        let q = -0.5 * (b + b.signum() * disc.sqrt());
+
+        if q == 0.0 {
+            [Complex64::default(); 2]
+        } else {
+            [Complex64::new(q / a, 0.0), Complex64::new(c / q, 0.0)]
+        }
+    }
+}
+
 fn gain_db_to_a(gain_db: f64) -> f64 {
     10.0f64.powf(gain_db * (1.0 / 40.0))
 }
@@ -245,12 +868,12 @@ fn scale_q_norm_for_order(q_norm: f64, scale: f64) -> f64 {
     }
 }
 
-#[cfg(feature = "portable-simd")]
+/// Several filters packed into SIMD vectors. Without the `portable-simd`
+/// feature the vectors are emulated with scalar code (see [`crate::simd`]).
 pub mod simd {
-    use std::{
-        array,
-        simd::{f64x2, f64x4},
-    };
+    use std::array;
+
+    use crate::simd::{f64x2, f64x4};
 
     use super::{SvfCoeff, SvfState};
 
@@ -342,7 +965,7 @@ pub mod simd {
             }
         }
 
-        pub fn load(&mut self, states: &[SvfState; 2]) -> Self {
+        pub fn load(states: &[SvfState; 2]) -> Self {
             Self {
                 ic1eq: f64x2::from_array(array::from_fn(|i| states[i].ic1eq)),
                 ic2eq: f64x2::from_array(array::from_fn(|i| states[i].ic2eq)),
@@ -395,7 +1018,7 @@ pub mod simd {
             }
         }
 
-        pub fn load(&mut self, states: &[SvfState; 4]) -> Self {
+        pub fn load(states: &[SvfState; 4]) -> Self {
             Self {
                 ic1eq: f64x4::from_array(array::from_fn(|i| states[i].ic1eq)),
                 ic2eq: f64x4::from_array(array::from_fn(|i| states[i].ic2eq)),
@@ -432,3 +1055,329 @@ pub mod simd {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{noise, sine_gain_db, Rng};
+
+    /// A random stable filter, with random output mix coefficients.
+    fn random_coeff(rng: &mut Rng) -> SvfCoeff {
+        let cutoff_hz = rng.log_range(10.0, 23_000.0);
+        let k = rng.log_range(0.02, 20.0);
+
+        SvfCoeff::from_g_and_k(
+            prewarp(cutoff_hz, 1.0 / 48_000.0),
+            k,
+            rng.range(-4.0, 4.0),
+            rng.range(-4.0, 4.0),
+            rng.range(-4.0, 4.0),
+        )
+    }
+
+    #[test]
+    fn tick_is_linear() {
+        let mut rng = Rng::new(2);
+
+        for seed in 0..500 {
+            let coeff = random_coeff(&mut rng);
+            let mut state = SvfState::default();
+            let mut state_x2 = SvfState::default();
+
+            // Scaling by two is exact in floating point, so the outputs must
+            // match bit for bit.
+            for x in noise(256, seed) {
+                let x = x as f64;
+                let out = tick(&mut state, x, &coeff);
+                let out_x2 = state_x2.tick(2.0 * x, &coeff);
+                assert_eq!(out_x2, 2.0 * out);
+            }
+        }
+    }
+
+    /// Regression test for the high shelf, which used to divide `g` by
+    /// `sqrt(A)` instead of multiplying it, which moved its midpoint away
+    /// from the cutoff by the shelf gain.
+    #[test]
+    fn shelf_midpoint_is_at_cutoff() {
+        let sample_rate_recip = 1.0 / 48_000.0;
+
+        for cutoff_hz in [100.0, 1_000.0, 10_000.0] {
+            for gain_db in [-24.0, -6.0, 6.0, 24.0] {
+                for (name, coeff) in [
+                    (
+                        "low shelf",
+                        SvfCoeff::low_shelf(cutoff_hz, 0.707, gain_db, sample_rate_recip),
+                    ),
+                    (
+                        "high shelf",
+                        SvfCoeff::high_shelf(cutoff_hz, 0.707, gain_db, sample_rate_recip),
+                    ),
+                ] {
+                    let magnitude = coeff.magnitude(cutoff_hz, sample_rate_recip);
+                    let midpoint_db = 20.0 * magnitude.log10();
+
+                    assert!(
+                        (midpoint_db - 0.5 * gain_db).abs() < 1e-3,
+                        "{name} {cutoff_hz} Hz {gain_db} dB: {midpoint_db} dB at the cutoff"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn bell_constant_q_bandwidth_is_independent_of_gain() {
+        let sample_rate_recip = 1.0 / 48_000.0;
+        let cutoff_hz = 1_000.0;
+
+        for q in [0.5, 1.0, 2.0, 4.0] {
+            // Up to about +/-6 dB the bell falls back to proportional Q.
+            for gain_db in [-24.0, -12.0, -7.0, 7.0, 9.0, 12.0, 18.0, 24.0] {
+                let coeff = SvfCoeff::bell_constant_q(cutoff_hz, q, gain_db, sample_rate_recip);
+                let gain_db_at =
+                    |freq_hz: f64| 20.0 * coeff.magnitude(freq_hz, sample_rate_recip).log10();
+
+                // The points 3 dB (half the power) below the peak, or above
+                // the dip.
+                let level_db = gain_db - (10.0 * 2.0f64.log10()).copysign(gain_db);
+                let crossing_hz = |lo_hz: f64, hi_hz: f64| {
+                    let (mut lo, mut hi) = (lo_hz.ln(), hi_hz.ln());
+                    let lo_inside = (gain_db_at(lo_hz) - level_db) * gain_db > 0.0;
+                    for _ in 0..64 {
+                        let mid = 0.5 * (lo + hi);
+                        let inside = (gain_db_at(mid.exp()) - level_db) * gain_db > 0.0;
+                        if inside == lo_inside {
+                            lo = mid;
+                        } else {
+                            hi = mid;
+                        }
+                    }
+                    (0.5 * (lo + hi)).exp()
+                };
+
+                // Measure the bandwidth in the prewarped (analog) frequencies,
+                // where the definition of `q` is exact.
+                let warped = |freq_hz: f64| {
+                    prewarp(freq_hz, sample_rate_recip) / prewarp(cutoff_hz, sample_rate_recip)
+                };
+                let bandwidth = warped(crossing_hz(cutoff_hz, cutoff_hz * 64.0))
+                    - warped(crossing_hz(cutoff_hz / 64.0, cutoff_hz));
+                let measured_q = bandwidth.recip();
+
+                assert!(
+                    (measured_q / q - 1.0).abs() < 1e-6,
+                    "q = {q}, {gain_db} dB: measured q = {measured_q}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn lowpass_corner_is_independent_of_sample_rate() {
+        for sample_rate in [44_100.0, 48_000.0, 96_000.0, 192_000.0] {
+            let coeff = SvfCoeff::lowpass_ord2(1_000.0, Q_BUTTERWORTH_ORD2, 1.0 / sample_rate);
+            let mut state = SvfState::default();
+
+            let gain_db = sine_gain_db(1_000.0, sample_rate, |buf| {
+                for s in buf.iter_mut() {
+                    *s = state.tick(*s as f64, &coeff) as f32;
+                }
+            });
+
+            // 0.01 dB corresponds to moving the corner by about 0.1%.
+            assert!(
+                (gain_db + 3.0103).abs() < 0.01,
+                "{sample_rate} Hz: {gain_db} dB at 1 kHz"
+            );
+        }
+    }
+
+    /// The flat side of a shelf is exactly 0 dB at DC or Nyquist, and
+    /// approaches it like the analog shelf towards the cutoff.
+    #[test]
+    fn shelf_flat_side_is_unity() {
+        let sample_rate = 48_000.0;
+        let sample_rate_recip = 1.0 / sample_rate;
+
+        for cutoff_hz in [100.0, 2_000.0, 20_000.0] {
+            for gain_db in [-12.0, 6.0, 12.0] {
+                let low =
+                    SvfCoeff::low_shelf(cutoff_hz, Q_BUTTERWORTH_ORD2, gain_db, sample_rate_recip);
+                let high =
+                    SvfCoeff::high_shelf(cutoff_hz, Q_BUTTERWORTH_ORD2, gain_db, sample_rate_recip);
+
+                // Evaluating right at Nyquist would divide by `tan(PI / 2)`.
+                let nyquist_db = 20.0
+                    * low
+                        .magnitude(0.5 * sample_rate - 1e-9, sample_rate_recip)
+                        .log10();
+                let dc_db = 20.0 * high.magnitude(1e-9, sample_rate_recip).log10();
+                assert!(
+                    nyquist_db.abs() < 1e-6,
+                    "low shelf {cutoff_hz} Hz {gain_db} dB: {nyquist_db} dB"
+                );
+                assert!(
+                    dc_db.abs() < 1e-6,
+                    "high shelf {cutoff_hz} Hz {gain_db} dB: {dc_db} dB"
+                );
+            }
+        }
+
+        for (q, tolerance_db) in [(Q_BUTTERWORTH_ORD2, 0.002), (0.5, 0.06), (2.0, 0.06)] {
+            let low = SvfCoeff::low_shelf(200.0, q, 6.0, sample_rate_recip);
+            let high = SvfCoeff::high_shelf(2_000.0, q, 6.0, sample_rate_recip);
+
+            let above_db = 20.0 * low.magnitude(2_000.0, sample_rate_recip).log10();
+            let below_db = 20.0 * high.magnitude(200.0, sample_rate_recip).log10();
+            assert!(
+                above_db.abs() < tolerance_db,
+                "low shelf q {q}: {above_db} dB"
+            );
+            assert!(
+                below_db.abs() < tolerance_db,
+                "high shelf q {q}: {below_db} dB"
+            );
+        }
+    }
+
+    /// Check the resonant overshoot and undershoot documented on
+    /// [`SvfCoeff::low_shelf`] and [`SvfCoeff::high_shelf`]: beyond the shelf
+    /// gain on the shelved side, and below 0 dB on the other side.
+    #[test]
+    fn shelf_resonance_matches_docs() {
+        let sample_rate_recip = 1.0 / 48_000.0;
+        let cutoff_hz = 1_000.0;
+        let gain_db = 6.0;
+
+        // At `Q_BUTTERWORTH_ORD2` the transition is monotonic.
+        for (q, expected_db) in [(Q_BUTTERWORTH_ORD2, 0.0), (2.0, 2.9), (4.0, 7.0)] {
+            for (name, coeff) in [
+                (
+                    "low shelf",
+                    SvfCoeff::low_shelf(cutoff_hz, q, gain_db, sample_rate_recip),
+                ),
+                (
+                    "high shelf",
+                    SvfCoeff::high_shelf(cutoff_hz, q, gain_db, sample_rate_recip),
+                ),
+            ] {
+                let (mut max_db, mut min_db) = (f64::MIN, f64::MAX);
+                for i in 0..=2_000 {
+                    let freq_hz = 20.0 * 1_000.0f64.powf(i as f64 / 2_000.0);
+                    let db = 20.0 * coeff.magnitude(freq_hz, sample_rate_recip).log10();
+                    max_db = max_db.max(db);
+                    min_db = min_db.min(db);
+                }
+                let overshoot_db = max_db - gain_db;
+                let undershoot_db = -min_db;
+                assert!(
+                    (overshoot_db - expected_db).abs() < 0.15,
+                    "{name} q {q}: {overshoot_db} dB overshoot"
+                );
+                assert!(
+                    (undershoot_db - expected_db).abs() < 0.15,
+                    "{name} q {q}: {undershoot_db} dB undershoot"
+                );
+            }
+        }
+    }
+
+    /// The poles of a Butterworth lowpass are the analog poles
+    /// `e^(+/-j*3*pi/4)` mapped through the bilinear transform
+    /// `z = (1 + g*s) / (1 - g*s)`, and both of its zeros are at Nyquist.
+    #[test]
+    fn lowpass_poles_and_zeros() {
+        let sample_rate_recip = 1.0 / 48_000.0;
+
+        for cutoff_hz in [50.0, 1_000.0, 10_000.0, 20_000.0] {
+            let coeff = SvfCoeff::lowpass_ord2(cutoff_hz, Q_BUTTERWORTH_ORD2, sample_rate_recip);
+            let g = prewarp(cutoff_hz, sample_rate_recip);
+
+            // `g*s` for the analog pole in the upper half plane.
+            let (gs_re, gs_im) = (-g * FRAC_1_SQRT_2, g * FRAC_1_SQRT_2);
+            let (num_re, num_im) = (1.0 + gs_re, gs_im);
+            let (den_re, den_im) = (1.0 - gs_re, -gs_im);
+            let den_norm_sqr = den_re * den_re + den_im * den_im;
+            let expected = Complex64::new(
+                (num_re * den_re + num_im * den_im) / den_norm_sqr,
+                (num_im * den_re - num_re * den_im) / den_norm_sqr,
+            );
+
+            let mut poles = coeff.poles();
+            poles.sort_by(|a, b| b.im.total_cmp(&a.im));
+            for (pole, expected) in poles.iter().zip([expected, expected.conj()]) {
+                assert!(
+                    (pole.re - expected.re).abs() < 1e-12 && (pole.im - expected.im).abs() < 1e-12,
+                    "{cutoff_hz} Hz: pole {pole:?}, expected {expected:?}"
+                );
+            }
+
+            // A double root, so it is only found to about the square root of
+            // the precision.
+            for zero in coeff.zeros() {
+                assert!(
+                    (zero.re + 1.0).abs() < 1e-6 && zero.im.abs() < 1e-6,
+                    "{cutoff_hz} Hz: zero {zero:?}"
+                );
+            }
+        }
+    }
+
+    /// `is_stable` agrees with both poles lying inside the unit circle.
+    #[test]
+    fn is_stable_matches_poles() {
+        let sample_rate_recip = 1.0 / 48_000.0;
+        let mut rng = Rng::new(5);
+
+        for _ in 0..10_000 {
+            let g = prewarp(rng.log_range(10.0, 23_000.0), sample_rate_recip);
+            let k = rng.range(-2.0, 4.0);
+            if k.abs() < 1e-6 {
+                // Both poles are on the unit circle.
+                continue;
+            }
+            let coeff = SvfCoeff::from_g_and_k(g, k, 0.0, 0.0, 1.0);
+
+            let poles_inside = coeff.poles().iter().all(|p| p.norm() < 1.0);
+            assert_eq!(coeff.is_stable(), poles_inside, "g = {g}, k = {k}");
+        }
+    }
+
+    /// Check the accuracy documented on [`prewarp_fast`].
+    #[test]
+    fn prewarp_fast_error_bounds() {
+        let sample_rate = 48_000.0;
+        let sample_rate_recip = 1.0 / sample_rate;
+
+        // The error of the cutoff which `prewarp_fast` actually tunes the
+        // filter to, in cents.
+        let error_cents = |cutoff_hz: f64| {
+            let g = prewarp_fast(cutoff_hz, sample_rate_recip);
+            let actual_hz = g.atan() / (PI * sample_rate_recip);
+            1200.0 * (actual_hz / cutoff_hz).log2()
+        };
+
+        for (max_ratio, max_cents) in [(0.125, 0.01), (0.25, 0.25), (0.45, 7.0)] {
+            let steps = 10_000;
+            for i in 1..=steps {
+                let cutoff_hz = max_ratio * sample_rate * i as f64 / steps as f64;
+                let error = error_cents(cutoff_hz);
+
+                // Allow for rounding at very low cutoffs, where the error
+                // is tiny.
+                assert!(error < 1e-9, "{cutoff_hz} Hz is {error} cents sharp");
+                assert!(error > -max_cents, "{cutoff_hz} Hz is {error} cents flat");
+            }
+        }
+
+        for cutoff_hz in [
+            0.45 * sample_rate,
+            0.499 * sample_rate,
+            0.5 * sample_rate - 1e-6,
+        ] {
+            let g = prewarp_fast(cutoff_hz, sample_rate_recip);
+            assert!(g.is_finite() && g > 0.0, "{cutoff_hz} Hz: g = {g}");
+        }
+    }
+}
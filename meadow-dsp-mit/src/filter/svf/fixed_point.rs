@@ -0,0 +1,230 @@
+//! A fixed-point version of the SVF (state variable filter) model, for
+//! targets without an FPU.
+//!
+//! The input and output samples are in Q1.31 ([`I1F31`]), the format of most
+//! fixed-point DSP chips. Only integer multiplies (32 x 32 -> 64 bits), adds,
+//! and shifts are used while processing, and every product is rounded to
+//! the nearest value.
+//!
+//! # Headroom
+//!
+//! The internal values of the filter can exceed the range of the input, so
+//! the state and the output mix coefficients are stored in Q4.28 ([`I4F28`]),
+//! which covers the range `[-8.0, 8.0)` at the cost of three bits of
+//! precision. The bandpass state grows with Q (to about `q` times the input
+//! at the cutoff), so with a full-scale input the Q should stay below about
+//! `4.0`. Mix coefficients beyond `[-8.0, 8.0)` (for example a `q` below
+//! `0.125`, or a bell with a `q` of `1.0` boosted by more than about 36 dB)
+//! cannot be represented.
+//!
+//! All arithmetic saturates instead of wrapping, so an overflow clips the
+//! signal rather than producing a full-scale burst of noise, and the output
+//! is clipped to the Q1.31 range.
+
+use fixed::types::{I1F31, I4F28};
+
+use super::f64::SvfCoeff as SvfCoeffF64;
+
+/// The coefficients for a fixed-point SVF (state variable filter) model.
+///
+/// These are converted from [`SvfCoeffF64`], which can be computed once ahead
+/// of time (or at startup) on targets which cannot afford to compute them in
+/// floating point.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SvfCoeffFixed {
+    pub a1: I1F31,
+    pub a2: I1F31,
+    pub a3: I1F31,
+
+    pub m0: I4F28,
+    pub m1: I4F28,
+    pub m2: I4F28,
+}
+
+impl SvfCoeffFixed {
+    pub const NO_OP: Self = Self {
+        a1: I1F31::ZERO,
+        a2: I1F31::ZERO,
+        a3: I1F31::ZERO,
+        m0: I4F28::ONE,
+        m1: I4F28::ZERO,
+        m2: I4F28::ZERO,
+    };
+
+    /// Convert the given coefficients, saturating any values which do not fit
+    /// (see the [module docs](self) for the ranges).
+    pub fn from_f64(coeff: &SvfCoeffF64) -> Self {
+        Self {
+            a1: I1F31::saturating_from_num(coeff.a1),
+            a2: I1F31::saturating_from_num(coeff.a2),
+            a3: I1F31::saturating_from_num(coeff.a3),
+            m0: I4F28::saturating_from_num(coeff.m0),
+            m1: I4F28::saturating_from_num(coeff.m1),
+            m2: I4F28::saturating_from_num(coeff.m2),
+        }
+    }
+
+    /// Returns `true` if every coefficient fits into the fixed-point formats
+    /// without saturating.
+    pub fn fits(coeff: &SvfCoeffF64) -> bool {
+        [coeff.a1, coeff.a2, coeff.a3]
+            .iter()
+            .all(|&a| I1F31::checked_from_num(a).is_some())
+            && [coeff.m0, coeff.m1, coeff.m2]
+                .iter()
+                .all(|&m| I4F28::checked_from_num(m).is_some())
+    }
+}
+
+/// The state of a fixed-point SVF (state variable filter) model.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SvfStateFixed {
+    pub ic1eq: I4F28,
+    pub ic2eq: I4F28,
+}
+
+impl SvfStateFixed {
+    /// The same as [`SvfState::tick`](super::f32::SvfState::tick), but in
+    /// fixed point.
+    #[inline(always)]
+    pub fn tick(&mut self, input: I1F31, coeff: &SvfCoeffFixed) -> I1F31 {
+        let input = I4F28::from_num(input);
+
+        let v3 = input.saturating_sub(self.ic2eq);
+        let v1 = mul_a(coeff.a1, self.ic1eq).saturating_add(mul_a(coeff.a2, v3));
+        let v2 = self
+            .ic2eq
+            .saturating_add(mul_a(coeff.a2, self.ic1eq))
+            .saturating_add(mul_a(coeff.a3, v3));
+        self.ic1eq = v1.saturating_mul_int(2).saturating_sub(self.ic1eq);
+        self.ic2eq = v2.saturating_mul_int(2).saturating_sub(self.ic2eq);
+
+        let out = mul_m(coeff.m0, input)
+            .saturating_add(mul_m(coeff.m1, v1))
+            .saturating_add(mul_m(coeff.m2, v2));
+
+        I1F31::saturating_from_num(out)
+    }
+
+    /// Process the given buffer in place.
+    pub fn process(&mut self, buf: &mut [I1F31], coeff: &SvfCoeffFixed) {
+        for s in buf.iter_mut() {
+            *s = self.tick(*s, coeff);
+        }
+    }
+
+    #[inline(always)]
+    pub fn reset(&mut self) {
+        self.ic1eq = I4F28::ZERO;
+        self.ic2eq = I4F28::ZERO;
+    }
+}
+
+/// Multiply a value by one of the `a` coefficients. Since `|a| < 1.0`, this
+/// cannot overflow.
+#[inline(always)]
+fn mul_a(a: I1F31, x: I4F28) -> I4F28 {
+    I4F28::from_bits(round_shift(a.to_bits() as i64 * x.to_bits() as i64, 31) as i32)
+}
+
+/// Multiply a value by one of the `m` coefficients, saturating on overflow.
+#[inline(always)]
+fn mul_m(m: I4F28, x: I4F28) -> I4F28 {
+    let product = round_shift(m.to_bits() as i64 * x.to_bits() as i64, 28);
+    I4F28::from_bits(product.clamp(i32::MIN as i64, i32::MAX as i64) as i32)
+}
+
+/// Shift right by `shift` bits, rounding to the nearest value.
+#[inline(always)]
+fn round_shift(x: i64, shift: u32) -> i64 {
+    (x + (1 << (shift - 1))) >> shift
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        filter::svf::f32::{SvfCoeff, SvfState},
+        test_util::{noise, sine, Rng},
+    };
+
+    const SAMPLE_RATE: f32 = 48_000.0;
+
+    fn to_fixed(buf: &[f32]) -> Vec<I1F31> {
+        buf.iter().map(|&s| I1F31::from_num(s)).collect()
+    }
+
+    #[test]
+    fn matches_f32_over_a_lowpass_sweep() {
+        let input = noise(4_800, 0);
+        let half_scale: Vec<f32> = input.iter().map(|s| 0.5 * s).collect();
+        let sr_recip = SAMPLE_RATE.recip();
+
+        let mut rng = Rng::new(1);
+        for _ in 0..32 {
+            let cutoff_hz = rng.log_range(20.0, 20_000.0);
+            let q = rng.log_range(0.5, 2.0);
+
+            let coeff = SvfCoeff::lowpass_ord2(cutoff_hz as f32, q as f32, sr_recip);
+            let coeff_fixed =
+                SvfCoeffFixed::from_f64(&SvfCoeffF64::lowpass_ord2(cutoff_hz, q, sr_recip as f64));
+            assert!(SvfCoeffFixed::fits(&coeff.to_f64()));
+
+            let mut state = SvfState::default();
+            let mut state_fixed = SvfStateFixed::default();
+            let mut out_fixed = to_fixed(&half_scale);
+            state_fixed.process(&mut out_fixed, &coeff_fixed);
+
+            for (&x, y_fixed) in half_scale.iter().zip(out_fixed) {
+                let y = state.tick(x, &coeff);
+                let error = (y - y_fixed.to_num::<f32>()).abs();
+                assert!(error < 1e-4, "{cutoff_hz} Hz, q = {q}: {error}");
+            }
+        }
+    }
+
+    #[test]
+    fn coefficients_outside_the_headroom_saturate() {
+        let sr_recip = 1.0 / SAMPLE_RATE as f64;
+
+        // The notch mixes in `-1 / q` of the bandpass.
+        let notch = SvfCoeffF64::notch(1_000.0, 0.2, sr_recip);
+        assert!(SvfCoeffFixed::fits(&notch));
+        let notch = SvfCoeffF64::notch(1_000.0, 0.1, sr_recip);
+        assert!(!SvfCoeffFixed::fits(&notch));
+        assert_eq!(SvfCoeffFixed::from_f64(&notch).m1, I4F28::MIN);
+
+        let bell = SvfCoeffF64::bell(1_000.0, 1.0, 30.0, sr_recip);
+        assert!(SvfCoeffFixed::fits(&bell));
+        let bell = SvfCoeffF64::bell(1_000.0, 1.0, 40.0, sr_recip);
+        assert!(!SvfCoeffFixed::fits(&bell));
+        assert_eq!(SvfCoeffFixed::from_f64(&bell).m1, I4F28::MAX);
+    }
+
+    #[test]
+    fn overdriven_filter_clips_without_wrapping() {
+        // At the cutoff, the lowpass with `q = 16` has a gain of 16, and its
+        // state grows past the Q4.28 range as well as the output.
+        let coeff = SvfCoeffFixed::from_f64(&SvfCoeffF64::lowpass_ord2(
+            1_000.0,
+            16.0,
+            1.0 / SAMPLE_RATE as f64,
+        ));
+        let input: Vec<f32> = sine(1_000.0, SAMPLE_RATE as f64, 4_800)
+            .iter()
+            .map(|s| 0.999 * s)
+            .collect();
+
+        let mut state = SvfStateFixed::default();
+        let mut out = to_fixed(&input);
+        state.process(&mut out, &coeff);
+
+        let out: Vec<f32> = out.iter().map(|s| s.to_num()).collect();
+        assert!(out.iter().any(|&s| s > 0.999));
+        assert!(out.iter().any(|&s| s < -0.999));
+        // A wrapped value would jump from one end of the range to the other.
+        for pair in out.windows(2) {
+            assert!((pair[1] - pair[0]).abs() < 1.0, "{pair:?}");
+        }
+    }
+}
@@ -1,2 +1,4 @@
 pub mod f32;
 pub mod f64;
+#[cfg(feature = "fixed-point")]
+pub mod fixed_point;
@@ -0,0 +1,163 @@
+use std::f64::consts::TAU;
+
+/// The shape of the waveform of an [`Lfo`].
+///
+/// All shapes start at `0.0` and rise (except for [`LfoShape::Square`],
+/// which starts at `1.0`), so they stay in phase with each other.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LfoShape {
+    #[default]
+    Sine,
+    Triangle,
+    /// A rising sawtooth.
+    Saw,
+    Square,
+}
+
+/// A low-frequency oscillator with an output in the range `[-1.0, 1.0]`.
+///
+/// The phase is accumulated in f64, so the rate stays accurate even for very
+/// slow LFOs. Changing the rate or the shape never resets the phase, so the
+/// output stays continuous.
+#[derive(Debug, Clone, Copy)]
+pub struct Lfo {
+    shape: LfoShape,
+    rate_hz: f64,
+
+    /// The phase in the range `[0.0, 1.0)`.
+    phase: f64,
+    phase_step: f64,
+}
+
+impl Lfo {
+    pub fn new(shape: LfoShape, rate_hz: f64, sample_rate: f64) -> Self {
+        let mut new_self = Self {
+            shape,
+            rate_hz: 0.0,
+            phase: 0.0,
+            phase_step: 0.0,
+        };
+
+        new_self.set_rate_hz(rate_hz, sample_rate);
+
+        new_self
+    }
+
+    pub fn shape(&self) -> LfoShape {
+        self.shape
+    }
+
+    pub fn set_shape(&mut self, shape: LfoShape) {
+        self.shape = shape;
+    }
+
+    pub fn rate_hz(&self) -> f64 {
+        self.rate_hz
+    }
+
+    /// Set the rate in Hz. Negative rates are clamped to `0.0`, which freezes
+    /// the LFO.
+    pub fn set_rate_hz(&mut self, rate_hz: f64, sample_rate: f64) {
+        self.rate_hz = rate_hz.max(0.0);
+        self.phase_step = self.rate_hz / sample_rate;
+    }
+
+    /// The phase of the next output in the range `[0.0, 1.0)`, where `1.0` is
+    /// a full cycle.
+    pub fn phase(&self) -> f64 {
+        self.phase
+    }
+
+    /// Set the phase of the next output, where `1.0` is a full cycle. Values
+    /// outside of `[0.0, 1.0)` are wrapped.
+    pub fn set_phase(&mut self, phase: f64) {
+        self.phase = phase.rem_euclid(1.0);
+
+        // `rem_euclid` can round up to exactly `1.0` for tiny negative values.
+        if self.phase >= 1.0 {
+            self.phase = 0.0;
+        }
+    }
+
+    /// Reset the phase to `0.0`.
+    pub fn reset(&mut self) {
+        self.phase = 0.0;
+    }
+
+    /// The output at the current phase, without advancing the LFO.
+    pub fn value(&self) -> f32 {
+        let p = self.phase;
+
+        let out = match self.shape {
+            LfoShape::Sine => (TAU * p).sin(),
+            LfoShape::Triangle => 1.0 - 4.0 * (((p + 0.25) % 1.0) - 0.5).abs(),
+            LfoShape::Saw => 2.0 * ((p + 0.5) % 1.0) - 1.0,
+            LfoShape::Square => {
+                if p < 0.5 {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+        };
+
+        out as f32
+    }
+
+    /// Get the output at the current phase and advance the LFO by one sample.
+    #[allow(clippy::should_implement_trait)]
+    #[inline]
+    pub fn next(&mut self) -> f32 {
+        let out = self.value();
+
+        self.phase += self.phase_step;
+        if self.phase >= 1.0 {
+            self.phase -= 1.0;
+
+            // Only possible with a rate above the sample rate.
+            if self.phase >= 1.0 {
+                self.phase = self.phase.fract();
+            }
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f64 = 48_000.0;
+
+    #[test]
+    fn phase_is_continuous_across_rate_changes() {
+        // The steepest slope of each shape, per cycle.
+        for (shape, max_slope) in [(LfoShape::Sine, TAU), (LfoShape::Triangle, 4.0)] {
+            let mut lfo = Lfo::new(shape, 2.0, SAMPLE_RATE);
+            let mut prev = lfo.next();
+
+            // Switch between slow and fast rates part way through cycles.
+            for (i, rate_hz) in [7.0, 0.5, 20.0, 3.0, 0.0, 11.0].into_iter().enumerate() {
+                for _ in 0..1_000 + 337 * i {
+                    prev = lfo.next();
+                }
+
+                let phase = lfo.phase();
+                let max_step = max_slope * lfo.rate_hz().max(rate_hz) / SAMPLE_RATE;
+                lfo.set_rate_hz(rate_hz, SAMPLE_RATE);
+                assert_eq!(lfo.phase(), phase);
+
+                // The output neither jumps nor restarts.
+                for _ in 0..2 {
+                    let out = lfo.next();
+                    assert!(
+                        ((out - prev).abs() as f64) < max_step + 1e-6,
+                        "{shape:?} at {rate_hz} Hz: {prev} -> {out}"
+                    );
+                    prev = out;
+                }
+            }
+        }
+    }
+}
@@ -1,4 +1,22 @@
 #![cfg_attr(feature = "portable-simd", feature(portable_simd))]
 
+pub mod complex;
+pub mod constant_q;
 pub mod decibel;
+pub mod delay;
+pub mod dynamics;
+#[cfg(feature = "ffi")]
+pub mod ffi;
 pub mod filter;
+pub mod lfo;
+pub mod modulation;
+pub mod phase_align;
+pub mod prelude;
+pub mod processor;
+pub mod simd;
+pub mod smooth;
+#[cfg(test)]
+mod test_util;
+pub mod tone;
+pub mod util;
+pub mod vocoder;
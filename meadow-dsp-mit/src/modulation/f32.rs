@@ -0,0 +1,438 @@
+use crate::{
+    decibel::f32::db_to_amp,
+    delay::f32::DelayLine,
+    dynamics::f32::{DetectionMode, EnvelopeFollower},
+    filter::svf::f32::{SvfCoeff, SvfState},
+    lfo::f32::{Lfo, LfoShape},
+    smooth::f32::{OnePoleSmoother, DEFAULT_SMOOTH_SECS},
+};
+
+/// The largest amount of feedback (in either polarity) allowed by
+/// [`ModulatedDelay::set_feedback`].
+pub const MAX_FEEDBACK: f32 = 0.95;
+
+/// A chorus or flanger: a delay line whose delay time is swept by a sine LFO,
+/// mixed with the dry signal.
+///
+/// A chorus uses a longer delay (around 10-30 ms) and no feedback, while a
+/// flanger uses a very short delay (around 1-5 ms) with feedback. See
+/// [`ModulatedDelay::chorus`] and [`ModulatedDelay::flanger`] for typical
+/// settings.
+///
+/// The delay sweeps between `delay_ms` and `delay_ms + depth_ms`, which must
+/// fit in the `CAPACITY` samples of the delay line (longer delays are
+/// clamped). For example, a `CAPACITY` of `2048` fits 40 ms at 48 kHz.
+///
+/// Changes to the delay, depth, feedback, and mix are smoothed to avoid
+/// clicks.
+#[derive(Clone, Copy)]
+pub struct ModulatedDelay<const CAPACITY: usize> {
+    delay_line: DelayLine<CAPACITY>,
+
+    rate_hz: f32,
+    delay_ms: f32,
+    depth_ms: f32,
+    feedback: f32,
+    mix: f32,
+
+    delay_samples: OnePoleSmoother,
+    depth_samples: OnePoleSmoother,
+    feedback_smoothed: OnePoleSmoother,
+    mix_smoothed: OnePoleSmoother,
+
+    lfo: Lfo,
+
+    sample_rate: f64,
+}
+
+impl<const CAPACITY: usize> ModulatedDelay<CAPACITY> {
+    /// The dry signal is not delayed, so there is no latency. (The wet signal
+    /// is delayed by [`ModulatedDelay::base_delay_samples`] on purpose.)
+    pub const LATENCY: u32 = 0;
+
+    /// Create a new modulated delay.
+    ///
+    /// See the setters for the meaning of the parameters.
+    pub fn new(
+        rate_hz: f32,
+        delay_ms: f32,
+        depth_ms: f32,
+        feedback: f32,
+        mix: f32,
+        sample_rate: f64,
+    ) -> Self {
+        let mut new_self = Self {
+            delay_line: DelayLine::new(),
+            rate_hz: 0.0,
+            delay_ms: 0.0,
+            depth_ms: 0.0,
+            feedback: 0.0,
+            mix: 0.0,
+            delay_samples: OnePoleSmoother::new(0.0, DEFAULT_SMOOTH_SECS, sample_rate),
+            depth_samples: OnePoleSmoother::new(0.0, DEFAULT_SMOOTH_SECS, sample_rate),
+            feedback_smoothed: OnePoleSmoother::new(0.0, DEFAULT_SMOOTH_SECS, sample_rate),
+            mix_smoothed: OnePoleSmoother::new(0.0, DEFAULT_SMOOTH_SECS, sample_rate),
+            lfo: Lfo::new(LfoShape::Sine, 0.0, sample_rate),
+            sample_rate,
+        };
+
+        new_self.set_rate_hz(rate_hz);
+        new_self.set_delay_ms(delay_ms);
+        new_self.set_depth_ms(depth_ms);
+        new_self.set_feedback(feedback);
+        new_self.set_mix(mix);
+
+        new_self
+            .delay_samples
+            .set_immediate(new_self.delay_samples.target());
+        new_self
+            .depth_samples
+            .set_immediate(new_self.depth_samples.target());
+        new_self.feedback_smoothed.set_immediate(new_self.feedback);
+        new_self.mix_smoothed.set_immediate(new_self.mix);
+        new_self.reset_lfo();
+
+        new_self
+    }
+
+    /// A typical chorus: a 15 ms delay swept by 5 ms at 0.8 Hz, with no
+    /// feedback and an equal mix.
+    pub fn chorus(sample_rate: f64) -> Self {
+        Self::new(0.8, 15.0, 5.0, 0.0, 0.5, sample_rate)
+    }
+
+    /// A typical flanger: a 1 ms delay swept by 3 ms at 0.25 Hz, with
+    /// feedback and an equal mix.
+    pub fn flanger(sample_rate: f64) -> Self {
+        Self::new(0.25, 1.0, 3.0, 0.5, 0.5, sample_rate)
+    }
+
+    pub fn rate_hz(&self) -> f32 {
+        self.rate_hz
+    }
+
+    /// Set the rate of the LFO in Hz.
+    pub fn set_rate_hz(&mut self, rate_hz: f32) {
+        self.rate_hz = rate_hz.max(0.0);
+        self.lfo.set_rate_hz(self.rate_hz as f64, self.sample_rate);
+    }
+
+    pub fn delay_ms(&self) -> f32 {
+        self.delay_ms
+    }
+
+    /// Set the shortest delay of the sweep in milliseconds.
+    pub fn set_delay_ms(&mut self, delay_ms: f32) {
+        self.delay_ms = delay_ms.max(0.0);
+        self.delay_samples.set(self.ms_to_samples(self.delay_ms));
+    }
+
+    pub fn depth_ms(&self) -> f32 {
+        self.depth_ms
+    }
+
+    /// Set how far the LFO sweeps the delay (above `delay_ms`) in
+    /// milliseconds.
+    pub fn set_depth_ms(&mut self, depth_ms: f32) {
+        self.depth_ms = depth_ms.max(0.0);
+        self.depth_samples.set(self.ms_to_samples(self.depth_ms));
+    }
+
+    pub fn feedback(&self) -> f32 {
+        self.feedback
+    }
+
+    /// Set how much of the delayed signal is fed back into the delay line, in
+    /// the range `[-MAX_FEEDBACK, MAX_FEEDBACK]`. Negative feedback inverts
+    /// the polarity, which moves the notches of a flanger.
+    pub fn set_feedback(&mut self, feedback: f32) {
+        self.feedback = feedback.clamp(-MAX_FEEDBACK, MAX_FEEDBACK);
+        self.feedback_smoothed.set(self.feedback);
+    }
+
+    pub fn mix(&self) -> f32 {
+        self.mix
+    }
+
+    /// Set the mix in the range `[0.0, 1.0]`, where `0.0` is fully dry and
+    /// `1.0` is fully wet.
+    pub fn set_mix(&mut self, mix: f32) {
+        self.mix = mix.clamp(0.0, 1.0);
+        self.mix_smoothed.set(self.mix);
+    }
+
+    /// The delay of the wet signal in samples at the start of the sweep.
+    pub fn base_delay_samples(&self) -> f32 {
+        self.delay_samples.target()
+    }
+
+    /// Clear the delay line, restart the LFO, and jump all smoothed
+    /// parameters straight to their targets. The parameters are left
+    /// untouched.
+    pub fn reset(&mut self) {
+        self.delay_line.reset();
+        self.reset_lfo();
+
+        self.delay_samples.finish();
+        self.depth_samples.finish();
+        self.feedback_smoothed.finish();
+        self.mix_smoothed.finish();
+    }
+
+    /// Process the given buffer in place.
+    pub fn process(&mut self, buf: &mut [f32]) {
+        for s in buf.iter_mut() {
+            let lfo = 0.5 + 0.5 * self.lfo.next();
+
+            let delay = self.delay_samples.next_value() + self.depth_samples.next_value() * lfo;
+            let wet = self.delay_line.tap(delay);
+
+            let dry = *s;
+            self.delay_line
+                .push(dry + wet * self.feedback_smoothed.next_value());
+
+            *s = dry + (wet - dry) * self.mix_smoothed.next_value();
+        }
+    }
+
+    /// Start the sweep at the shortest delay.
+    fn reset_lfo(&mut self) {
+        self.lfo.set_phase(0.75);
+    }
+
+    fn ms_to_samples(&self, ms: f32) -> f32 {
+        (ms as f64 * 0.001 * self.sample_rate) as f32
+    }
+}
+
+pub const DEFAULT_WAH_SENSITIVITY_DB: f32 = 12.0;
+pub const DEFAULT_WAH_MIN_HZ: f32 = 300.0;
+pub const DEFAULT_WAH_MAX_HZ: f32 = 2_500.0;
+pub const DEFAULT_WAH_Q: f32 = 4.0;
+pub const DEFAULT_WAH_ATTACK_MS: f32 = 5.0;
+pub const DEFAULT_WAH_RELEASE_MS: f32 = 120.0;
+
+/// How often (in samples) the filter coefficients of an [`AutoWah`] are
+/// recomputed. The envelope is already smooth, so this is inaudible.
+const WAH_COEFF_INTERVAL: u32 = 16;
+
+/// An envelope-controlled wah: the level of the input sweeps the center
+/// frequency of a resonant bandpass filter between `min_hz` and `max_hz`.
+///
+/// The envelope is boosted by the sensitivity, and reaching an amplitude of
+/// `1.0` (0 dB) moves the filter all the way to `max_hz`. The sweep is
+/// exponential, so equal changes in level give equal changes in pitch.
+#[derive(Clone, Copy)]
+pub struct AutoWah {
+    sensitivity_db: f32,
+    sensitivity: f32,
+    min_hz: f32,
+    max_hz: f32,
+    q: f32,
+    mix: f32,
+
+    envelope: EnvelopeFollower,
+    mix_smoothed: OnePoleSmoother,
+
+    filter: SvfState,
+    coeff: SvfCoeff,
+    center_hz: f32,
+    samples_until_update: u32,
+
+    max_cutoff_hz: f32,
+    sample_rate_recip: f32,
+}
+
+impl AutoWah {
+    pub const LATENCY: u32 = 0;
+
+    pub fn new(sample_rate: f64) -> Self {
+        let mut new_self = Self {
+            sensitivity_db: 0.0,
+            sensitivity: 1.0,
+            min_hz: DEFAULT_WAH_MIN_HZ,
+            max_hz: DEFAULT_WAH_MAX_HZ,
+            q: DEFAULT_WAH_Q,
+            mix: 1.0,
+            envelope: EnvelopeFollower::new(
+                DEFAULT_WAH_ATTACK_MS,
+                DEFAULT_WAH_RELEASE_MS,
+                DetectionMode::Peak,
+                sample_rate,
+            ),
+            mix_smoothed: OnePoleSmoother::new(1.0, DEFAULT_SMOOTH_SECS, sample_rate),
+            filter: SvfState::default(),
+            coeff: SvfCoeff::NO_OP,
+            center_hz: DEFAULT_WAH_MIN_HZ,
+            samples_until_update: 0,
+            // Keep the filter well below the Nyquist frequency.
+            max_cutoff_hz: (sample_rate * 0.45) as f32,
+            sample_rate_recip: sample_rate.recip() as f32,
+        };
+
+        new_self.set_sensitivity_db(DEFAULT_WAH_SENSITIVITY_DB);
+        new_self.update_coeff(0.0);
+
+        new_self
+    }
+
+    pub fn sensitivity_db(&self) -> f32 {
+        self.sensitivity_db
+    }
+
+    /// Set how much the envelope is boosted (in decibels) before it drives
+    /// the sweep. Higher values open the filter further for quiet signals.
+    pub fn set_sensitivity_db(&mut self, sensitivity_db: f32) {
+        self.sensitivity_db = sensitivity_db;
+        self.sensitivity = db_to_amp(sensitivity_db);
+    }
+
+    pub fn min_hz(&self) -> f32 {
+        self.min_hz
+    }
+
+    pub fn max_hz(&self) -> f32 {
+        self.max_hz
+    }
+
+    /// Set the range of the sweep in Hz. If `min_hz` is greater than
+    /// `max_hz`, then the sweep is inverted (louder input lowers the center
+    /// frequency).
+    pub fn set_range_hz(&mut self, min_hz: f32, max_hz: f32) {
+        self.min_hz = min_hz.clamp(1.0, self.max_cutoff_hz);
+        self.max_hz = max_hz.clamp(1.0, self.max_cutoff_hz);
+    }
+
+    pub fn q(&self) -> f32 {
+        self.q
+    }
+
+    /// Set the Q of the bandpass filter. Higher values give a more
+    /// pronounced "wah".
+    pub fn set_q(&mut self, q: f32) {
+        self.q = q.max(0.1);
+    }
+
+    pub fn mix(&self) -> f32 {
+        self.mix
+    }
+
+    /// Set the mix in the range `[0.0, 1.0]`, where `0.0` is fully dry and
+    /// `1.0` is fully wet.
+    pub fn set_mix(&mut self, mix: f32) {
+        self.mix = mix.clamp(0.0, 1.0);
+        self.mix_smoothed.set(self.mix);
+    }
+
+    pub fn attack_ms(&self) -> f32 {
+        self.envelope.attack_ms()
+    }
+
+    pub fn set_attack_ms(&mut self, attack_ms: f32) {
+        self.envelope.set_attack_ms(attack_ms);
+    }
+
+    pub fn release_ms(&self) -> f32 {
+        self.envelope.release_ms()
+    }
+
+    pub fn set_release_ms(&mut self, release_ms: f32) {
+        self.envelope.set_release_ms(release_ms);
+    }
+
+    /// The current center frequency of the filter in Hz. This is meant to be
+    /// used for visualizing the sweep.
+    pub fn center_hz(&self) -> f32 {
+        self.center_hz
+    }
+
+    /// Process the given buffer in place.
+    pub fn process(&mut self, buf: &mut [f32]) {
+        for s in buf.iter_mut() {
+            let env = self.envelope.process(*s);
+
+            if self.samples_until_update == 0 {
+                self.update_coeff(env);
+                self.samples_until_update = WAH_COEFF_INTERVAL;
+            }
+            self.samples_until_update -= 1;
+
+            let dry = *s;
+            let wet = self.filter.tick(dry, &self.coeff);
+
+            *s = dry + (wet - dry) * self.mix_smoothed.next_value();
+        }
+    }
+
+    /// Reset the envelope and the state of the filter, and jump the mix
+    /// straight to its target. The parameters are left untouched, and the
+    /// filter starts again from `min_hz`.
+    pub fn reset(&mut self) {
+        self.envelope.reset();
+        self.filter.reset();
+        self.mix_smoothed.finish();
+        self.samples_until_update = 0;
+    }
+
+    fn update_coeff(&mut self, env: f32) {
+        let pos = (env * self.sensitivity).min(1.0);
+
+        self.center_hz = self.min_hz * (self.max_hz / self.min_hz).powf(pos);
+        self.coeff = SvfCoeff::bandpass(self.center_hz, self.q, self.sample_rate_recip);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::sine;
+
+    const SAMPLE_RATE: f64 = 48_000.0;
+
+    /// The center frequency of a wah after a sine of the given amplitude has
+    /// been playing for a while.
+    fn settled_center_hz(wah: &mut AutoWah, amplitude: f32) -> f32 {
+        let mut buf: Vec<f32> = sine(220.0, SAMPLE_RATE, SAMPLE_RATE as usize / 4)
+            .iter()
+            .map(|s| s * amplitude)
+            .collect();
+        wah.reset();
+        wah.process(&mut buf);
+        wah.center_hz()
+    }
+
+    #[test]
+    fn louder_input_raises_center() {
+        let mut wah = AutoWah::new(SAMPLE_RATE);
+        let centers_hz: Vec<f32> = [0.0, 0.01, 0.03, 0.1, 0.5]
+            .into_iter()
+            .map(|amplitude| settled_center_hz(&mut wah, amplitude))
+            .collect();
+
+        assert_eq!(centers_hz[0], DEFAULT_WAH_MIN_HZ);
+        assert!(centers_hz.windows(2).all(|w| w[0] < w[1]), "{centers_hz:?}");
+        // The envelope is boosted past `1.0`, so the loudest input reaches
+        // the top of the range.
+        assert_eq!(centers_hz[4], DEFAULT_WAH_MAX_HZ);
+
+        // An inverted range sweeps the other way.
+        wah.set_range_hz(DEFAULT_WAH_MAX_HZ, DEFAULT_WAH_MIN_HZ);
+        let quiet_hz = settled_center_hz(&mut wah, 0.01);
+        let loud_hz = settled_center_hz(&mut wah, 0.1);
+        assert!(loud_hz < quiet_hz, "{quiet_hz} Hz -> {loud_hz} Hz");
+    }
+
+    #[test]
+    fn reset_finishes_mix_smoothing() {
+        let input = sine(220.0, SAMPLE_RATE, 480);
+
+        let mut wah = AutoWah::new(SAMPLE_RATE);
+        wah.process(&mut input.clone());
+        wah.set_mix(0.0);
+        wah.reset();
+
+        let mut buf = input.clone();
+        wah.process(&mut buf);
+        assert_eq!(buf, input);
+    }
+}
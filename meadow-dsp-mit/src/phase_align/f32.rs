@@ -0,0 +1,156 @@
+use std::f64::consts::PI;
+
+use crate::filter::one_pole_iir::f64::{OnePoleIirCoeff, OnePoleIirState};
+
+/// Shifts the phase of a signal by a given amount at a target frequency, for
+/// example to align the phase of one microphone of a pair to the other.
+///
+/// This uses a first-order allpass filter (see
+/// [`OnePoleIirCoeff::allpass`]), which delays the phase by
+/// anywhere between 0 and 180 degrees at the target frequency without
+/// changing the magnitude at any frequency. Shifts of 180 degrees or more are
+/// made by also inverting the polarity. Note that the shift is only exact at
+/// the target frequency: the allpass delays lower frequencies less and higher
+/// frequencies more.
+/// The filter runs in `f64`, since its coefficients get large when the
+/// allpass cutoff is close to a quarter of the sample rate.
+///
+/// The parameters can be changed while processing.
+#[derive(Clone, Copy)]
+pub struct PhaseAligner {
+    target_hz: f32,
+    phase_shift_deg: f32,
+
+    /// The allpass, or `None` if it is bypassed.
+    coeff: Option<OnePoleIirCoeff>,
+    invert: bool,
+    state: OnePoleIirState,
+
+    sample_rate_recip: f64,
+}
+
+impl PhaseAligner {
+    pub const LATENCY: u32 = 0;
+
+    /// Create a new phase aligner.
+    ///
+    /// See [`PhaseAligner::set_phase_shift`] for the meaning of the
+    /// parameters.
+    pub fn new(target_hz: f32, phase_shift_deg: f32, sample_rate: f64) -> Self {
+        let mut new_self = Self {
+            target_hz,
+            phase_shift_deg,
+            coeff: None,
+            invert: false,
+            state: OnePoleIirState::default(),
+            sample_rate_recip: sample_rate.recip(),
+        };
+        new_self.update_coeff();
+        new_self
+    }
+
+    pub fn target_hz(&self) -> f32 {
+        self.target_hz
+    }
+
+    pub fn phase_shift_deg(&self) -> f32 {
+        self.phase_shift_deg
+    }
+
+    /// Set the frequency (which must be below the Nyquist frequency) at which
+    /// the phase is shifted by exactly `phase_shift_deg` degrees.
+    ///
+    /// Positive shifts delay the phase, and negative shifts advance it.
+    /// (Since an advance of `x` degrees is the same as a delay of
+    /// `360 - x` degrees, any shift is possible.)
+    pub fn set_phase_shift(&mut self, target_hz: f32, phase_shift_deg: f32) {
+        self.target_hz = target_hz;
+        self.phase_shift_deg = phase_shift_deg;
+        self.update_coeff();
+    }
+
+    /// Reset the state of the filter.
+    pub fn reset(&mut self) {
+        self.state.reset();
+    }
+
+    /// Process the given buffer in place.
+    pub fn process(&mut self, buf: &mut [f32]) {
+        if let Some(coeff) = &self.coeff {
+            for s in buf.iter_mut() {
+                *s = self.state.tick(*s as f64, coeff) as f32;
+            }
+        }
+
+        if self.invert {
+            for s in buf.iter_mut() {
+                *s = -*s;
+            }
+        }
+    }
+
+    fn update_coeff(&mut self) {
+        let mut delay_deg = (self.phase_shift_deg as f64).rem_euclid(360.0);
+
+        self.invert = delay_deg >= 180.0;
+        if self.invert {
+            delay_deg -= 180.0;
+        }
+
+        if delay_deg == 0.0 {
+            self.coeff = None;
+            self.state.reset();
+            return;
+        }
+
+        // The allpass delays the phase by `2 * atan(w / wc)`, where `w` and
+        // `wc` are the prewarped target and cutoff. Solve for the cutoff
+        // which gives the requested delay at the target.
+        let w = (PI * self.target_hz as f64 * self.sample_rate_recip).tan();
+        let wc = w / (delay_deg.to_radians() * 0.5).tan();
+        let cutoff_hz = wc.atan() * (1.0 / PI) / self.sample_rate_recip;
+
+        self.coeff = Some(OnePoleIirCoeff::allpass(cutoff_hz, self.sample_rate_recip));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::sine;
+
+    /// The phase delay of `out` relative to a sine at `freq_hz` starting at
+    /// phase 0, in degrees between 0 and 360, measured over the second half.
+    fn phase_delay_deg(out: &[f32], freq_hz: f64, sample_rate: f64) -> f64 {
+        let (mut re, mut im) = (0.0, 0.0);
+        for (n, &s) in out.iter().enumerate().skip(out.len() / 2) {
+            let phase = 2.0 * PI * freq_hz * n as f64 / sample_rate;
+            re += s as f64 * phase.sin();
+            im += s as f64 * phase.cos();
+        }
+
+        (-im.atan2(re)).to_degrees().rem_euclid(360.0)
+    }
+
+    #[test]
+    fn phase_shift_at_target() {
+        let sample_rate = 48_000.0;
+
+        for target_hz in [100.0, 1_000.0, 12_000.0, 20_000.0] {
+            for phase_shift_deg in [10.0, 90.0, 170.0, 200.0, 300.0, -45.0] {
+                let mut aligner = PhaseAligner::new(target_hz, phase_shift_deg, sample_rate);
+
+                let mut buf = sine(target_hz as f64, sample_rate, 96_000);
+                aligner.process(&mut buf);
+
+                let delay_deg = phase_delay_deg(&buf, target_hz as f64, sample_rate);
+                let expected_deg = (phase_shift_deg as f64).rem_euclid(360.0);
+                let error_deg = (delay_deg - expected_deg + 180.0).rem_euclid(360.0) - 180.0;
+                assert!(
+                    error_deg.abs() < 0.05,
+                    "{target_hz} Hz {phase_shift_deg} deg: delayed by {delay_deg} deg"
+                );
+            }
+        }
+    }
+}
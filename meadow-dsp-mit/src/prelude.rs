@@ -0,0 +1,16 @@
+//! Re-exports of the most commonly used types and functions, so that they
+//! can be imported with `use meadow_dsp_mit::prelude::*;`.
+//!
+//! Only the f32 versions are included, since the f32 and f64 versions share
+//! their names. Everything else (including the f64 versions) is available
+//! from its own module.
+
+pub use crate::{
+    decibel::f32::{amp_to_db, db_to_amp},
+    filter::{
+        one_pole_iir::f32::{OnePoleIirCoeff, OnePoleIirState},
+        svf::f32::{SvfCoeff, SvfState},
+    },
+    processor::AudioProcessor,
+    smooth::f32::OnePoleSmoother,
+};
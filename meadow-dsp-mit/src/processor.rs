@@ -0,0 +1,18 @@
+/// An object-safe interface for stereo audio processors.
+///
+/// This allows processors of different types (for example EQs with different
+/// numbers of bands) to be stored together, i.e. as a
+/// `Vec<Box<dyn AudioProcessor>>` effect chain.
+pub trait AudioProcessor {
+    /// Process the given stereo buffers in place. This is the same as the
+    /// `process` method of the implementing type. The buffers must have the
+    /// same length.
+    fn process_stereo(&mut self, buf_l: &mut [f32], buf_r: &mut [f32]);
+
+    /// Reset the internal state of the processor. This is the same as the
+    /// `reset` method of the implementing type.
+    fn reset(&mut self);
+
+    /// The latency of the processor in frames.
+    fn latency(&self) -> u32;
+}
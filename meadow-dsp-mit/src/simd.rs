@@ -0,0 +1,192 @@
+//! The vector types used by the packed (`simd`) versions of the filters.
+//!
+//! With the `portable-simd` feature (which needs a nightly compiler), these
+//! wrap the types from `std::simd`. Without it, they wrap plain arrays, and
+//! the operations are a scalar emulation (although the compiler may still
+//! vectorize the loops on its own). Both support the same operations
+//! (`splat`, `from_array`, `to_array`, element-wise arithmetic, and
+//! `reduce_sum`), and give the same results bit for bit.
+//!
+//! Because these are wrappers rather than re-exports, code which uses them
+//! compiles the same way with or without the `portable-simd` feature, and
+//! does not need any `cfg`s or `#![feature(portable_simd)]` of its own.
+
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+#[cfg(feature = "portable-simd")]
+use std::simd::{num::SimdFloat, Simd};
+
+#[cfg(feature = "portable-simd")]
+type Repr<T, const LANES: usize> = Simd<T, LANES>;
+#[cfg(not(feature = "portable-simd"))]
+type Repr<T, const LANES: usize> = [T; LANES];
+
+macro_rules! vector {
+    ($name:ident, $t:ty, $lanes:literal) => {
+        #[doc = concat!(
+            "A vector of ", stringify!($lanes), " `", stringify!($t),
+            "` lanes, which wraps `std::simd::", stringify!($name),
+            "` with the `portable-simd` feature. See the [module docs](self)."
+        )]
+        #[allow(non_camel_case_types)]
+        #[derive(Default, Debug, Clone, Copy, PartialEq)]
+        #[repr(transparent)]
+        pub struct $name(Repr<$t, $lanes>);
+
+        impl $name {
+            pub const LEN: usize = $lanes;
+
+            #[cfg(feature = "portable-simd")]
+            pub const fn splat(value: $t) -> Self {
+                Self(Simd::splat(value))
+            }
+
+            #[cfg(not(feature = "portable-simd"))]
+            pub const fn splat(value: $t) -> Self {
+                Self([value; $lanes])
+            }
+
+            #[cfg(feature = "portable-simd")]
+            pub const fn from_array(array: [$t; $lanes]) -> Self {
+                Self(Simd::from_array(array))
+            }
+
+            #[cfg(not(feature = "portable-simd"))]
+            pub const fn from_array(array: [$t; $lanes]) -> Self {
+                Self(array)
+            }
+
+            #[cfg(feature = "portable-simd")]
+            pub const fn to_array(self) -> [$t; $lanes] {
+                self.0.to_array()
+            }
+
+            #[cfg(not(feature = "portable-simd"))]
+            pub const fn to_array(self) -> [$t; $lanes] {
+                self.0
+            }
+
+            #[cfg(feature = "portable-simd")]
+            pub const fn as_array(&self) -> &[$t; $lanes] {
+                self.0.as_array()
+            }
+
+            #[cfg(not(feature = "portable-simd"))]
+            pub const fn as_array(&self) -> &[$t; $lanes] {
+                &self.0
+            }
+
+            /// The sum of the lanes, added in order.
+            #[cfg(feature = "portable-simd")]
+            #[inline(always)]
+            pub fn reduce_sum(self) -> $t {
+                self.0.reduce_sum()
+            }
+
+            /// The sum of the lanes, added in order.
+            #[cfg(not(feature = "portable-simd"))]
+            #[inline(always)]
+            pub fn reduce_sum(self) -> $t {
+                self.0.iter().fold(-0.0, |sum, x| sum + x)
+            }
+        }
+
+        impl From<[$t; $lanes]> for $name {
+            fn from(array: [$t; $lanes]) -> Self {
+                Self::from_array(array)
+            }
+        }
+
+        impl From<$name> for [$t; $lanes] {
+            fn from(vector: $name) -> Self {
+                vector.to_array()
+            }
+        }
+
+        #[cfg(feature = "portable-simd")]
+        impl From<std::simd::$name> for $name {
+            fn from(vector: std::simd::$name) -> Self {
+                Self(vector)
+            }
+        }
+
+        #[cfg(feature = "portable-simd")]
+        impl From<$name> for std::simd::$name {
+            fn from(vector: $name) -> Self {
+                vector.0
+            }
+        }
+
+        vector!(@op $name, Add, add, AddAssign, add_assign, +=);
+        vector!(@op $name, Sub, sub, SubAssign, sub_assign, -=);
+        vector!(@op $name, Mul, mul, MulAssign, mul_assign, *=);
+
+        impl Neg for $name {
+            type Output = Self;
+
+            #[cfg(feature = "portable-simd")]
+            #[inline(always)]
+            fn neg(self) -> Self {
+                Self(-self.0)
+            }
+
+            #[cfg(not(feature = "portable-simd"))]
+            #[inline(always)]
+            fn neg(self) -> Self {
+                Self(self.0.map(|x| -x))
+            }
+        }
+    };
+    (@op $name:ident, $trait:ident, $fn:ident, $assign_trait:ident, $assign_fn:ident, $op:tt) => {
+        impl $trait for $name {
+            type Output = Self;
+
+            #[inline(always)]
+            fn $fn(mut self, rhs: Self) -> Self {
+                $assign_trait::$assign_fn(&mut self, rhs);
+                self
+            }
+        }
+
+        impl $assign_trait for $name {
+            #[cfg(feature = "portable-simd")]
+            #[inline(always)]
+            fn $assign_fn(&mut self, rhs: Self) {
+                self.0 $op rhs.0;
+            }
+
+            #[cfg(not(feature = "portable-simd"))]
+            #[inline(always)]
+            fn $assign_fn(&mut self, rhs: Self) {
+                for (a, b) in self.0.iter_mut().zip(rhs.0) {
+                    *a $op b;
+                }
+            }
+        }
+    };
+}
+
+vector!(f32x2, f32, 2);
+vector!(f32x4, f32, 4);
+vector!(f32x8, f32, 8);
+vector!(f64x2, f64, 2);
+vector!(f64x4, f64, 4);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reduce_sum_adds_lanes_in_order() {
+        // Adding in a different order would round differently.
+        let lanes = [1.0e8, 1.0, -1.0e8, 1.0];
+        let sum = lanes.iter().fold(-0.0, |sum: f32, x| sum + x);
+        assert_eq!(sum, 1.0);
+        assert_eq!(f32x4::from_array(lanes).reduce_sum(), sum);
+
+        assert_eq!(
+            f32x2::splat(-0.0).reduce_sum().to_bits(),
+            (-0.0f32).to_bits()
+        );
+        assert_eq!(f64x2::from_array([0.1, 0.2]).reduce_sum(), 0.1 + 0.2);
+    }
+}
@@ -0,0 +1,162 @@
+/// The default smoothing time constant in seconds.
+pub const DEFAULT_SMOOTH_SECS: f64 = 0.005;
+
+/// Once the smoothed value is within this distance of the target, it snaps to
+/// the target.
+const SETTLE_EPSILON: f32 = 0.00001;
+
+/// A parameter smoothed with a one-pole lowpass filter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OnePoleSmoother {
+    current: f32,
+    target: f32,
+
+    b1: f32,
+}
+
+impl OnePoleSmoother {
+    /// Create a new smoother which starts at (and targets) `value`.
+    ///
+    /// `smooth_secs` is the time constant of the smoothing, i.e. the time it
+    /// takes to move about 63% of the way towards the target.
+    pub fn new(value: f32, smooth_secs: f64, sample_rate: f64) -> Self {
+        Self {
+            current: value,
+            target: value,
+            b1: b1(smooth_secs, sample_rate),
+        }
+    }
+
+    pub fn set_smooth_secs(&mut self, smooth_secs: f64, sample_rate: f64) {
+        self.b1 = b1(smooth_secs, sample_rate);
+    }
+
+    /// Set the value to smooth towards.
+    pub fn set(&mut self, target: f32) {
+        self.target = target;
+    }
+
+    /// Set the value immediately without smoothing.
+    pub fn set_immediate(&mut self, value: f32) {
+        self.current = value;
+        self.target = value;
+    }
+
+    /// Jump straight to the target, ending any smoothing in progress.
+    pub fn finish(&mut self) {
+        self.current = self.target;
+    }
+
+    pub fn target(&self) -> f32 {
+        self.target
+    }
+
+    pub fn current(&self) -> f32 {
+        self.current
+    }
+
+    /// Returns `true` if the value has not yet reached the target.
+    pub fn is_smoothing(&self) -> bool {
+        self.current != self.target
+    }
+
+    /// Advance by one sample and return the new smoothed value.
+    #[inline(always)]
+    pub fn next_value(&mut self) -> f32 {
+        self.current = self.target + self.b1 * (self.current - self.target);
+
+        if (self.current - self.target).abs() < SETTLE_EPSILON {
+            self.current = self.target;
+        }
+
+        self.current
+    }
+}
+
+fn b1(smooth_secs: f64, sample_rate: f64) -> f32 {
+    if smooth_secs <= 0.0 {
+        0.0
+    } else {
+        (-1.0 / (smooth_secs * sample_rate)).exp() as f32
+    }
+}
+
+/// A smoother which outputs the average of the last `window` input samples (a
+/// moving-average FIR filter).
+///
+/// Unlike [`OnePoleSmoother`], the output settles exactly after `window`
+/// samples. The history is stored inline with room for up to `MAX_WINDOW`
+/// samples, so this never allocates.
+#[derive(Debug, Clone, Copy)]
+pub struct BoxSmoother<const MAX_WINDOW: usize> {
+    history: [f32; MAX_WINDOW],
+    window: usize,
+    write_i: usize,
+
+    /// The running sum of the samples in the window. (This is an f64 so that
+    /// rounding errors do not accumulate noticeably.)
+    sum: f64,
+}
+
+impl<const MAX_WINDOW: usize> BoxSmoother<MAX_WINDOW> {
+    /// Create a new smoother whose history is filled with `value`.
+    ///
+    /// `window` is clamped to the range `[1, MAX_WINDOW]`.
+    pub fn new(value: f32, window: usize) -> Self {
+        const { assert!(MAX_WINDOW > 0) };
+
+        let mut new_self = Self {
+            history: [0.0; MAX_WINDOW],
+            window: window.clamp(1, MAX_WINDOW),
+            write_i: 0,
+            sum: 0.0,
+        };
+        new_self.reset(value);
+        new_self
+    }
+
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// Set the number of samples which are averaged, clamped to the range
+    /// `[1, MAX_WINDOW]`.
+    ///
+    /// The history is refilled with the current output so that the output
+    /// does not jump.
+    pub fn set_window(&mut self, window: usize) {
+        let window = window.clamp(1, MAX_WINDOW);
+
+        if self.window != window {
+            let current = self.current();
+            self.window = window;
+            self.reset(current);
+        }
+    }
+
+    /// The average of the samples currently in the window.
+    pub fn current(&self) -> f32 {
+        (self.sum / self.window as f64) as f32
+    }
+
+    /// Fill the history with `value`.
+    pub fn reset(&mut self, value: f32) {
+        self.history[..self.window].fill(value);
+        self.write_i = 0;
+        self.sum = value as f64 * self.window as f64;
+    }
+
+    /// Push a new sample into the window and return the new average.
+    #[inline(always)]
+    pub fn process(&mut self, sample: f32) -> f32 {
+        self.sum += sample as f64 - self.history[self.write_i] as f64;
+        self.history[self.write_i] = sample;
+
+        self.write_i += 1;
+        if self.write_i == self.window {
+            self.write_i = 0;
+        }
+
+        self.current()
+    }
+}
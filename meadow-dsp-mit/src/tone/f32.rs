@@ -0,0 +1,89 @@
+use crate::filter::svf::{
+    f32::{SvfCoeff, SvfState},
+    f64::{SvfCoeff as SvfCoeffF64, Q_BUTTERWORTH_ORD2},
+};
+
+/// The cutoff of the low shelf of a [`ToneControl`] in Hz.
+pub const LOW_SHELF_HZ: f32 = 250.0;
+/// The cutoff of the high shelf of a [`ToneControl`] in Hz.
+pub const HIGH_SHELF_HZ: f32 = 4_000.0;
+/// The gain of each shelf of a [`ToneControl`] in decibels when the tone is
+/// turned all the way to either side.
+pub const MAX_SHELF_GAIN_DB: f32 = 6.0;
+
+/// A simple tone control with a single knob.
+///
+/// This tilts the spectrum with a low shelf at [`LOW_SHELF_HZ`] and a high
+/// shelf at [`HIGH_SHELF_HZ`] which move in opposite directions. Turning the
+/// tone down boosts the low shelf and cuts the high shelf (darker), and
+/// turning it up does the opposite (brighter), by up to [`MAX_SHELF_GAIN_DB`]
+/// each.
+#[derive(Clone, Copy)]
+pub struct ToneControl {
+    tone: f32,
+
+    low_coeff: SvfCoeff,
+    high_coeff: SvfCoeff,
+    low_state: SvfState,
+    high_state: SvfState,
+
+    sample_rate_recip: f64,
+}
+
+impl ToneControl {
+    pub const LATENCY: u32 = 0;
+
+    pub fn new(sample_rate: f64) -> Self {
+        let mut new_self = Self {
+            tone: 0.0,
+            low_coeff: SvfCoeff::NO_OP,
+            high_coeff: SvfCoeff::NO_OP,
+            low_state: SvfState::default(),
+            high_state: SvfState::default(),
+            sample_rate_recip: sample_rate.recip(),
+        };
+        new_self.set_tone(0.0);
+        new_self
+    }
+
+    pub fn tone(&self) -> f32 {
+        self.tone
+    }
+
+    /// Set the tone in the range `[-1.0, 1.0]`, where negative values are
+    /// darker, positive values are brighter, and `0.0` is flat.
+    pub fn set_tone(&mut self, tone: f32) {
+        self.tone = tone.clamp(-1.0, 1.0);
+
+        let gain_db = (self.tone * MAX_SHELF_GAIN_DB) as f64;
+
+        self.low_coeff = SvfCoeffF64::low_shelf(
+            LOW_SHELF_HZ as f64,
+            Q_BUTTERWORTH_ORD2,
+            -gain_db,
+            self.sample_rate_recip,
+        )
+        .to_f32();
+        self.high_coeff = SvfCoeffF64::high_shelf(
+            HIGH_SHELF_HZ as f64,
+            Q_BUTTERWORTH_ORD2,
+            gain_db,
+            self.sample_rate_recip,
+        )
+        .to_f32();
+    }
+
+    /// Reset the state of the filters.
+    pub fn reset(&mut self) {
+        self.low_state.reset();
+        self.high_state.reset();
+    }
+
+    /// Process the given buffer in place.
+    pub fn process(&mut self, buf: &mut [f32]) {
+        for s in buf.iter_mut() {
+            let low = self.low_state.tick(*s, &self.low_coeff);
+            *s = self.high_state.tick(low, &self.high_coeff);
+        }
+    }
+}
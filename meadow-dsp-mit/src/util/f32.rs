@@ -0,0 +1,47 @@
+use std::f32::consts::FRAC_1_SQRT_2;
+
+/// The gain applied to each channel when summing stereo to mono.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MonoSumLaw {
+    /// Each channel is attenuated by about 3 dB (`1 / sqrt(2)`), which keeps
+    /// the level of uncorrelated (wide) material the same.
+    #[default]
+    Minus3Db,
+    /// Each channel is attenuated by about 6 dB (`0.5`), which keeps the
+    /// level of correlated (centered) material the same. Summing the output
+    /// of [`mono_to_stereo`] with this law gives back the original signal.
+    Minus6Db,
+}
+
+impl MonoSumLaw {
+    /// The linear gain applied to each channel.
+    pub fn gain(&self) -> f32 {
+        match self {
+            Self::Minus3Db => FRAC_1_SQRT_2,
+            Self::Minus6Db => 0.5,
+        }
+    }
+}
+
+/// Sum the stereo buffers `buf_l` and `buf_r` to mono into `out`, with each
+/// channel attenuated according to `law`.
+///
+/// Only the frames that all buffers have in common are processed.
+pub fn sum_to_mono(buf_l: &[f32], buf_r: &[f32], out: &mut [f32], law: MonoSumLaw) {
+    let gain = law.gain();
+
+    for ((out, &l), &r) in out.iter_mut().zip(buf_l.iter()).zip(buf_r.iter()) {
+        *out = (l + r) * gain;
+    }
+}
+
+/// Copy the mono buffer `mono` into both `buf_l` and `buf_r` at unity gain
+/// (i.e. a mono signal panned to the center without a pan law).
+///
+/// Only the frames that all buffers have in common are processed.
+pub fn mono_to_stereo(mono: &[f32], buf_l: &mut [f32], buf_r: &mut [f32]) {
+    for ((&m, l), r) in mono.iter().zip(buf_l.iter_mut()).zip(buf_r.iter_mut()) {
+        *l = m;
+        *r = m;
+    }
+}
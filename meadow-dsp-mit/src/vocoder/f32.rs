@@ -0,0 +1,173 @@
+use std::f32::consts::FRAC_PI_2;
+
+use crate::{
+    constant_q::f32::{constant_q, log_spaced_freqs},
+    filter::{
+        one_pole_iir::{
+            f32::{OnePoleIirCoeff, OnePoleIirState},
+            f64::OnePoleIirCoeff as OnePoleIirCoeffF64,
+        },
+        svf::{
+            f32::{SvfCoeff, SvfState},
+            f64::SvfCoeff as SvfCoeffF64,
+        },
+    },
+};
+
+/// The default cutoff of the one-pole envelope followers in Hz.
+pub const DEFAULT_ENVELOPE_CUTOFF_HZ: f32 = 30.0;
+
+/// A channel vocoder with `NUM_BANDS` log-spaced bands.
+///
+/// Both the carrier and the modulator are split into matching bands using
+/// bandpass filters. The envelope of each modulator band is then applied to
+/// the corresponding carrier band, and the carrier bands are summed together.
+pub struct Vocoder<const NUM_BANDS: usize> {
+    svf_coeffs: [SvfCoeff; NUM_BANDS],
+    carrier_states: [SvfState; NUM_BANDS],
+    modulator_states: [SvfState; NUM_BANDS],
+
+    envelope_coeff: OnePoleIirCoeff,
+    envelope_states: [OnePoleIirState; NUM_BANDS],
+
+    sample_rate_recip: f64,
+}
+
+impl<const NUM_BANDS: usize> Vocoder<NUM_BANDS> {
+    pub const LATENCY: u32 = 0;
+
+    /// Create a new vocoder with bands log-spaced between `min_hz` and `max_hz`
+    /// (inclusive).
+    ///
+    /// # Panics
+    /// Panics if `NUM_BANDS == 0`.
+    pub fn new(min_hz: f32, max_hz: f32, sample_rate: f64) -> Self {
+        assert_ne!(NUM_BANDS, 0);
+
+        let sample_rate_recip = sample_rate.recip();
+
+        let center_freqs_hz = log_spaced_freqs(NUM_BANDS, min_hz, max_hz);
+        let q = constant_q(NUM_BANDS, min_hz, max_hz);
+
+        Self {
+            svf_coeffs: std::array::from_fn(|i| {
+                SvfCoeffF64::bandpass(center_freqs_hz[i] as f64, q as f64, sample_rate_recip)
+                    .to_f32()
+            }),
+            carrier_states: [SvfState::default(); NUM_BANDS],
+            modulator_states: [SvfState::default(); NUM_BANDS],
+            envelope_coeff: OnePoleIirCoeffF64::lowpass(
+                DEFAULT_ENVELOPE_CUTOFF_HZ as f64,
+                sample_rate_recip,
+            )
+            .to_f32(),
+            envelope_states: [OnePoleIirState::default(); NUM_BANDS],
+            sample_rate_recip,
+        }
+    }
+
+    /// Set the cutoff of the modulator envelope followers in Hz. Higher values
+    /// track the modulator more closely at the cost of more roughness.
+    pub fn set_envelope_cutoff_hz(&mut self, cutoff_hz: f32) {
+        self.envelope_coeff =
+            OnePoleIirCoeffF64::lowpass(cutoff_hz as f64, self.sample_rate_recip).to_f32();
+    }
+
+    /// Process the carrier in place using the given modulator.
+    ///
+    /// `carrier` and `modulator` must have the same length. This is checked
+    /// with a debug assertion. In release builds only the samples they have
+    /// in common are processed.
+    pub fn process(&mut self, carrier: &mut [f32], modulator: &[f32]) {
+        debug_assert_eq!(
+            carrier.len(),
+            modulator.len(),
+            "the carrier and modulator buffers must have the same length"
+        );
+
+        for (out_s, &m) in carrier.iter_mut().zip(modulator.iter()) {
+            let c = *out_s;
+            let mut sum = 0.0;
+
+            for i in 0..NUM_BANDS {
+                let carrier_band = self.carrier_states[i].tick(c, &self.svf_coeffs[i]);
+                let modulator_band = self.modulator_states[i].tick(m, &self.svf_coeffs[i]);
+
+                let env = self.envelope_states[i].tick(modulator_band.abs(), &self.envelope_coeff);
+
+                sum += carrier_band * env;
+            }
+
+            // The mean of a rectified sine wave is `2/pi` times its amplitude.
+            *out_s = sum * FRAC_PI_2;
+        }
+    }
+
+    pub fn reset(&mut self) {
+        for s in self.carrier_states.iter_mut() {
+            s.reset();
+        }
+        for s in self.modulator_states.iter_mut() {
+            s.reset();
+        }
+        for s in self.envelope_states.iter_mut() {
+            s.reset();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        decibel::f64::amp_to_db,
+        test_util::{noise, rms, sine},
+    };
+
+    const SAMPLE_RATE: f64 = 48_000.0;
+    const MIN_HZ: f32 = 100.0;
+    const MAX_HZ: f32 = 6_400.0;
+
+    #[test]
+    fn silent_modulator_gives_silence() {
+        let mut vocoder = Vocoder::<8>::new(MIN_HZ, MAX_HZ, SAMPLE_RATE);
+        let mut carrier = noise(4_800, 0);
+        vocoder.process(&mut carrier, &[0.0; 4_800]);
+
+        assert!(carrier.iter().all(|&s| s == 0.0));
+    }
+
+    /// A modulator at one band's center frequency lets through the carrier in
+    /// that band, and less of it the further away the carrier is. The bands
+    /// are an octave apart and overlap a lot, so this falls off at only about
+    /// 3.5 dB per band.
+    #[test]
+    fn modulator_band_selects_carrier_band() {
+        const FRAMES: usize = 24_000;
+
+        let center_freqs_hz = log_spaced_freqs(8, MIN_HZ, MAX_HZ);
+
+        for (modulator_i, &modulator_hz) in center_freqs_hz.iter().enumerate() {
+            let modulator = sine(modulator_hz as f64, SAMPLE_RATE, FRAMES);
+
+            let levels: Vec<f64> = center_freqs_hz
+                .iter()
+                .map(|&carrier_hz| {
+                    let mut vocoder = Vocoder::<8>::new(MIN_HZ, MAX_HZ, SAMPLE_RATE);
+                    let mut carrier = sine(carrier_hz as f64, SAMPLE_RATE, FRAMES);
+                    vocoder.process(&mut carrier, &modulator);
+                    rms(&carrier[FRAMES / 2..])
+                })
+                .collect();
+
+            for (carrier_i, &level) in levels.iter().enumerate() {
+                let distance = carrier_i.abs_diff(modulator_i);
+                let below_db = amp_to_db(levels[modulator_i] / level);
+                assert!(
+                    distance == 0 || below_db > 2.5 * distance as f64,
+                    "{modulator_hz} Hz modulator: band {carrier_i} is only {below_db} dB down"
+                );
+            }
+        }
+    }
+}